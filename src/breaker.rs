@@ -0,0 +1,263 @@
+//! Per-host circuit breaker for outages.
+//!
+//! After `threshold` consecutive failures to a host within `window`, calls to
+//! that host fail fast for `cooldown`. The first call after the cooldown is let
+//! through as a probe (half-open): success closes the circuit, failure reopens
+//! it for another cooldown. A probe dropped before it reports, e.g. by a
+//! cancelled tool call, frees the slot for the next call.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct HostState {
+    /// Consecutive failures since `first_failure`
+    failures: u32,
+    first_failure: Option<Instant>,
+    /// Set while the circuit is open
+    opened_at: Option<Instant>,
+    /// Whether a half-open probe is in flight
+    probing: bool,
+}
+
+pub struct CircuitBreaker {
+    threshold: u32,
+    window: Duration,
+    cooldown: Duration,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl CircuitBreaker {
+    /// A `threshold` of 0 disables the breaker
+    pub fn new(threshold: u32, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            cooldown,
+            hosts: Mutex::default(),
+        }
+    }
+
+    /// Whether a call to `host` may proceed. Returns a permit to report the
+    /// call's result through, or the time left in the cooldown when the
+    /// circuit is open.
+    pub fn check(&self, host: &str) -> Result<Permit<'_>, Duration> {
+        let mut permit = Permit {
+            breaker: self,
+            host: host.to_string(),
+            probe: false,
+        };
+        if self.threshold == 0 {
+            return Ok(permit);
+        }
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return Ok(permit);
+        };
+        let Some(state) = hosts.get_mut(host) else {
+            return Ok(permit);
+        };
+        let Some(opened_at) = state.opened_at else {
+            return Ok(permit);
+        };
+        if let Some(remaining) = self
+            .cooldown
+            .checked_sub(opened_at.elapsed())
+            .filter(|r| !r.is_zero())
+        {
+            return Err(remaining);
+        }
+        if state.probing {
+            // Another call is already probing; keep failing fast until it reports
+            return Err(Duration::ZERO);
+        }
+        tracing::info!("Circuit half-open for {}, sending a probe", host);
+        state.probing = true;
+        permit.probe = true;
+        Ok(permit)
+    }
+
+    /// Record a call that reached the host and got a usable answer
+    pub fn record_success(&self, host: &str) {
+        if self.threshold == 0 {
+            return;
+        }
+        if let Ok(mut hosts) = self.hosts.lock() {
+            if let Some(state) = hosts.remove(host) {
+                if state.opened_at.is_some() {
+                    tracing::info!("Circuit closed for {}", host);
+                }
+            }
+        }
+    }
+
+    /// Record a network failure or server error from the host
+    pub fn record_failure(&self, host: &str) {
+        if self.threshold == 0 {
+            return;
+        }
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return;
+        };
+        let state = hosts.entry(host.to_string()).or_default();
+        let now = Instant::now();
+
+        if state.probing {
+            tracing::warn!("Probe to {} failed, circuit reopened", host);
+            state.probing = false;
+            state.opened_at = Some(now);
+            return;
+        }
+        match state.first_failure {
+            Some(first) if now.duration_since(first) <= self.window => state.failures += 1,
+            _ => {
+                state.failures = 1;
+                state.first_failure = Some(now);
+            }
+        }
+        if state.failures >= self.threshold && state.opened_at.is_none() {
+            tracing::warn!(
+                "Circuit opened for {} after {} consecutive failures",
+                host,
+                state.failures
+            );
+            state.opened_at = Some(now);
+        }
+    }
+}
+
+/// Leave to call a host, from [`CircuitBreaker::check`]. Report the outcome
+/// through it; a half-open probe dropped without a report is abandoned, so
+/// the next call probes instead of failing fast forever.
+#[must_use]
+pub struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    host: String,
+    /// Whether this call is the half-open probe
+    probe: bool,
+}
+
+impl Permit<'_> {
+    pub fn record_success(mut self) {
+        self.probe = false;
+        self.breaker.record_success(&self.host);
+    }
+
+    pub fn record_failure(mut self) {
+        self.probe = false;
+        self.breaker.record_failure(&self.host);
+    }
+}
+
+impl fmt::Debug for Permit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Permit")
+            .field("host", &self.host)
+            .field("probe", &self.probe)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.probe {
+            return;
+        }
+        if let Ok(mut hosts) = self.breaker.hosts.lock() {
+            if let Some(state) = hosts.get_mut(&self.host) {
+                tracing::info!("Probe to {} abandoned, the next call probes", self.host);
+                state.probing = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_within_window() {
+        let breaker = CircuitBreaker::new(3, Duration::from_mins(1), Duration::from_mins(1));
+
+        breaker.record_failure("coda.io");
+        breaker.record_failure("coda.io");
+        assert!(breaker.check("coda.io").is_ok());
+
+        breaker.record_failure("coda.io");
+        let remaining = breaker.check("coda.io").unwrap_err();
+        assert!(remaining > Duration::from_secs(50));
+        // Other hosts are unaffected
+        assert!(breaker.check("codahosted.io").is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breaker = CircuitBreaker::new(2, Duration::from_mins(1), Duration::from_mins(1));
+
+        breaker.record_failure("coda.io");
+        breaker.record_success("coda.io");
+        breaker.record_failure("coda.io");
+
+        assert!(breaker.check("coda.io").is_ok());
+    }
+
+    #[test]
+    fn test_failures_outside_window_do_not_accumulate() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO, Duration::from_mins(1));
+
+        breaker.record_failure("coda.io");
+        std::thread::sleep(Duration::from_millis(2));
+        breaker.record_failure("coda.io");
+
+        assert!(breaker.check("coda.io").is_ok());
+    }
+
+    #[test]
+    fn test_half_open_probe_after_cooldown() {
+        let breaker = CircuitBreaker::new(1, Duration::from_mins(1), Duration::from_millis(5));
+        breaker.record_failure("coda.io");
+        assert!(breaker.check("coda.io").is_err());
+
+        std::thread::sleep(Duration::from_millis(10));
+        // One probe is let through; concurrent calls still fail fast
+        let probe = breaker.check("coda.io").unwrap();
+        assert!(breaker.check("coda.io").is_err());
+
+        // A failed probe reopens the circuit for a full cooldown
+        probe.record_failure();
+        assert!(breaker.check("coda.io").is_err());
+
+        std::thread::sleep(Duration::from_millis(10));
+        breaker.check("coda.io").unwrap().record_success();
+        assert!(breaker.check("coda.io").is_ok());
+        assert!(breaker.check("coda.io").is_ok());
+    }
+
+    #[test]
+    fn test_dropped_probe_frees_the_slot() {
+        let breaker = CircuitBreaker::new(1, Duration::from_mins(1), Duration::from_millis(5));
+        breaker.record_failure("coda.io");
+        std::thread::sleep(Duration::from_millis(10));
+
+        let probe = breaker.check("coda.io").unwrap();
+        assert!(breaker.check("coda.io").is_err());
+        drop(probe);
+
+        // The circuit stays open, but the next call is the new probe
+        let probe = breaker.check("coda.io").unwrap();
+        assert!(breaker.check("coda.io").is_err());
+        probe.record_success();
+        assert!(breaker.check("coda.io").is_ok());
+    }
+
+    #[test]
+    fn test_zero_threshold_disables() {
+        let breaker = CircuitBreaker::new(0, Duration::from_mins(1), Duration::from_mins(1));
+        for _ in 0..10 {
+            breaker.record_failure("coda.io");
+        }
+        assert!(breaker.check("coda.io").is_ok());
+    }
+}
@@ -0,0 +1,289 @@
+//! Per-server caches: doc context loaded by `load_doc_context`, and the
+//! column lists of individual tables.
+//!
+//! Doc context entries expire after a TTL and the oldest entry is evicted once
+//! the cache holds `max_docs` docs. Tools that list tables or columns consult
+//! it before calling the API. [`ColumnCache`] keeps plain column lists for a
+//! shorter TTL, evicting the least recently used table when full.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::models::{Column, Doc, Page, Row, Table};
+
+/// Everything `load_doc_context` fetched for one doc
+#[derive(Debug, Clone)]
+pub struct DocContext {
+    pub doc: Doc,
+    pub pages: Vec<Page>,
+    /// Every table in the doc, in listing order
+    pub tables: Vec<Table>,
+    /// Columns by table ID, for the tables that were introspected
+    pub columns: HashMap<String, Vec<Column>>,
+    /// Sample rows by table ID
+    pub samples: HashMap<String, Vec<Row>>,
+}
+
+impl DocContext {
+    /// Cached columns of a table, looked up by ID or name
+    pub fn columns_of(&self, table: &str) -> Option<&[Column]> {
+        let id = self
+            .tables
+            .iter()
+            .find(|t| t.id == table || t.name == table)
+            .map_or(table, |t| t.id.as_str());
+        self.columns.get(id).map(Vec::as_slice)
+    }
+}
+
+pub struct DocContextCache {
+    ttl: Duration,
+    max_docs: usize,
+    entries: Mutex<HashMap<String, (Instant, Arc<DocContext>)>>,
+}
+
+impl DocContextCache {
+    /// A `max_docs` of 0 disables caching
+    pub fn new(ttl: Duration, max_docs: usize) -> Self {
+        Self {
+            ttl,
+            max_docs,
+            entries: Mutex::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_docs > 0 && !self.ttl.is_zero()
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Store a doc's context, replacing any previous entry
+    pub fn insert(&self, doc_id: &str, context: DocContext) {
+        if !self.is_enabled() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|_, (loaded, _)| loaded.elapsed() < self.ttl);
+        entries.remove(doc_id);
+        while entries.len() >= self.max_docs {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (loaded, _))| *loaded)
+                .map(|(id, _)| id.clone());
+            match oldest {
+                Some(id) => entries.remove(&id),
+                None => break,
+            };
+        }
+        entries.insert(doc_id.to_string(), (Instant::now(), Arc::new(context)));
+    }
+
+    /// A doc's context if it was loaded within the TTL
+    pub fn get(&self, doc_id: &str) -> Option<Arc<DocContext>> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(doc_id) {
+            Some((loaded, context)) if loaded.elapsed() < self.ttl => Some(Arc::clone(context)),
+            Some(_) => {
+                entries.remove(doc_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Drop a doc's context, e.g. after a change to its structure
+    pub fn invalidate(&self, doc_id: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(doc_id);
+        }
+    }
+}
+
+/// Tables whose columns [`ColumnCache`] holds at once
+const COLUMN_CACHE_MAX_TABLES: usize = 100;
+
+/// A cached column list, with when it was fetched and last read
+struct ColumnEntry {
+    loaded: Instant,
+    used: Instant,
+    columns: Arc<Vec<Column>>,
+}
+
+/// Column lists by `(doc_id, table_id)`, so tools that need a table's
+/// columns on every call don't refetch them each time
+pub struct ColumnCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, String), ColumnEntry>>,
+}
+
+impl ColumnCache {
+    /// A zero `ttl` disables caching
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Store a table's complete column list, replacing any previous entry
+    pub fn insert(&self, doc_id: &str, table_id: &str, columns: Vec<Column>) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|_, entry| entry.loaded.elapsed() < self.ttl);
+        let key = (doc_id.to_string(), table_id.to_string());
+        entries.remove(&key);
+        if entries.len() >= COLUMN_CACHE_MAX_TABLES {
+            let least_used = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = least_used {
+                entries.remove(&key);
+            }
+        }
+        let now = Instant::now();
+        entries.insert(
+            key,
+            ColumnEntry {
+                loaded: now,
+                used: now,
+                columns: Arc::new(columns),
+            },
+        );
+    }
+
+    /// A table's columns if they were fetched within the TTL
+    pub fn get(&self, doc_id: &str, table_id: &str) -> Option<Arc<Vec<Column>>> {
+        let mut entries = self.entries.lock().ok()?;
+        let key = (doc_id.to_string(), table_id.to_string());
+        match entries.get_mut(&key) {
+            Some(entry) if entry.loaded.elapsed() < self.ttl => {
+                entry.used = Instant::now();
+                Some(Arc::clone(&entry.columns))
+            }
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Drop every cached table of a doc, e.g. after a column is renamed
+    pub fn invalidate(&self, doc_id: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|(doc, _), _| doc != doc_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(doc_id: &str) -> DocContext {
+        let table: Table =
+            serde_json::from_value(serde_json::json!({"id": "grid-1", "name": "Tasks"})).unwrap();
+        let column: Column =
+            serde_json::from_value(serde_json::json!({"id": "c-1", "name": "Title"})).unwrap();
+        DocContext {
+            doc: serde_json::from_value(serde_json::json!({"id": doc_id, "name": "Doc"})).unwrap(),
+            pages: Vec::new(),
+            tables: vec![table],
+            columns: HashMap::from([("grid-1".to_string(), vec![column])]),
+            samples: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_columns_of_by_id_or_name() {
+        let ctx = context("doc1");
+
+        assert_eq!(ctx.columns_of("grid-1").unwrap()[0].id, "c-1");
+        assert_eq!(ctx.columns_of("Tasks").unwrap()[0].id, "c-1");
+        assert!(ctx.columns_of("Other").is_none());
+    }
+
+    #[test]
+    fn test_evicts_oldest_when_full() {
+        let cache = DocContextCache::new(Duration::from_mins(1), 2);
+        cache.insert("doc1", context("doc1"));
+        cache.insert("doc2", context("doc2"));
+        cache.insert("doc3", context("doc3"));
+
+        assert!(cache.get("doc1").is_none());
+        assert!(cache.get("doc2").is_some());
+        assert!(cache.get("doc3").is_some());
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let cache = DocContextCache::new(Duration::from_nanos(1), 2);
+        cache.insert("doc1", context("doc1"));
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert!(cache.get("doc1").is_none());
+    }
+
+    fn columns() -> Vec<Column> {
+        vec![serde_json::from_value(serde_json::json!({"id": "c-1", "name": "Title"})).unwrap()]
+    }
+
+    #[test]
+    fn test_column_cache_expiry_and_invalidate() {
+        let cache = ColumnCache::new(Duration::from_mins(1));
+        cache.insert("doc1", "grid-1", columns());
+        cache.insert("doc1", "grid-2", columns());
+        assert_eq!(cache.get("doc1", "grid-1").unwrap()[0].id, "c-1");
+        assert!(cache.get("doc2", "grid-1").is_none());
+
+        cache.invalidate("doc1");
+        assert!(cache.get("doc1", "grid-2").is_none());
+
+        let short = ColumnCache::new(Duration::from_nanos(1));
+        short.insert("doc1", "grid-1", columns());
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(short.get("doc1", "grid-1").is_none());
+
+        let disabled = ColumnCache::new(Duration::ZERO);
+        disabled.insert("doc1", "grid-1", columns());
+        assert!(disabled.get("doc1", "grid-1").is_none());
+    }
+
+    #[test]
+    fn test_column_cache_evicts_least_recently_used() {
+        let cache = ColumnCache::new(Duration::from_mins(1));
+        for i in 0..COLUMN_CACHE_MAX_TABLES {
+            cache.insert("doc1", &format!("grid-{i}"), columns());
+        }
+        // Reading the oldest entry makes grid-1 the least recently used
+        assert!(cache.get("doc1", "grid-0").is_some());
+        cache.insert("doc1", "grid-new", columns());
+
+        assert!(cache.get("doc1", "grid-0").is_some());
+        assert!(cache.get("doc1", "grid-1").is_none());
+        assert!(cache.get("doc1", "grid-new").is_some());
+    }
+
+    #[test]
+    fn test_disabled_and_invalidate() {
+        let disabled = DocContextCache::new(Duration::from_mins(1), 0);
+        disabled.insert("doc1", context("doc1"));
+        assert!(disabled.get("doc1").is_none());
+
+        let cache = DocContextCache::new(Duration::from_mins(1), 2);
+        cache.insert("doc1", context("doc1"));
+        cache.invalidate("doc1");
+        assert!(cache.get("doc1").is_none());
+    }
+}
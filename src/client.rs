@@ -1,12 +1,62 @@
 use flate2::read::GzDecoder;
-use reqwest::Client;
+use reqwest::{Client, Method};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Write as _;
 use std::io::Read;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use crate::breaker::CircuitBreaker;
 use crate::config::Config;
-use crate::error::CodaError;
+use crate::echo;
+use crate::error::{CodaError, ErrorCategory};
+
+/// Download hosts probed by `check_connectivity`, alongside the API base URL
+#[cfg(not(test))]
+const CONNECTIVITY_PROBE_URLS: &[&str] = &[
+    "https://coda.io",
+    "https://codahosted.io",
+    "https://storage.googleapis.com",
+];
+
+#[cfg(test)]
+const CONNECTIVITY_PROBE_URLS: &[&str] = &["http://127.0.0.1:1"];
+
+/// Content fetched by `download_raw`, with integrity info for the bytes received
+#[derive(Debug, Clone)]
+pub struct Download {
+    pub content: String,
+    /// Length of the raw response body, before gzip decoding
+    pub byte_len: usize,
+    /// Hex-encoded SHA-256 of the raw response body
+    pub sha256: String,
+}
+
+/// Per-host timeout for connectivity probes
+const CONNECTIVITY_PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// Response headers never captured or logged, even with `CODA_DEBUG_HEADERS`
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// Response headers as (name, value) pairs, in response order
+pub type HeaderList = Vec<(String, String)>;
+
+/// First wait before retrying a 429 without `Retry-After`, doubled on each retry
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest `Retry-After` honored, so a bad header can't stall a tool call
+const MAX_RETRY_AFTER: Duration = Duration::from_mins(1);
+
+/// Number of recent API calls kept for diagnostics
+const RECENT_REQUESTS_CAPACITY: usize = 50;
 
 /// Trusted hosts for downloading export content
 #[cfg(not(test))]
@@ -21,11 +71,76 @@ const ALLOWED_DOWNLOAD_HOSTS: &[&str] = &[
     "localhost",
 ];
 
+/// Whether `host` is an allowed download host or a subdomain of one
+fn is_allowed_download_host(host: &str) -> bool {
+    ALLOWED_DOWNLOAD_HOSTS.iter().any(|h| {
+        host == *h
+            || host
+                .strip_suffix(h)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+tokio::task_local! {
+    /// Name of the tool being handled, so API calls can be attributed to it
+    pub static CURRENT_TOOL: String;
+}
+
+/// Outcome of a single API call, kept in the recent-requests buffer
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestRecord {
+    /// Tool whose handler made the call, if it ran through `call_tool`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    pub method: String,
+    pub path: String,
+    /// When the call started, as RFC 3339
+    pub started_at: String,
+    pub elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<ErrorCategory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RequestRecord {
+    pub fn is_error(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// Result of probing a single host for reachability
+#[derive(Debug, Clone, Serialize)]
+pub struct HostProbe {
+    pub url: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct CodaClient {
     client: Client,
     base_url: String,
     api_token: String,
+    recent: Arc<Mutex<VecDeque<RequestRecord>>>,
+    debug_headers: bool,
+    strict_deserialize: bool,
+    last_headers: Arc<Mutex<Option<HeaderList>>>,
+    breaker: Arc<CircuitBreaker>,
+    /// `host[:port]` of `base_url`, the key for the API's circuit
+    api_host: String,
+    /// Times a 429 response is retried before `RateLimited` is returned
+    rate_limit_retries: u32,
+    /// First wait between 429 retries when there is no `Retry-After`; doubles each retry
+    rate_limit_backoff: Duration,
+    /// Cap on an export download's size, raw and after gzip decoding
+    max_download_bytes: usize,
 }
 
 impl CodaClient {
@@ -36,7 +151,7 @@ impl CodaClient {
         // - Set reasonable timeouts
         let client = Client::builder()
             .pool_max_idle_per_host(0) // Disable connection pooling
-            .timeout(Duration::from_secs(60))
+            .timeout(Duration::from_mins(1))
             .connect_timeout(Duration::from_secs(30))
             .build()
             .expect("Failed to create HTTP client");
@@ -45,42 +160,121 @@ impl CodaClient {
             client,
             base_url: config.base_url.clone(),
             api_token: config.api_token.clone(),
+            recent: Arc::default(),
+            debug_headers: config.debug_headers,
+            strict_deserialize: config.strict_deserialize,
+            last_headers: Arc::default(),
+            breaker: Arc::new(CircuitBreaker::new(
+                config.circuit_failure_threshold,
+                Duration::from_secs(config.circuit_window_secs),
+                Duration::from_secs(config.circuit_cooldown_secs),
+            )),
+            api_host: host_key(&config.base_url),
+            rate_limit_retries: config.rate_limit_retries,
+            rate_limit_backoff: RATE_LIMIT_BACKOFF,
+            max_download_bytes: config.max_download_bytes,
         }
     }
 
-    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, CodaError> {
-        let url = format!("{}{}", self.base_url, path);
+    /// Recent API calls, oldest first
+    pub fn recent_requests(&self) -> Vec<RequestRecord> {
+        self.recent
+            .lock()
+            .map(|r| r.iter().cloned().collect())
+            .unwrap_or_default()
+    }
 
-        tracing::info!("GET {}", url);
+    /// Recent API calls, oldest first, emptying the buffer
+    pub fn drain_recent_requests(&self) -> Vec<RequestRecord> {
+        self.recent
+            .lock()
+            .map(|mut r| r.drain(..).collect())
+            .unwrap_or_default()
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .send()
-            .await?;
+    /// The most recent failed API call, if any is still in the buffer
+    pub fn last_error(&self) -> Option<RequestRecord> {
+        self.recent
+            .lock()
+            .ok()
+            .and_then(|r| r.iter().rev().find(|r| r.is_error()).cloned())
+    }
 
-        let status = response.status();
-        tracing::debug!("Response status: {}", status);
+    /// Whether `CODA_DEBUG_HEADERS` capture is enabled
+    pub fn debug_headers_enabled(&self) -> bool {
+        self.debug_headers
+    }
 
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            tracing::error!("API error {}: {}", status.as_u16(), body);
-            return Err(match status.as_u16() {
-                401 => CodaError::Unauthorized,
-                403 => CodaError::Forbidden,
-                404 => CodaError::NotFound,
-                429 => CodaError::RateLimited,
-                _ => CodaError::Api {
-                    status: status.as_u16(),
-                    body,
-                },
-            });
+    /// Headers of the most recent API response, with sensitive headers removed
+    pub fn last_response_headers(&self) -> Option<HeaderList> {
+        self.last_headers.lock().ok().and_then(|h| h.clone())
+    }
+
+    fn capture_headers(&self, headers: &reqwest::header::HeaderMap) {
+        if !self.debug_headers {
+            return;
         }
+        let filtered = filter_headers(headers, &self.api_token);
+        tracing::debug!("Response headers: {:?}", filtered);
+        if let Ok(mut last) = self.last_headers.lock() {
+            *last = Some(filtered);
+        }
+    }
 
-        let body = response.text().await?;
-        tracing::debug!("Response body: {}", body);
-        Ok(serde_json::from_str(&body)?)
+    /// Deserialize a response body. In strict mode, fields the model
+    /// ignores are collected while parsing and logged.
+    fn parse_body<T: DeserializeOwned>(&self, path: &str, body: &str) -> Result<T, CodaError> {
+        if !self.strict_deserialize {
+            return Ok(serde_json::from_str(body)?);
+        }
+        let mut unknown = BTreeSet::new();
+        let mut deserializer = serde_json::Deserializer::from_str(body);
+        let parsed: T = serde_ignored::deserialize(&mut deserializer, |field| {
+            unknown.insert(field_path(&field));
+        })?;
+        deserializer.end()?;
+        if !unknown.is_empty() {
+            let fields: Vec<String> = unknown.into_iter().collect();
+            tracing::warn!(
+                "Unrecognized fields in response from {}: {}",
+                path,
+                fields.join(", ")
+            );
+        }
+        Ok(parsed)
+    }
+
+    fn record<T>(
+        &self,
+        method: &Method,
+        path: &str,
+        started: Instant,
+        result: &Result<T, CodaError>,
+    ) {
+        let err = result.as_ref().err();
+        let elapsed = started.elapsed();
+        let started_at = chrono::Utc::now()
+            - chrono::TimeDelta::from_std(elapsed).unwrap_or(chrono::TimeDelta::zero());
+        let record = RequestRecord {
+            tool: CURRENT_TOOL.try_with(Clone::clone).ok(),
+            method: method.to_string(),
+            path: echo::mask_path(path, &self.api_token),
+            started_at: started_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            elapsed_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+            status: err.and_then(CodaError::status),
+            category: err.map(CodaError::category),
+            error: err.map(ToString::to_string),
+        };
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_REQUESTS_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(record);
+        }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, CodaError> {
+        self.request(Method::GET, path, None::<&()>).await
     }
 
     pub async fn post<T: DeserializeOwned, B: Serialize>(
@@ -88,62 +282,141 @@ impl CodaClient {
         path: &str,
         body: &B,
     ) -> Result<T, CodaError> {
-        let url = format!("{}{}", self.base_url, path);
-        tracing::info!("POST {}", url);
-
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(body)
-            .send()
-            .await?;
+        self.request(Method::POST, path, Some(body)).await
+    }
 
-        let status = response.status();
-        tracing::debug!("Response status: {}", status);
+    pub async fn put<T: DeserializeOwned, B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, CodaError> {
+        self.request(Method::PUT, path, Some(body)).await
+    }
 
-        if !status.is_success() && status.as_u16() != 202 {
-            let body = response.text().await.unwrap_or_default();
-            tracing::error!("API error {}: {}", status.as_u16(), body);
-            return Err(match status.as_u16() {
-                401 => CodaError::Unauthorized,
-                403 => CodaError::Forbidden,
-                404 => CodaError::NotFound,
-                429 => CodaError::RateLimited,
-                _ => CodaError::Api {
-                    status: status.as_u16(),
-                    body,
-                },
-            });
-        }
+    pub async fn patch<T: DeserializeOwned, B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, CodaError> {
+        self.request(Method::PATCH, path, Some(body)).await
+    }
 
-        let body = response.text().await?;
-        tracing::debug!("Response body: {}", body);
-        Ok(serde_json::from_str(&body)?)
+    /// Delete responses carry nothing callers need, so the parsed body is dropped
+    pub async fn delete(&self, path: &str) -> Result<(), CodaError> {
+        self.request::<serde_json::Value, ()>(Method::DELETE, path, None)
+            .await
+            .map(|_| ())
     }
 
-    pub async fn put<T: DeserializeOwned, B: Serialize>(
+    /// DELETE with a JSON payload, for bulk deletes that name their targets
+    /// in the body; the response is parsed like any other
+    pub async fn delete_with_body<T: DeserializeOwned, B: Serialize>(
         &self,
         path: &str,
         body: &B,
     ) -> Result<T, CodaError> {
-        let url = format!("{}{}", self.base_url, path);
-        tracing::debug!("PUT {}", url);
+        self.request(Method::DELETE, path, Some(body)).await
+    }
 
-        let response = self
-            .client
-            .put(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
-            .header("Content-Type", "application/json")
-            .json(body)
-            .send()
-            .await?;
+    /// Send a request and parse its JSON response, recording the call.
+    ///
+    /// A `204 No Content` (or an empty body on any success status) yields
+    /// `None`/`null` for `Option<T>`/`Value` callers and
+    /// `CodaError::NoContent` for anything else.
+    async fn request<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T, CodaError> {
+        let started = Instant::now();
+        let result = match self.send(&method, path, body).await {
+            Ok(text) if text.trim().is_empty() => {
+                tracing::debug!("No content");
+                serde_json::from_value(serde_json::Value::Null).map_err(|_| CodaError::NoContent)
+            }
+            Ok(text) => self.parse_body(path, &text),
+            Err(e) => Err(e),
+        };
+        self.record(&method, path, started, &result);
+        result
+    }
 
+    /// Send an authenticated request and return the body of any 2xx response
+    /// (including `202 Accepted` from async mutations). Error statuses map to
+    /// `CodaError` here, for every method.
+    async fn send<B: Serialize>(
+        &self,
+        method: &Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<String, CodaError> {
+        let url = format!("{}{}", self.base_url, path);
+        tracing::info!("{} {}", method, url);
+        echo::record(method.as_str(), path, &self.api_token, body);
+
+        let mut attempt = 0;
+        let response = loop {
+            let permit = match self.breaker.check(&self.api_host) {
+                Ok(permit) => permit,
+                Err(remaining) => {
+                    return Err(CodaError::CircuitOpen {
+                        host: self.api_host.clone(),
+                        retry_after_secs: remaining.as_secs().max(1),
+                    });
+                }
+            };
+
+            let mut request = self
+                .client
+                .request(method.clone(), &url)
+                .header("Authorization", format!("Bearer {}", self.api_token));
+            if let Some(body) = body {
+                request = request
+                    .header("Content-Type", "application/json")
+                    .json(body);
+            }
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    permit.record_failure();
+                    return Err(e.into());
+                }
+            };
+
+            let status = response.status();
+            self.capture_headers(response.headers());
+            tracing::debug!("Response status: {}", status);
+            // Client errors mean the host is up; only outages count against it
+            if status.is_server_error() {
+                permit.record_failure();
+            } else {
+                permit.record_success();
+            }
+
+            if status != reqwest::StatusCode::TOO_MANY_REQUESTS
+                || attempt >= self.rate_limit_retries
+            {
+                break response;
+            }
+            let delay = retry_after(response.headers())
+                .unwrap_or_else(|| self.rate_limit_backoff.saturating_mul(1 << attempt.min(16)));
+            attempt += 1;
+            tracing::warn!(
+                "Rate limited on {} {}, retry {}/{} in {:?}",
+                method,
+                path,
+                attempt,
+                self.rate_limit_retries,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+        };
         let status = response.status();
 
-        if !status.is_success() && status.as_u16() != 202 {
+        if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
+            tracing::error!("API error {}: {}", status.as_u16(), body);
             return Err(match status.as_u16() {
                 401 => CodaError::Unauthorized,
                 403 => CodaError::Forbidden,
@@ -156,45 +429,71 @@ impl CodaClient {
             });
         }
 
+        if status == reqwest::StatusCode::NO_CONTENT {
+            return Ok(String::new());
+        }
         let body = response.text().await?;
-        tracing::trace!("Response: {}", body);
-        Ok(serde_json::from_str(&body)?)
+        tracing::debug!("Response body: {}", body);
+        Ok(body)
     }
 
-    pub async fn delete(&self, path: &str) -> Result<(), CodaError> {
-        let url = format!("{}{}", self.base_url, path);
-        tracing::debug!("DELETE {}", url);
+    /// Probe the API base URL and each download host.
+    ///
+    /// Sends an unauthenticated HEAD request; any HTTP response (even 401/404)
+    /// counts as reachable, since only network-level failures matter here.
+    pub async fn check_connectivity(&self) -> Vec<HostProbe> {
+        let urls = std::iter::once(self.base_url.clone())
+            .chain(CONNECTIVITY_PROBE_URLS.iter().map(ToString::to_string));
+        futures::future::join_all(urls.map(|url| self.probe(url))).await
+    }
 
-        let response = self
+    async fn probe(&self, url: String) -> HostProbe {
+        let started = Instant::now();
+        let result = self
             .client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.api_token))
+            .head(&url)
+            .timeout(Duration::from_secs(CONNECTIVITY_PROBE_TIMEOUT_SECS))
             .send()
-            .await?;
-
-        let status = response.status();
+            .await;
+        let latency_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
 
-        if !status.is_success() && status.as_u16() != 202 {
-            let body = response.text().await.unwrap_or_default();
-            return Err(match status.as_u16() {
-                401 => CodaError::Unauthorized,
-                403 => CodaError::Forbidden,
-                404 => CodaError::NotFound,
-                429 => CodaError::RateLimited,
-                _ => CodaError::Api {
-                    status: status.as_u16(),
-                    body,
-                },
-            });
+        match result {
+            Ok(response) => HostProbe {
+                url,
+                reachable: true,
+                status: Some(response.status().as_u16()),
+                latency_ms,
+                error: None,
+            },
+            Err(e) => {
+                tracing::warn!("Connectivity probe to {} failed: {}", url, e);
+                HostProbe {
+                    url,
+                    reachable: false,
+                    status: None,
+                    latency_ms,
+                    error: Some(e.to_string()),
+                }
+            }
         }
-
-        Ok(())
     }
 
     /// Download raw content from an external URL (used for export downloads)
     /// Automatically decompresses gzip content if detected
     /// Only allows downloads from trusted Coda-related hosts
-    pub async fn download_raw(&self, url: &str) -> Result<String, CodaError> {
+    /// Fails with `DownloadTooLarge` once the body, or its decompressed form,
+    /// passes `max_download_bytes`
+    pub async fn download_raw(&self, url: &str) -> Result<Download, CodaError> {
+        self.download_raw_with_progress(url, |_, _| {}).await
+    }
+
+    /// `download_raw`, calling `on_progress` with the bytes received so far
+    /// and the `Content-Length`, if sent, after each chunk of the body
+    pub async fn download_raw_with_progress(
+        &self,
+        url: &str,
+        mut on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<Download, CodaError> {
         // Validate URL is from a trusted host
         let parsed = url::Url::parse(url).map_err(|e| CodaError::Api {
             status: 0,
@@ -203,7 +502,7 @@ impl CodaClient {
 
         let host = parsed.host_str().unwrap_or("");
 
-        if !ALLOWED_DOWNLOAD_HOSTS.iter().any(|h| host.ends_with(h)) {
+        if !is_allowed_download_host(host) {
             tracing::warn!("Blocked download from untrusted host: {}", host);
             return Err(CodaError::Api {
                 status: 0,
@@ -213,7 +512,7 @@ impl CodaClient {
 
         tracing::debug!("Downloading from external URL: {}", url);
 
-        let response = self.client.get(url).send().await?;
+        let mut response = self.client.get(url).send().await?;
 
         let status = response.status();
 
@@ -225,33 +524,74 @@ impl CodaClient {
             });
         }
 
-        let bytes = response.bytes().await?;
+        let content_length = response.content_length();
+        let max_bytes = self.max_download_bytes;
+        let too_large = || {
+            tracing::warn!("Download from {} exceeded {} bytes", host, max_bytes);
+            CodaError::DownloadTooLarge { max_bytes }
+        };
+        if content_length.is_some_and(|len| len > max_bytes as u64) {
+            return Err(too_large());
+        }
+
+        // Read in chunks so an oversized body is abandoned as soon as it passes the cap
+        let mut bytes = Vec::new();
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = response.chunk().await? {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(too_large());
+            }
+            hasher.update(&chunk);
+            bytes.extend_from_slice(&chunk);
+            on_progress(bytes.len() as u64, content_length);
+        }
         tracing::debug!("Downloaded {} bytes", bytes.len());
 
+        let sha256 = hasher
+            .finalize()
+            .iter()
+            .fold(String::new(), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            });
+
         // Check for gzip magic bytes (0x1f, 0x8b)
-        if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
+        let content = if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
             tracing::debug!("Detected gzip content, decompressing...");
-            let mut decoder = GzDecoder::new(&bytes[..]);
-            let mut decompressed = String::new();
+            // A small gzip can expand enormously, so cap the decoded size too
+            let mut decoder = GzDecoder::new(&bytes[..]).take(max_bytes as u64 + 1);
+            let mut decompressed = Vec::new();
             decoder
-                .read_to_string(&mut decompressed)
+                .read_to_end(&mut decompressed)
                 .map_err(|e| CodaError::Api {
                     status: 0,
                     body: format!("Failed to decompress gzip: {e}"),
                 })?;
+            if decompressed.len() > max_bytes {
+                return Err(too_large());
+            }
             tracing::debug!("Decompressed to {} bytes", decompressed.len());
-            Ok(decompressed)
+            String::from_utf8(decompressed).map_err(|e| CodaError::Api {
+                status: 0,
+                body: format!("Failed to decompress gzip: {e}"),
+            })?
         } else {
             // Not gzip, return as string
-            Ok(String::from_utf8_lossy(&bytes).to_string())
-        }
+            String::from_utf8_lossy(&bytes).to_string()
+        };
+
+        Ok(Download {
+            content,
+            byte_len: bytes.len(),
+            sha256,
+        })
     }
 
     #[cfg(test)]
     pub fn new_with_base_url(api_token: &str, base_url: &str) -> Self {
         let client = Client::builder()
             .pool_max_idle_per_host(0)
-            .timeout(Duration::from_secs(60))
+            .timeout(Duration::from_mins(1))
             .connect_timeout(Duration::from_secs(30))
             .build()
             .expect("Failed to create HTTP client");
@@ -260,15 +600,105 @@ impl CodaClient {
             client,
             base_url: base_url.to_string(),
             api_token: api_token.to_string(),
+            recent: Arc::default(),
+            debug_headers: false,
+            strict_deserialize: false,
+            last_headers: Arc::default(),
+            breaker: Arc::new(CircuitBreaker::new(0, Duration::ZERO, Duration::ZERO)),
+            api_host: host_key(base_url),
+            rate_limit_retries: 0,
+            rate_limit_backoff: Duration::ZERO,
+            max_download_bytes: Config::default().max_download_bytes,
         }
     }
+
+    /// Retry 429 responses, which clients built for tests don't
+    #[cfg(test)]
+    pub fn with_rate_limit_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.rate_limit_retries = retries;
+        self.rate_limit_backoff = backoff;
+        self
+    }
+
+    /// Lower the download cap so tests can exceed it
+    #[cfg(test)]
+    pub fn with_max_download_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_download_bytes = max_bytes;
+        self
+    }
+
+    /// Replace the circuit breaker, e.g. to enable it in tests
+    #[cfg(test)]
+    pub fn with_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Arc::new(breaker);
+        self
+    }
+}
+
+/// Wait requested by a `Retry-After` header in seconds, capped at
+/// `MAX_RETRY_AFTER`. The HTTP-date form is not supported.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let secs: u64 = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+/// `host[:port]` of a URL, falling back to the URL itself if it doesn't parse
+fn host_key(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            let host = u.host_str()?.to_string();
+            Some(match u.port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host,
+            })
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Dotted path of an ignored field, with array elements shown as `field[]`
+fn field_path(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, .. } => format!("{}[]", field_path(parent)),
+        serde_ignored::Path::Map { parent, key } => match field_path(parent) {
+            prefix if prefix.is_empty() => key.clone(),
+            prefix => format!("{prefix}.{key}"),
+        },
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => field_path(parent),
+    }
+}
+
+/// Drop credential-bearing headers and any value that echoes the API token
+fn filter_headers(headers: &reqwest::header::HeaderMap, api_token: &str) -> HeaderList {
+    headers
+        .iter()
+        .filter(|(name, _)| !SENSITIVE_HEADERS.contains(&name.as_str()))
+        .map(|(name, value)| {
+            (
+                name.as_str().to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .filter(|(_, value)| api_token.is_empty() || !value.contains(api_token))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::Config;
-    use wiremock::matchers::{header, method, path};
+    use crate::models::Doc;
+    use serde::Deserialize;
+    use wiremock::matchers::{body_json, header, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
@@ -276,6 +706,7 @@ mod tests {
         let config = Config {
             api_token: "test_token_abc".to_string(),
             base_url: "https://coda.io/apis/v1".to_string(),
+            ..Config::default()
         };
         let client = CodaClient::new(&config);
         assert_eq!(client.base_url, "https://coda.io/apis/v1");
@@ -366,6 +797,261 @@ mod tests {
         assert!(matches!(result, Err(CodaError::Unauthorized)));
     }
 
+    #[tokio::test]
+    async fn test_recent_requests_tracks_last_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/d1/tables/t1/rows"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        assert!(client.last_error().is_none());
+
+        let _: Result<serde_json::Value, _> = client
+            .post("/docs/d1/tables/t1/rows", &serde_json::json!({}))
+            .await;
+        let _: serde_json::Value = client.get("/docs").await.unwrap();
+
+        let recent = client.recent_requests();
+        assert_eq!(recent.len(), 2);
+        assert!(!recent[1].is_error());
+
+        let last = client.last_error().unwrap();
+        assert_eq!(last.method, "POST");
+        assert_eq!(last.path, "/docs/d1/tables/t1/rows");
+        assert_eq!(last.status, Some(403));
+        assert_eq!(last.category, Some(ErrorCategory::Forbidden));
+    }
+
+    #[tokio::test]
+    async fn test_recent_requests_mask_query_secrets() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let _: serde_json::Value = client
+            .get("/docs?apiKey=hunter2&query=test_token&limit=5")
+            .await
+            .unwrap();
+
+        let recent = client.recent_requests();
+        assert_eq!(
+            recent[0].path,
+            "/docs?apiKey=[REDACTED]&query=[REDACTED]&limit=5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recent_requests_capped() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        for i in 0..=RECENT_REQUESTS_CAPACITY {
+            let _: serde_json::Value = client.get(&format!("/docs/d{i}")).await.unwrap();
+        }
+
+        let recent = client.recent_requests();
+        assert_eq!(recent.len(), RECENT_REQUESTS_CAPACITY);
+        assert_eq!(recent[0].path, "/docs/d1");
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let probes = client.check_connectivity().await;
+
+        assert_eq!(probes.len(), 1 + CONNECTIVITY_PROBE_URLS.len());
+        assert_eq!(probes[0].url, mock_server.uri());
+        assert!(probes[0].reachable);
+        assert_eq!(probes[0].status, Some(401));
+
+        assert_eq!(probes[1].url, "http://127.0.0.1:1");
+        assert!(!probes[1].reachable);
+        assert!(probes[1].error.is_some());
+
+        // Probes are unauthenticated
+        let requests = mock_server.received_requests().await.unwrap();
+        assert!(requests[0].headers.get("Authorization").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_debug_headers_filters_sensitive() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({}))
+                    .insert_header("x-ratelimit-remaining", "99")
+                    .insert_header("set-cookie", "session=abc123")
+                    .insert_header("authorization", "Bearer secret_token")
+                    .insert_header("x-echo", "token=secret_token"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let config = Config {
+            api_token: "secret_token".to_string(),
+            base_url: mock_server.uri(),
+            debug_headers: true,
+            ..Config::default()
+        };
+        let client = CodaClient::new(&config);
+        let _: serde_json::Value = client.get("/docs").await.unwrap();
+
+        let headers = client.last_response_headers().unwrap();
+        assert!(headers
+            .iter()
+            .any(|(k, v)| k == "x-ratelimit-remaining" && v == "99"));
+        assert!(!headers.iter().any(|(k, _)| k == "set-cookie"));
+        assert!(!headers.iter().any(|(k, _)| k == "authorization"));
+        assert!(!headers.iter().any(|(_, v)| v.contains("secret_token")));
+    }
+
+    /// Tracing writer that appends to a shared buffer
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// GET `/docs/doc1` as a `Doc` and return the warnings logged meanwhile
+    async fn strict_get_logs(strict_deserialize: bool) -> String {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Roadmap",
+                "owner": null,
+                "brandNewField": true,
+                "workspace": {"id": "ws-1"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let config = Config {
+            base_url: mock_server.uri(),
+            strict_deserialize,
+            ..Config::default()
+        };
+        let client = CodaClient::new(&config);
+
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::WARN)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let doc: Doc = client.get("/docs/doc1").await.unwrap();
+        assert_eq!(doc.name, "Roadmap");
+
+        let bytes = logs.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_strict_deserialize_warns_on_unknown_fields() {
+        let logs = strict_get_logs(true).await;
+
+        assert!(logs.contains("Unrecognized fields in response from /docs/doc1"));
+        assert!(logs.contains("brandNewField, workspace"));
+        // Known fields sent as null are not reported
+        assert!(!logs.contains("owner"));
+    }
+
+    #[tokio::test]
+    async fn test_strict_deserialize_off_by_default() {
+        let logs = strict_get_logs(false).await;
+
+        assert!(!logs.contains("Unrecognized fields"));
+    }
+
+    #[test]
+    fn test_field_path_nested() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Item {
+            id: String,
+        }
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct List {
+            items: Vec<Item>,
+            next: Option<Item>,
+        }
+
+        let body = r#"{
+            "items": [{"id": "a", "extra": 1}, {"id": "b", "extra": 2}],
+            "next": {"id": "c", "extra": 3},
+            "total": 3
+        }"#;
+        let mut unknown = BTreeSet::new();
+        let mut deserializer = serde_json::Deserializer::from_str(body);
+        let _: List = serde_ignored::deserialize(&mut deserializer, |field| {
+            unknown.insert(field_path(&field));
+        })
+        .unwrap();
+
+        assert_eq!(
+            unknown.into_iter().collect::<Vec<_>>(),
+            ["items[].extra", "next.extra", "total"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_debug_headers_disabled_by_default() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let _: serde_json::Value = client.get("/docs").await.unwrap();
+
+        assert!(!client.debug_headers_enabled());
+        assert!(client.last_response_headers().is_none());
+    }
+
     #[tokio::test]
     async fn test_post_success() {
         let mock_server = MockServer::start().await;
@@ -412,6 +1098,24 @@ mod tests {
         assert_eq!(result["requestId"], "req456");
     }
 
+    #[tokio::test]
+    async fn test_patch_success() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/docs/doc1"))
+            .and(header("Content-Type", "application/json"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let body = serde_json::json!({"title": "Renamed"});
+        let result: serde_json::Value = client.patch("/docs/doc1", &body).await.unwrap();
+
+        assert!(result.is_object());
+    }
+
     #[tokio::test]
     async fn test_delete_success() {
         let mock_server = MockServer::start().await;
@@ -428,6 +1132,122 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_delete_parses_and_records_like_other_verbs() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({})))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc2"))
+            .respond_with(ResponseTemplate::new(202).set_body_string("not json"))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        client.delete("/docs/doc1").await.unwrap();
+        assert!(matches!(
+            client.delete("/docs/doc2").await,
+            Err(CodaError::Json(_))
+        ));
+
+        let recent = client.recent_requests();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].method, "DELETE");
+        assert!(!recent[0].is_error());
+        assert!(recent[1].is_error());
+    }
+
+    #[tokio::test]
+    async fn test_delete_with_body_sends_json_payload() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(header("Content-Type", "application/json"))
+            .and(body_json(serde_json::json!({"rowIds": ["r1", "r2"]})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-1",
+                "rowIds": ["r1", "r2"]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let body = serde_json::json!({"rowIds": ["r1", "r2"]});
+        let result: serde_json::Value = client
+            .delete_with_body("/docs/doc1/tables/tbl1/rows", &body)
+            .await
+            .unwrap();
+
+        assert_eq!(result["requestId"], "req-1");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_retries_then_succeeds() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"id": "doc1", "name": "Doc"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri())
+            .with_rate_limit_retries(3, Duration::from_secs(30));
+        let doc: Doc = client.get("/docs/doc1").await.unwrap();
+
+        assert_eq!(doc.id, "doc1");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_gives_up_after_retries() {
+        let mock_server = MockServer::start().await;
+
+        // No Retry-After, so the (zero) exponential backoff is used
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows/row1"))
+            .respond_with(ResponseTemplate::new(429))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri())
+            .with_rate_limit_retries(2, Duration::ZERO);
+        let result = client.delete("/docs/doc1/tables/tbl1/rows/row1").await;
+
+        assert!(matches!(result, Err(CodaError::RateLimited)));
+    }
+
+    #[test]
+    fn test_retry_after_parsing() {
+        let headers = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(retry_after(&headers("2")), Some(Duration::from_secs(2)));
+        assert_eq!(retry_after(&headers("86400")), Some(MAX_RETRY_AFTER));
+        assert_eq!(retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")), None);
+        assert_eq!(retry_after(&reqwest::header::HeaderMap::new()), None);
+    }
+
     #[tokio::test]
     async fn test_delete_rate_limited() {
         let mock_server = MockServer::start().await;
@@ -460,6 +1280,179 @@ mod tests {
         assert!(matches!(result, Err(CodaError::Json(_))));
     }
 
+    #[tokio::test]
+    async fn test_get_no_content() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+
+        let optional: Option<Doc> = client.get("/docs/doc1").await.unwrap();
+        assert!(optional.is_none());
+
+        let required: Result<Doc, _> = client.get("/docs/doc1").await;
+        let err = required.unwrap_err();
+        assert!(matches!(err, CodaError::NoContent));
+        assert_eq!(err.status(), Some(204));
+    }
+
+    #[tokio::test]
+    async fn test_accepted_and_no_content_consistent_across_methods() {
+        let mock_server = MockServer::start().await;
+
+        for verb in ["GET", "POST", "PUT", "PATCH"] {
+            Mock::given(method(verb))
+                .and(path("/docs/doc1/accepted"))
+                .respond_with(
+                    ResponseTemplate::new(202)
+                        .set_body_json(serde_json::json!({"requestId": "req-1"})),
+                )
+                .mount(&mock_server)
+                .await;
+            Mock::given(method(verb))
+                .and(path("/docs/doc1/empty"))
+                .respond_with(ResponseTemplate::new(204))
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let body = serde_json::json!({});
+        let accepted: Vec<serde_json::Value> = vec![
+            client.get("/docs/doc1/accepted").await.unwrap(),
+            client.post("/docs/doc1/accepted", &body).await.unwrap(),
+            client.put("/docs/doc1/accepted", &body).await.unwrap(),
+            client.patch("/docs/doc1/accepted", &body).await.unwrap(),
+        ];
+        for response in accepted {
+            assert_eq!(response["requestId"], "req-1");
+        }
+
+        let empty: Vec<Result<Doc, CodaError>> = vec![
+            client.get("/docs/doc1/empty").await,
+            client.post("/docs/doc1/empty", &body).await,
+            client.put("/docs/doc1/empty", &body).await,
+            client.patch("/docs/doc1/empty", &body).await,
+        ];
+        for result in empty {
+            assert!(matches!(result, Err(CodaError::NoContent)));
+        }
+
+        let methods: Vec<String> = client
+            .recent_requests()
+            .into_iter()
+            .map(|r| r.method)
+            .collect();
+        assert_eq!(methods[..4], ["GET", "POST", "PUT", "PATCH"]);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_after_server_errors_and_half_opens() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1", "name": "Doc"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri()).with_breaker(
+            CircuitBreaker::new(2, Duration::from_mins(1), Duration::from_millis(50)),
+        );
+
+        for _ in 0..2 {
+            let err = client.get::<Doc>("/docs/doc1").await.unwrap_err();
+            assert_eq!(err.status(), Some(503));
+        }
+        // Open: fails fast without a request
+        let err = client.get::<Doc>("/docs/doc1").await.unwrap_err();
+        assert!(matches!(err, CodaError::CircuitOpen { .. }));
+        assert_eq!(err.category(), ErrorCategory::Unavailable);
+
+        // Half-open after the cooldown: the probe succeeds and closes the circuit
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let doc: Doc = client.get("/docs/doc1").await.unwrap();
+        assert_eq!(doc.id, "doc1");
+    }
+
+    #[tokio::test]
+    async fn test_circuit_cancelled_probe_lets_next_call_probe() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        // The first probe hangs until its caller gives up
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1", "name": "Doc"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri()).with_breaker(
+            CircuitBreaker::new(1, Duration::from_mins(1), Duration::from_millis(50)),
+        );
+
+        client.get::<Doc>("/docs/doc1").await.unwrap_err();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(100), client.get::<Doc>("/docs/doc1")).await;
+        assert!(cancelled.is_err());
+
+        // The dropped probe doesn't leave the circuit stuck open
+        let doc: Doc = client.get("/docs/doc1").await.unwrap();
+        assert_eq!(doc.id, "doc1");
+    }
+
+    #[tokio::test]
+    async fn test_circuit_ignores_client_errors() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri()).with_breaker(
+            CircuitBreaker::new(2, Duration::from_mins(1), Duration::from_mins(1)),
+        );
+
+        for _ in 0..3 {
+            let err = client.get::<Doc>("/docs/missing").await.unwrap_err();
+            assert!(matches!(err, CodaError::NotFound));
+        }
+    }
+
     // Note: download_raw_success test removed because it used localhost which is now
     // blocked by the trusted host validation. The functionality is tested via
     // test_download_raw_allows_trusted_hosts which validates the host allowlist.
@@ -779,17 +1772,30 @@ mod tests {
     async fn test_download_raw_rejects_subdomain_spoofing() {
         let client = CodaClient::new_with_base_url("test_token", "https://api.coda.io");
 
-        // A host that ends with a trusted domain but isn't one
-        let result = client.download_raw("https://evil-coda.io/file").await;
-
-        match result {
-            Err(CodaError::Api { body, .. }) if body.contains("Untrusted") => {}
-            // evil-coda.io ends_with coda.io — this is a known limitation
-            // If it passes validation, that's a finding worth noting
-            _ => {}
+        // Hosts that end with a trusted domain but aren't one
+        for url in ["https://evil-coda.io/file", "https://notcoda.io/file"] {
+            match client.download_raw(url).await {
+                Err(CodaError::Api { status, body }) => {
+                    assert_eq!(status, 0);
+                    assert!(body.contains("Untrusted download host"), "{url}: {body}");
+                }
+                other => panic!("Expected {url} to be rejected, got: {other:?}"),
+            }
         }
     }
 
+    #[test]
+    fn test_is_allowed_download_host() {
+        assert!(is_allowed_download_host("coda.io"));
+        assert!(is_allowed_download_host("export.codahosted.io"));
+        assert!(is_allowed_download_host("a.b.storage.googleapis.com"));
+        assert!(!is_allowed_download_host("evil-coda.io"));
+        assert!(!is_allowed_download_host("notcoda.io"));
+        assert!(!is_allowed_download_host("coda.io.evil.com"));
+        assert!(!is_allowed_download_host(".coda.io.example"));
+        assert!(!is_allowed_download_host(""));
+    }
+
     #[tokio::test]
     async fn test_download_raw_url_without_host() {
         let client = CodaClient::new_with_base_url("test_token", "https://api.coda.io");
@@ -824,11 +1830,33 @@ mod tests {
 
         let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
         let url = format!("{}/export/file.html", mock_server.uri());
-        let content = client.download_raw(&url).await.unwrap();
+        let content = client.download_raw(&url).await.unwrap().content;
 
         assert_eq!(content, "<html><body>Hello</body></html>");
     }
 
+    #[tokio::test]
+    async fn test_download_raw_checksum() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/export/abc.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("abc"))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let url = format!("{}/export/abc.txt", mock_server.uri());
+        let download = client.download_raw(&url).await.unwrap();
+
+        assert_eq!(download.byte_len, 3);
+        // SHA-256 test vector for "abc" (FIPS 180-2)
+        assert_eq!(
+            download.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
     #[tokio::test]
     async fn test_download_raw_gzip_content() {
         use flate2::write::GzEncoder;
@@ -851,11 +1879,105 @@ mod tests {
 
         let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
         let url = format!("{}/export/file.html.gz", mock_server.uri());
-        let content = client.download_raw(&url).await.unwrap();
+        let content = client.download_raw(&url).await.unwrap().content;
 
         assert_eq!(content, original);
     }
 
+    #[tokio::test]
+    async fn test_download_raw_rejects_oversized_body() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/export/big.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(2048)))
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/export/big.html", mock_server.uri());
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri())
+            .with_max_download_bytes(1024);
+        let err = client.download_raw(&url).await.unwrap_err();
+        assert!(
+            matches!(err, CodaError::DownloadTooLarge { max_bytes: 1024 }),
+            "{err:?}"
+        );
+
+        // Exactly at the cap is fine
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri())
+            .with_max_download_bytes(2048);
+        assert_eq!(client.download_raw(&url).await.unwrap().byte_len, 2048);
+    }
+
+    #[tokio::test]
+    async fn test_download_raw_with_progress_reassembles_chunks() {
+        let mock_server = MockServer::start().await;
+        let body = (0..50_000).fold(String::new(), |mut body, i| {
+            let _ = writeln!(body, "{i:06}");
+            body
+        });
+        Mock::given(method("GET"))
+            .and(path("/export/big.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body.clone()))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
+        let url = format!("{}/export/big.txt", mock_server.uri());
+        let mut reports = Vec::new();
+        let download = client
+            .download_raw_with_progress(&url, |bytes, total| reports.push((bytes, total)))
+            .await
+            .unwrap();
+
+        assert_eq!(download.content, body);
+        assert_eq!(download.byte_len, body.len());
+        // One report per chunk, counting up to the Content-Length
+        let total = body.len() as u64;
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(reports.iter().all(|&(_, t)| t == Some(total)));
+        assert_eq!(reports.last(), Some(&(total, Some(total))));
+
+        let mut reported = 0;
+        let client = client.with_max_download_bytes(body.len() - 1);
+        let err = client
+            .download_raw_with_progress(&url, |bytes, _| reported = bytes)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CodaError::DownloadTooLarge { .. }), "{err:?}");
+        assert_eq!(reported, 0);
+    }
+
+    #[tokio::test]
+    async fn test_download_raw_caps_decompressed_size() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mock_server = MockServer::start().await;
+
+        // Compresses to far less than the cap but expands well past it
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all("a".repeat(64 * 1024).as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(compressed.len() < 1024);
+
+        Mock::given(method("GET"))
+            .and(path("/export/bomb.html.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(compressed))
+            .mount(&mock_server)
+            .await;
+
+        let client = CodaClient::new_with_base_url("test_token", &mock_server.uri())
+            .with_max_download_bytes(1024);
+        let url = format!("{}/export/bomb.html.gz", mock_server.uri());
+        let err = client.download_raw(&url).await.unwrap_err();
+
+        assert!(
+            matches!(err, CodaError::DownloadTooLarge { max_bytes: 1024 }),
+            "{err:?}"
+        );
+    }
+
     #[tokio::test]
     async fn test_download_raw_http_error() {
         let mock_server = MockServer::start().await;
@@ -891,7 +2013,7 @@ mod tests {
 
         let client = CodaClient::new_with_base_url("test_token", &mock_server.uri());
         let url = format!("{}/export/empty", mock_server.uri());
-        let content = client.download_raw(&url).await.unwrap();
+        let content = client.download_raw(&url).await.unwrap().content;
 
         assert_eq!(content, "");
     }
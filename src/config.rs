@@ -1,24 +1,178 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt;
+use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::models::{validate_aliases, TableAlias, ValueFormat};
+use crate::render::OutputFormat;
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("CODA_API_TOKEN environment variable is required")]
     MissingToken,
+
+    #[error("Invalid table aliases in {origin}: {message}")]
+    InvalidAliases { origin: String, message: String },
 }
 
+const DEFAULT_BASE_URL: &str = "https://coda.io/apis/v1";
+const DEFAULT_CONTEXT_CACHE_TTL_SECS: u64 = 600;
+const DEFAULT_CONTEXT_CACHE_MAX_DOCS: usize = 10;
+const DEFAULT_COLUMN_CACHE_TTL_SECS: u64 = 60;
+const DEFAULT_CIRCUIT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_CIRCUIT_WINDOW_SECS: u64 = 60;
+const DEFAULT_CIRCUIT_COOLDOWN_SECS: u64 = 30;
+const DEFAULT_EXPORT_FAILURE_RETRIES: u32 = 2;
+/// Export status polls, and the pause between them; tests poll fast
+#[cfg(not(test))]
+const DEFAULT_EXPORT_MAX_ATTEMPTS: u32 = 30;
+#[cfg(not(test))]
+const DEFAULT_EXPORT_POLL_SECS: u64 = 1;
+#[cfg(test)]
+const DEFAULT_EXPORT_MAX_ATTEMPTS: u32 = 3;
+#[cfg(test)]
+const DEFAULT_EXPORT_POLL_SECS: u64 = 0;
+const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+const DEFAULT_MAX_DOWNLOAD_BYTES: usize = 25 * 1024 * 1024;
+/// Export error substrings worth a fresh export; anything else is permanent
+const DEFAULT_EXPORT_TRANSIENT_ERRORS: &[&str] = &[
+    "internal error",
+    "temporar",
+    "timed out",
+    "timeout",
+    "try again",
+    "unavailable",
+];
+
 #[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent env flags
 pub struct Config {
     pub api_token: String,
+    /// Tokens of further accounts, for tools that span accounts
+    pub api_tokens: Vec<String>,
     pub base_url: String,
+    /// Column names whose cell values are replaced with `[REDACTED]` in row output
+    pub redact_columns: Vec<String>,
+    /// Log response headers at debug level and keep the last set for `get_last_response_headers`
+    pub debug_headers: bool,
+    /// If non-empty, only these tools are registered
+    pub enabled_tools: Vec<String>,
+    /// Tools removed from the router, applied after `enabled_tools`
+    pub disabled_tools: Vec<String>,
+    /// Require a matching `confirm` value on every destructive tool
+    pub safe_mode: bool,
+    /// Directory that `to_file` exports must be written inside; unset disables `to_file`
+    pub export_dir: Option<PathBuf>,
+    /// Log a warning listing response fields the models don't recognize
+    pub strict_deserialize: bool,
+    /// How long `load_doc_context` results stay cached
+    pub context_cache_ttl_secs: u64,
+    /// Docs kept in the context cache before the oldest is evicted; 0 disables it
+    pub context_cache_max_docs: usize,
+    /// How long a table's fetched columns are reused; 0 disables the column cache
+    pub column_cache_ttl_secs: u64,
+    /// Format of the data block in tool output
+    pub output_format: OutputFormat,
+    /// Consecutive network or server failures that open a host's circuit; 0 disables the breaker
+    pub circuit_failure_threshold: u32,
+    /// Window in which those failures must occur
+    pub circuit_window_secs: u64,
+    /// How long an open circuit fails fast before a probe is allowed
+    pub circuit_cooldown_secs: u64,
+    /// `useColumnNames` for row reads that don't set it
+    pub default_use_column_names: bool,
+    /// `valueFormat` for row reads that don't set it; unset leaves the API default
+    pub default_value_format: Option<ValueFormat>,
+    /// Fresh exports `retry_on_failure` may start after transient failures
+    pub export_failure_retries: u32,
+    /// Case-insensitive substrings marking an export failure as transient
+    pub export_transient_errors: Vec<String>,
+    /// Export status polls before a page export is handed back as timed out
+    pub export_max_attempts: u32,
+    /// Seconds between export status polls
+    pub export_poll_secs: u64,
+    /// Times a 429 response is retried, honoring `Retry-After`; 0 disables retries
+    pub rate_limit_retries: u32,
+    /// Largest export download, before and after gzip decoding, that is read into memory
+    pub max_download_bytes: usize,
+    /// Friendly table names that row and table tools accept as `alias`
+    pub aliases: BTreeMap<String, TableAlias>,
+    /// Append a masked description of each API request to every tool result
+    pub echo_requests: bool,
+    /// Append each rendered data block as a raw JSON content item
+    pub structured_output: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            api_token: String::new(),
+            api_tokens: Vec::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            redact_columns: Vec::new(),
+            debug_headers: false,
+            enabled_tools: Vec::new(),
+            disabled_tools: Vec::new(),
+            safe_mode: false,
+            export_dir: None,
+            strict_deserialize: false,
+            context_cache_ttl_secs: DEFAULT_CONTEXT_CACHE_TTL_SECS,
+            context_cache_max_docs: DEFAULT_CONTEXT_CACHE_MAX_DOCS,
+            column_cache_ttl_secs: DEFAULT_COLUMN_CACHE_TTL_SECS,
+            output_format: OutputFormat::default(),
+            circuit_failure_threshold: DEFAULT_CIRCUIT_FAILURE_THRESHOLD,
+            circuit_window_secs: DEFAULT_CIRCUIT_WINDOW_SECS,
+            circuit_cooldown_secs: DEFAULT_CIRCUIT_COOLDOWN_SECS,
+            default_use_column_names: true,
+            default_value_format: None,
+            export_failure_retries: DEFAULT_EXPORT_FAILURE_RETRIES,
+            export_transient_errors: default_export_transient_errors(),
+            export_max_attempts: DEFAULT_EXPORT_MAX_ATTEMPTS,
+            export_poll_secs: DEFAULT_EXPORT_POLL_SECS,
+            rate_limit_retries: DEFAULT_RATE_LIMIT_RETRIES,
+            max_download_bytes: DEFAULT_MAX_DOWNLOAD_BYTES,
+            aliases: BTreeMap::new(),
+            echo_requests: false,
+            structured_output: false,
+        }
+    }
 }
 
 impl fmt::Debug for Config {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Config")
             .field("api_token", &"[REDACTED]")
+            .field(
+                "api_tokens",
+                &format!("[{} REDACTED]", self.api_tokens.len()),
+            )
             .field("base_url", &self.base_url)
+            .field("redact_columns", &self.redact_columns)
+            .field("debug_headers", &self.debug_headers)
+            .field("enabled_tools", &self.enabled_tools)
+            .field("disabled_tools", &self.disabled_tools)
+            .field("safe_mode", &self.safe_mode)
+            .field("export_dir", &self.export_dir)
+            .field("strict_deserialize", &self.strict_deserialize)
+            .field("context_cache_ttl_secs", &self.context_cache_ttl_secs)
+            .field("context_cache_max_docs", &self.context_cache_max_docs)
+            .field("column_cache_ttl_secs", &self.column_cache_ttl_secs)
+            .field("output_format", &self.output_format)
+            .field("circuit_failure_threshold", &self.circuit_failure_threshold)
+            .field("circuit_window_secs", &self.circuit_window_secs)
+            .field("circuit_cooldown_secs", &self.circuit_cooldown_secs)
+            .field("default_use_column_names", &self.default_use_column_names)
+            .field("default_value_format", &self.default_value_format)
+            .field("export_failure_retries", &self.export_failure_retries)
+            .field("export_transient_errors", &self.export_transient_errors)
+            .field("export_max_attempts", &self.export_max_attempts)
+            .field("export_poll_secs", &self.export_poll_secs)
+            .field("rate_limit_retries", &self.rate_limit_retries)
+            .field("max_download_bytes", &self.max_download_bytes)
+            .field("aliases", &self.aliases)
+            .field("echo_requests", &self.echo_requests)
+            .field("structured_output", &self.structured_output)
             .finish()
     }
 }
@@ -26,17 +180,204 @@ impl fmt::Debug for Config {
 impl Config {
     pub fn from_env() -> Result<Self, ConfigError> {
         let api_token = env::var("CODA_API_TOKEN").map_err(|_| ConfigError::MissingToken)?;
+        let api_tokens = env::var("CODA_API_TOKENS")
+            .map(|v| parse_list(&v))
+            .unwrap_or_default();
+
+        let base_url = env::var("CODA_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+
+        let redact_columns = env::var("CODA_REDACT_COLUMNS")
+            .map(|v| parse_list(&v))
+            .unwrap_or_default();
+
+        let debug_headers = env::var("CODA_DEBUG_HEADERS").is_ok_and(|v| parse_flag(&v));
+
+        let enabled_tools = env::var("CODA_ENABLED_TOOLS")
+            .map(|v| parse_list(&v))
+            .unwrap_or_default();
+        let disabled_tools = env::var("CODA_DISABLED_TOOLS")
+            .map(|v| parse_list(&v))
+            .unwrap_or_default();
+
+        let safe_mode = env::var("CODA_SAFE_MODE").is_ok_and(|v| parse_flag(&v));
+
+        let export_dir = env::var("CODA_EXPORT_DIR")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(PathBuf::from);
+
+        let strict_deserialize = env::var("CODA_STRICT_DESERIALIZE").is_ok_and(|v| parse_flag(&v));
 
-        let base_url =
-            env::var("CODA_BASE_URL").unwrap_or_else(|_| "https://coda.io/apis/v1".to_string());
+        let context_cache_ttl_secs =
+            parse_env("CODA_CONTEXT_CACHE_TTL_SECS").unwrap_or(DEFAULT_CONTEXT_CACHE_TTL_SECS);
+        let context_cache_max_docs =
+            parse_env("CODA_CONTEXT_CACHE_MAX_DOCS").unwrap_or(DEFAULT_CONTEXT_CACHE_MAX_DOCS);
+        let column_cache_ttl_secs =
+            parse_env("CODA_CACHE_TTL_SECS").unwrap_or(DEFAULT_COLUMN_CACHE_TTL_SECS);
+
+        let output_format = match env::var("CODA_OUTPUT_FORMAT") {
+            Ok(v) => OutputFormat::parse(&v).unwrap_or_else(|| {
+                tracing::warn!("Unknown CODA_OUTPUT_FORMAT '{}', using json", v);
+                OutputFormat::default()
+            }),
+            Err(_) => OutputFormat::default(),
+        };
+
+        let circuit_failure_threshold = parse_env("CODA_CIRCUIT_FAILURE_THRESHOLD")
+            .unwrap_or(DEFAULT_CIRCUIT_FAILURE_THRESHOLD);
+        let circuit_window_secs =
+            parse_env("CODA_CIRCUIT_WINDOW_SECS").unwrap_or(DEFAULT_CIRCUIT_WINDOW_SECS);
+        let circuit_cooldown_secs =
+            parse_env("CODA_CIRCUIT_COOLDOWN_SECS").unwrap_or(DEFAULT_CIRCUIT_COOLDOWN_SECS);
+
+        let default_use_column_names =
+            env::var("CODA_DEFAULT_USE_COLUMN_NAMES").map_or(true, |v| parse_flag(&v));
+        let default_value_format = env::var("CODA_DEFAULT_VALUE_FORMAT").ok().and_then(|v| {
+            let format = ValueFormat::parse(&v);
+            if format.is_none() {
+                tracing::warn!(
+                    "Unknown CODA_DEFAULT_VALUE_FORMAT '{}', using the API default",
+                    v
+                );
+            }
+            format
+        });
+
+        let export_failure_retries =
+            parse_env("CODA_EXPORT_FAILURE_RETRIES").unwrap_or(DEFAULT_EXPORT_FAILURE_RETRIES);
+        let export_transient_errors = env::var("CODA_EXPORT_TRANSIENT_ERRORS")
+            .map_or_else(|_| default_export_transient_errors(), |v| parse_list(&v));
+        let export_max_attempts = parse_env("CODA_EXPORT_MAX_ATTEMPTS")
+            .filter(|&attempts: &u32| attempts > 0)
+            .unwrap_or(DEFAULT_EXPORT_MAX_ATTEMPTS);
+        let export_poll_secs =
+            parse_env("CODA_EXPORT_POLL_SECS").unwrap_or(DEFAULT_EXPORT_POLL_SECS);
+
+        let rate_limit_retries =
+            parse_env("CODA_RATE_LIMIT_RETRIES").unwrap_or(DEFAULT_RATE_LIMIT_RETRIES);
+        let max_download_bytes = parse_env("CODA_MAX_DOWNLOAD_BYTES")
+            .filter(|&bytes: &usize| bytes > 0)
+            .unwrap_or(DEFAULT_MAX_DOWNLOAD_BYTES);
+
+        let aliases = load_aliases()?;
+        let echo_requests = env::var("CODA_ECHO_REQUESTS").is_ok_and(|v| parse_flag(&v));
+        let structured_output = env::var("CODA_STRUCTURED_OUTPUT").is_ok_and(|v| parse_flag(&v));
 
         tracing::info!("Config loaded: base_url={}", base_url);
 
         Ok(Self {
             api_token,
+            api_tokens,
             base_url,
+            redact_columns,
+            debug_headers,
+            enabled_tools,
+            disabled_tools,
+            safe_mode,
+            export_dir,
+            strict_deserialize,
+            context_cache_ttl_secs,
+            context_cache_max_docs,
+            column_cache_ttl_secs,
+            output_format,
+            circuit_failure_threshold,
+            circuit_window_secs,
+            circuit_cooldown_secs,
+            default_use_column_names,
+            default_value_format,
+            export_failure_retries,
+            export_transient_errors,
+            export_max_attempts,
+            export_poll_secs,
+            rate_limit_retries,
+            max_download_bytes,
+            aliases,
+            echo_requests,
+            structured_output,
         })
     }
+
+    /// How long a page export is polled before it is handed back as timed out
+    pub fn export_timeout_secs(&self) -> u64 {
+        u64::from(self.export_max_attempts) * self.export_poll_secs
+    }
+
+    /// `api_token` followed by each distinct token from `api_tokens`
+    pub fn account_tokens(&self) -> Vec<&str> {
+        let mut tokens: Vec<&str> = Vec::new();
+        for token in std::iter::once(&self.api_token).chain(&self.api_tokens) {
+            if !token.is_empty() && !tokens.contains(&token.as_str()) {
+                tokens.push(token);
+            }
+        }
+        tokens
+    }
+
+    /// Whether an export error message matches a transient pattern
+    pub fn is_transient_export_error(&self, message: &str) -> bool {
+        let message = message.to_lowercase();
+        self.export_transient_errors
+            .iter()
+            .any(|pattern| message.contains(&pattern.to_lowercase()))
+    }
+}
+
+/// A numeric or other `FromStr` env var, `None` if unset or unparseable
+fn parse_env<T: std::str::FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+/// Table aliases from `CODA_ALIASES` (inline JSON) or `CODA_ALIASES_FILE` (a
+/// path to the same JSON); `CODA_ALIASES` wins if both are set
+fn load_aliases() -> Result<BTreeMap<String, TableAlias>, ConfigError> {
+    let (origin, json) = if let Ok(json) = env::var("CODA_ALIASES") {
+        ("CODA_ALIASES".to_string(), json)
+    } else if let Ok(path) = env::var("CODA_ALIASES_FILE") {
+        let json = std::fs::read_to_string(&path).map_err(|e| ConfigError::InvalidAliases {
+            origin: path.clone(),
+            message: e.to_string(),
+        })?;
+        (path, json)
+    } else {
+        return Ok(BTreeMap::new());
+    };
+    parse_aliases(&json).map_err(|message| ConfigError::InvalidAliases { origin, message })
+}
+
+/// Parse `{"name": {"doc_id": "...", "table_id": "..."}}`
+fn parse_aliases(json: &str) -> Result<BTreeMap<String, TableAlias>, String> {
+    if json.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let aliases: BTreeMap<String, TableAlias> =
+        serde_json::from_str(json).map_err(|e| e.to_string())?;
+    validate_aliases(&aliases)?;
+    Ok(aliases)
+}
+
+fn default_export_transient_errors() -> Vec<String> {
+    DEFAULT_EXPORT_TRANSIENT_ERRORS
+        .iter()
+        .map(|s| (*s).to_string())
+        .collect()
+}
+
+/// Parse a comma-separated env value, trimming whitespace and skipping empty entries
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parse a boolean env value: `1`, `true`, `yes` or `on` (case-insensitive)
+fn parse_flag(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
 }
 
 #[cfg(test)]
@@ -57,6 +398,7 @@ mod tests {
         let config = Config {
             api_token: "token123".to_string(),
             base_url: "https://api.example.com".to_string(),
+            ..Config::default()
         };
 
         let cloned = config.clone();
@@ -69,6 +411,7 @@ mod tests {
         let config = Config {
             api_token: "super_secret_token_12345".to_string(),
             base_url: "https://api.example.com".to_string(),
+            ..Config::default()
         };
 
         let debug_str = format!("{config:?}");
@@ -79,6 +422,85 @@ mod tests {
         assert!(!debug_str.contains("super_secret_token_12345"));
     }
 
+    #[test]
+    fn test_account_tokens_redacted_and_deduped() {
+        let config = Config {
+            api_token: "tok-a".to_string(),
+            api_tokens: vec!["tok-b".to_string(), "tok-a".to_string()],
+            ..Config::default()
+        };
+
+        assert_eq!(config.account_tokens(), ["tok-a", "tok-b"]);
+        let debug_str = format!("{config:?}");
+        assert!(debug_str.contains("[2 REDACTED]"));
+        assert!(!debug_str.contains("tok-b"));
+    }
+
+    #[test]
+    fn test_parse_aliases() {
+        let aliases =
+            parse_aliases(r#"{"tasks": {"doc_id": "AbCd", "table_id": "grid-1"}}"#).unwrap();
+        assert_eq!(aliases["tasks"].table_id, "grid-1");
+        assert!(parse_aliases(" ").unwrap().is_empty());
+
+        assert!(parse_aliases(r#"{"tasks": {"doc_id": "AbCd"}}"#)
+            .unwrap_err()
+            .contains("missing field `table_id`"));
+        assert!(
+            parse_aliases(r#"{"tasks": {"doc_id": "", "table_id": "t"}}"#)
+                .unwrap_err()
+                .contains("'tasks'")
+        );
+    }
+
+    #[test]
+    fn test_from_env_invalid_aliases() {
+        with_env_vars(|| {
+            env::set_var("CODA_API_TOKEN", "test_token_789");
+            env::set_var("CODA_ALIASES", "not json");
+
+            let err = Config::from_env().unwrap_err();
+            assert!(err
+                .to_string()
+                .starts_with("Invalid table aliases in CODA_ALIASES"));
+
+            env::remove_var("CODA_ALIASES");
+        });
+    }
+
+    #[test]
+    fn test_from_env_api_tokens() {
+        with_env_vars(|| {
+            env::set_var("CODA_API_TOKEN", "tok-a");
+            env::set_var("CODA_API_TOKENS", "tok-b, tok-c,");
+
+            let config = Config::from_env().unwrap();
+            assert_eq!(config.api_tokens, ["tok-b", "tok-c"]);
+            assert_eq!(config.account_tokens(), ["tok-a", "tok-b", "tok-c"]);
+
+            env::remove_var("CODA_API_TOKENS");
+        });
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(
+            parse_list(" SSN, Email ,,Salary "),
+            vec!["SSN".to_string(), "Email".to_string(), "Salary".to_string()]
+        );
+        assert!(parse_list("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_flag() {
+        assert!(parse_flag("1"));
+        assert!(parse_flag("TRUE"));
+        assert!(parse_flag(" yes "));
+        assert!(!parse_flag("0"));
+        assert!(!parse_flag("false"));
+        assert!(!parse_flag(""));
+    }
+
     /// Helper to save, run test, and restore env vars.
     /// Always sets a sentinel value before the test so restore branches are exercised.
     fn with_env_vars<F: FnOnce()>(f: F) {
@@ -137,6 +559,112 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_from_env_row_read_defaults() {
+        with_env_vars(|| {
+            env::set_var("CODA_API_TOKEN", "test_token_789");
+
+            env::set_var("CODA_DEFAULT_USE_COLUMN_NAMES", "false");
+            env::set_var("CODA_DEFAULT_VALUE_FORMAT", "SimpleWithArrays");
+            let config = Config::from_env().unwrap();
+            assert!(!config.default_use_column_names);
+            assert_eq!(
+                config.default_value_format,
+                Some(ValueFormat::SimpleWithArrays)
+            );
+
+            env::set_var("CODA_DEFAULT_VALUE_FORMAT", "fancy");
+            env::remove_var("CODA_DEFAULT_USE_COLUMN_NAMES");
+            let config = Config::from_env().unwrap();
+            assert!(config.default_use_column_names);
+            assert_eq!(config.default_value_format, None);
+
+            env::remove_var("CODA_DEFAULT_VALUE_FORMAT");
+        });
+    }
+
+    #[test]
+    fn test_transient_export_errors() {
+        let config = Config::default();
+        assert!(config.is_transient_export_error("Temporary INTERNAL error"));
+        assert!(!config.is_transient_export_error("Page too large to export"));
+
+        with_env_vars(|| {
+            env::set_var("CODA_API_TOKEN", "test_token_789");
+            env::set_var("CODA_EXPORT_TRANSIENT_ERRORS", "flaky, busy");
+            env::set_var("CODA_EXPORT_FAILURE_RETRIES", "5");
+
+            let config = Config::from_env().unwrap();
+            assert_eq!(config.export_failure_retries, 5);
+            assert!(config.is_transient_export_error("Worker busy"));
+            assert!(!config.is_transient_export_error("Temporary internal error"));
+
+            env::remove_var("CODA_EXPORT_TRANSIENT_ERRORS");
+            env::remove_var("CODA_EXPORT_FAILURE_RETRIES");
+        });
+    }
+
+    #[test]
+    fn test_from_env_export_polling() {
+        with_env_vars(|| {
+            env::set_var("CODA_API_TOKEN", "test_token_789");
+            env::set_var("CODA_EXPORT_MAX_ATTEMPTS", "120");
+            env::set_var("CODA_EXPORT_POLL_SECS", "2");
+
+            let config = Config::from_env().unwrap();
+            assert_eq!(config.export_max_attempts, 120);
+            assert_eq!(config.export_poll_secs, 2);
+            assert_eq!(config.export_timeout_secs(), 240);
+
+            // Zero attempts would never poll, so it falls back to the default
+            env::set_var("CODA_EXPORT_MAX_ATTEMPTS", "0");
+            let config = Config::from_env().unwrap();
+            assert_eq!(config.export_max_attempts, DEFAULT_EXPORT_MAX_ATTEMPTS);
+
+            env::remove_var("CODA_EXPORT_MAX_ATTEMPTS");
+            env::remove_var("CODA_EXPORT_POLL_SECS");
+        });
+    }
+
+    #[test]
+    fn test_from_env_rate_limit_retries() {
+        with_env_vars(|| {
+            env::set_var("CODA_API_TOKEN", "test_token_789");
+            assert_eq!(
+                Config::from_env().unwrap().rate_limit_retries,
+                DEFAULT_RATE_LIMIT_RETRIES
+            );
+
+            env::set_var("CODA_RATE_LIMIT_RETRIES", "0");
+            assert_eq!(Config::from_env().unwrap().rate_limit_retries, 0);
+
+            env::remove_var("CODA_RATE_LIMIT_RETRIES");
+        });
+    }
+
+    #[test]
+    fn test_from_env_max_download_bytes() {
+        with_env_vars(|| {
+            env::set_var("CODA_API_TOKEN", "test_token_789");
+            assert_eq!(
+                Config::from_env().unwrap().max_download_bytes,
+                DEFAULT_MAX_DOWNLOAD_BYTES
+            );
+
+            env::set_var("CODA_MAX_DOWNLOAD_BYTES", "1048576");
+            assert_eq!(Config::from_env().unwrap().max_download_bytes, 1_048_576);
+
+            // 0 would reject every download, so it falls back to the default
+            env::set_var("CODA_MAX_DOWNLOAD_BYTES", "0");
+            assert_eq!(
+                Config::from_env().unwrap().max_download_bytes,
+                DEFAULT_MAX_DOWNLOAD_BYTES
+            );
+
+            env::remove_var("CODA_MAX_DOWNLOAD_BYTES");
+        });
+    }
+
     #[test]
     fn test_with_env_vars_restores_existing_values() {
         // Pre-set env vars so that saved_token/saved_url are Some(_)
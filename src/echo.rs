@@ -0,0 +1,222 @@
+//! Echo of the HTTP requests a tool call made, for `CODA_ECHO_REQUESTS`.
+//!
+//! `call_tool` scopes an [`EchoLog`] over the call when echoing is on, and
+//! `CodaClient` records each API request into it through [`record`]. The log
+//! is appended to the tool result afterwards. Secrets are masked as requests
+//! are recorded, so the raw token never reaches the log.
+
+use rmcp::model::{CallToolResult, Content};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+/// Replacement for masked values
+const MASK: &str = "[REDACTED]";
+
+/// Body and query keys masked wherever they appear, compared lowercase with
+/// `_` and `-` removed
+const SENSITIVE_KEYS: &[&str] = &[
+    "token",
+    "apitoken",
+    "accesstoken",
+    "apikey",
+    "authorization",
+    "password",
+    "secret",
+    "clientsecret",
+];
+
+tokio::task_local! {
+    /// Requests made by the tool call being handled, when echoing is on
+    pub static ECHO: EchoLog;
+}
+
+/// Requests recorded during one tool call, oldest first
+#[derive(Clone, Default)]
+pub struct EchoLog(Arc<Mutex<Vec<EchoedRequest>>>);
+
+/// One outgoing API request, with secrets masked
+#[derive(Debug, Clone, Serialize)]
+pub struct EchoedRequest {
+    pub method: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    pub headers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+impl EchoLog {
+    pub fn requests(&self) -> Vec<EchoedRequest> {
+        self.0.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Add the recorded requests to `result`: a text block for people, and a
+    /// `requests` field when the structured content is an object
+    pub fn append_to(&self, result: &mut CallToolResult) {
+        let requests = self.requests();
+        let mut text = format!("Requests made ({}):", requests.len());
+        for request in &requests {
+            let _ = write!(text, "\n{} {}", request.method, request.path);
+            if let Some(query) = &request.query {
+                let _ = write!(text, "?{query}");
+            }
+            for header in &request.headers {
+                let _ = write!(text, "\n  {header}");
+            }
+            if let Some(body) = &request.body {
+                let _ = write!(text, "\n  Body: {body}");
+            }
+        }
+        result.content.push(Content::text(text));
+
+        if let Some(serde_json::Value::Object(structured)) = &mut result.structured_content {
+            if let Ok(requests) = serde_json::to_value(&requests) {
+                structured.insert("requests".to_string(), requests);
+            }
+        }
+    }
+}
+
+/// Record a request in the current tool call's echo log, if there is one.
+/// `path` may carry a query string; `api_token` is masked wherever it appears.
+pub fn record<B: Serialize>(method: &str, path: &str, api_token: &str, body: Option<&B>) {
+    let _ = ECHO.try_with(|log| {
+        let (path, query) = match path.split_once('?') {
+            Some((path, query)) => (path, Some(mask_query(query, api_token))),
+            None => (path, None),
+        };
+        let mut headers = vec![format!("Authorization: Bearer {MASK}")];
+        let body = body
+            .and_then(|b| serde_json::to_value(b).ok())
+            .map(|mut b| {
+                headers.push("Content-Type: application/json".to_string());
+                mask_value(&mut b, api_token);
+                b
+            });
+        let request = EchoedRequest {
+            method: method.to_string(),
+            path: mask_token(path, api_token),
+            query,
+            headers,
+            body,
+        };
+        if let Ok(mut requests) = log.0.lock() {
+            requests.push(request);
+        }
+    });
+}
+
+/// `path` with sensitive query values and `api_token` masked
+pub fn mask_path(path: &str, api_token: &str) -> String {
+    match path.split_once('?') {
+        Some((path, query)) => format!(
+            "{}?{}",
+            mask_token(path, api_token),
+            mask_query(query, api_token)
+        ),
+        None => mask_token(path, api_token),
+    }
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let normalized: String = key
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect();
+    SENSITIVE_KEYS.contains(&normalized.as_str())
+}
+
+fn mask_token(text: &str, api_token: &str) -> String {
+    if api_token.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(api_token, MASK)
+    }
+}
+
+fn mask_query(query: &str, api_token: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_sensitive_key(key) => format!("{key}={MASK}"),
+            _ => mask_token(pair, api_token),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn mask_value(value: &mut serde_json::Value, api_token: &str) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *value = serde_json::Value::String(MASK.to_string());
+                } else {
+                    mask_value(value, api_token);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                mask_value(item, api_token);
+            }
+        }
+        serde_json::Value::String(s) if !api_token.is_empty() && s.contains(api_token) => {
+            *s = mask_token(s, api_token);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_value() {
+        let mut body = serde_json::json!({
+            "rows": [{"cells": [{"column": "Note", "value": "uses tok-123 here"}]}],
+            "api_key": "k",
+            "clientSecret": "s",
+            "pageToken": "keep",
+        });
+        mask_value(&mut body, "tok-123");
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "rows": [{"cells": [{"column": "Note", "value": "uses [REDACTED] here"}]}],
+                "api_key": "[REDACTED]",
+                "clientSecret": "[REDACTED]",
+                "pageToken": "keep",
+            })
+        );
+    }
+
+    #[test]
+    fn test_mask_query() {
+        assert_eq!(
+            mask_query("limit=5&token=abc&pageToken=p1&q=tok-123", "tok-123"),
+            "limit=5&token=[REDACTED]&pageToken=p1&q=[REDACTED]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_outside_scope_is_ignored() {
+        record("GET", "/docs", "tok", None::<&()>);
+
+        let log = EchoLog::default();
+        ECHO.scope(log.clone(), async {
+            record("GET", "/docs?limit=1", "tok", None::<&()>);
+        })
+        .await;
+
+        let requests = log.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/docs");
+        assert_eq!(requests[0].query.as_deref(), Some("limit=1"));
+    }
+}
@@ -1,3 +1,5 @@
+use rmcp::ErrorData as McpError;
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -23,17 +25,172 @@ pub enum CodaError {
     #[error("JSON parse error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("The API returned 204 No Content where a response body was expected")]
+    NoContent,
+
     #[error("Export timed out after {seconds} seconds")]
     ExportTimeout { seconds: u64 },
 
     #[error("Export failed: {message}")]
     ExportFailed { message: String },
+
+    #[error("Export download exceeded the {max_bytes}-byte limit. Export a smaller page or raise CODA_MAX_DOWNLOAD_BYTES.")]
+    DownloadTooLarge { max_bytes: usize },
+
+    #[error("Circuit open for {host} after repeated failures; failing fast. Retry in {retry_after_secs}s.")]
+    CircuitOpen { host: String, retry_after_secs: u64 },
+
+    #[error("/whoami returned no workspace for {login_id}")]
+    NoWorkspace { login_id: String },
+}
+
+/// Broad classification of a failure, used to suggest a fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Network,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    RateLimited,
+    BadRequest,
+    Server,
+    Parse,
+    Export,
+    Unavailable,
+}
+
+impl CodaError {
+    /// HTTP status associated with this error, if any
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Unauthorized => Some(401),
+            Self::Forbidden => Some(403),
+            Self::NotFound => Some(404),
+            Self::RateLimited => Some(429),
+            Self::NoContent => Some(204),
+            Self::Api { status, .. } if *status != 0 => Some(*status),
+            Self::Request(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Message from an API error body: the `message` field of Coda's JSON
+    /// error, or the raw body when it isn't JSON
+    pub fn api_message(&self) -> Option<String> {
+        let Self::Api { body, .. } = self else {
+            return None;
+        };
+        let parsed: Option<serde_json::Value> = serde_json::from_str(body).ok();
+        let message = parsed
+            .as_ref()
+            .and_then(|json| json["message"].as_str())
+            .unwrap_or(body)
+            .trim();
+        (!message.is_empty()).then(|| message.to_string())
+    }
+
+    /// MCP error carrying `{status, category}` as structured data, so clients
+    /// can branch on the HTTP status without parsing the message
+    pub fn into_mcp_error(self) -> McpError {
+        let data = serde_json::json!({
+            "status": self.status(),
+            "category": self.category(),
+        });
+        McpError::internal_error(self.to_string(), Some(data))
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::Request(_) => ErrorCategory::Network,
+            Self::Unauthorized => ErrorCategory::Unauthorized,
+            Self::Forbidden => ErrorCategory::Forbidden,
+            Self::NotFound => ErrorCategory::NotFound,
+            Self::RateLimited => ErrorCategory::RateLimited,
+            Self::Api { status, .. } if *status >= 500 => ErrorCategory::Server,
+            Self::Api { .. } => ErrorCategory::BadRequest,
+            Self::Json(_) | Self::NoContent | Self::NoWorkspace { .. } => ErrorCategory::Parse,
+            Self::ExportTimeout { .. }
+            | Self::ExportFailed { .. }
+            | Self::DownloadTooLarge { .. } => ErrorCategory::Export,
+            Self::CircuitOpen { .. } => ErrorCategory::Unavailable,
+        }
+    }
+}
+
+impl ErrorCategory {
+    /// Remediation hint for a failed `method` request to `path`
+    pub fn hint(self, method: &str, path: &str) -> String {
+        let is_write = method != "GET";
+        match self {
+            Self::Network => "Could not reach the Coda API. Check network access to coda.io and any proxy or firewall settings.".to_string(),
+            Self::Unauthorized => "The API token is invalid or expired. Generate a new one at https://coda.io/account and update CODA_API_TOKEN.".to_string(),
+            Self::Forbidden if is_write => "The token lacks write scope. Regenerate it at https://coda.io/account with write access enabled.".to_string(),
+            Self::Forbidden => "The token cannot read this doc. Ask the owner to share it with your account, or check the doc ID.".to_string(),
+            Self::NotFound => {
+                let resource = resource_kind(path);
+                format!("The {resource} was not found. Check the {resource} ID or name and that your account has access to the doc.")
+            }
+            Self::RateLimited if is_write => "Write rate limit hit (10 requests per 6 seconds). Wait a few seconds and batch changes where possible.".to_string(),
+            Self::RateLimited => "Read rate limit hit (100 requests per 6 seconds). Wait a few seconds before retrying.".to_string(),
+            Self::BadRequest if path.contains("query=") => "The row query could not be parsed. Use the structured `filter` param ({column, value}) instead, which escapes values for you.".to_string(),
+            Self::BadRequest => "Coda rejected the request. Check the parameter values against the tool description.".to_string(),
+            Self::Server => "Coda returned a server error. Retry shortly; if it persists, check https://status.coda.io.".to_string(),
+            Self::Parse => "The response did not match the expected shape. Retry with RUST_LOG=debug to inspect the raw body.".to_string(),
+            Self::Export => "The page export did not complete. Retry, or use a smaller page.".to_string(),
+            Self::Unavailable => "Recent calls to Coda kept failing, so calls are short-circuited until the cooldown ends. Wait and retry; check https://status.coda.io if it persists.".to_string(),
+        }
+    }
+}
+
+/// Most specific resource named in an API path, e.g. `/docs/d/tables/t` -> "table"
+fn resource_kind(path: &str) -> &'static str {
+    let path = path.split('?').next().unwrap_or(path);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    // `/docs/{id}/tables/{id}` names a table; `/docs/{id}/tables/{id}/rows` lists
+    // rows of that table, so a 404 there still means the table is missing.
+    let index = if segments.len().is_multiple_of(2) {
+        segments.len().checked_sub(2)
+    } else {
+        segments.len().checked_sub(3)
+    };
+    match index.map(|i| segments[i]).unwrap_or_default() {
+        "docs" => "doc",
+        "pages" => "page",
+        "tables" => "table",
+        "columns" => "column",
+        "rows" => "row",
+        "formulas" => "formula",
+        "controls" => "control",
+        _ => "resource",
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_api_message() {
+        let api = |body: &str| CodaError::Api {
+            status: 400,
+            body: body.to_string(),
+        };
+
+        assert_eq!(
+            api(r#"{"statusCode":400,"statusMessage":"Bad Request","message":"Invalid query: x"}"#)
+                .api_message()
+                .as_deref(),
+            Some("Invalid query: x")
+        );
+        assert_eq!(
+            api(" plain text\n").api_message().as_deref(),
+            Some("plain text")
+        );
+        assert_eq!(api("").api_message(), None);
+        assert_eq!(CodaError::NotFound.api_message(), None);
+    }
+
     #[test]
     fn test_rate_limited_error_display() {
         let err = CodaError::RateLimited;
@@ -84,6 +241,156 @@ mod tests {
         assert_eq!(err.to_string(), "Export failed: Invalid format");
     }
 
+    #[test]
+    fn test_download_too_large_error() {
+        let err = CodaError::DownloadTooLarge { max_bytes: 1024 };
+        assert!(err.to_string().contains("1024-byte limit"));
+        assert_eq!(err.category(), ErrorCategory::Export);
+        assert_eq!(err.status(), None);
+    }
+
+    #[test]
+    fn test_category_mapping() {
+        assert_eq!(
+            CodaError::Unauthorized.category(),
+            ErrorCategory::Unauthorized
+        );
+        assert_eq!(CodaError::Forbidden.category(), ErrorCategory::Forbidden);
+        assert_eq!(CodaError::NotFound.category(), ErrorCategory::NotFound);
+        assert_eq!(
+            CodaError::RateLimited.category(),
+            ErrorCategory::RateLimited
+        );
+        let bad = CodaError::Api {
+            status: 400,
+            body: String::new(),
+        };
+        assert_eq!(bad.category(), ErrorCategory::BadRequest);
+        assert_eq!(bad.status(), Some(400));
+        let server = CodaError::Api {
+            status: 503,
+            body: String::new(),
+        };
+        assert_eq!(server.category(), ErrorCategory::Server);
+        assert_eq!(
+            CodaError::ExportTimeout { seconds: 30 }.category(),
+            ErrorCategory::Export
+        );
+        let open = CodaError::CircuitOpen {
+            host: "coda.io".to_string(),
+            retry_after_secs: 12,
+        };
+        assert_eq!(open.category(), ErrorCategory::Unavailable);
+        assert_eq!(open.status(), None);
+        assert!(open.to_string().contains("Retry in 12s"));
+    }
+
+    #[test]
+    fn test_mcp_error_carries_status() {
+        let cases = [
+            (CodaError::Unauthorized, 401, "unauthorized"),
+            (CodaError::Forbidden, 403, "forbidden"),
+            (CodaError::NotFound, 404, "not_found"),
+            (CodaError::RateLimited, 429, "rate_limited"),
+            (
+                CodaError::Api {
+                    status: 422,
+                    body: "bad column".to_string(),
+                },
+                422,
+                "bad_request",
+            ),
+        ];
+        for (err, status, category) in cases {
+            let message = err.to_string();
+            let mcp = err.into_mcp_error();
+            let data = mcp.data.unwrap();
+            assert_eq!(data["status"], status, "{message}");
+            assert_eq!(data["category"], category, "{message}");
+            assert_eq!(mcp.message, message);
+        }
+
+        let data = CodaError::ExportTimeout { seconds: 30 }
+            .into_mcp_error()
+            .data
+            .unwrap();
+        assert!(data["status"].is_null());
+    }
+
+    #[test]
+    fn test_hint_forbidden_write_vs_read() {
+        let write = ErrorCategory::Forbidden.hint("POST", "/docs/d1/tables/t1/rows");
+        assert!(write.contains("lacks write scope"));
+        assert!(write.contains("coda.io/account"));
+
+        let read = ErrorCategory::Forbidden.hint("GET", "/docs/d1");
+        assert!(read.contains("cannot read this doc"));
+    }
+
+    #[test]
+    fn test_hint_not_found_names_resource() {
+        let hint = ErrorCategory::NotFound.hint("GET", "/docs/d1/tables/t1");
+        assert!(hint.contains("table was not found"));
+        assert!(hint.contains("table ID or name"));
+
+        let hint = ErrorCategory::NotFound.hint("GET", "/docs/d1/tables/t1/rows?limit=10");
+        assert!(hint.contains("table was not found"));
+
+        let hint = ErrorCategory::NotFound.hint("GET", "/docs/d1/tables/t1/rows/r1");
+        assert!(hint.contains("row was not found"));
+
+        let hint = ErrorCategory::NotFound.hint("GET", "/docs/d1");
+        assert!(hint.contains("doc was not found"));
+    }
+
+    #[test]
+    fn test_hint_bad_query() {
+        let hint = ErrorCategory::BadRequest.hint("GET", "/docs/d1/tables/t1/rows?query=Status");
+        assert!(hint.contains("query could not be parsed"));
+        assert!(hint.contains("structured `filter` param"));
+
+        let hint = ErrorCategory::BadRequest.hint("POST", "/docs/d1/tables/t1/rows");
+        assert!(hint.contains("rejected the request"));
+    }
+
+    #[test]
+    fn test_hint_other_categories() {
+        assert!(ErrorCategory::Unauthorized
+            .hint("GET", "/docs")
+            .contains("invalid or expired"));
+        assert!(ErrorCategory::RateLimited
+            .hint("PUT", "/docs/d1/tables/t1/rows/r1")
+            .contains("Write rate limit"));
+        assert!(ErrorCategory::RateLimited
+            .hint("GET", "/docs")
+            .contains("Read rate limit"));
+        assert!(ErrorCategory::Server
+            .hint("GET", "/docs")
+            .contains("server error"));
+        assert!(ErrorCategory::Network
+            .hint("GET", "/docs")
+            .contains("network"));
+        assert!(ErrorCategory::Parse
+            .hint("GET", "/docs")
+            .contains("RUST_LOG=debug"));
+        assert!(ErrorCategory::Export
+            .hint("POST", "/docs/d1/pages/p1/export")
+            .contains("export"));
+    }
+
+    #[test]
+    fn test_no_workspace_error() {
+        let err = CodaError::NoWorkspace {
+            login_id: "ada@example.com".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "/whoami returned no workspace for ada@example.com"
+        );
+        assert_eq!(err.status(), None);
+        assert_eq!(err.category(), ErrorCategory::Parse);
+    }
+
     #[test]
     fn test_json_error_from() {
         let json_err: Result<serde_json::Value, _> = serde_json::from_str("invalid json");
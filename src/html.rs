@@ -0,0 +1,392 @@
+//! Minimal HTML table and text extraction for page exports.
+//!
+//! Coda's HTML exports are machine-generated and well-formed enough that a
+//! small tag scanner suffices; this is not a general-purpose HTML parser.
+
+use std::fmt::Write as _;
+
+/// A `<table>` found in an HTML document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlTable {
+    /// Text of the table's `<caption>`, if it has one
+    pub caption: Option<String>,
+    /// Cell text, row by row; rows may differ in length
+    pub rows: Vec<Vec<String>>,
+}
+
+impl HtmlTable {
+    /// Render as CSV, quoting fields that contain commas, quotes or newlines
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in &self.rows {
+            let fields: Vec<String> = row.iter().map(|cell| csv_field(cell)).collect();
+            let _ = writeln!(csv, "{}", fields.join(","));
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field per RFC 4180 if it contains commas, quotes or newlines
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A table being filled in while scanning
+#[derive(Default)]
+struct TableBuilder {
+    /// Position of the opening tag, so nested tables sort after their parent
+    order: usize,
+    caption: Option<String>,
+    in_caption: bool,
+    rows: Vec<Vec<String>>,
+    row: Option<Vec<String>>,
+    cell: Option<String>,
+}
+
+impl TableBuilder {
+    fn end_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            self.row
+                .get_or_insert_with(Vec::new)
+                .push(collapse_whitespace(&cell));
+        }
+    }
+
+    fn end_row(&mut self) {
+        self.end_cell();
+        if let Some(row) = self.row.take() {
+            if !row.is_empty() {
+                self.rows.push(row);
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.in_caption {
+            self.caption.get_or_insert_with(String::new).push_str(text);
+        } else if let Some(cell) = &mut self.cell {
+            cell.push_str(text);
+        }
+    }
+
+    fn finish(mut self) -> (usize, HtmlTable) {
+        self.end_row();
+        let caption = self
+            .caption
+            .map(|c| collapse_whitespace(&c))
+            .filter(|c| !c.is_empty());
+        (
+            self.order,
+            HtmlTable {
+                caption,
+                rows: self.rows,
+            },
+        )
+    }
+}
+
+/// Extract every `<table>` in `html`, in document order.
+///
+/// `<th>` and `<td>` are both treated as cells; tags inside cells are dropped
+/// and their text kept. A nested table becomes a separate entry rather than
+/// part of its parent's cell text.
+pub fn extract_tables(html: &str) -> Vec<HtmlTable> {
+    let mut open: Vec<TableBuilder> = Vec::new();
+    let mut done: Vec<(usize, HtmlTable)> = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            if let Some(table) = open.last_mut() {
+                table.push_text(&decode_entities(rest));
+            }
+            break;
+        };
+        if start > 0 {
+            if let Some(table) = open.last_mut() {
+                table.push_text(&decode_entities(&rest[..start]));
+            }
+        }
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let (name, closing) = tag_name(&rest[1..end]);
+        rest = &rest[end + 1..];
+
+        match (name.as_str(), closing) {
+            ("script" | "style", false) => rest = skip_element(rest, &name),
+            ("table", false) => open.push(TableBuilder {
+                order: done.len() + open.len(),
+                ..TableBuilder::default()
+            }),
+            ("table", true) => {
+                if let Some(table) = open.pop() {
+                    done.push(table.finish());
+                }
+            }
+            (_, _) if open.is_empty() => {}
+            ("caption", closing) => {
+                if let Some(table) = open.last_mut() {
+                    table.in_caption = !closing;
+                }
+            }
+            ("tr", closing) => {
+                if let Some(table) = open.last_mut() {
+                    table.end_row();
+                    if !closing {
+                        table.row = Some(Vec::new());
+                    }
+                }
+            }
+            ("td" | "th", closing) => {
+                if let Some(table) = open.last_mut() {
+                    table.end_cell();
+                    if !closing {
+                        table.cell = Some(String::new());
+                    }
+                }
+            }
+            ("br" | "p" | "li" | "div", _) => {
+                if let Some(table) = open.last_mut() {
+                    table.push_text(" ");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Unclosed tables still count
+    while let Some(table) = open.pop() {
+        done.push(table.finish());
+    }
+    done.sort_by_key(|(order, _)| *order);
+    done.into_iter()
+        .map(|(_, table)| table)
+        .filter(|table| !table.rows.is_empty())
+        .collect()
+}
+
+/// Plain text of an HTML document: tags dropped, entities decoded, and
+/// block-level elements on their own lines. Table cells are separated by tabs.
+pub fn to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..start]));
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let (name, closing) = tag_name(&rest[1..end]);
+        rest = &rest[end + 1..];
+
+        match (name.as_str(), closing) {
+            ("script" | "style", false) => rest = skip_element(rest, &name),
+            ("td" | "th", false) => text.push('\t'),
+            (
+                "br" | "p" | "div" | "li" | "tr" | "table" | "ul" | "ol" | "pre" | "blockquote"
+                | "hr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6",
+                _,
+            ) => text.push('\n'),
+            _ => {}
+        }
+    }
+    text.push_str(&decode_entities(rest));
+
+    text.lines()
+        .map(|line| {
+            line.split('\t')
+                .map(collapse_whitespace)
+                .filter(|cell| !cell.is_empty())
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Lowercased element name of a tag's inner text, and whether it is a closing tag
+fn tag_name(tag: &str) -> (String, bool) {
+    let name = tag
+        .trim_start_matches('/')
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    (name, tag.starts_with('/'))
+}
+
+/// Skip the raw text of a `<script>` or `<style>` element, up to its closing tag
+fn skip_element<'a>(rest: &'a str, name: &str) -> &'a str {
+    let close = format!("</{name}");
+    rest.to_ascii_lowercase()
+        .find(&close)
+        .map_or("", |i| &rest[i..])
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode the named entities Coda exports use, plus numeric references
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        if let Some((c, end)) = decoded {
+            out.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_simple_table() {
+        let html = r#"<html><body>
+            <h1>Tasks</h1>
+            <table class="grid">
+              <thead><tr><th>Name</th><th>Status</th></tr></thead>
+              <tbody>
+                <tr><td>Write <b>docs</b></td><td>Done</td></tr>
+                <tr><td>Ship</td><td>Open</td></tr>
+              </tbody>
+            </table>
+        </body></html>"#;
+
+        let tables = extract_tables(html);
+
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].caption, None);
+        assert_eq!(
+            tables[0].rows,
+            vec![
+                vec!["Name", "Status"],
+                vec!["Write docs", "Done"],
+                vec!["Ship", "Open"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_caption_and_entities() {
+        let html = "<table><caption>Q1 &amp; Q2</caption>\
+            <tr><td>Tom&#39;s</td><td>&lt;5&gt;</td><td>a&nbsp;b</td><td>&#x263A;</td></tr>\
+            </table>";
+
+        let tables = extract_tables(html);
+
+        assert_eq!(tables[0].caption.as_deref(), Some("Q1 & Q2"));
+        assert_eq!(tables[0].rows[0], vec!["Tom's", "<5>", "a b", "\u{263A}"]);
+    }
+
+    #[test]
+    fn test_extract_unclosed_cells_and_line_breaks() {
+        // Optional end tags omitted, as HTML allows
+        let html = "<table><tr><td>one<br>two<td>three<tr><td>four</table>";
+
+        let tables = extract_tables(html);
+
+        assert_eq!(tables[0].rows, vec![vec!["one two", "three"], vec!["four"]]);
+    }
+
+    #[test]
+    fn test_extract_multiple_and_nested_tables_in_order() {
+        let html = "<table><tr><td>outer<table><tr><td>inner</td></tr></table></td></tr></table>\
+            <p>between</p>\
+            <table><tr><td>second</td></tr></table>";
+
+        let tables = extract_tables(html);
+
+        let firsts: Vec<&str> = tables.iter().map(|t| t.rows[0][0].as_str()).collect();
+        assert_eq!(firsts, ["outer", "inner", "second"]);
+    }
+
+    #[test]
+    fn test_extract_ignores_comments_scripts_and_empty_tables() {
+        let html = "<!-- <table><tr><td>hidden</td></tr></table> -->\
+            <script>var t = '<table>';</script>\
+            <table></table>\
+            <p>No data here</p>";
+
+        assert!(extract_tables(html).is_empty());
+    }
+
+    #[test]
+    fn test_to_text_strips_tags_and_keeps_structure() {
+        let html = "<html><head><style>p { color: red }</style></head><body>\
+            <h1>Launch&nbsp;plan</h1><!-- draft -->\
+            <p>Ship <b>v2</b> &amp; announce</p>\
+            <ul><li>Docs</li><li>Blog</li></ul>\
+            <table><tr><th>Task</th><th>Owner</th></tr><tr><td>QA</td><td>Ann</td></tr></table>\
+            <script>alert('<p>')</script></body></html>";
+
+        assert_eq!(
+            to_text(html),
+            "Launch plan\nShip v2 & announce\nDocs\nBlog\nTask\tOwner\nQA\tAnn"
+        );
+    }
+
+    #[test]
+    fn test_to_csv_quotes_special_characters() {
+        let table = HtmlTable {
+            caption: None,
+            rows: vec![
+                vec!["Name".to_string(), "Note".to_string()],
+                vec!["Smith, J".to_string(), "said \"hi\"".to_string()],
+            ],
+        };
+
+        assert_eq!(
+            table.to_csv(),
+            "Name,Note\n\"Smith, J\",\"said \"\"hi\"\"\"\n"
+        );
+    }
+}
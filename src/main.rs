@@ -1,11 +1,13 @@
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    handler::server::{router::tool::ToolRouter, tool::ToolCallContext, wrapper::Parameters},
     model::{
-        CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
+        CallToolRequestParams, CallToolResult, Content, Implementation, ListToolsResult,
+        PaginatedRequestParams, ProtocolVersion, ServerCapabilities, ServerInfo,
     },
-    tool, tool_handler, tool_router,
+    service::RequestContext,
+    tool, tool_router,
     transport::stdio,
-    ErrorData as McpError, ServerHandler, ServiceExt,
+    ErrorData as McpError, RoleServer, ServerHandler, ServiceExt,
 };
 use std::fmt::Write as _;
 
@@ -18,38 +20,181 @@ const POLL_INTERVAL_SECS: u64 = 1;
 const MAX_POLL_ATTEMPTS: u32 = 3;
 #[cfg(test)]
 const POLL_INTERVAL_SECS: u64 = 0;
+
+/// Bounds for `get_doc_schema`, `get_table_relationships` and `load_doc_context`
+/// fan-out on docs with many tables
+const SCHEMA_DEFAULT_TABLE_LIMIT: u32 = 50;
+const SCHEMA_MAX_TABLE_LIMIT: u32 = 200;
+const SCHEMA_DEFAULT_CONCURRENCY: u32 = 4;
+const SCHEMA_MAX_CONCURRENCY: u32 = 8;
+
+/// Bounds for `load_doc_context` row samples; table bounds are shared with `get_doc_schema`
+const CONTEXT_DEFAULT_SAMPLE_ROWS: u32 = 5;
+const CONTEXT_MAX_SAMPLE_ROWS: u32 = 50;
+
+/// Bounds for per-column detail fetches in `list_columns` with `include_formulas`
+const COLUMN_FORMULA_CONCURRENCY: usize = 4;
+const COLUMN_FORMULA_MAX_FETCHES: usize = 50;
+
+/// Bounds for `get_rows_multi`
+const MULTI_ROWS_MAX_SOURCES: usize = 20;
+const MULTI_ROWS_CONCURRENCY: usize = 4;
+
+/// Page size and row cap for `get_rows` with `fetch_all`
+const GET_ROWS_PAGE_SIZE: u32 = 1000;
+#[cfg(not(test))]
+const GET_ROWS_MAX_ROWS: usize = 10_000;
+#[cfg(test)]
+const GET_ROWS_MAX_ROWS: usize = 4;
+
+/// Bounds for `table_data_quality` and `column_profile` row sampling
+const DATA_QUALITY_DEFAULT_ROWS: u32 = 1000;
+const DATA_QUALITY_MAX_ROWS: u32 = 10_000;
+const DATA_QUALITY_PAGE_SIZE: u32 = 500;
+
+/// How many frequent values `column_profile` returns
+const PROFILE_DEFAULT_TOP_K: u32 = 5;
+const PROFILE_MAX_TOP_K: u32 = 50;
+
+/// Bounds for `export_pages`
+const EXPORT_PAGES_MAX_PAGES: usize = 20;
+const EXPORT_PAGES_CONCURRENCY: usize = 3;
+
+/// Most rows `add_rows` sends in one request
+const ADD_ROWS_MAX_ROWS: usize = 500;
+
+/// Most rows `append_to_multiselect` updates in one call
+const MULTISELECT_MAX_ROWS: usize = 100;
+
+/// Most pages `create_page_outline` creates in one call
+const OUTLINE_MAX_PAGES: usize = 100;
+
+/// Bounds for the row read-back in `get_mutation_result`
+const MUTATION_RESULT_MAX_ROWS: usize = 100;
+const MUTATION_RESULT_CONCURRENCY: usize = 4;
+
+/// Bounds for `list_docs_with_table_counts`; concurrency is shared with `get_doc_schema`
+const TABLE_COUNT_DEFAULT_DOCS: u32 = 50;
+const TABLE_COUNT_MAX_DOCS: u32 = 200;
+const TABLE_COUNT_PAGE_SIZE: u32 = 500;
+
+/// Bounds for the doc scan in `list_docs_active_since`
+const ACTIVE_DOCS_DEFAULT_SCAN: u32 = 500;
+const ACTIVE_DOCS_MAX_SCAN: u32 = 5000;
+const ACTIVE_DOCS_PAGE_SIZE: u32 = 100;
+
+/// Docs listed per account by `list_all_docs`
+const ALL_DOCS_DEFAULT_PER_ACCOUNT: u32 = 200;
+const ALL_DOCS_MAX_PER_ACCOUNT: u32 = 1000;
+
+/// Coda applies row mutations asynchronously
+const MUTATION_DELAY_NOTE: &str = "Changes may take a few seconds to appear.";
+
+/// Note for `copy_doc`, whose copy Coda fills in after responding
+const COPY_DOC_NOTE: &str =
+    "Coda copies the source's content in the background; large docs may take a while to fill in.";
+
+/// Bounds for `poll_formula`, `wait_for_row_value` and `get_mutation_result`
+const FORMULA_DEFAULT_POLLS: u32 = 10;
+const FORMULA_MAX_POLLS: u32 = 60;
+const FORMULA_DEFAULT_INTERVAL_SECS: u64 = 5;
+const FORMULA_MAX_INTERVAL_SECS: u64 = 60;
+#[cfg(not(test))]
+const FORMULA_MIN_INTERVAL_SECS: u64 = 1;
+#[cfg(test)]
+const FORMULA_MIN_INTERVAL_SECS: u64 = 0;
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write as _;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tracing_subscriber::EnvFilter;
 
+mod breaker;
+mod cache;
 mod client;
 mod config;
+mod echo;
 mod error;
+mod html;
 mod models;
+mod progress;
+mod render;
+mod structured;
+mod time;
 
-use client::CodaClient;
+use cache::{ColumnCache, DocContext, DocContextCache};
+use client::{CodaClient, Download, CURRENT_TOOL};
 use config::Config;
+use echo::{EchoLog, ECHO};
+use error::{CodaError, ErrorCategory};
 use models::{
-    AddRowParams, ColumnList, ControlList, CreateDocParams, DeleteDocParams, DeleteRowParams, Doc,
-    DocList, ExportRequest, ExportResponse, Formula, FormulaList, GetDocParams, GetFormulaParams,
-    GetPageParams, GetRowParams, GetRowsParams, GetTableParams, ListColumnsParams,
-    ListControlsParams, ListDocsParams, ListFormulasParams, ListPagesParams, ListTablesParams,
-    Page, PageList, Row, RowList, RowMutationResponse, SearchDocsParams, Table, TableList,
-    UpdateRowParams,
+    build_page_tree, cell_matches, check_content_format, coerce_cells, dedupe_by_id,
+    effective_access, merge_select_values, resolve_target, write_page_outline, AccountDoc,
+    AccountDocCount, AclMetadata, AddPermissionParams, AddRowParams, AddRowsParams,
+    AnalyticsParams, ApiLink, AppendToMultiselectParams, AutomationResponse, BatchFailure,
+    BatchResult, CategoryList, CheckQueryParams, Coercion, Column, ColumnFillRate, ColumnKeys,
+    ColumnList, ColumnProfile, ColumnProfileParams, ColumnarRows, ControlList, CopyDocParams,
+    CreateDocParams, CreatePageOutlineParams, CreatePageParams, CreatePageRequest, DeleteDocParams,
+    DeletePermissionParams, DeleteRowParams, DeleteRowsParams, DiffRowsParams, Doc, DocAccess,
+    DocAnalytics, DocList, DocTableCount, ExistsKind, ExistsParams, ExportPagesParams,
+    ExportRequest, ExportResponse, ExportSessionLogParams, ExtractPageTablesParams, Formula,
+    FormulaList, GetColumnParams, GetDocParams, GetDocSchemaParams, GetFormulaParams,
+    GetMutationResultParams, GetMutationStatusParams, GetPageParams, GetRowParams,
+    GetRowsDeltaParams, GetRowsMultiParams, GetRowsParams, GetTableParams,
+    GetTableRelationshipsParams, GetWorkspaceParams, ListAllDocsParams, ListColumnsParams,
+    ListControlsParams, ListDocsActiveSinceParams, ListDocsParams, ListDocsWithTableCountsParams,
+    ListFormulasParams, ListPagesParams, ListPermissionsParams, ListTablesParams,
+    LoadDocContextParams, MoveDocParams, MutationStatus, OutlineNode, OutlinePage, Page, PageList,
+    PageMutationResponse, PageSeed, PageTransform, PermissionList, PollFormulaParams,
+    PublishDocParams, PushButtonParams, RenameKind, RenameParams, ResolveLinkParams,
+    ResumePageExportParams, Row, RowDiff, RowList, RowMutationResponse, RowsDeleteResponse,
+    RowsFormat, SearchDocsParams, SeededPage, SelectOption, SessionLogEntry, SetControlValueParams,
+    SourcedRow, SourcedTable, Table, TableDataQualityParams, TableEdge, TableList, TableSchema,
+    TaggedRow, ToolOutcome, TriggerAutomationParams, UnpublishDocParams, UpdatePageParams,
+    UpdatePageRequest, UpdateRowParams, User, ValueFormat, WaitForRowValueParams,
+    WorkspaceReference,
 };
+use progress::{Progress, PROGRESS};
+use structured::{DataBlocks, STRUCTURED};
 
 #[derive(Clone)]
 pub struct CodaMcpServer {
     client: Arc<CodaClient>,
+    config: Arc<Config>,
+    context_cache: Arc<DocContextCache>,
+    column_cache: Arc<ColumnCache>,
+    /// The token's workspace, from `/whoami`, once looked up
+    workspace: Arc<std::sync::Mutex<Option<WorkspaceReference>>>,
     tool_router: ToolRouter<Self>,
 }
 
 #[tool_router]
 impl CodaMcpServer {
-    pub fn new(client: Arc<CodaClient>) -> Self {
+    pub fn new(client: Arc<CodaClient>, config: &Config) -> Self {
+        let mut tool_router = Self::tool_router();
+        for name in filter_tools(
+            &mut tool_router,
+            &config.enabled_tools,
+            &config.disabled_tools,
+        ) {
+            tracing::warn!("Unknown tool name in CODA_ENABLED_TOOLS/CODA_DISABLED_TOOLS: {name}");
+        }
+
         Self {
             client,
-            tool_router: Self::tool_router(),
+            config: Arc::new(config.clone()),
+            context_cache: Arc::new(DocContextCache::new(
+                Duration::from_secs(config.context_cache_ttl_secs),
+                config.context_cache_max_docs,
+            )),
+            column_cache: Arc::new(ColumnCache::new(Duration::from_secs(
+                config.column_cache_ttl_secs,
+            ))),
+            workspace: Arc::default(),
+            tool_router,
         }
     }
 
@@ -66,22 +211,34 @@ impl CodaMcpServer {
         if let Some(query) = &params.query {
             let _ = write!(path, "&query={}", urlencoding::encode(query));
         }
+        if let Some(token) = &params.page_token {
+            let _ = write!(path, "&pageToken={}", urlencoding::encode(token));
+        }
 
-        tracing::info!("list_docs: limit={}, query={:?}", limit, params.query);
+        tracing::info!(
+            "list_docs: limit={}, query={:?}, page_token={:?}",
+            limit,
+            params.query,
+            params.page_token
+        );
 
-        let docs: DocList = self
+        let mut docs: DocList = self
             .client
             .get(&path)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(CodaError::into_mcp_error)?;
+
+        if params.dedupe.unwrap_or(false) {
+            dedupe_by_id(&mut docs.items);
+        }
 
         let summary = format!("Found {} documents", docs.items.len());
-        let json = serde_json::to_string_pretty(&docs.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let block = self.render(&docs.items)?;
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
+        let mut output = format!("{summary}\n\n{block}");
+        push_next_page(&mut output, docs.next_page_token.as_deref());
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
     #[tool(description = "Get detailed information about a specific Coda document.")]
@@ -97,1006 +254,12190 @@ impl CodaMcpServer {
             .client
             .get(&path)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(CodaError::into_mcp_error)?;
 
-        let json = serde_json::to_string_pretty(&doc)
+        let block = self.render(&doc)?;
+        let data = serde_json::to_value(&doc)
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Document: {}\n\n```json\n{}\n```",
-            doc.name, json
-        ))]))
+        Ok(
+            ToolOutcome::success("get_doc", format!("Fetched document '{}'", doc.name), data)
+                .into_result(format!("Document: {}\n\n{}", doc.name, block)),
+        )
     }
 
-    #[tool(description = "Search for Coda documents by name or content.")]
-    async fn search_docs(
+    #[tool(
+        description = "Get a document's size metrics: total rows, tables and views, pages, and whether it exceeds the API size limit."
+    )]
+    async fn get_doc_size(
         &self,
-        Parameters(params): Parameters<SearchDocsParams>,
+        Parameters(params): Parameters<GetDocParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs?query={}", urlencoding::encode(&params.query));
+        let path = format!("/docs/{}", params.doc_id);
 
-        tracing::info!("search_docs: query={}", params.query);
+        tracing::info!("get_doc_size: doc_id={}", params.doc_id);
 
-        let docs: DocList = self
+        let doc: Doc = self
             .client
             .get(&path)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(CodaError::into_mcp_error)?;
 
-        let summary = format!(
-            "Found {} documents matching '{}'",
-            docs.items.len(),
-            params.query
+        let Some(size) = doc.doc_size else {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "Document: {}\n\nSize information is not available for this doc.",
+                doc.name
+            ))]));
+        };
+
+        let count = |n: Option<u64>| n.map_or_else(|| "unknown".to_string(), |n| n.to_string());
+        let mut output = String::new();
+        if size.over_api_size_limit {
+            output.push_str(
+                "WARNING: this doc is over the API size limit. Row reads may be incomplete \
+                 or fail; narrow requests with `query` or smaller tables.\n\n",
+            );
+        }
+        let _ = writeln!(output, "Document: {}", doc.name);
+        let _ = writeln!(output, "Total rows: {}", count(size.total_row_count));
+        let _ = writeln!(
+            output,
+            "Tables and views: {}",
+            count(size.table_and_view_count)
         );
-        let json = serde_json::to_string_pretty(&docs.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let _ = writeln!(output, "Pages: {}", count(size.page_count));
+        let _ = write!(output, "Over API size limit: {}", size.over_api_size_limit);
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
+        Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
     #[tool(
-        description = "Create a new Coda document. Optionally specify a folder, source document (template), or timezone."
+        description = "Get a document's icon (name and image URL) and cover image URL, e.g. for building doc galleries. Read-only."
     )]
-    async fn create_doc(
+    async fn get_doc_appearance(
         &self,
-        Parameters(params): Parameters<CreateDocParams>,
+        Parameters(params): Parameters<GetDocParams>,
     ) -> Result<CallToolResult, McpError> {
-        tracing::info!(
-            "create_doc: title={}, folder_id={:?}, source_doc={:?}, timezone={:?}",
-            params.title,
-            params.folder_id,
-            params.source_doc,
-            params.timezone
-        );
+        let path = format!("/docs/{}", params.doc_id);
 
-        let doc: Doc = match self.client.post("/docs", &params).await {
+        tracing::info!("get_doc_appearance: doc_id={}", params.doc_id);
+
+        let doc: Doc = match self.client.get(&path).await {
             Ok(doc) => doc,
             Err(e) => {
-                return Ok(CallToolResult::error(vec![Content::text(e.to_string())]));
+                return Ok(
+                    ToolOutcome::from_error("get_doc_appearance", &e).into_result(e.to_string())
+                );
             }
         };
 
-        let json = serde_json::to_string_pretty(&doc)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Document created successfully!\n\nName: {}\nID: {}\n\n```json\n{}\n```",
-            doc.name, doc.id, json
-        ))]))
+        let summary = format!("Appearance of '{}'", doc.name);
+        let text = format!("Document: {}\n{}", doc.name, doc.appearance());
+        Ok(ToolOutcome::success(
+            "get_doc_appearance",
+            summary,
+            serde_json::json!({"icon": doc.icon, "cover_image": doc.cover_image}),
+        )
+        .into_result(text))
     }
 
-    #[tool(description = "Delete a Coda document. This action is permanent and cannot be undone.")]
-    async fn delete_doc(
+    #[tool(
+        description = "Get a shareable browser link for a document, plus its public URL if the doc is published."
+    )]
+    async fn get_doc_link(
         &self,
-        Parameters(params): Parameters<DeleteDocParams>,
+        Parameters(params): Parameters<GetDocParams>,
     ) -> Result<CallToolResult, McpError> {
         let path = format!("/docs/{}", params.doc_id);
 
-        tracing::info!("delete_doc: doc_id={}", params.doc_id);
-
-        if let Err(e) = self.client.delete(&path).await {
-            return Ok(CallToolResult::error(vec![Content::text(e.to_string())]));
-        }
-
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Document '{}' deleted successfully.",
-            params.doc_id
-        ))]))
-    }
-
-    // === Page Tools ===
-
-    #[tool(description = "List all pages in a Coda document.")]
-    async fn list_pages(
-        &self,
-        Parameters(params): Parameters<ListPagesParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/pages", params.doc_id);
-
-        tracing::info!("list_pages: doc_id={}", params.doc_id);
+        tracing::info!("get_doc_link: doc_id={}", params.doc_id);
 
-        let pages: PageList = self
+        let doc: Doc = self
             .client
             .get(&path)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(CodaError::into_mcp_error)?;
 
-        let summary = format!("Found {} pages", pages.items.len());
-        let json = serde_json::to_string_pretty(&pages.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let mut output = format!("Document: {}\nLink: {}", doc.name, doc.link());
+        match &doc.published {
+            Some(published) => {
+                let _ = write!(output, "\nPublished: {}", published.browser_link);
+            }
+            None => output.push_str("\nPublished: no"),
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
+        Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
-    #[tool(description = "Get a specific page's content in HTML format.")]
-    async fn get_page(
+    #[tool(
+        description = "Report the current user's effective access to a doc (owner, write, comment, readonly, none or unknown) and the evidence for it. Use before attempting writes."
+    )]
+    async fn get_my_doc_access(
         &self,
-        Parameters(params): Parameters<GetPageParams>,
+        Parameters(params): Parameters<GetDocParams>,
     ) -> Result<CallToolResult, McpError> {
-        tracing::info!(
-            "get_page: doc_id={}, page_id={}",
-            params.doc_id,
-            params.page_id
-        );
-
-        // Step 1: Initiate export
-        let export_path = format!("/docs/{}/pages/{}/export", params.doc_id, params.page_id);
-        let export_request = ExportRequest {
-            output_format: "html".to_string(),
-        };
-
-        tracing::info!("Initiating page export: POST {}", export_path);
-        let export: ExportResponse = self
-            .client
-            .post(&export_path, &export_request)
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to initiate export: {}", e);
-                McpError::internal_error(e.to_string(), None)
-            })?;
-        tracing::info!(
-            "Export initiated: id={}, status={}",
-            export.id,
-            export.status
-        );
+        tracing::info!("get_my_doc_access: doc_id={}", params.doc_id);
 
-        // Step 2: Poll for completion (max 30 attempts, 1s interval)
-        let status_path = format!(
-            "/docs/{}/pages/{}/export/{}",
-            params.doc_id, params.page_id, export.id
+        let doc_path = format!("/docs/{}", params.doc_id);
+        let (user, doc) = tokio::join!(
+            self.client.get::<User>("/whoami"),
+            self.client.get::<Doc>(&doc_path)
         );
-
-        for attempt in 1..=MAX_POLL_ATTEMPTS {
-            tracing::info!(
-                "Polling export status, attempt {}/{}: GET {}",
-                attempt,
-                MAX_POLL_ATTEMPTS,
-                status_path
-            );
-
-            let status: ExportResponse = self.client.get(&status_path).await.map_err(|e| {
-                tracing::error!("Failed to poll export status: {}", e);
-                McpError::internal_error(e.to_string(), None)
-            })?;
-            tracing::info!("Export status: {}", status.status);
-
-            match status.status.as_str() {
-                "complete" => {
-                    // Step 3: Download content from temporary link
-                    let download_link = status.download_link.ok_or_else(|| {
-                        McpError::internal_error(
-                            "Export complete but no download link provided".to_string(),
-                            None,
-                        )
-                    })?;
-
-                    tracing::info!("Export complete, downloading from: {}", download_link);
-                    let content = self
-                        .client
-                        .download_raw(&download_link)
-                        .await
-                        .map_err(|e| {
-                            tracing::error!("Failed to download export: {}", e);
-                            McpError::internal_error(e.to_string(), None)
-                        })?;
-                    tracing::info!("Downloaded {} bytes", content.len());
-
-                    // Get page metadata for the name
-                    let page_path = format!("/docs/{}/pages/{}", params.doc_id, params.page_id);
-                    let page: Page = self
-                        .client
-                        .get(&page_path)
-                        .await
-                        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Page: {}\n\nContent:\n{}",
-                        page.name, content
-                    ))]));
-                }
-                "failed" => {
-                    let error_msg = status.error.unwrap_or_else(|| "Unknown error".to_string());
-                    return Err(McpError::internal_error(
-                        format!("Export failed: {error_msg}"),
-                        None,
-                    ));
+        let user = user.map_err(CodaError::into_mcp_error)?;
+
+        let (access, evidence) = match doc {
+            Ok(doc) => {
+                let metadata_path = format!("/docs/{}/acl/metadata", params.doc_id);
+                let permissions_path = format!("/docs/{}/acl/permissions", params.doc_id);
+                let (metadata, permissions) = tokio::join!(
+                    self.client.get::<AclMetadata>(&metadata_path),
+                    self.client.get::<PermissionList>(&permissions_path)
+                );
+                let mut unavailable = Vec::new();
+                if let Err(e) = &metadata {
+                    unavailable.push(format!("ACL metadata unavailable: {e}"));
                 }
-                _ => {
-                    // Still processing, wait and retry
-                    tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+                if let Err(e) = &permissions {
+                    unavailable.push(format!("Permission list unavailable: {e}"));
                 }
+
+                let (access, mut evidence) = effective_access(
+                    &user.login_id,
+                    doc.owner.as_deref(),
+                    metadata.as_ref().ok(),
+                    permissions.as_ref().ok().map(|list| list.items.as_slice()),
+                );
+                evidence.extend(unavailable);
+                (access, evidence)
             }
+            Err(e @ (CodaError::NotFound | CodaError::Forbidden)) => (
+                DocAccess::None,
+                vec![format!("Doc is not visible to this token: {e}")],
+            ),
+            Err(e) => return Err(e.into_mcp_error()),
+        };
+
+        let mut output = format!("Access: {}\n\nUser: {}", access.as_str(), user.login_id);
+        output.push_str("\nEvidence:");
+        for item in &evidence {
+            let _ = write!(output, "\n- {item}");
         }
 
-        Err(McpError::internal_error(
-            format!(
-                "Export timed out after {} seconds",
-                u64::from(MAX_POLL_ATTEMPTS) * POLL_INTERVAL_SECS
-            ),
-            None,
-        ))
+        Ok(ToolOutcome::success(
+            "get_my_doc_access",
+            format!("{} has {} access", user.login_id, access.as_str()),
+            serde_json::json!({
+                "access": access,
+                "login_id": user.login_id,
+                "evidence": evidence,
+            }),
+        )
+        .into_result(output))
     }
 
-    // === Table Tools ===
-
-    #[tool(description = "List all tables in a Coda document.")]
-    async fn list_tables(
+    #[tool(
+        description = "List a doc's sharing permissions: who (email, domain or anyone) has which access."
+    )]
+    async fn list_permissions(
         &self,
-        Parameters(params): Parameters<ListTablesParams>,
+        Parameters(params): Parameters<ListPermissionsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/tables", params.doc_id);
+        let path = format!(
+            "/docs/{}/acl/permissions{}",
+            params.doc_id,
+            page_query(params.limit, params.page_token.as_deref())
+        );
 
-        tracing::info!("list_tables: doc_id={}", params.doc_id);
+        tracing::info!("list_permissions: doc_id={}", params.doc_id);
 
-        let tables: TableList = self
+        let permissions: PermissionList = self
             .client
             .get(&path)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(CodaError::into_mcp_error)?;
 
-        let summary = format!("Found {} tables", tables.items.len());
-        let json = serde_json::to_string_pretty(&tables.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let summary = format!("Found {} permissions", permissions.items.len());
+        let block = self.render(&permissions.items)?;
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
+        let mut output = format!("{summary}\n\n{block}");
+        push_next_page(&mut output, permissions.next_page_token.as_deref());
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
-    #[tool(description = "Get detailed information about a specific table.")]
-    async fn get_table(
+    #[tool(
+        description = "Share a doc with an email address or an email domain at readonly, comment or write access. Set `suppress_email` to skip the notification email."
+    )]
+    async fn add_permission(
         &self,
-        Parameters(params): Parameters<GetTableParams>,
+        Parameters(params): Parameters<AddPermissionParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/tables/{}", params.doc_id, params.table_id);
+        let request = match params.to_request() {
+            Ok(request) => request,
+            Err(message) => {
+                return Ok(
+                    ToolOutcome::failure("add_permission", message.clone()).into_result(message)
+                );
+            }
+        };
+        let path = format!("/docs/{}/acl/permissions", params.doc_id);
+        let principal = request
+            .principal
+            .email
+            .as_deref()
+            .or(request.principal.domain.as_deref())
+            .unwrap_or_default()
+            .to_string();
 
         tracing::info!(
-            "get_table: doc_id={}, table_id={}",
+            "add_permission: doc_id={}, principal={}, access={}",
             params.doc_id,
-            params.table_id
+            principal,
+            request.access.as_str()
         );
 
-        let table: Table = self
+        if let Err(e) = self
             .client
-            .get(&path)
+            .post::<serde_json::Value, _>(&path, &request)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let json = serde_json::to_string_pretty(&table)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        {
+            return Ok(ToolOutcome::from_error("add_permission", &e).into_result(e.to_string()));
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Table: {}\n\n```json\n{}\n```",
-            table.name, json
-        ))]))
+        let summary = format!(
+            "Granted {} access to {} '{}'",
+            request.access.as_str(),
+            request.principal.principal_type,
+            principal
+        );
+        Ok(ToolOutcome::success(
+            "add_permission",
+            summary.clone(),
+            serde_json::json!({
+                "access": request.access,
+                "principal": request.principal,
+            }),
+        )
+        .into_result(summary))
     }
 
-    #[tool(description = "List all columns in a table.")]
-    async fn list_columns(
+    #[tool(
+        description = "Remove a sharing permission from a doc by its ID (from list_permissions). In safe mode, confirm with the permission ID."
+    )]
+    async fn delete_permission(
         &self,
-        Parameters(params): Parameters<ListColumnsParams>,
+        Parameters(params): Parameters<DeletePermissionParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/tables/{}/columns", params.doc_id, params.table_id);
+        let path = format!(
+            "/docs/{}/acl/permissions/{}",
+            params.doc_id, params.permission_id
+        );
 
         tracing::info!(
-            "list_columns: doc_id={}, table_id={}",
+            "delete_permission: doc_id={}, permission_id={}",
             params.doc_id,
-            params.table_id
+            params.permission_id
         );
 
-        let columns: ColumnList = self
-            .client
-            .get(&path)
+        if let Some(refusal) = self
+            .check_confirmation(
+                "delete_permission",
+                &params.permission_id,
+                &path,
+                params.confirm.as_deref(),
+            )
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        {
+            return Ok(refusal);
+        }
 
-        let summary = format!("Found {} columns", columns.items.len());
-        let json = serde_json::to_string_pretty(&columns.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        if let Err(e) = self.client.delete(&path).await {
+            return Ok(ToolOutcome::from_error("delete_permission", &e).into_result(e.to_string()));
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
+        let summary = format!("Permission '{}' deleted", params.permission_id);
+        Ok(ToolOutcome::success(
+            "delete_permission",
+            summary.clone(),
+            serde_json::json!({"permission_id": params.permission_id}),
+        )
+        .into_result(summary))
     }
 
-    // === Row Tools ===
-
     #[tool(
-        description = "Get rows from a table with optional filtering. Returns rows with column values using column names as keys."
+        description = "Get a doc's analytics (views, sessions, copies, likes) per day, optionally between `since` and `until` dates (YYYY-MM-DD or e.g. 30d) and with scale daily or cumulative."
     )]
-    async fn get_rows(
+    async fn analytics_doc(
         &self,
-        Parameters(params): Parameters<GetRowsParams>,
+        Parameters(params): Parameters<AnalyticsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let limit = params.limit.unwrap_or(100).min(1000);
-        let mut path = format!(
-            "/docs/{}/tables/{}/rows?limit={}&useColumnNames=true",
-            params.doc_id, params.table_id, limit
-        );
-
-        if let Some(query) = &params.query {
-            let _ = write!(path, "&query={}", urlencoding::encode(query));
+        let now = Utc::now();
+        let mut query = Vec::new();
+        for (name, value) in [("sinceDate", &params.since), ("untilDate", &params.until)] {
+            let Some(value) = value else { continue };
+            match time::parse_date(value, now) {
+                Ok(date) => query.push(format!("{name}={date}")),
+                Err(message) => {
+                    return Ok(
+                        ToolOutcome::failure("analytics_doc", message.clone()).into_result(message)
+                    );
+                }
+            }
+        }
+        if let Some(scale) = &params.scale {
+            query.push(format!("scale={}", urlencoding::encode(scale)));
         }
+        let mut path = format!("/docs/{}/analytics", params.doc_id);
+        if !query.is_empty() {
+            let _ = write!(path, "?{}", query.join("&"));
+        }
+        let path = paged_path(&path, params.limit, params.page_token.as_deref());
 
         tracing::info!(
-            "get_rows: doc_id={}, table_id={}, limit={}, query={:?}",
+            "analytics_doc: doc_id={}, since={:?}, until={:?}, scale={:?}",
             params.doc_id,
-            params.table_id,
-            limit,
-            params.query
+            params.since,
+            params.until,
+            params.scale
         );
 
-        let rows: RowList = self
-            .client
-            .get(&path)
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let analytics: DocAnalytics = match self.client.get(&path).await {
+            Ok(analytics) => analytics,
+            // Coda answers 401 rather than 403 when analytics aren't enabled
+            Err(e @ CodaError::Unauthorized) => {
+                let message = "Coda returned 401 for doc analytics. Analytics are probably not enabled for this account; if other tools work with this token, the token itself is fine.".to_string();
+                return Ok(ToolOutcome {
+                    summary: message.clone(),
+                    ..ToolOutcome::from_error("analytics_doc", &e)
+                }
+                .into_result(message));
+            }
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("analytics_doc", &e).into_result(e.to_string()));
+            }
+        };
 
-        let summary = format!("Found {} rows", rows.items.len());
-        let json = serde_json::to_string_pretty(&rows.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let summary = format!(
+            "{} entries: {} views, {} sessions",
+            analytics.items.len(),
+            analytics.total(|e| e.views),
+            analytics.total(|e| e.total_sessions)
+        );
+        let data = serde_json::to_value(&analytics)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let block = self.render(&analytics.items)?;
+        let mut output = format!("{summary}\n\n{block}");
+        push_next_page(&mut output, analytics.next_page_token.as_deref());
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
+        Ok(ToolOutcome::success("analytics_doc", summary, data).into_result(output))
     }
 
-    #[tool(description = "Get a specific row by ID.")]
-    async fn get_row(
+    #[tool(
+        description = "Get a compact table of contents for a document: its pages as a tree and its tables with row counts."
+    )]
+    async fn get_doc_toc(
         &self,
-        Parameters(params): Parameters<GetRowParams>,
+        Parameters(params): Parameters<GetDocParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!(
-            "/docs/{}/tables/{}/rows/{}?useColumnNames=true",
-            params.doc_id, params.table_id, params.row_id
-        );
+        tracing::info!("get_doc_toc: doc_id={}", params.doc_id);
+
+        let pages_path = format!("/docs/{}/pages", params.doc_id);
+        let tables_path = format!("/docs/{}/tables", params.doc_id);
+        let (pages, tables): (PageList, TableList) =
+            tokio::try_join!(self.client.get(&pages_path), self.client.get(&tables_path))
+                .map_err(CodaError::into_mcp_error)?;
+
+        let tree = build_page_tree(&pages.items);
+
+        let mut output = format!("Pages ({}):", pages.items.len());
+        write_page_outline(&mut output, &tree, 0);
+        let _ = write!(output, "\n\nTables ({}):", tables.items.len());
+        for table in &tables.items {
+            match table.row_count {
+                Some(count) => {
+                    let _ = write!(output, "\n- {} ({count} rows)", table.name);
+                }
+                None => {
+                    let _ = write!(output, "\n- {}", table.name);
+                }
+            }
+        }
+
+        let tables_json: Vec<serde_json::Value> = tables
+            .items
+            .iter()
+            .map(|t| serde_json::json!({"id": t.id, "name": t.name, "rowCount": t.row_count}))
+            .collect();
+        let mut result = CallToolResult::structured(serde_json::json!({
+            "pages": tree,
+            "tables": tables_json,
+        }));
+        result.content = vec![Content::text(output)];
+        Ok(result)
+    }
+
+    #[tool(
+        description = "List docs updated within a time window, newest first. `since` is an RFC3339 timestamp or a relative duration like 7d, 24h or 30m."
+    )]
+    async fn list_docs_active_since(
+        &self,
+        Parameters(params): Parameters<ListDocsActiveSinceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let cutoff = match time::parse_since(&params.since, Utc::now()) {
+            Ok(cutoff) => cutoff,
+            Err(message) => {
+                return Ok(
+                    ToolOutcome::failure("list_docs_active_since", message.clone())
+                        .into_result(message),
+                );
+            }
+        };
+        let max_docs = params
+            .max_docs
+            .unwrap_or(ACTIVE_DOCS_DEFAULT_SCAN)
+            .clamp(1, ACTIVE_DOCS_MAX_SCAN) as usize;
 
         tracing::info!(
-            "get_row: doc_id={}, table_id={}, row_id={}",
-            params.doc_id,
-            params.table_id,
-            params.row_id
+            "list_docs_active_since: since={}, max_docs={}, query={:?}",
+            cutoff,
+            max_docs,
+            params.query
         );
 
-        let row: Row = self
-            .client
-            .get(&path)
+        let (scanned, truncated) = scan_docs(&self.client, params.query.as_deref(), max_docs)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(CodaError::into_mcp_error)?;
+
+        let scanned_count = scanned.len();
+        let mut active: Vec<(DateTime<Utc>, Doc)> = scanned
+            .into_iter()
+            .filter_map(|doc| doc.updated_time().map(|t| (t, doc)))
+            .filter(|(updated, _)| *updated >= cutoff)
+            .collect();
+        active.sort_by_key(|(updated, _)| std::cmp::Reverse(*updated));
+        let docs: Vec<Doc> = active.into_iter().map(|(_, doc)| doc).collect();
+
+        let since = cutoff.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let summary = format!(
+            "Found {} documents updated since {since} (of {scanned_count} scanned)",
+            docs.len()
+        );
+        let mut output = summary.clone();
+        for doc in &docs {
+            let _ = write!(
+                output,
+                "\n- {} ({}) updated {}",
+                doc.name,
+                doc.id,
+                doc.updated_at.as_deref().unwrap_or_default()
+            );
+        }
+        let _ = write!(output, "\n\n{}", self.render(&docs)?);
+
+        let mut outcome = ToolOutcome::success(
+            "list_docs_active_since",
+            summary,
+            serde_json::json!({"since": since, "scanned": scanned_count, "docs": docs}),
+        );
+        if truncated {
+            outcome = outcome.with_warning(format!(
+                "Stopped after scanning {max_docs} docs; older or unscanned docs may be missing. Raise max_docs or narrow query."
+            ));
+        }
+
+        Ok(outcome.into_result(output))
+    }
+
+    #[tool(
+        description = "List docs annotated with how many tables (and views) each has, to find the docs with substantial data. Scans up to max_docs docs (default 50, max 200), counting tables a few docs at a time; sort_by_count orders them by table count, most first."
+    )]
+    async fn list_docs_with_table_counts(
+        &self,
+        Parameters(params): Parameters<ListDocsWithTableCountsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_docs = params
+            .max_docs
+            .unwrap_or(TABLE_COUNT_DEFAULT_DOCS)
+            .clamp(1, TABLE_COUNT_MAX_DOCS) as usize;
+        let concurrency = params
+            .concurrency
+            .unwrap_or(SCHEMA_DEFAULT_CONCURRENCY)
+            .clamp(1, SCHEMA_MAX_CONCURRENCY) as usize;
+
+        tracing::info!(
+            "list_docs_with_table_counts: max_docs={}, concurrency={}, query={:?}",
+            max_docs,
+            concurrency,
+            params.query
+        );
+
+        let (docs, truncated) =
+            match scan_docs(&self.client, params.query.as_deref(), max_docs).await {
+                Ok(scan) => scan,
+                Err(e) => {
+                    return Ok(ToolOutcome::from_error("list_docs_with_table_counts", &e)
+                        .into_result(e.to_string()));
+                }
+            };
+
+        let doc_ids: Vec<String> = docs.iter().map(|doc| doc.id.clone()).collect();
+        let counts: Vec<Result<usize, CodaError>> = stream::iter(doc_ids)
+            .map(|doc_id| async move { self.count_tables(&doc_id).await })
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        let mut batch = BatchResult::default();
+        for (doc, count) in docs.iter().zip(counts) {
+            match count {
+                Ok(table_count) => batch.succeeded.push(DocTableCount {
+                    id: doc.id.clone(),
+                    name: doc.name.clone(),
+                    table_count,
+                }),
+                Err(e) => batch.push_failure(&serde_json::json!({"doc_id": doc.id}), &e),
+            }
+        }
+        if params.sort_by_count.unwrap_or(false) {
+            // Stable, so ties keep the listing order
+            batch
+                .succeeded
+                .sort_by_key(|doc| std::cmp::Reverse(doc.table_count));
+        }
 
-        let json = serde_json::to_string_pretty(&row)
+        let mut summary = format!("Counted tables in {} docs", batch.succeeded.len());
+        if !batch.failed.is_empty() {
+            let _ = write!(summary, " ({} failed)", batch.failed.len());
+        }
+        let mut output = summary.clone();
+        for doc in &batch.succeeded {
+            let _ = write!(
+                output,
+                "\n- {} ({}): {} tables",
+                doc.name, doc.id, doc.table_count
+            );
+        }
+        for failure in &batch.failed {
+            let _ = write!(
+                output,
+                "\n- {} FAILED: {}",
+                failure.input["doc_id"].as_str().unwrap_or_default(),
+                failure.message
+            );
+        }
+        let data = serde_json::to_value(&batch)
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Row: {}\n\n```json\n{}\n```",
-            row.id, json
-        ))]))
+        let mut outcome = ToolOutcome::success("list_docs_with_table_counts", summary, data);
+        if truncated {
+            outcome = outcome.with_warning(format!(
+                "Stopped after {max_docs} docs; more docs match. Raise max_docs or narrow query."
+            ));
+        }
+        Ok(outcome.into_result(output))
     }
 
     #[tool(
-        description = "Add a new row to a table. Cells should be a dictionary mapping column names to values."
+        description = "List docs across every configured Coda account (CODA_API_TOKEN plus CODA_API_TOKENS). Docs visible to several accounts appear once, annotated with each account that can see them. Tokens are never shown; accounts are labelled 'token N' with their login."
     )]
-    async fn add_row(
+    async fn list_all_docs(
         &self,
-        Parameters(params): Parameters<AddRowParams>,
+        Parameters(params): Parameters<ListAllDocsParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/tables/{}/rows", params.doc_id, params.table_id);
+        let max_docs = params
+            .max_docs_per_account
+            .unwrap_or(ALL_DOCS_DEFAULT_PER_ACCOUNT)
+            .clamp(1, ALL_DOCS_MAX_PER_ACCOUNT) as usize;
+        let tokens = self.config.account_tokens();
 
-        let cells: Vec<serde_json::Value> = params
-            .cells
-            .iter()
-            .map(|(col, val)| {
-                serde_json::json!({
-                    "column": col,
-                    "value": val
-                })
-            })
-            .collect();
+        tracing::info!(
+            "list_all_docs: accounts={}, max_docs_per_account={}, query={:?}",
+            tokens.len(),
+            max_docs,
+            params.query
+        );
 
-        let body = serde_json::json!({
-            "rows": [{
-                "cells": cells
-            }]
+        let scans = tokens.iter().enumerate().map(|(i, token)| {
+            // A temporary client per account, sharing every other setting
+            let client = CodaClient::new(&Config {
+                api_token: (*token).to_string(),
+                ..(*self.config).clone()
+            });
+            let query = params.query.clone();
+            async move {
+                let login = client
+                    .get::<User>("/whoami")
+                    .await
+                    .ok()
+                    .map(|user| user.login_id);
+                let scan = scan_docs(&client, query.as_deref(), max_docs).await;
+                let label = match login {
+                    Some(login) => format!("token {} ({login})", i + 1),
+                    None => format!("token {}", i + 1),
+                };
+                (label, scan)
+            }
         });
+        let scans = futures::future::join_all(scans).await;
+
+        let mut docs: Vec<AccountDoc> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut batch = BatchResult::default();
+        for (account, scan) in scans {
+            let (account_docs, truncated) = match scan {
+                Ok(scan) => scan,
+                Err(e) => {
+                    batch.push_failure(&serde_json::json!({"account": account}), &e);
+                    continue;
+                }
+            };
+            batch.succeeded.push(AccountDocCount {
+                account: account.clone(),
+                doc_count: account_docs.len(),
+                truncated,
+            });
+            for doc in account_docs {
+                if let Some(&i) = positions.get(&doc.id) {
+                    docs[i].accounts.push(account.clone());
+                } else {
+                    positions.insert(doc.id.clone(), docs.len());
+                    docs.push(AccountDoc {
+                        doc,
+                        accounts: vec![account.clone()],
+                    });
+                }
+            }
+        }
 
-        tracing::info!(
-            "add_row: doc_id={}, table_id={}, cells={:?}",
-            params.doc_id,
-            params.table_id,
-            params.cells
+        if batch.succeeded.is_empty() {
+            let message = format!(
+                "Could not list docs for any of the {} accounts",
+                tokens.len()
+            );
+            let mut output = message.clone();
+            for failure in &batch.failed {
+                let _ = write!(
+                    output,
+                    "\n- {}: {}",
+                    failure.input["account"].as_str().unwrap_or_default(),
+                    failure.message
+                );
+            }
+            let data = serde_json::to_value(&batch)
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            return Ok(ToolOutcome {
+                data,
+                ..ToolOutcome::failure("list_all_docs", message)
+            }
+            .into_result(output));
+        }
+
+        let summary = format!(
+            "Found {} documents across {} accounts",
+            docs.len(),
+            batch.succeeded.len()
         );
+        let mut output = summary.clone();
+        for doc in &docs {
+            let _ = write!(
+                output,
+                "\n- {} ({}): {}",
+                doc.doc.name,
+                doc.doc.id,
+                doc.accounts.join(", ")
+            );
+        }
+        let _ = write!(output, "\n\n{}", self.render(&docs)?);
 
-        let result: RowMutationResponse = self
+        let truncated: Vec<&str> = batch
+            .succeeded
+            .iter()
+            .filter(|account| account.truncated)
+            .map(|account| account.account.as_str())
+            .collect();
+        let mut outcome = ToolOutcome::success(
+            "list_all_docs",
+            summary,
+            serde_json::json!({"docs": docs, "accounts": batch}),
+        );
+        for failure in &batch.failed {
+            outcome = outcome.with_warning(format!(
+                "Could not list docs for {}: {}",
+                failure.input["account"].as_str().unwrap_or_default(),
+                failure.message
+            ));
+        }
+        if !truncated.is_empty() {
+            outcome = outcome.with_warning(format!(
+                "Stopped after {max_docs} docs for {}; more docs exist. Raise max_docs_per_account or narrow query.",
+                truncated.join(", ")
+            ));
+        }
+        Ok(outcome.into_result(output))
+    }
+
+    #[tool(description = "Search for Coda documents by name or content.")]
+    async fn search_docs(
+        &self,
+        Parameters(params): Parameters<SearchDocsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!("/docs?query={}", urlencoding::encode(&params.query));
+
+        tracing::info!("search_docs: query={}", params.query);
+
+        let docs: DocList = self
             .client
-            .post(&path, &body)
+            .get(&path)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .map_err(CodaError::into_mcp_error)?;
 
-        let added_ids = result
-            .added_row_ids
-            .map(|ids| ids.join(", "))
-            .unwrap_or_default();
+        let summary = format!(
+            "Found {} documents matching '{}'",
+            docs.items.len(),
+            params.query
+        );
+        let block = self.render(&docs.items)?;
 
         Ok(CallToolResult::success(vec![Content::text(format!(
-            "Row added successfully.\nRequest ID: {}\nAdded row IDs: {}\n\nNote: Changes may take a few seconds to appear.",
-            result.request_id, added_ids
+            "{summary}\n\n{block}"
         ))]))
     }
 
-    #[tool(description = "Update an existing row in a table.")]
-    async fn update_row(
+    #[tool(
+        description = "Create a new Coda document. Optionally specify a folder, source document (template), or timezone."
+    )]
+    async fn create_doc(
         &self,
-        Parameters(params): Parameters<UpdateRowParams>,
+        Parameters(params): Parameters<CreateDocParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!(
-            "/docs/{}/tables/{}/rows/{}",
-            params.doc_id, params.table_id, params.row_id
+        tracing::info!(
+            "create_doc: title={}, folder_id={:?}, source_doc={:?}, timezone={:?}",
+            params.title,
+            params.folder_id,
+            params.source_doc,
+            params.timezone
         );
 
-        let cells: Vec<serde_json::Value> = params
-            .cells
+        if let Some(Err(message)) = params
+            .initial_pages
             .iter()
-            .map(|(col, val)| {
-                serde_json::json!({
-                    "column": col,
-                    "value": val
-                })
-            })
-            .collect();
+            .flatten()
+            .map(PageSeed::check_format)
+            .find(Result::is_err)
+        {
+            return Ok(ToolOutcome::failure("create_doc", message.clone()).into_result(message));
+        }
 
-        let body = serde_json::json!({
-            "row": {
-                "cells": cells
+        let mut doc: Doc = match self.client.post("/docs", &params).await {
+            Ok(doc) => doc,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("create_doc", &e).into_result(e.to_string()));
             }
-        });
+        };
+
+        let mut output = format!(
+            "Document created successfully!\n\nName: {}\nID: {}",
+            doc.name, doc.id
+        );
+
+        let return_full = params.return_full.unwrap_or(false);
+        let seeds = params.initial_pages.unwrap_or_default();
+        if !seeds.is_empty() || return_full {
+            match self.wait_ready(&doc.id).await {
+                // The creation response omits most fields; the ready doc has them all
+                Ok(ready) if return_full => doc = ready,
+                Ok(_) => {}
+                Err(e) => {
+                    return Ok(ToolOutcome::from_error("create_doc", &e)
+                        .into_result(format!("{output}\n\nDocument was not ready: {e}")));
+                }
+            }
+        }
+
+        let mut pages: Vec<Page> = Vec::new();
+        let mut batch = BatchResult::default();
+        if !seeds.is_empty() {
+            output.push_str("\n\nPages:");
+            for seed in &seeds {
+                let created = match self.post_page(&doc.id, &seed.into()).await {
+                    Ok(page) if return_full => {
+                        let path = format!("/docs/{}/pages/{}", doc.id, page.id);
+                        self.get_when_ready::<Page>(&path)
+                            .await
+                            .map(|full| (page, Some(full)))
+                    }
+                    result => result.map(|page| (page, None)),
+                };
+                match created {
+                    Ok((page, full)) => {
+                        let _ = write!(output, "\n- {} ({})", seed.name, page.id);
+                        pages.extend(full);
+                        batch.succeeded.push(SeededPage {
+                            name: seed.name.clone(),
+                            id: page.id,
+                        });
+                    }
+                    Err(e) => {
+                        let _ = write!(output, "\n- {} FAILED: {e}", seed.name);
+                        batch.push_failure(seed, &e);
+                    }
+                }
+            }
+        }
+
+        let summary = format!("Created doc '{}'", doc.name);
+        let data = serde_json::json!({ "doc": doc, "pages": batch });
+        if !batch.failed.is_empty() {
+            let outcome = ToolOutcome {
+                data,
+                ..ToolOutcome::failure(
+                    "create_doc",
+                    format!(
+                        "{summary}, but {} of {} pages failed",
+                        batch.failed.len(),
+                        seeds.len()
+                    ),
+                )
+            };
+            return Ok(outcome.into_result(output));
+        }
 
+        let block = self.render(&doc)?;
+        let _ = write!(output, "\n\n{block}");
+        if !pages.is_empty() {
+            let pages_block = self.render(&pages)?;
+            let _ = write!(output, "\n\nCreated pages:\n{pages_block}");
+        }
+
+        Ok(ToolOutcome::success("create_doc", summary, data).into_result(output))
+    }
+
+    #[tool(
+        description = "Copy an existing doc, with all of its pages, tables and rows, into a new doc with the given title. Optionally set the folder and timezone. The copy fills in over time after the call returns."
+    )]
+    async fn copy_doc(
+        &self,
+        Parameters(params): Parameters<CopyDocParams>,
+    ) -> Result<CallToolResult, McpError> {
         tracing::info!(
-            "update_row: doc_id={}, table_id={}, row_id={}",
-            params.doc_id,
-            params.table_id,
-            params.row_id
+            "copy_doc: source_doc_id={}, title={}, folder_id={:?}",
+            params.source_doc_id,
+            params.title,
+            params.folder_id
         );
 
-        let result: RowMutationResponse = self
-            .client
-            .put(&path, &body)
+        let request = CreateDocParams::from(&params);
+        let doc: Doc = match self.client.post("/docs", &request).await {
+            Ok(doc) => doc,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("copy_doc", &e).into_result(e.to_string()));
+            }
+        };
+
+        let summary = format!("Copied doc '{}' to '{}'", params.source_doc_id, doc.name);
+        Ok(ToolOutcome::success(
+            "copy_doc",
+            summary.clone(),
+            serde_json::json!({"doc_id": doc.id, "source_doc_id": params.source_doc_id, "doc": doc}),
+        )
+        .with_warning(COPY_DOC_NOTE)
+        .into_result(format!("{summary}\nID: {}\n\nNote: {COPY_DOC_NOTE}", doc.id)))
+    }
+
+    #[tool(description = "Delete a Coda document. This action is permanent and cannot be undone.")]
+    async fn delete_doc(
+        &self,
+        Parameters(params): Parameters<DeleteDocParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!("/docs/{}", params.doc_id);
+
+        tracing::info!("delete_doc: doc_id={}", params.doc_id);
+
+        if let Some(refusal) = self
+            .check_confirmation(
+                "delete_doc",
+                &params.doc_id,
+                &path,
+                params.confirm.as_deref(),
+            )
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        {
+            return Ok(refusal);
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Row updated successfully.\nRequest ID: {}\n\nNote: Changes may take a few seconds to appear.",
-            result.request_id
-        ))]))
+        if let Err(e) = self.client.delete(&path).await {
+            return Ok(ToolOutcome::from_error("delete_doc", &e).into_result(e.to_string()));
+        }
+        self.context_cache.invalidate(&params.doc_id);
+        self.column_cache.invalidate(&params.doc_id);
+
+        let summary = format!("Document '{}' deleted successfully.", params.doc_id);
+        Ok(ToolOutcome::success(
+            "delete_doc",
+            summary.clone(),
+            serde_json::json!({"doc_id": params.doc_id}),
+        )
+        .into_result(summary))
     }
 
-    #[tool(description = "Delete a row from a table.")]
-    async fn delete_row(
+    #[tool(
+        description = "Publish a doc, optionally with a URL slug, gallery categories, discoverability and a reader mode (view, play or edit). Republishing updates these settings."
+    )]
+    async fn publish_doc(
         &self,
-        Parameters(params): Parameters<DeleteRowParams>,
+        Parameters(params): Parameters<PublishDocParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = format!(
-            "/docs/{}/tables/{}/rows/{}",
-            params.doc_id, params.table_id, params.row_id
+        let path = format!("/docs/{}/publish", params.doc_id);
+
+        tracing::info!(
+            "publish_doc: doc_id={}, slug={:?}",
+            params.doc_id,
+            params.slug
         );
 
+        let response: serde_json::Value = match self.client.put(&path, &params).await {
+            Ok(response) => response,
+            // Ineligible docs get a 400 whose body says why
+            Err(e) if e.status() == Some(400) => {
+                let message = e.api_message().unwrap_or_else(|| e.to_string());
+                let summary = format!("Doc '{}' could not be published: {message}", params.doc_id);
+                return Ok(ToolOutcome {
+                    summary: summary.clone(),
+                    ..ToolOutcome::from_error("publish_doc", &e)
+                }
+                .into_result(summary));
+            }
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("publish_doc", &e).into_result(e.to_string()));
+            }
+        };
+        self.context_cache.invalidate(&params.doc_id);
+
+        let request_id = response["requestId"].as_str().unwrap_or_default();
+        let summary = format!("Doc '{}' published", params.doc_id);
+        Ok(ToolOutcome::success(
+            "publish_doc",
+            summary.clone(),
+            serde_json::json!({"doc_id": params.doc_id, "request_id": request_id}),
+        )
+        .with_warning(MUTATION_DELAY_NOTE)
+        .into_result(format!(
+            "{summary}.\nRequest ID: {request_id}\n\nNote: {MUTATION_DELAY_NOTE}"
+        )))
+    }
+
+    #[tool(
+        description = "Unpublish a published doc. In safe mode, confirm with the doc ID or name."
+    )]
+    async fn unpublish_doc(
+        &self,
+        Parameters(params): Parameters<UnpublishDocParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!("/docs/{}/publish", params.doc_id);
+
+        tracing::info!("unpublish_doc: doc_id={}", params.doc_id);
+
+        if let Some(refusal) = self
+            .check_confirmation(
+                "unpublish_doc",
+                &params.doc_id,
+                &format!("/docs/{}", params.doc_id),
+                params.confirm.as_deref(),
+            )
+            .await
+        {
+            return Ok(refusal);
+        }
+
+        if let Err(e) = self.client.delete(&path).await {
+            return Ok(ToolOutcome::from_error("unpublish_doc", &e).into_result(e.to_string()));
+        }
+        self.context_cache.invalidate(&params.doc_id);
+
+        let summary = format!("Doc '{}' unpublished", params.doc_id);
+        Ok(ToolOutcome::success(
+            "unpublish_doc",
+            summary.clone(),
+            serde_json::json!({"doc_id": params.doc_id}),
+        )
+        .into_result(summary))
+    }
+
+    #[tool(
+        description = "Move a doc into another folder. Returns the updated doc, after checking it now reports the new folder."
+    )]
+    async fn move_doc(
+        &self,
+        Parameters(params): Parameters<MoveDocParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!("/docs/{}", params.doc_id);
+
         tracing::info!(
-            "delete_row: doc_id={}, table_id={}, row_id={}",
+            "move_doc: doc_id={}, folder_id={}",
             params.doc_id,
-            params.table_id,
-            params.row_id
+            params.folder_id
         );
 
-        self.client
-            .delete(&path)
+        let doc: Doc = match self.client.patch(&path, &params).await {
+            Ok(doc) => doc,
+            Err(e) => return Ok(ToolOutcome::from_error("move_doc", &e).into_result(e.to_string())),
+        };
+        self.context_cache.invalidate(&params.doc_id);
+
+        let block = self.render(&doc)?;
+        if doc.folder_id.as_deref() != Some(params.folder_id.as_str()) {
+            let summary = format!(
+                "Doc '{}' was not moved: Coda reports it in folder '{}', not '{}'",
+                params.doc_id,
+                doc.folder_id.as_deref().unwrap_or("none"),
+                params.folder_id
+            );
+            return Ok(ToolOutcome {
+                data: serde_json::json!(doc),
+                ..ToolOutcome::failure("move_doc", summary.clone())
+            }
+            .into_result(format!("{summary}\n\n{block}")));
+        }
+
+        let summary = format!(
+            "Moved doc '{}' to folder '{}'",
+            params.doc_id, params.folder_id
+        );
+        Ok(
+            ToolOutcome::success("move_doc", summary.clone(), serde_json::json!(doc))
+                .into_result(format!("{summary}\n\n{block}")),
+        )
+    }
+
+    #[tool(
+        description = "List the gallery category names accepted by publish_doc's category_names."
+    )]
+    async fn list_categories(&self) -> Result<CallToolResult, McpError> {
+        tracing::info!("list_categories");
+
+        let categories: CategoryList = self
+            .client
+            .get("/categories")
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let names: Vec<&str> = categories.items.iter().map(|c| c.name.as_str()).collect();
+        let summary = format!("Found {} categories", names.len());
+        let mut output = summary.clone();
+        for name in &names {
+            let _ = write!(output, "\n- {name}");
+        }
+        let block = self.render(&names)?;
+        let _ = write!(output, "\n\n{block}");
+
+        Ok(ToolOutcome::success(
+            "list_categories",
+            summary,
+            serde_json::json!({ "names": names }),
+        )
+        .into_result(output))
+    }
+
+    #[tool(
+        description = "Rename a doc or page. Set kind, and page_id for pages. Tables and columns cannot be renamed via the API."
+    )]
+    async fn rename(
+        &self,
+        Parameters(params): Parameters<RenameParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "rename: kind={}, doc_id={}, new_name={}",
+            params.kind.as_str(),
+            params.doc_id,
+            params.new_name
+        );
+
+        let missing = |field: &str| {
+            let message = format!(
+                "`{field}` is required to rename a {}.",
+                params.kind.as_str()
+            );
+            Ok(ToolOutcome::failure("rename", message.clone()).into_result(message))
+        };
+
+        let (path, body, target) = match params.kind {
+            RenameKind::Doc => (
+                format!("/docs/{}", params.doc_id),
+                serde_json::json!({"title": params.new_name}),
+                format!("doc '{}'", params.doc_id),
+            ),
+            RenameKind::Page => {
+                let Some(page_id) = &params.page_id else {
+                    return missing("page_id");
+                };
+                (
+                    format!("/docs/{}/pages/{page_id}", params.doc_id),
+                    serde_json::json!({"name": params.new_name}),
+                    format!("page '{page_id}'"),
+                )
+            }
+            RenameKind::Table | RenameKind::Column => {
+                let kind = params.kind.as_str();
+                let message = format!(
+                    "Renaming {kind}s is not supported by the Coda API. Rename the {kind} in the Coda UI instead."
+                );
+                return Ok(ToolOutcome::failure("rename", message.clone()).into_result(message));
+            }
+        };
+
+        let result: Result<serde_json::Value, CodaError> = match params.kind {
+            RenameKind::Doc => self.client.patch(&path, &body).await,
+            _ => self.client.put(&path, &body).await,
+        };
+        if let Err(e) = result {
+            return Ok(ToolOutcome::from_error("rename", &e).into_result(e.to_string()));
+        }
+        self.context_cache.invalidate(&params.doc_id);
+
+        let summary = format!("Renamed {target} to '{}'.", params.new_name);
+        Ok(ToolOutcome::success(
+            "rename",
+            summary.clone(),
+            serde_json::json!({"kind": params.kind.as_str(), "path": path, "new_name": params.new_name}),
+        )
+        .into_result(summary))
+    }
+
+    #[tool(
+        description = "Check whether a doc, page, table, or row exists. Returns {exists: true/false} instead of a not-found error; other failures such as auth or rate limits are still errors. Set kind and the IDs it needs: page_id for pages, table_id for tables, table_id and row_id for rows."
+    )]
+    async fn exists(
+        &self,
+        Parameters(params): Parameters<ExistsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "exists: kind={}, doc_id={}",
+            params.kind.as_str(),
+            params.doc_id
+        );
+
+        let missing = |field: &str| {
+            let message = format!(
+                "`{field}` is required to look up a {}.",
+                params.kind.as_str()
+            );
+            Ok(ToolOutcome::failure("exists", message.clone()).into_result(message))
+        };
+
+        let (path, target) = match params.kind {
+            ExistsKind::Doc => (
+                format!("/docs/{}", params.doc_id),
+                format!("Doc '{}'", params.doc_id),
+            ),
+            ExistsKind::Page => {
+                let Some(page_id) = &params.page_id else {
+                    return missing("page_id");
+                };
+                (
+                    format!(
+                        "/docs/{}/pages/{}",
+                        params.doc_id,
+                        urlencoding::encode(page_id)
+                    ),
+                    format!("Page '{page_id}'"),
+                )
+            }
+            ExistsKind::Table => {
+                let Some(table_id) = &params.table_id else {
+                    return missing("table_id");
+                };
+                (
+                    format!(
+                        "/docs/{}/tables/{}",
+                        params.doc_id,
+                        urlencoding::encode(table_id)
+                    ),
+                    format!("Table '{table_id}'"),
+                )
+            }
+            ExistsKind::Row => {
+                let Some(table_id) = &params.table_id else {
+                    return missing("table_id");
+                };
+                let Some(row_id) = &params.row_id else {
+                    return missing("row_id");
+                };
+                (
+                    format!(
+                        "/docs/{}/tables/{}/rows/{}",
+                        params.doc_id,
+                        urlencoding::encode(table_id),
+                        urlencoding::encode(row_id)
+                    ),
+                    format!("Row '{row_id}'"),
+                )
+            }
+        };
+
+        let exists = match self.client.get::<serde_json::Value>(&path).await {
+            Ok(_) => true,
+            Err(CodaError::NotFound) => false,
+            Err(e) => return Ok(ToolOutcome::from_error("exists", &e).into_result(e.to_string())),
+        };
+
+        let summary = format!(
+            "{target} {}.",
+            if exists { "exists" } else { "does not exist" }
+        );
+        Ok(ToolOutcome::success(
+            "exists",
+            summary.clone(),
+            serde_json::json!({"exists": exists, "kind": params.kind.as_str()}),
+        )
+        .into_result(summary))
+    }
+
+    // === Page Tools ===
+
+    #[tool(
+        description = "Create a page in a Coda document, optionally under a parent page and with initial canvas content (HTML by default, or markdown)."
+    )]
+    async fn create_page(
+        &self,
+        Parameters(params): Parameters<CreatePageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = check_content_format(params.format.as_deref()) {
+            return Ok(ToolOutcome::failure("create_page", message.clone()).into_result(message));
+        }
+
+        let page = match self.post_page(&params.doc_id, &(&params).into()).await {
+            Ok(page) => page,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("create_page", &e).into_result(e.to_string()));
+            }
+        };
+
+        let summary = format!("Created page '{}'", params.name);
+        let mut output = format!("{summary}\n\nID: {}", page.id);
+        let mut data = serde_json::json!({"id": page.id, "request_id": page.request_id});
+        if params.return_full.unwrap_or(false) {
+            let path = format!("/docs/{}/pages/{}", params.doc_id, page.id);
+            match self.get_when_ready::<Page>(&path).await {
+                Ok(full) => {
+                    let block = self.render(&full)?;
+                    let _ = write!(output, "\n\n{block}");
+                    data["page"] = serde_json::to_value(&full)
+                        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                }
+                Err(e) => {
+                    let _ = write!(output, "\n\nPage was not ready: {e}");
+                    let outcome = ToolOutcome {
+                        data,
+                        ..ToolOutcome::from_error("create_page", &e)
+                    };
+                    return Ok(outcome.into_result(output));
+                }
+            }
+        }
+        output.push_str("\n\nNote: page content may take a few seconds to render.");
+        Ok(ToolOutcome::success("create_page", summary, data).into_result(output))
+    }
+
+    #[tool(
+        description = "Create a tree of pages from a nested outline ([{name, content, children: [...]}]). Pages are created in order, each subpage under its created parent. Returns the created page IDs as a tree."
+    )]
+    async fn create_page_outline(
+        &self,
+        Parameters(params): Parameters<CreatePageOutlineParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let total: usize = params.outline.iter().map(OutlineNode::page_count).sum();
+        if total == 0 || total > OUTLINE_MAX_PAGES {
+            let message = format!(
+                "The outline has {total} pages; between 1 and {OUTLINE_MAX_PAGES} can be created per call."
+            );
+            return Ok(
+                ToolOutcome::failure("create_page_outline", message.clone()).into_result(message)
+            );
+        }
+        if let Err(message) = params
+            .outline
+            .iter()
+            .try_for_each(OutlineNode::check_formats)
+        {
+            return Ok(
+                ToolOutcome::failure("create_page_outline", message.clone()).into_result(message)
+            );
+        }
+
+        tracing::info!(
+            "create_page_outline: doc_id={}, pages={}, parent={:?}",
+            params.doc_id,
+            total,
+            params.parent_page_id
+        );
+
+        // Depth-first, so every parent exists before its subpages are created
+        let mut stack: Vec<(&OutlineNode, Option<String>, usize)> = params
+            .outline
+            .iter()
+            .rev()
+            .map(|node| (node, params.parent_page_id.clone(), 0))
+            .collect();
+        let mut created: Vec<OutlinePage> = Vec::new();
+        let mut failure = None;
+        while let Some((node, parent, depth)) = stack.pop() {
+            let request = CreatePageRequest {
+                parent_page_id: parent.clone(),
+                ..CreatePageRequest::from(&node.seed())
+            };
+            let page = match self.post_page(&params.doc_id, &request).await {
+                Ok(page) => page,
+                Err(e) => {
+                    failure = Some((node.name.clone(), e));
+                    break;
+                }
+            };
+            if !node.children.is_empty() {
+                // Page creation is asynchronous; subpages need the parent to exist
+                let path = format!("/docs/{}/pages/{}", params.doc_id, page.id);
+                match self.get_when_ready::<Page>(&path).await {
+                    Ok(_) => {
+                        for child in node.children.iter().rev() {
+                            stack.push((child, Some(page.id.clone()), depth + 1));
+                        }
+                    }
+                    Err(e) => failure = Some((node.name.clone(), e)),
+                }
+            }
+            created.push(OutlinePage {
+                name: node.name.clone(),
+                id: page.id,
+                parent_page_id: parent,
+                depth,
+            });
+            if failure.is_some() {
+                break;
+            }
+        }
+
+        let mut tree = String::new();
+        for page in &created {
+            let _ = write!(
+                tree,
+                "\n{}- {} ({})",
+                "  ".repeat(page.depth),
+                page.name,
+                page.id
+            );
+        }
+        let data = serde_json::json!({ "pages": created });
+
+        if let Some((name, e)) = failure {
+            let message = format!(
+                "Failed at page '{name}': {e}. Created {} of {total} pages.",
+                created.len()
+            );
+            let mut output = message.clone();
+            if !created.is_empty() {
+                let _ = write!(output, "\n\nCreated pages:{tree}");
+            }
+            let outcome = ToolOutcome {
+                data,
+                ..ToolOutcome::from_error("create_page_outline", &e)
+            };
+            return Ok(outcome.into_result(output));
+        }
+
+        let summary = format!("Created {} pages", created.len());
+        Ok(
+            ToolOutcome::success("create_page_outline", summary.clone(), data)
+                .into_result(format!("{summary}:{tree}\n\n{MUTATION_DELAY_NOTE}")),
+        )
+    }
+
+    #[tool(
+        description = "Update a page: rename it, change its subtitle or icon, and/or append to or replace its canvas content. Only the fields given are changed."
+    )]
+    async fn update_page(
+        &self,
+        Parameters(params): Parameters<UpdatePageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let request = UpdatePageRequest::from(&params);
+        if request.is_empty() {
+            let message =
+                "Nothing to update. Set at least one of name, subtitle, icon_name or content_insert.";
+            return Ok(ToolOutcome::failure("update_page", message).into_result(message));
+        }
+        let path = format!(
+            "/docs/{}/pages/{}",
+            params.doc_id,
+            urlencoding::encode(&params.page_id)
+        );
+
+        tracing::info!(
+            "update_page: doc_id={}, page_id={}",
+            params.doc_id,
+            params.page_id
+        );
+
+        let result: PageMutationResponse = self
+            .client
+            .put(&path, &request)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+        let request_id = result.request_id.unwrap_or_default();
+
+        let text = format!(
+            "Page updated successfully.\nRequest ID: {request_id}\n\nNote: {MUTATION_DELAY_NOTE}"
+        );
+        Ok(ToolOutcome::success(
+            "update_page",
+            format!("Page '{}' updated", params.page_id),
+            serde_json::json!({"request_id": request_id, "page_id": result.id}),
+        )
+        .with_warning(MUTATION_DELAY_NOTE)
+        .into_result(text))
+    }
+
+    #[tool(description = "List all pages in a Coda document.")]
+    async fn list_pages(
+        &self,
+        Parameters(params): Parameters<ListPagesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!("/docs/{}/pages", params.doc_id);
+
+        tracing::info!("list_pages: doc_id={}", params.doc_id);
+
+        let mut pages: PageList = self
+            .client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        if params.dedupe.unwrap_or(false) {
+            dedupe_by_id(&mut pages.items);
+        }
+
+        let summary = format!("Found {} pages", pages.items.len());
+        let block = self.render(&pages.items)?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{summary}\n\n{block}"
+        ))]))
+    }
+
+    #[tool(description = "Get a specific page's content as HTML (default) or markdown.")]
+    async fn get_page(
+        &self,
+        Parameters(params): Parameters<GetPageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "get_page: doc_id={}, page_id={}",
+            params.doc_id,
+            params.page_id
+        );
+
+        // Validate the format and target before starting a potentially slow export
+        let (format, to_file) = match self.page_export_target("get_page", &params) {
+            Ok(target) => target,
+            Err(refusal) => return Ok(refusal),
+        };
+
+        let retry = params.retry_on_failure.unwrap_or(false);
+        let mut download = match self
+            .export_page_content(&params.doc_id, &params.page_id, format, retry)
+            .await?
+        {
+            ExportOutcome::Complete(download) => download,
+            ExportOutcome::TimedOut {
+                export_id,
+                status_path,
+            } => {
+                return Ok(ExportOutcome::timeout_result(
+                    &export_id,
+                    &status_path,
+                    &self.config,
+                ))
+            }
+        };
+        let raw = apply_page_transform(&mut download, params.transform, params.include_raw);
+
+        // Get page metadata for the name
+        let page_path = format!("/docs/{}/pages/{}", params.doc_id, params.page_id);
+        let page: Page = self
+            .client
+            .get(&page_path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        if let Some(path) = &to_file {
+            write_export(path, &download.content)?;
+        }
+
+        let mut content = vec![Content::text(format_page(
+            &page.name,
+            &download,
+            params.include_checksum.unwrap_or(false),
+            to_file.as_deref(),
+        ))];
+        if let Some(raw) = raw {
+            content.push(Content::text(format!("Raw export:\n{raw}")));
+        }
+        Ok(CallToolResult::success(content))
+    }
+
+    #[tool(
+        description = "Export several pages at once (up to 20, a few at a time), returning each page as its own labeled content block. A page that fails gets a placeholder block with the error instead of failing the call."
+    )]
+    async fn export_pages(
+        &self,
+        Parameters(params): Parameters<ExportPagesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if params.page_ids.is_empty() || params.page_ids.len() > EXPORT_PAGES_MAX_PAGES {
+            let message =
+                format!("`page_ids` must list between 1 and {EXPORT_PAGES_MAX_PAGES} pages.");
+            return Ok(ToolOutcome::failure("export_pages", message.clone()).into_result(message));
+        }
+        let format = match params.export_format() {
+            Ok(format) => format,
+            Err(message) => {
+                return Ok(
+                    ToolOutcome::failure("export_pages", message.clone()).into_result(message)
+                );
+            }
+        };
+        let retry = params.retry_on_failure.unwrap_or(false);
+        let doc_id = params.doc_id.as_str();
+
+        tracing::info!(
+            "export_pages: doc_id={}, pages={}",
+            params.doc_id,
+            params.page_ids.len()
+        );
+
+        let exports: Vec<Result<(Page, ExportOutcome), BatchFailure>> =
+            stream::iter(params.page_ids.iter().cloned())
+                .map(|page_id| async move {
+                    // Metadata fails fast for unknown pages and gives the block its label
+                    let page_path = format!("/docs/{doc_id}/pages/{page_id}");
+                    let page: Page = self
+                        .client
+                        .get(&page_path)
+                        .await
+                        .map_err(|e| BatchFailure::new(&page_id, &e))?;
+                    let outcome = self
+                        .export_page_content(doc_id, &page_id, format, retry)
+                        .await
+                        .map_err(|e| BatchFailure {
+                            input: serde_json::json!(page_id),
+                            error_category: ErrorCategory::Export,
+                            message: e.message.to_string(),
+                        })?;
+                    Ok((page, outcome))
+                })
+                .buffered(EXPORT_PAGES_CONCURRENCY)
+                .collect()
+                .await;
+
+        let mut batch = BatchResult::default();
+        let mut blocks = Vec::new();
+        for (page_id, export) in params.page_ids.iter().zip(exports) {
+            match export {
+                Ok((page, ExportOutcome::Complete(mut download))) => {
+                    apply_page_transform(&mut download, params.transform, None);
+                    blocks.push(Content::text(format_page(
+                        &page.name, &download, false, None,
+                    )));
+                    batch.succeeded.push(serde_json::json!({
+                        "page_id": page.id,
+                        "name": page.name,
+                        "bytes": download.content.len(),
+                    }));
+                }
+                Ok((page, ExportOutcome::TimedOut { export_id, .. })) => {
+                    let err = CodaError::ExportTimeout {
+                        seconds: self.config.export_timeout_secs(),
+                    };
+                    blocks.push(Content::text(format!(
+                        "Page: {}\n\n{err}. Call resume_page_export with page_id '{}' and export_id '{export_id}' to keep polling.",
+                        page.name, page.id
+                    )));
+                    batch.push_failure(page_id, &err);
+                }
+                Err(failure) => {
+                    blocks.push(Content::text(format!(
+                        "Page: {page_id}\n\nExport failed: {}",
+                        failure.message
+                    )));
+                    batch.failed.push(failure);
+                }
+            }
+        }
+
+        let summary = format!(
+            "Exported {} of {} pages",
+            batch.succeeded.len(),
+            params.page_ids.len()
+        );
+        let data = serde_json::to_value(&batch)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let mut outcome = if batch.succeeded.is_empty() {
+            ToolOutcome {
+                data,
+                ..ToolOutcome::failure("export_pages", summary.clone())
+            }
+        } else {
+            ToolOutcome::success("export_pages", summary.clone(), data)
+        };
+        for failure in &batch.failed {
+            outcome = outcome.with_warning(format!("{}: {}", failure.input, failure.message));
+        }
+        let mut result = outcome.into_result(summary);
+        result.content.extend(blocks);
+        Ok(result)
+    }
+
+    #[tool(
+        description = "Get a page's metadata (name, parent, content type) and its exported HTML content in a single structured result."
+    )]
+    async fn get_page_full(
+        &self,
+        Parameters(params): Parameters<GetPageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "get_page_full: doc_id={}, page_id={}",
+            params.doc_id,
+            params.page_id
+        );
+
+        let (format, to_file) = match self.page_export_target("get_page_full", &params) {
+            Ok(target) => target,
+            Err(refusal) => return Ok(refusal),
+        };
+
+        // Metadata is cheap and fails fast for unknown pages, so fetch it before exporting
+        let page_path = format!("/docs/{}/pages/{}", params.doc_id, params.page_id);
+        let page: Page = self
+            .client
+            .get(&page_path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let retry = params.retry_on_failure.unwrap_or(false);
+        let mut download = match self
+            .export_page_content(&params.doc_id, &params.page_id, format, retry)
+            .await?
+        {
+            ExportOutcome::Complete(download) => download,
+            ExportOutcome::TimedOut {
+                export_id,
+                status_path,
+            } => {
+                return Ok(ExportOutcome::timeout_result(
+                    &export_id,
+                    &status_path,
+                    &self.config,
+                ))
+            }
+        };
+        let raw = apply_page_transform(&mut download, params.transform, params.include_raw);
+
+        let mut result = serde_json::json!({ "metadata": page });
+        match &to_file {
+            Some(path) => {
+                write_export(path, &download.content)?;
+                result["file"] = serde_json::json!({
+                    "path": path,
+                    "bytes": download.content.len(),
+                });
+            }
+            None => result["content"] = serde_json::json!(download.content),
+        }
+        if params.include_checksum.unwrap_or(false) {
+            result["checksum"] = serde_json::json!({
+                "bytes": download.byte_len,
+                "sha256": download.sha256,
+            });
+        }
+        if let Some(raw) = raw {
+            result["raw"] = serde_json::json!(raw);
+        }
+
+        Ok(CallToolResult::structured(result))
+    }
+
+    #[tool(
+        description = "Resume a page export that timed out in get_page or get_page_full. Continues polling the given export_id and returns the page content once complete."
+    )]
+    async fn resume_page_export(
+        &self,
+        Parameters(params): Parameters<ResumePageExportParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "resume_page_export: doc_id={}, page_id={}, export_id={}",
+            params.doc_id,
+            params.page_id,
+            params.export_id
+        );
+
+        let download = match self
+            .poll_export(&params.doc_id, &params.page_id, &params.export_id)
+            .await?
+        {
+            ExportOutcome::Complete(download) => download,
+            ExportOutcome::TimedOut {
+                export_id,
+                status_path,
+            } => {
+                return Ok(ExportOutcome::timeout_result(
+                    &export_id,
+                    &status_path,
+                    &self.config,
+                ))
+            }
+        };
+
+        let page_path = format!("/docs/{}/pages/{}", params.doc_id, params.page_id);
+        let page: Page = self
+            .client
+            .get(&page_path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(format_page(
+            &page.name,
+            &download,
+            params.include_checksum.unwrap_or(false),
+            None,
+        ))]))
+    }
+
+    #[tool(
+        description = "Export a canvas page and return only the tables embedded in it, each as CSV. Use when you need a page's tabular data rather than its full HTML."
+    )]
+    async fn extract_page_tables(
+        &self,
+        Parameters(params): Parameters<ExtractPageTablesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "extract_page_tables: doc_id={}, page_id={}",
+            params.doc_id,
+            params.page_id
+        );
+
+        let download = match self
+            .export_page_content(&params.doc_id, &params.page_id, "html", false)
+            .await?
+        {
+            ExportOutcome::Complete(download) => download,
+            ExportOutcome::TimedOut {
+                export_id,
+                status_path,
+            } => {
+                return Ok(ExportOutcome::timeout_result(
+                    &export_id,
+                    &status_path,
+                    &self.config,
+                ))
+            }
+        };
+
+        let tables = html::extract_tables(&download.content);
+        if tables.is_empty() {
+            let summary = format!("No tables found on page '{}'", params.page_id);
+            return Ok(ToolOutcome::success(
+                "extract_page_tables",
+                summary.clone(),
+                serde_json::json!({ "tables": [] }),
+            )
+            .into_result(format!("{summary}.")));
+        }
+
+        let summary = format!("Found {} tables on page '{}'", tables.len(), params.page_id);
+        let mut output = summary.clone();
+        let mut data = Vec::with_capacity(tables.len());
+        for (i, table) in tables.iter().enumerate() {
+            let csv = table.to_csv();
+            let heading = match &table.caption {
+                Some(caption) => format!("Table {}: {caption}", i + 1),
+                None => format!("Table {}", i + 1),
+            };
+            let _ = write!(output, "\n\n## {heading}\n\n```csv\n{csv}```");
+            data.push(serde_json::json!({
+                "position": i + 1,
+                "caption": table.caption,
+                "rows": table.rows.len(),
+                "csv": csv,
+            }));
+        }
+
+        Ok(ToolOutcome::success(
+            "extract_page_tables",
+            summary,
+            serde_json::json!({ "tables": data }),
+        )
+        .into_result(output))
+    }
+
+    // === Table Tools ===
+
+    #[tool(description = "List all tables in a Coda document.")]
+    async fn list_tables(
+        &self,
+        Parameters(params): Parameters<ListTablesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut path = format!(
+            "/docs/{}/tables{}",
+            params.doc_id,
+            page_query(params.limit, params.page_token.as_deref())
+        );
+        let table_types = params.table_types.as_deref().unwrap_or_default();
+        if !table_types.is_empty() {
+            let types: Vec<&str> = table_types.iter().map(|t| t.as_str()).collect();
+            path.push(if path.contains('?') { '&' } else { '?' });
+            let _ = write!(path, "tableTypes={}", types.join(","));
+        }
+
+        tracing::info!(
+            "list_tables: doc_id={}, limit={:?}, table_types={:?}",
+            params.doc_id,
+            params.limit,
+            table_types
+        );
+
+        // The cached context holds every table type, so filtered lists go to the API
+        let paged = params.limit.is_some() || params.page_token.is_some();
+        let cached = self
+            .context_cache
+            .get(&params.doc_id)
+            .filter(|_| !paged && table_types.is_empty())
+            .map(|context| TableList {
+                items: context.tables.clone(),
+                next_page_token: None,
+            });
+        let from_cache = cached.is_some();
+        let mut tables: TableList = match cached {
+            Some(tables) => tables,
+            None => self
+                .client
+                .get(&path)
+                .await
+                .map_err(CodaError::into_mcp_error)?,
+        };
+
+        if params.dedupe.unwrap_or(false) {
+            dedupe_by_id(&mut tables.items);
+        }
+
+        let mut summary = format!("Found {} tables", tables.items.len());
+        if from_cache {
+            summary.push_str(" (from doc context cache)");
+        }
+        let block = self.render(&tables.items)?;
+
+        let mut output = format!("{summary}\n\n{block}");
+        push_next_page(&mut output, tables.next_page_token.as_deref());
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get detailed information about a specific table.")]
+    async fn get_table(
+        &self,
+        Parameters(mut params): Parameters<GetTableParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("get_table", message.clone()).into_result(message));
+        }
+        let path = format!("/docs/{}/tables/{}", params.doc_id, params.table_id);
+
+        tracing::info!(
+            "get_table: doc_id={}, table_id={}",
+            params.doc_id,
+            params.table_id
+        );
+
+        let table: Table = self
+            .client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let block = self.render(&table)?;
+
+        let mut header = format!("Table: {}", table.name);
+        if let Some(display_column) = &table.display_column {
+            let _ = write!(header, "\nDisplay column: {}", display_column.id);
+        }
+        if let Some(link) = &table.browser_link {
+            let _ = write!(header, "\nLink: {link}");
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{header}\n\n{block}"
+        ))]))
+    }
+
+    #[tool(description = "List all columns in a table.")]
+    async fn list_columns(
+        &self,
+        Parameters(mut params): Parameters<ListColumnsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("list_columns", message.clone()).into_result(message));
+        }
+        let path = format!(
+            "/docs/{}/tables/{}/columns{}",
+            params.doc_id,
+            params.table_id,
+            page_query(params.limit, params.page_token.as_deref())
+        );
+
+        tracing::info!(
+            "list_columns: doc_id={}, table_id={}, limit={:?}",
+            params.doc_id,
+            params.table_id,
+            params.limit
+        );
+
+        let paged = params.limit.is_some() || params.page_token.is_some();
+        let cached = if paged {
+            None
+        } else {
+            self.cached_columns(&params.doc_id, &params.table_id)
+        };
+        let from_cache = cached.as_ref().map(|(_, source)| *source);
+        let mut columns: ColumnList = if let Some((columns, _)) = cached {
+            columns
+        } else {
+            let columns = self
+                .client
+                .get(&path)
+                .await
+                .map_err(CodaError::into_mcp_error)?;
+            if !paged {
+                self.cache_columns(&params.doc_id, &params.table_id, &columns);
+            }
+            columns
+        };
+
+        if params.dedupe.unwrap_or(false) {
+            dedupe_by_id(&mut columns.items);
+        }
+
+        if params.writable_only.unwrap_or(false) {
+            columns.items.retain(Column::is_writable);
+        }
+
+        let include_formulas = params.include_formulas.unwrap_or(false);
+        if include_formulas {
+            self.fill_column_formulas(&params.doc_id, &params.table_id, &mut columns.items)
+                .await
+                .map_err(CodaError::into_mcp_error)?;
+        }
+
+        let mut summary = format!("Found {} columns", columns.items.len());
+        if let Some(source) = from_cache {
+            let _ = write!(summary, " (from {source})");
+        }
+        let block = self.render(&columns.items)?;
+
+        let mut output = format!("{summary}\n\n{block}");
+        if include_formulas {
+            output.push_str("\n\nFormulas:");
+            let mut any = false;
+            for column in &columns.items {
+                if let Some(formula) = &column.formula {
+                    any = true;
+                    let _ = write!(output, "\n- {}: `{formula}`", column.name);
+                }
+            }
+            if !any {
+                output.push_str(" none");
+            }
+        }
+        push_next_page(&mut output, columns.next_page_token.as_deref());
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Describe a document's schema: its tables and their columns. Use table_limit to cap how many tables are introspected on large docs (the rest are listed by name only)."
+    )]
+    async fn get_doc_schema(
+        &self,
+        Parameters(params): Parameters<GetDocSchemaParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let table_limit = params
+            .table_limit
+            .unwrap_or(SCHEMA_DEFAULT_TABLE_LIMIT)
+            .min(SCHEMA_MAX_TABLE_LIMIT) as usize;
+        let concurrency = params
+            .concurrency
+            .unwrap_or(SCHEMA_DEFAULT_CONCURRENCY)
+            .clamp(1, SCHEMA_MAX_CONCURRENCY) as usize;
+
+        tracing::info!(
+            "get_doc_schema: doc_id={}, table_limit={}, concurrency={}",
+            params.doc_id,
+            table_limit,
+            concurrency
+        );
+
+        let tables = self
+            .list_all_tables(&params.doc_id)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let total = tables.len();
+        let mut remaining = tables.into_iter();
+        let doc_id = &params.doc_id;
+
+        let mut schema: Vec<TableSchema> = stream::iter(remaining.by_ref().take(table_limit))
+            .map(|table| async move {
+                let path = format!("/docs/{doc_id}/tables/{}/columns", table.id);
+                let columns: ColumnList = self.client.get(&path).await?;
+                Ok::<_, CodaError>(TableSchema {
+                    id: table.id,
+                    name: table.name,
+                    row_count: table.row_count,
+                    columns: Some(columns.items),
+                })
+            })
+            .buffered(concurrency)
+            .try_collect()
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let described = schema.len();
+        schema.extend(remaining.map(|table| TableSchema {
+            id: table.id,
+            name: table.name,
+            row_count: table.row_count,
+            columns: None,
+        }));
+
+        let summary = format!(
+            "Described {described} of {total} tables with columns ({} listed by name only)",
+            total - described
+        );
+        let block = self.render(&schema)?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{summary}\n\n{block}"
+        ))]))
+    }
+
+    #[tool(
+        description = "Prefetch a doc's metadata, page list, table schemas and a few sample rows per table in one bounded sweep, and cache them so later list_tables/list_columns calls on this doc need no API requests. Returns a compact summary."
+    )]
+    async fn load_doc_context(
+        &self,
+        Parameters(params): Parameters<LoadDocContextParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let table_limit = params
+            .table_limit
+            .unwrap_or(SCHEMA_DEFAULT_TABLE_LIMIT)
+            .min(SCHEMA_MAX_TABLE_LIMIT) as usize;
+        let sample_rows = params
+            .sample_rows
+            .unwrap_or(CONTEXT_DEFAULT_SAMPLE_ROWS)
+            .min(CONTEXT_MAX_SAMPLE_ROWS);
+        let concurrency = params
+            .concurrency
+            .unwrap_or(SCHEMA_DEFAULT_CONCURRENCY)
+            .clamp(1, SCHEMA_MAX_CONCURRENCY) as usize;
+
+        tracing::info!(
+            "load_doc_context: doc_id={}, table_limit={}, sample_rows={}, concurrency={}",
+            params.doc_id,
+            table_limit,
+            sample_rows,
+            concurrency
+        );
+
+        let doc_id = &params.doc_id;
+        let doc_path = format!("/docs/{doc_id}");
+        let pages_path = format!("{doc_path}/pages");
+        let tables_path = format!("{doc_path}/tables");
+        let (doc, pages, tables): (Doc, PageList, TableList) = tokio::try_join!(
+            self.client.get(&doc_path),
+            self.client.get(&pages_path),
+            self.client.get(&tables_path),
+        )
+        .map_err(CodaError::into_mcp_error)?;
+
+        let redact = self.redacted_columns(None);
+        let loaded: Vec<(String, Vec<Column>, Vec<Row>)> =
+            stream::iter(tables.items.iter().take(table_limit).cloned())
+                .map(|table| {
+                    let redact = &redact;
+                    async move {
+                        let table_path = format!("/docs/{doc_id}/tables/{}", table.id);
+                        let columns_path = format!("{table_path}/columns");
+                        let columns: ColumnList = self.client.get(&columns_path).await?;
+                        let mut rows = if sample_rows == 0 {
+                            Vec::new()
+                        } else {
+                            let rows_path = format!(
+                                "{table_path}/rows?limit={sample_rows}&useColumnNames=true"
+                            );
+                            self.client.get::<RowList>(&rows_path).await?.items
+                        };
+                        for row in &mut rows {
+                            row.redact(redact);
+                        }
+                        Ok::<_, CodaError>((table.id, columns.items, rows))
+                    }
+                })
+                .buffered(concurrency)
+                .try_collect()
+                .await
+                .map_err(CodaError::into_mcp_error)?;
+
+        let mut context = DocContext {
+            doc,
+            pages: pages.items,
+            tables: tables.items,
+            columns: HashMap::new(),
+            samples: HashMap::new(),
+        };
+        for (table_id, columns, rows) in loaded {
+            context.columns.insert(table_id.clone(), columns);
+            context.samples.insert(table_id, rows);
+        }
+
+        let table_summaries: Vec<serde_json::Value> = context
+            .tables
+            .iter()
+            .map(|table| {
+                serde_json::json!({
+                    "id": table.id,
+                    "name": table.name,
+                    "rowCount": table.row_count,
+                    "columns": context.columns.get(&table.id).map(Vec::len),
+                    "sampleRows": context.samples.get(&table.id).map(Vec::len),
+                })
+            })
+            .collect();
+
+        let described = context.columns.len();
+        let summary = format!(
+            "Loaded '{}': {} pages, {} tables ({described} with columns and samples)",
+            context.doc.name,
+            context.pages.len(),
+            context.tables.len(),
+        );
+        let mut output = format!("{summary}\n\nTables:");
+        for table in &context.tables {
+            match context.columns.get(&table.id) {
+                Some(columns) => {
+                    let _ = write!(
+                        output,
+                        "\n- {} ({}): {} columns",
+                        table.name,
+                        table.id,
+                        columns.len()
+                    );
+                }
+                None => {
+                    let _ = write!(output, "\n- {} ({}): not loaded", table.name, table.id);
+                }
+            }
+        }
+
+        let mut outcome = ToolOutcome::success(
+            "load_doc_context",
+            summary,
+            serde_json::json!({
+                "doc_id": context.doc.id,
+                "name": context.doc.name,
+                "pages": context.pages.len(),
+                "tables": table_summaries,
+                "samples": context.samples,
+            }),
+        );
+        if self.context_cache.is_enabled() {
+            let _ = write!(
+                output,
+                "\n\nCached for {}s; list_tables and list_columns on this doc will use it.",
+                self.context_cache.ttl().as_secs()
+            );
+            self.context_cache.insert(&params.doc_id, context);
+        } else {
+            outcome = outcome.with_warning("The doc context cache is disabled");
+        }
+
+        Ok(outcome.into_result(output))
+    }
+
+    #[tool(
+        description = "Get one column's full definition: format type with precision, currency, date pattern or slider bounds, select options, formula, and default value. Use it to learn a column's valid values before writing to it."
+    )]
+    async fn get_column(
+        &self,
+        Parameters(mut params): Parameters<GetColumnParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("get_column", message.clone()).into_result(message));
+        }
+        let path = format!(
+            "/docs/{}/tables/{}/columns/{}",
+            params.doc_id, params.table_id, params.column_id
+        );
+
+        tracing::info!(
+            "get_column: doc_id={}, table_id={}, column_id={}",
+            params.doc_id,
+            params.table_id,
+            params.column_id
+        );
+
+        let column: Column = self
+            .client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let block = self.render(&column)?;
+        let data = serde_json::to_value(&column)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        let mut header = format!(
+            "Column: {}\nType: {}",
+            column.name,
+            column.format_type().unwrap_or("unknown")
+        );
+        if let Some(formula) = &column.formula {
+            let _ = write!(header, "\nFormula: {formula}");
+        }
+        if let Some(default_value) = &column.default_value {
+            let _ = write!(header, "\nDefault: {default_value}");
+        }
+        if let Some(options) = column.format.as_ref().and_then(|f| f.options.as_ref()) {
+            let labels: Vec<&str> = options.iter().map(SelectOption::label).collect();
+            let _ = write!(header, "\nOptions: {}", labels.join(", "));
+        }
+
+        Ok(ToolOutcome::success(
+            "get_column",
+            format!("Fetched column '{}'", column.name),
+            data,
+        )
+        .into_result(format!("{header}\n\n{block}")))
+    }
+
+    #[tool(
+        description = "Get the allowed option labels of a select or multi-select column, so writes use valid values."
+    )]
+    async fn get_column_options(
+        &self,
+        Parameters(params): Parameters<GetColumnParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!(
+            "/docs/{}/tables/{}/columns/{}",
+            params.doc_id, params.table_id, params.column_id
+        );
+
+        tracing::info!(
+            "get_column_options: doc_id={}, table_id={}, column_id={}",
+            params.doc_id,
+            params.table_id,
+            params.column_id
+        );
+
+        let column: Column = self
+            .client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        if !column.is_select() {
+            let message = format!(
+                "Column '{}' is not a select column (format type: {}).",
+                column.name,
+                column.format_type().unwrap_or("unknown")
+            );
+            return Ok(
+                ToolOutcome::failure("get_column_options", message.clone()).into_result(message)
+            );
+        }
+
+        let labels: Vec<&str> = column
+            .format
+            .iter()
+            .flat_map(|f| f.options.iter().flatten())
+            .map(SelectOption::label)
+            .collect();
+
+        let mut output = format!("Column: {}\nOptions ({}):", column.name, labels.len());
+        for label in &labels {
+            let _ = write!(output, "\n- {label}");
+        }
+
+        let mut outcome = ToolOutcome::success(
+            "get_column_options",
+            format!("{} options for '{}'", labels.len(), column.name),
+            serde_json::json!({"column_id": column.id, "options": labels}),
+        );
+        if labels.is_empty() {
+            outcome = outcome.with_warning(
+                "The API returned no options; the column may allow free-form values.",
+            );
+        }
+
+        Ok(outcome.into_result(output))
+    }
+
+    #[tool(
+        description = "Map how a doc's tables link to each other: lists lookup columns and the tables they reference, as edges plus an adjacency list. Bounded by table_limit on large docs."
+    )]
+    async fn get_table_relationships(
+        &self,
+        Parameters(params): Parameters<GetTableRelationshipsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let table_limit = params
+            .table_limit
+            .unwrap_or(SCHEMA_DEFAULT_TABLE_LIMIT)
+            .min(SCHEMA_MAX_TABLE_LIMIT) as usize;
+        let concurrency = params
+            .concurrency
+            .unwrap_or(SCHEMA_DEFAULT_CONCURRENCY)
+            .clamp(1, SCHEMA_MAX_CONCURRENCY) as usize;
+
+        tracing::info!(
+            "get_table_relationships: doc_id={}, table_limit={}, concurrency={}",
+            params.doc_id,
+            table_limit,
+            concurrency
+        );
+
+        let tables: TableList = self
+            .client
+            .get(&format!("/docs/{}/tables", params.doc_id))
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let total = tables.items.len();
+        let doc_id = &params.doc_id;
+
+        let per_table: Vec<Vec<TableEdge>> =
+            stream::iter(tables.items.into_iter().take(table_limit))
+                .map(|table| async move {
+                    let path = format!("/docs/{doc_id}/tables/{}/columns", table.id);
+                    let columns: ColumnList = self.client.get(&path).await?;
+                    let edges = columns
+                        .items
+                        .iter()
+                        .filter_map(|column| {
+                            let target = column.referenced_table()?;
+                            Some(TableEdge {
+                                from_table_id: table.id.clone(),
+                                from_table: table.name.clone(),
+                                column_id: column.id.clone(),
+                                column: column.name.clone(),
+                                to_table_id: target.id.clone(),
+                                to_table: target.name.clone(),
+                            })
+                        })
+                        .collect();
+                    Ok::<_, CodaError>(edges)
+                })
+                .buffered(concurrency)
+                .try_collect()
+                .await
+                .map_err(CodaError::into_mcp_error)?;
+
+        let inspected = per_table.len();
+        let edges: Vec<TableEdge> = per_table.into_iter().flatten().collect();
+
+        let mut adjacency: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for edge in &edges {
+            let targets = adjacency.entry(&edge.from_table_id).or_default();
+            if !targets.contains(&edge.to_table_id.as_str()) {
+                targets.push(&edge.to_table_id);
+            }
+        }
+
+        let mut output = format!(
+            "Found {} relationships across {inspected} of {total} tables",
+            edges.len()
+        );
+        if inspected < total {
+            let _ = write!(
+                output,
+                " ({} tables not inspected; raise table_limit to include them)",
+                total - inspected
+            );
+        }
+        for edge in &edges {
+            let _ = write!(
+                output,
+                "\n- {}.{} -> {}",
+                edge.from_table,
+                edge.column,
+                edge.to_table.as_deref().unwrap_or(&edge.to_table_id)
+            );
+        }
+
+        let block = self.render(&serde_json::json!({
+            "edges": edges,
+            "adjacency": adjacency,
+        }))?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{output}\n\n{block}"
+        ))]))
+    }
+
+    // === Row Tools ===
+
+    #[tool(
+        description = "Get rows from a table with optional filtering. Returns rows with column values using column names as keys."
+    )]
+    async fn get_rows(
+        &self,
+        Parameters(mut params): Parameters<GetRowsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("get_rows", message.clone()).into_result(message));
+        }
+        let budget = params.max_output_bytes.map(|b| b as usize);
+        let from_end = params.from_end.map(|n| n as usize);
+        if from_end.is_some() && (budget.is_some() || params.page_token.is_some()) {
+            let message = "`from_end` scans the whole table; it can't be combined with `max_output_bytes` or `page_token`.".to_string();
+            return Ok(ToolOutcome::failure("get_rows", message.clone()).into_result(message));
+        }
+        let fetch_all = params.fetch_all.unwrap_or(false) || budget.is_some() || from_end.is_some();
+        // A budget stops between pages, so it keeps `limit`-sized pages for finer steps
+        let limit = if fetch_all && budget.is_none() {
+            GET_ROWS_PAGE_SIZE
+        } else {
+            params.limit.unwrap_or(100).min(1000)
+        };
+        let reference_ids = params.reference_ids.unwrap_or(false);
+        let redact = self.redacted_columns(params.redact_columns.as_deref());
+        let column_keys = self
+            .colliding_column_keys(
+                &params.doc_id,
+                &params.table_id,
+                params.use_column_names,
+                !redact.is_empty(),
+            )
+            .await;
+        let mut path = format!(
+            "/docs/{}/tables/{}/rows?limit={}&{}",
+            params.doc_id,
+            params.table_id,
+            limit,
+            self.keyed_row_read_query(
+                column_keys.as_ref(),
+                params.use_column_names,
+                params.value_format,
+                !redact.is_empty(),
+                reference_ids
+            )
+        );
+
+        let query = match (&params.query, &params.filter) {
+            (Some(_), Some(_)) => {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "Use either `query` or `filter`, not both.",
+                )]));
+            }
+            (Some(query), None) => Some(query.clone()),
+            (None, Some(filter)) => Some(filter.to_query()),
+            (None, None) => None,
+        };
+
+        if let Some(query) = &query {
+            let _ = write!(path, "&query={}", urlencoding::encode(query));
+        }
+        if let Some(sort_by) = params.sort_by {
+            let _ = write!(path, "&sortBy={}", sort_by.as_str());
+        }
+        if let Some(visible_only) = params.visible_only {
+            let _ = write!(path, "&visibleOnly={visible_only}");
+        }
+
+        tracing::info!(
+            "get_rows: doc_id={}, table_id={}, limit={}, query={:?}, fetch_all={}, max_output_bytes={:?}",
+            params.doc_id,
+            params.table_id,
+            limit,
+            query,
+            fetch_all,
+            budget
+        );
+
+        let first_path = match &params.page_token {
+            Some(token) => format!("{path}&pageToken={}", urlencoding::encode(token)),
+            None => path.clone(),
+        };
+        let mut rows: RowList = self
+            .client
+            .get(&first_path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let mut pages = 1;
+        let mut truncated = false;
+        let mut repeated_token = false;
+        let mut over_budget = false;
+        // The first page is always returned, even when it alone exceeds the budget
+        let mut used_bytes = match budget {
+            Some(_) => self.rendered_len(&rows.items)?,
+            None => 0,
+        };
+        if fetch_all {
+            let mut seen_tokens = HashSet::new();
+            while let Some(token) = rows.next_page_token.take() {
+                if rows.items.len() >= GET_ROWS_MAX_ROWS {
+                    truncated = true;
+                    break;
+                }
+                if !seen_tokens.insert(token.clone()) {
+                    tracing::warn!("get_rows: API repeated page token {}, stopping", token);
+                    repeated_token = true;
+                    break;
+                }
+                let page_path = paged_path(&path, None, Some(&token));
+                let page: RowList = self
+                    .client
+                    .get(&page_path)
+                    .await
+                    .map_err(CodaError::into_mcp_error)?;
+                if let Some(budget) = budget {
+                    let page_bytes = self.rendered_len(&page.items)?;
+                    if used_bytes + page_bytes > budget {
+                        // Leave the whole page for the next call to fetch with this token
+                        over_budget = true;
+                        rows.next_page_token = Some(token);
+                        break;
+                    }
+                    used_bytes += page_bytes;
+                }
+                rows.items.extend(page.items);
+                rows.next_page_token = page.next_page_token;
+                pages += 1;
+            }
+            if rows.items.len() > GET_ROWS_MAX_ROWS {
+                rows.items.truncate(GET_ROWS_MAX_ROWS);
+                truncated = true;
+            }
+        }
+        let scanned = rows.items.len();
+        if let Some(n) = from_end {
+            rows.items.drain(..scanned.saturating_sub(n));
+        }
+
+        if params.dedupe.unwrap_or(false) {
+            dedupe_by_id(&mut rows.items);
+        }
+
+        let redact = match &column_keys {
+            Some(keys) => keys.redacted(&redact),
+            None => redact,
+        };
+        for row in &mut rows.items {
+            if let Some(keys) = &column_keys {
+                keys.rekey(row);
+            }
+            row.redact(&redact);
+        }
+
+        let mut summary = if from_end.is_some() {
+            format!("Found the last {} of {scanned} rows", rows.items.len())
+        } else {
+            format!("Found {} rows", rows.items.len())
+        };
+        if fetch_all {
+            let _ = write!(summary, " across {pages} pages");
+            if truncated && from_end.is_some() {
+                let _ = write!(
+                    summary,
+                    " (the scan stopped at the {GET_ROWS_MAX_ROWS}-row cap, so more rows follow these)"
+                );
+            } else if truncated {
+                let _ = write!(
+                    summary,
+                    " (truncated at the {GET_ROWS_MAX_ROWS}-row cap; more rows match)"
+                );
+            } else if repeated_token {
+                summary.push_str(" (stopped early: the API repeated a page token)");
+            } else if over_budget {
+                let _ = write!(
+                    summary,
+                    " (stopped at the {}-byte output budget; more rows match)",
+                    budget.unwrap_or_default()
+                );
+            } else if budget.is_some_and(|budget| used_bytes > budget) {
+                let _ = write!(
+                    summary,
+                    " (the first page alone is {used_bytes} bytes, over the budget; lower `limit` for smaller pages)"
+                );
+            } else {
+                summary.push_str(" (all matching rows)");
+            }
+        }
+        let block = match params.format.unwrap_or_default() {
+            RowsFormat::Rows => self.render(&rows.items),
+            RowsFormat::Columnar => self.render(&ColumnarRows::from_rows(&rows.items)),
+            RowsFormat::Csv => Ok(format!(
+                "```csv\n{}```",
+                ColumnarRows::from_rows(&rows.items).to_csv()
+            )),
+        }?;
+
+        if let Some(keys) = &column_keys {
+            let _ = write!(summary, "\n\n{}", keys.warning());
+        }
+        let mut output = format!("{summary}\n\n{block}");
+        if reference_ids {
+            let references: serde_json::Map<String, serde_json::Value> = rows
+                .items
+                .iter()
+                .map(|row| (row.id.clone(), row.references()))
+                .filter(|(_, refs)| !refs.is_empty())
+                .map(|(id, refs)| (id, serde_json::json!(refs)))
+                .collect();
+            let refs_block = self.render(&references)?;
+            let _ = write!(output, "\n\nReferences:\n{refs_block}");
+        }
+        push_next_page(&mut output, rows.next_page_token.as_deref());
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Check whether Coda accepts a row query against a table, without fetching row data. Returns the API's error message when it is rejected, so a filter can be fixed before running get_rows."
+    )]
+    async fn check_query(
+        &self,
+        Parameters(mut params): Parameters<CheckQueryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("check_query", message.clone()).into_result(message));
+        }
+        let path = format!(
+            "/docs/{}/tables/{}/rows?limit=1&query={}",
+            params.doc_id,
+            params.table_id,
+            urlencoding::encode(&params.query)
+        );
+
+        tracing::info!(
+            "check_query: doc_id={}, table_id={}, query={}",
+            params.doc_id,
+            params.table_id,
+            params.query
+        );
+
+        match self.client.get::<RowList>(&path).await {
+            Ok(rows) => {
+                let matches = !rows.items.is_empty();
+                let summary = format!(
+                    "Query ok; it {} rows",
+                    if matches { "matches" } else { "matches no" }
+                );
+                Ok(ToolOutcome::success(
+                    "check_query",
+                    summary.clone(),
+                    serde_json::json!({"valid": true, "matches_rows": matches}),
+                )
+                .into_result(summary))
+            }
+            Err(e) if e.status() == Some(400) => {
+                let message = e.api_message().unwrap_or_else(|| e.to_string());
+                let summary = format!("Query rejected: {message}");
+                Ok(ToolOutcome {
+                    summary: summary.clone(),
+                    data: serde_json::json!({"valid": false, "message": message}),
+                    ..ToolOutcome::from_error("check_query", &e)
+                }
+                .into_result(summary))
+            }
+            Err(e) => Ok(ToolOutcome::from_error("check_query", &e).into_result(e.to_string())),
+        }
+    }
+
+    #[tool(
+        description = "Get rows from several tables, possibly across docs, in one call. Each row is annotated with the doc_id, table_id and table_name it came from."
+    )]
+    async fn get_rows_multi(
+        &self,
+        Parameters(params): Parameters<GetRowsMultiParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if params.sources.is_empty() || params.sources.len() > MULTI_ROWS_MAX_SOURCES {
+            let message =
+                format!("`sources` must list between 1 and {MULTI_ROWS_MAX_SOURCES} tables.");
+            return Ok(ToolOutcome::failure("get_rows_multi", message.clone()).into_result(message));
+        }
+
+        let limit = params.limit.unwrap_or(100).min(1000);
+        let query = params
+            .query
+            .as_deref()
+            .map(|q| format!("&query={}", urlencoding::encode(q)))
+            .unwrap_or_default();
+        let redact = self.redacted_columns(params.redact_columns.as_deref());
+
+        tracing::info!(
+            "get_rows_multi: sources={}, limit={}, query={:?}",
+            params.sources.len(),
+            limit,
+            params.query
+        );
+
+        let per_table: Vec<Result<SourcedTable, CodaError>> =
+            stream::iter(params.sources.iter().cloned())
+                .map(|source| {
+                    let table_path = format!("/docs/{}/tables/{}", source.doc_id, source.table_id);
+                    let rows_path =
+                        format!("{table_path}/rows?limit={limit}&useColumnNames=true{query}");
+                    let redact = &redact;
+                    async move {
+                        let (table, rows): (Table, RowList) = tokio::try_join!(
+                            self.client.get(&table_path),
+                            self.client.get(&rows_path)
+                        )?;
+                        let rows = rows
+                            .items
+                            .into_iter()
+                            .map(|mut row| {
+                                row.redact(redact);
+                                row
+                            })
+                            .collect();
+                        Ok(SourcedTable {
+                            doc_id: source.doc_id,
+                            table_id: table.id,
+                            table_name: table.name,
+                            rows,
+                        })
+                    }
+                })
+                .buffered(MULTI_ROWS_CONCURRENCY)
+                .collect()
+                .await;
+
+        // One unreadable table does not sink the others
+        let mut batch = BatchResult::default();
+        let mut output = String::new();
+        for (source, result) in params.sources.iter().zip(per_table) {
+            match result {
+                Ok(table) => {
+                    let _ = writeln!(
+                        output,
+                        "- {} ({}/{}): {} rows",
+                        table.table_name,
+                        source.doc_id,
+                        source.table_id,
+                        table.rows.len()
+                    );
+                    batch.succeeded.push(table);
+                }
+                Err(e) => {
+                    let _ = writeln!(
+                        output,
+                        "- {}/{} FAILED: {e}",
+                        source.doc_id, source.table_id
+                    );
+                    batch.push_failure(source, &e);
+                }
+            }
+        }
+        let rows: Vec<SourcedRow> = batch
+            .succeeded
+            .iter()
+            .flat_map(|table| {
+                table.rows.iter().map(|row| SourcedRow {
+                    doc_id: table.doc_id.clone(),
+                    table_id: table.table_id.clone(),
+                    table_name: table.table_name.clone(),
+                    row: row.clone(),
+                })
+            })
+            .collect();
+        let mut summary = format!(
+            "Found {} rows across {} tables",
+            rows.len(),
+            batch.succeeded.len()
+        );
+        if !batch.failed.is_empty() {
+            let _ = write!(summary, " ({} failed)", batch.failed.len());
+        }
+
+        let block = self.render(&rows)?;
+        let text = format!("{summary}\n{output}\n{block}");
+        let data = serde_json::to_value(&batch)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        let outcome = if batch.succeeded.is_empty() {
+            ToolOutcome {
+                data,
+                ..ToolOutcome::failure("get_rows_multi", summary)
+            }
+        } else if batch.failed.is_empty() {
+            ToolOutcome::success("get_rows_multi", summary, data)
+        } else {
+            ToolOutcome::success("get_rows_multi", summary, data).with_warning(format!(
+                "{} of {} tables could not be read",
+                batch.failed.len(),
+                params.sources.len()
+            ))
+        };
+        Ok(outcome.into_result(text))
+    }
+
+    #[tool(
+        description = "Compare a table's current rows against a baseline (row ID -> {column: value}), e.g. a copy held in another system. Returns only what differs: new rows, rows missing from the table, and changed cells with old and new values. Only the columns a baseline row lists are compared."
+    )]
+    async fn get_rows_delta(
+        &self,
+        Parameters(mut params): Parameters<GetRowsDeltaParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("get_rows_delta", message.clone()).into_result(message));
+        }
+        let redact = self.redacted_columns(None);
+        let rows_path = format!(
+            "/docs/{}/tables/{}/rows?{}",
+            params.doc_id,
+            params.table_id,
+            self.row_read_query(params.use_column_names, None, !redact.is_empty(), false)
+        );
+
+        tracing::info!(
+            "get_rows_delta: doc_id={}, table_id={}, baseline_rows={}",
+            params.doc_id,
+            params.table_id,
+            params.baseline.len()
+        );
+
+        let (mut current, truncated) = match self
+            .fetch_rows(&rows_path, GET_ROWS_MAX_ROWS, GET_ROWS_PAGE_SIZE)
+            .await
+        {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("get_rows_delta", &e).into_result(e.to_string()));
+            }
+        };
+        for row in &mut current {
+            row.redact(&redact);
+        }
+
+        let mut diff = RowDiff::against_baseline(params.baseline, &current);
+
+        let mut warnings = Vec::new();
+        if truncated {
+            // Baseline rows past the cap weren't fetched, so they can't be called removed
+            diff.removed.clear();
+            warnings.push(format!(
+                "Compared only the first {GET_ROWS_MAX_ROWS} rows; removed rows are not reported"
+            ));
+        }
+
+        let summary = diff.summary();
+        let mut output = diff.describe();
+        let data = serde_json::to_value(&diff)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let block = self.render(&data)?;
+        let _ = write!(output, "\n\n{block}");
+
+        let mut outcome = ToolOutcome::success("get_rows_delta", summary, data);
+        for warning in warnings {
+            outcome = outcome.with_warning(warning);
+        }
+        Ok(outcome.into_result(output))
+    }
+
+    #[tool(
+        description = "Profile how complete a table is: pages through its rows (bounded by max_rows) and reports, per column, how many cells are non-empty, ranked from most to least complete."
+    )]
+    async fn table_data_quality(
+        &self,
+        Parameters(params): Parameters<TableDataQualityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_rows = params
+            .max_rows
+            .unwrap_or(DATA_QUALITY_DEFAULT_ROWS)
+            .clamp(1, DATA_QUALITY_MAX_ROWS) as usize;
+        let rows_path = format!(
+            "/docs/{}/tables/{}/rows?useColumnNames=true",
+            params.doc_id, params.table_id
+        );
+
+        tracing::info!(
+            "table_data_quality: doc_id={}, table_id={}, max_rows={}",
+            params.doc_id,
+            params.table_id,
+            max_rows
+        );
+
+        let (rows, truncated) = self
+            .fetch_rows(&rows_path, max_rows, DATA_QUALITY_PAGE_SIZE)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let rates = ColumnFillRate::from_rows(&rows);
+        let summary = format!("Profiled {} columns over {} rows", rates.len(), rows.len());
+
+        let mut output = format!("{summary}\n");
+        for rate in &rates {
+            let _ = write!(
+                output,
+                "\n- {}: {}/{} ({}%)",
+                rate.column, rate.filled, rate.total, rate.percent
+            );
+        }
+
+        let data = serde_json::json!({
+            "rows_sampled": rows.len(),
+            "truncated": truncated,
+            "columns": rates,
+        });
+        let mut outcome = ToolOutcome::success("table_data_quality", summary, data);
+        if truncated {
+            let warning = format!(
+                "Only the first {max_rows} rows were sampled; raise max_rows to profile more"
+            );
+            let _ = write!(output, "\n\n{warning}.");
+            outcome = outcome.with_warning(warning);
+        }
+
+        Ok(outcome.into_result(output))
+    }
+
+    #[tool(
+        description = "Profile one column in a single bounded scan: non-empty and empty counts, distinct values, min and max (numeric, or lexical for text) and the most frequent values."
+    )]
+    async fn column_profile(
+        &self,
+        Parameters(params): Parameters<ColumnProfileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_rows = params
+            .max_rows
+            .unwrap_or(DATA_QUALITY_DEFAULT_ROWS)
+            .clamp(1, DATA_QUALITY_MAX_ROWS) as usize;
+        let top_k = params
+            .top_k
+            .unwrap_or(PROFILE_DEFAULT_TOP_K)
+            .clamp(1, PROFILE_MAX_TOP_K) as usize;
+        let column_path = format!(
+            "/docs/{}/tables/{}/columns/{}",
+            params.doc_id, params.table_id, params.column_id
+        );
+        let rows_path = format!(
+            "/docs/{}/tables/{}/rows?valueFormat=simple",
+            params.doc_id, params.table_id
+        );
+
+        tracing::info!(
+            "column_profile: doc_id={}, table_id={}, column_id={}, max_rows={}",
+            params.doc_id,
+            params.table_id,
+            params.column_id,
+            max_rows
+        );
+
+        let (column, (rows, truncated)) = tokio::try_join!(
+            self.client.get::<Column>(&column_path),
+            self.fetch_rows(&rows_path, max_rows, DATA_QUALITY_PAGE_SIZE)
+        )
+        .map_err(CodaError::into_mcp_error)?;
+
+        // Rows are keyed by column ID, which also covers a column given by name
+        let profile = ColumnProfile::from_rows(&rows, &column.id, top_k);
+        let summary = format!("Profiled column '{}' over {} rows", column.name, rows.len());
+
+        let mut output = format!(
+            "{summary}\n\nCount: {} ({} empty)\nDistinct: {}",
+            profile.count, profile.empty, profile.distinct
+        );
+        if let (Some(min), Some(max)) = (&profile.min, &profile.max) {
+            let _ = write!(output, "\nMin: {min}\nMax: {max}");
+        }
+        if !profile.top_values.is_empty() {
+            output.push_str("\nTop values:");
+            for top in &profile.top_values {
+                let _ = write!(output, "\n- {}: {}", top.value, top.count);
+            }
+        }
+
+        let data = serde_json::json!({
+            "column": {"id": column.id, "name": column.name},
+            "rows_scanned": rows.len(),
+            "truncated": truncated,
+            "profile": profile,
+        });
+        let mut outcome = ToolOutcome::success("column_profile", summary, data);
+        if truncated {
+            let warning = format!(
+                "Only the first {max_rows} rows were scanned; raise max_rows to profile more"
+            );
+            let _ = write!(output, "\n\n{warning}.");
+            outcome = outcome.with_warning(warning);
+        }
+
+        Ok(outcome.into_result(output))
+    }
+
+    #[tool(
+        description = "Diff two row snapshots (e.g. get_rows output captured before and after a change). Rows are matched by id; returns added rows, removed rows and changed cells with old and new values. Makes no API calls."
+    )]
+    async fn diff_rows(
+        &self,
+        Parameters(params): Parameters<DiffRowsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "diff_rows: before={}, after={}",
+            params.before.len(),
+            params.after.len()
+        );
+
+        let parse = |label: &str, rows: Vec<serde_json::Value>| {
+            rows.into_iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    serde_json::from_value::<Row>(row)
+                        .map_err(|e| format!("Invalid row {i} in `{label}`: {e}"))
+                })
+                .collect::<Result<Vec<Row>, String>>()
+        };
+        let (before, after) = match (parse("before", params.before), parse("after", params.after)) {
+            (Ok(before), Ok(after)) => (before, after),
+            (Err(message), _) | (_, Err(message)) => {
+                return Ok(ToolOutcome::failure("diff_rows", message.clone()).into_result(message));
+            }
+        };
+
+        let diff = RowDiff::between(&before, &after);
+        let summary = diff.summary();
+        let mut output = diff.describe();
+        let data = serde_json::to_value(&diff)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let block = self.render(&data)?;
+        let _ = write!(output, "\n\n{block}");
+
+        Ok(ToolOutcome::success("diff_rows", summary, data).into_result(output))
+    }
+
+    #[tool(description = "Get a specific row by ID.")]
+    async fn get_row(
+        &self,
+        Parameters(mut params): Parameters<GetRowParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("get_row", message.clone()).into_result(message));
+        }
+        let reference_ids = params.reference_ids.unwrap_or(false);
+        let redact = self.redacted_columns(params.redact_columns.as_deref());
+        let column_keys = self
+            .colliding_column_keys(
+                &params.doc_id,
+                &params.table_id,
+                params.use_column_names,
+                !redact.is_empty(),
+            )
+            .await;
+        let path = format!(
+            "/docs/{}/tables/{}/rows/{}?{}",
+            params.doc_id,
+            params.table_id,
+            params.row_id,
+            self.keyed_row_read_query(
+                column_keys.as_ref(),
+                params.use_column_names,
+                params.value_format,
+                !redact.is_empty(),
+                reference_ids
+            )
+        );
+
+        tracing::info!(
+            "get_row: doc_id={}, table_id={}, row_id={}",
+            params.doc_id,
+            params.table_id,
+            params.row_id
+        );
+
+        let mut row: Row = self
+            .client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        match &column_keys {
+            Some(keys) => {
+                keys.rekey(&mut row);
+                row.redact(&keys.redacted(&redact));
+            }
+            None => row.redact(&redact),
+        }
+
+        let block = self.render(&row)?;
+
+        let mut output = format!("Row: {}\n\n{}", row.id, block);
+        if let Some(keys) = &column_keys {
+            let _ = write!(output, "\n\n{}", keys.warning());
+        }
+        if reference_ids {
+            let refs_block = self.render(&row.references())?;
+            let _ = write!(output, "\n\nReferences:\n{refs_block}");
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Wait for a row's cell to reach an expected value, e.g. after pushing a button whose automation updates the row. Polls the row (bounded) and returns the final value and whether it matched."
+    )]
+    async fn wait_for_row_value(
+        &self,
+        Parameters(params): Parameters<WaitForRowValueParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_polls = params
+            .max_polls
+            .unwrap_or(FORMULA_DEFAULT_POLLS)
+            .min(FORMULA_MAX_POLLS);
+        let interval_secs = params
+            .interval_secs
+            .unwrap_or(FORMULA_DEFAULT_INTERVAL_SECS)
+            .clamp(FORMULA_MIN_INTERVAL_SECS, FORMULA_MAX_INTERVAL_SECS);
+        let path = format!(
+            "/docs/{}/tables/{}/rows/{}?useColumnNames=true",
+            params.doc_id, params.table_id, params.row_id
+        );
+
+        tracing::info!(
+            "wait_for_row_value: doc_id={}, table_id={}, row_id={}, column={}, max_polls={}, interval_secs={}",
+            params.doc_id,
+            params.table_id,
+            params.row_id,
+            params.column_name,
+            max_polls,
+            interval_secs
+        );
+
+        let fetch_cell = || async {
+            let row: Row = self
+                .client
+                .get(&path)
+                .await
+                .map_err(CodaError::into_mcp_error)?;
+            Ok::<_, McpError>(row.values.and_then(|mut v| v.remove(&params.column_name)))
+        };
+
+        let Some(mut value) = fetch_cell().await? else {
+            let message = format!(
+                "Column '{}' not found in row '{}'. Use the column's display name.",
+                params.column_name, params.row_id
+            );
+            return Ok(
+                ToolOutcome::failure("wait_for_row_value", message.clone()).into_result(message)
+            );
+        };
+
+        let mut matched = cell_matches(&value, &params.expected_value);
+        let mut polls = 0;
+        while !matched && polls < max_polls {
+            tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+            polls += 1;
+
+            value = fetch_cell().await?.unwrap_or(serde_json::Value::Null);
+            matched = cell_matches(&value, &params.expected_value);
+        }
+
+        let summary = if matched {
+            format!(
+                "'{}' reached the expected value after {polls} polls",
+                params.column_name
+            )
+        } else {
+            format!(
+                "'{}' did not reach the expected value after {polls} polls",
+                params.column_name
+            )
+        };
+        let data = serde_json::json!({
+            "matched": matched,
+            "polls": polls,
+            "value": value,
+        });
+        let block = self.render(&data)?;
+
+        Ok(
+            ToolOutcome::success("wait_for_row_value", summary.clone(), data)
+                .into_result(format!("{summary}\n\n{block}")),
+        )
+    }
+
+    #[tool(
+        description = "Check whether a write has been applied, by the request_id it returned. With wait, polls until the mutation completes or the poll limit is reached."
+    )]
+    async fn get_mutation_status(
+        &self,
+        Parameters(params): Parameters<GetMutationStatusParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_polls = if params.wait.unwrap_or(false) {
+            MAX_POLL_ATTEMPTS
+        } else {
+            0
+        };
+
+        tracing::info!(
+            "get_mutation_status: request_id={}, wait={}",
+            params.request_id,
+            max_polls > 0
+        );
+
+        let (status, polls) = match self
+            .poll_mutation_status(&params.request_id, max_polls, POLL_INTERVAL_SECS)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                return Ok(
+                    ToolOutcome::from_error("get_mutation_status", &e).into_result(e.to_string())
+                );
+            }
+        };
+
+        let summary = if status.completed {
+            format!("Mutation '{}' completed", params.request_id)
+        } else if max_polls > 0 {
+            format!(
+                "Mutation '{}' still pending after {polls} polls",
+                params.request_id
+            )
+        } else {
+            format!("Mutation '{}' still pending", params.request_id)
+        };
+        let data = serde_json::json!({
+            "request_id": params.request_id,
+            "completed": status.completed,
+            "polls": polls,
+            "warning": status.warning,
+        });
+
+        let mut outcome = ToolOutcome::success("get_mutation_status", summary.clone(), data);
+        let mut text = summary;
+        if let Some(warning) = &status.warning {
+            let _ = write!(text, "\nWarning: {warning}");
+            outcome = outcome.with_warning(format!("Mutation warning: {warning}"));
+        }
+        Ok(outcome.into_result(text))
+    }
+
+    #[tool(
+        description = "Read back the result of a row write: waits for the mutation's request_id to complete, then fetches the given row_ids and returns their current values. Pass request_id, row_ids, or both."
+    )]
+    async fn get_mutation_result(
+        &self,
+        Parameters(params): Parameters<GetMutationResultParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let row_ids = params.row_ids.clone().unwrap_or_default();
+        if params.request_id.is_none() && row_ids.is_empty() {
+            let message = "Pass `request_id`, `row_ids`, or both.";
+            return Ok(ToolOutcome::failure("get_mutation_result", message).into_result(message));
+        }
+        if row_ids.len() > MUTATION_RESULT_MAX_ROWS {
+            let message = format!(
+                "Too many row_ids ({}); at most {MUTATION_RESULT_MAX_ROWS} can be read back per call.",
+                row_ids.len()
+            );
+            return Ok(
+                ToolOutcome::failure("get_mutation_result", message.clone()).into_result(message)
+            );
+        }
+        let max_polls = params
+            .max_polls
+            .unwrap_or(FORMULA_DEFAULT_POLLS)
+            .min(FORMULA_MAX_POLLS);
+        let interval_secs = params
+            .interval_secs
+            .unwrap_or(FORMULA_DEFAULT_INTERVAL_SECS)
+            .clamp(FORMULA_MIN_INTERVAL_SECS, FORMULA_MAX_INTERVAL_SECS);
+
+        tracing::info!(
+            "get_mutation_result: doc_id={}, table_id={}, request_id={:?}, rows={}",
+            params.doc_id,
+            params.table_id,
+            params.request_id,
+            row_ids.len()
+        );
+
+        let mut outcome_warnings = Vec::new();
+        let mut status: Option<MutationStatus> = None;
+        let mut polls = 0;
+        if let Some(request_id) = &params.request_id {
+            let (current, status_polls) = self
+                .poll_mutation_status(request_id, max_polls, interval_secs)
+                .await
+                .map_err(CodaError::into_mcp_error)?;
+            polls = status_polls;
+            if !current.completed {
+                outcome_warnings.push(format!(
+                    "Mutation '{request_id}' was still pending after {polls} polls; rows may not reflect it yet"
+                ));
+            }
+            if let Some(warning) = &current.warning {
+                outcome_warnings.push(format!("Mutation warning: {warning}"));
+            }
+            status = Some(current);
+        }
+
+        let redact = self.redacted_columns(None);
+        let fetched: Vec<(String, Option<Row>)> = stream::iter(row_ids.iter().cloned())
+            .map(|row_id| {
+                let path = format!(
+                    "/docs/{}/tables/{}/rows/{}?useColumnNames=true",
+                    params.doc_id,
+                    params.table_id,
+                    urlencoding::encode(&row_id)
+                );
+                async move {
+                    match self.client.get::<Row>(&path).await {
+                        Ok(row) => Ok((row_id, Some(row))),
+                        Err(CodaError::NotFound) => Ok((row_id, None)),
+                        Err(e) => Err(e),
+                    }
+                }
+            })
+            .buffered(MUTATION_RESULT_CONCURRENCY)
+            .try_collect()
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let mut rows = Vec::new();
+        let mut missing = Vec::new();
+        for (row_id, row) in fetched {
+            match row {
+                Some(mut row) => {
+                    row.redact(&redact);
+                    rows.push(row);
+                }
+                None => missing.push(row_id),
+            }
+        }
+        if !missing.is_empty() {
+            outcome_warnings.push(format!("Rows not found: {}", missing.join(", ")));
+        }
+
+        let completed = status.as_ref().map(|s| s.completed);
+        let mut summary = match completed {
+            Some(true) => format!("Mutation completed after {polls} polls"),
+            Some(false) => format!("Mutation still pending after {polls} polls"),
+            None => "No request_id given; rows read as they are now".to_string(),
+        };
+        if !row_ids.is_empty() {
+            let _ = write!(
+                summary,
+                "; fetched {} of {} rows",
+                rows.len(),
+                row_ids.len()
+            );
+        }
+        let data = serde_json::json!({
+            "request_id": params.request_id,
+            "completed": completed,
+            "polls": polls,
+            "rows": rows,
+            "missing_row_ids": missing,
+        });
+        let block = self.render(&data)?;
+
+        let mut outcome = ToolOutcome::success("get_mutation_result", summary.clone(), data);
+        for warning in outcome_warnings {
+            outcome = outcome.with_warning(warning);
+        }
+        Ok(outcome.into_result(format!("{summary}\n\n{block}")))
+    }
+
+    #[tool(
+        description = "Add a new row to a table. Cells should be a dictionary mapping column names to values."
+    )]
+    async fn add_row(
+        &self,
+        Parameters(mut params): Parameters<AddRowParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("add_row", message.clone()).into_result(message));
+        }
+        let path = format!("/docs/{}/tables/{}/rows", params.doc_id, params.table_id);
+        let coercions = self
+            .coerce_row_cells(
+                &params.doc_id,
+                &params.table_id,
+                params.coerce,
+                &mut params.cells,
+            )
+            .await?;
+
+        let cells = cells_json(&params.cells);
+
+        let mut body = serde_json::json!({
+            "rows": [{
+                "cells": cells
+            }]
+        });
+        let key_columns = params.key_columns.unwrap_or_default();
+        if !key_columns.is_empty() {
+            body["keyColumns"] = serde_json::json!(key_columns);
+        }
+
+        tracing::info!(
+            "add_row: doc_id={}, table_id={}, cells={:?}, key_columns={:?}",
+            params.doc_id,
+            params.table_id,
+            params.cells,
+            key_columns
+        );
+
+        let result: RowMutationResponse = self
+            .client
+            .post(&path, &body)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let added_ids = result
+            .added_row_ids
+            .as_ref()
+            .map(|ids| ids.join(", "))
+            .unwrap_or_default();
+        let mut text = if key_columns.is_empty() {
+            "Row added successfully.".to_string()
+        } else {
+            format!("Row upserted on {}.", key_columns.join(", "))
+        };
+        let _ = write!(
+            text,
+            "\nRequest ID: {}\nAdded row IDs: {}",
+            result.request_id, added_ids
+        );
+        let row_id_lines = result.row_id_lines();
+        if !row_id_lines.is_empty() {
+            text.push_str("\nRow IDs by input:");
+            for line in &row_id_lines {
+                let _ = write!(text, "\n- {line}");
+            }
+        }
+        push_coercions(&mut text, &coercions);
+        let _ = write!(text, "\n\nNote: {MUTATION_DELAY_NOTE}");
+        let mut data = serde_json::to_value(&result)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        if params.coerce.unwrap_or(false) {
+            data["coercions"] = serde_json::json!(coercions);
+        }
+
+        let summary = if key_columns.is_empty() {
+            "Row added"
+        } else {
+            "Row upserted"
+        };
+        Ok(ToolOutcome::success("add_row", summary, data)
+            .with_warning(MUTATION_DELAY_NOTE)
+            .into_result(text))
+    }
+
+    #[tool(
+        description = "Add several rows to a table in one request (up to 500). Each row is a map of column name to value. Optionally upsert on key_columns."
+    )]
+    async fn add_rows(
+        &self,
+        Parameters(mut params): Parameters<AddRowsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("add_rows", message.clone()).into_result(message));
+        }
+        if params.rows.is_empty() || params.rows.len() > ADD_ROWS_MAX_ROWS {
+            let message = format!("`rows` must hold between 1 and {ADD_ROWS_MAX_ROWS} rows.");
+            return Ok(ToolOutcome::failure("add_rows", message.clone()).into_result(message));
+        }
+        let path = format!("/docs/{}/tables/{}/rows", params.doc_id, params.table_id);
+
+        let rows: Vec<serde_json::Value> = params
+            .rows
+            .iter()
+            .map(|cells| serde_json::json!({ "cells": cells_json(cells) }))
+            .collect();
+        let mut body = serde_json::json!({ "rows": rows });
+        let key_columns = params.key_columns.unwrap_or_default();
+        if !key_columns.is_empty() {
+            body["keyColumns"] = serde_json::json!(key_columns);
+        }
+
+        tracing::info!(
+            "add_rows: doc_id={}, table_id={}, rows={}, key_columns={:?}",
+            params.doc_id,
+            params.table_id,
+            params.rows.len(),
+            key_columns
+        );
+
+        let result: RowMutationResponse = match self.client.post(&path, &body).await {
+            Ok(result) => result,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("add_rows", &e).into_result(e.to_string()));
+            }
+        };
+
+        let added = result.added_row_ids.as_deref().unwrap_or_default();
+        let summary = if key_columns.is_empty() {
+            format!("Added {} rows", params.rows.len())
+        } else {
+            format!(
+                "Upserted {} rows on {}",
+                params.rows.len(),
+                key_columns.join(", ")
+            )
+        };
+        let mut text = format!(
+            "{summary}.\nRequest ID: {}\nAdded row IDs: {}",
+            result.request_id,
+            added.join(", ")
+        );
+        let row_id_lines = result.row_id_lines();
+        if !row_id_lines.is_empty() {
+            text.push_str("\nRow IDs by input:");
+            for line in &row_id_lines {
+                let _ = write!(text, "\n- {line}");
+            }
+        }
+        let _ = write!(text, "\n\nNote: {MUTATION_DELAY_NOTE}");
+        let data = serde_json::to_value(&result)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(ToolOutcome::success("add_rows", summary, data)
+            .with_warning(MUTATION_DELAY_NOTE)
+            .into_result(text))
+    }
+
+    #[tool(description = "Update an existing row in a table.")]
+    async fn update_row(
+        &self,
+        Parameters(mut params): Parameters<UpdateRowParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("update_row", message.clone()).into_result(message));
+        }
+        let path = format!(
+            "/docs/{}/tables/{}/rows/{}",
+            params.doc_id, params.table_id, params.row_id
+        );
+        let coercions = self
+            .coerce_row_cells(
+                &params.doc_id,
+                &params.table_id,
+                params.coerce,
+                &mut params.cells,
+            )
+            .await?;
+
+        let cells = cells_json(&params.cells);
+
+        let body = serde_json::json!({
+            "row": {
+                "cells": cells
+            }
+        });
+
+        tracing::info!(
+            "update_row: doc_id={}, table_id={}, row_id={}",
+            params.doc_id,
+            params.table_id,
+            params.row_id
+        );
+
+        let result: RowMutationResponse = self
+            .client
+            .put(&path, &body)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        let mut text = format!(
+            "Row updated successfully.\nRequest ID: {}",
+            result.request_id
+        );
+        push_coercions(&mut text, &coercions);
+        let _ = write!(text, "\n\nNote: {MUTATION_DELAY_NOTE}");
+        let mut data =
+            serde_json::json!({"request_id": result.request_id, "row_id": params.row_id});
+        if params.coerce.unwrap_or(false) {
+            data["coercions"] = serde_json::json!(coercions);
+        }
+
+        Ok(ToolOutcome::success(
+            "update_row",
+            format!("Row '{}' updated", params.row_id),
+            data,
+        )
+        .with_warning(MUTATION_DELAY_NOTE)
+        .into_result(text))
+    }
+
+    #[tool(
+        description = "Add options to a multi-select column on many rows without dropping the options they already have. Select rows by row_ids or query (up to 100); each row's current value is read, merged with values_to_add, and written back."
+    )]
+    async fn append_to_multiselect(
+        &self,
+        Parameters(mut params): Parameters<AppendToMultiselectParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(
+                ToolOutcome::failure("append_to_multiselect", message.clone()).into_result(message),
+            );
+        }
+        let invalid = if params.values_to_add.is_empty() {
+            Some("`values_to_add` must name at least one option.".to_string())
+        } else {
+            match (&params.row_ids, &params.query) {
+                (Some(_), Some(_)) => {
+                    Some("Pass either `row_ids` or `query`, not both.".to_string())
+                }
+                (None, None) => Some("Pass `row_ids` or `query` to select the rows.".to_string()),
+                (Some(ids), None) if ids.is_empty() || ids.len() > MULTISELECT_MAX_ROWS => Some(
+                    format!("`row_ids` must hold between 1 and {MULTISELECT_MAX_ROWS} rows."),
+                ),
+                _ => None,
+            }
+        };
+        if let Some(message) = invalid {
+            return Ok(
+                ToolOutcome::failure("append_to_multiselect", message.clone()).into_result(message),
+            );
+        }
+        let table_path = format!("/docs/{}/tables/{}", params.doc_id, params.table_id);
+
+        tracing::info!(
+            "append_to_multiselect: doc_id={}, table_id={}, column={}, rows={:?}, query={:?}",
+            params.doc_id,
+            params.table_id,
+            params.column_name,
+            params.row_ids.as_ref().map(Vec::len),
+            params.query
+        );
+
+        let column_path = format!(
+            "{table_path}/columns/{}",
+            urlencoding::encode(&params.column_name)
+        );
+        let column: Column = match self.client.get(&column_path).await {
+            Ok(column) => column,
+            Err(e) => {
+                return Ok(
+                    ToolOutcome::from_error("append_to_multiselect", &e).into_result(e.to_string())
+                );
+            }
+        };
+        if !column.is_multi_select() {
+            let message = format!(
+                "Column '{}' is not a multi-select column (format type: {}).",
+                column.name,
+                column.format_type().unwrap_or("unknown")
+            );
+            return Ok(
+                ToolOutcome::failure("append_to_multiselect", message.clone()).into_result(message),
+            );
+        }
+
+        let read_query = "useColumnNames=true&valueFormat=simpleWithArrays";
+        let mut batch: BatchResult<TaggedRow> = BatchResult::default();
+        let mut rows = Vec::new();
+        if let Some(query) = &params.query {
+            let rows_path = format!(
+                "{table_path}/rows?{read_query}&query={}",
+                urlencoding::encode(query)
+            );
+            let limit = u32::try_from(MULTISELECT_MAX_ROWS).unwrap_or(u32::MAX);
+            match self
+                .fetch_rows(&rows_path, MULTISELECT_MAX_ROWS, limit)
+                .await
+            {
+                Ok((_, true)) => {
+                    let message = format!(
+                        "Query matches more than {MULTISELECT_MAX_ROWS} rows; narrow it or pass row_ids. Nothing was changed."
+                    );
+                    return Ok(
+                        ToolOutcome::failure("append_to_multiselect", message.clone())
+                            .into_result(message),
+                    );
+                }
+                Ok((matched, false)) => rows = matched,
+                Err(e) => {
+                    return Ok(ToolOutcome::from_error("append_to_multiselect", &e)
+                        .into_result(e.to_string()));
+                }
+            }
+        }
+        for row_id in params.row_ids.iter().flatten() {
+            let row_path = format!(
+                "{table_path}/rows/{}?{read_query}",
+                urlencoding::encode(row_id)
+            );
+            match self.client.get::<Row>(&row_path).await {
+                Ok(row) => rows.push(row),
+                Err(e) => batch.push_failure(&serde_json::json!({"row_id": row_id}), &e),
+            }
+        }
+
+        // Writes go one at a time to stay within the write rate limit
+        for row in &rows {
+            let current = row
+                .values
+                .as_ref()
+                .and_then(|values| values.get(&column.name).or_else(|| values.get(&column.id)))
+                .unwrap_or(&serde_json::Value::Null);
+            let (merged, added) = merge_select_values(current, &params.values_to_add);
+            if added.is_empty() {
+                batch.succeeded.push(TaggedRow {
+                    row_id: row.id.clone(),
+                    added,
+                    request_id: None,
+                });
+                continue;
+            }
+            let body = serde_json::json!({
+                "row": {"cells": [{"column": column.id, "value": merged}]}
+            });
+            let row_path = format!("{table_path}/rows/{}", urlencoding::encode(&row.id));
+            match self
+                .client
+                .put::<RowMutationResponse, _>(&row_path, &body)
+                .await
+            {
+                Ok(result) => batch.succeeded.push(TaggedRow {
+                    row_id: row.id.clone(),
+                    added,
+                    request_id: Some(result.request_id),
+                }),
+                Err(e) => batch.push_failure(&serde_json::json!({"row_id": row.id}), &e),
+            }
+        }
+
+        let updated = batch
+            .succeeded
+            .iter()
+            .filter(|row| !row.added.is_empty())
+            .count();
+        let mut summary = format!(
+            "Added options to {updated} rows in '{}' ({} already had them)",
+            column.name,
+            batch.succeeded.len() - updated
+        );
+        if !batch.failed.is_empty() {
+            let _ = write!(summary, " ({} failed)", batch.failed.len());
+        }
+        let mut text = summary.clone();
+        for row in &batch.succeeded {
+            if row.added.is_empty() {
+                let _ = write!(text, "\n- {}: unchanged", row.row_id);
+            } else {
+                let _ = write!(text, "\n- {}: +{}", row.row_id, row.added.join(", +"));
+            }
+        }
+        for failure in &batch.failed {
+            let _ = write!(
+                text,
+                "\n- {} FAILED: {}",
+                failure.input["row_id"].as_str().unwrap_or_default(),
+                failure.message
+            );
+        }
+        if updated > 0 {
+            let _ = write!(text, "\n\nNote: {MUTATION_DELAY_NOTE}");
+        }
+        let data = serde_json::to_value(&batch)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        let outcome = if batch.succeeded.is_empty() && !batch.failed.is_empty() {
+            ToolOutcome {
+                data,
+                ..ToolOutcome::failure("append_to_multiselect", summary)
+            }
+        } else {
+            let mut outcome = ToolOutcome::success("append_to_multiselect", summary, data);
+            if !batch.failed.is_empty() {
+                outcome = outcome
+                    .with_warning(format!("{} rows could not be updated", batch.failed.len()));
+            }
+            if updated > 0 {
+                outcome = outcome.with_warning(MUTATION_DELAY_NOTE);
+            }
+            outcome
+        };
+        Ok(outcome.into_result(text))
+    }
+
+    #[tool(description = "Delete a row from a table.")]
+    async fn delete_row(
+        &self,
+        Parameters(mut params): Parameters<DeleteRowParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("delete_row", message.clone()).into_result(message));
+        }
+        let path = format!(
+            "/docs/{}/tables/{}/rows/{}",
+            params.doc_id, params.table_id, params.row_id
+        );
+
+        tracing::info!(
+            "delete_row: doc_id={}, table_id={}, row_id={}",
+            params.doc_id,
+            params.table_id,
+            params.row_id
+        );
+
+        if let Some(refusal) = self
+            .check_confirmation(
+                "delete_row",
+                &params.row_id,
+                &path,
+                params.confirm.as_deref(),
+            )
+            .await
+        {
+            return Ok(refusal);
+        }
+
+        self.client
+            .delete(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        Ok(ToolOutcome::success(
+            "delete_row",
+            format!("Row '{}' deleted", params.row_id),
+            serde_json::json!({"row_id": params.row_id}),
+        )
+        .with_warning(MUTATION_DELAY_NOTE)
+        .into_result(format!(
+            "Row deleted successfully.\n\nNote: {MUTATION_DELAY_NOTE}"
+        )))
+    }
+
+    #[tool(
+        description = "Delete several rows from a table in one request. In safe mode, confirm with the table's ID or name."
+    )]
+    async fn delete_rows(
+        &self,
+        Parameters(mut params): Parameters<DeleteRowsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("delete_rows", message.clone()).into_result(message));
+        }
+        if params.row_ids.is_empty() {
+            let message = "`row_ids` must name at least one row.".to_string();
+            return Ok(ToolOutcome::failure("delete_rows", message.clone()).into_result(message));
+        }
+        let table_path = format!("/docs/{}/tables/{}", params.doc_id, params.table_id);
+
+        tracing::info!(
+            "delete_rows: doc_id={}, table_id={}, rows={}",
+            params.doc_id,
+            params.table_id,
+            params.row_ids.len()
+        );
+
+        if let Some(refusal) = self
+            .check_confirmation(
+                "delete_rows",
+                &params.table_id,
+                &table_path,
+                params.confirm.as_deref(),
+            )
+            .await
+        {
+            return Ok(refusal);
+        }
+
+        let body = serde_json::json!({ "rowIds": params.row_ids });
+        let result: RowsDeleteResponse = match self
+            .client
+            .delete_with_body(&format!("{table_path}/rows"), &body)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("delete_rows", &e).into_result(e.to_string()));
+            }
+        };
+
+        let summary = format!("Deleted {} rows", result.row_ids.len());
+        let text = format!(
+            "{summary}.\nRequest ID: {}\nDeleted row IDs: {}\n\nNote: {MUTATION_DELAY_NOTE}",
+            result.request_id,
+            result.row_ids.join(", ")
+        );
+        let data = serde_json::to_value(&result)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(ToolOutcome::success("delete_rows", summary, data)
+            .with_warning(MUTATION_DELAY_NOTE)
+            .into_result(text))
+    }
+
+    #[tool(
+        description = "Push the button in a row's button column, running its action (e.g. an automation). Use wait_for_row_value to see the result."
+    )]
+    async fn push_button(
+        &self,
+        Parameters(mut params): Parameters<PushButtonParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Err(message) = resolve_target(&self.config.aliases, &mut params) {
+            return Ok(ToolOutcome::failure("push_button", message.clone()).into_result(message));
+        }
+        let path = format!(
+            "/docs/{}/tables/{}/rows/{}/buttons/{}",
+            params.doc_id,
+            params.table_id,
+            urlencoding::encode(&params.row_id),
+            urlencoding::encode(&params.column_id)
+        );
+
+        tracing::info!(
+            "push_button: doc_id={}, table_id={}, row_id={}, column_id={}",
+            params.doc_id,
+            params.table_id,
+            params.row_id,
+            params.column_id
+        );
+
+        let result: RowMutationResponse =
+            match self.client.post(&path, &serde_json::json!({})).await {
+                Ok(result) => result,
+                Err(e) => {
+                    let mut message = format!("Could not push button: {e}");
+                    if e.status() == Some(400) {
+                        let _ = write!(
+                            message,
+                            "\n\nColumn '{}' may not be a button column.",
+                            params.column_id
+                        );
+                    }
+                    return Ok(ToolOutcome::from_error("push_button", &e).into_result(message));
+                }
+            };
+
+        let summary = format!(
+            "Pushed button '{}' on row '{}'",
+            params.column_id, params.row_id
+        );
+        let text = format!(
+            "{summary}.\nRequest ID: {}\n\nNote: {MUTATION_DELAY_NOTE}",
+            result.request_id
+        );
+        let data = serde_json::to_value(&result)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(ToolOutcome::success("push_button", summary, data)
+            .with_warning(MUTATION_DELAY_NOTE)
+            .into_result(text))
+    }
+
+    #[tool(
+        description = "Trigger a Coda automation rule that has a webhook trigger, optionally passing a JSON payload the rule can read. Returns the request ID."
+    )]
+    async fn trigger_automation(
+        &self,
+        Parameters(params): Parameters<TriggerAutomationParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!(
+            "/docs/{}/hooks/automation/{}",
+            params.doc_id,
+            urlencoding::encode(&params.rule_id)
+        );
+
+        tracing::info!(
+            "trigger_automation: doc_id={}, rule_id={}",
+            params.doc_id,
+            params.rule_id
+        );
+
+        let payload = params.payload.unwrap_or_else(|| serde_json::json!({}));
+        let result: AutomationResponse = match self.client.post(&path, &payload).await {
+            Ok(result) => result,
+            Err(e) => {
+                let mut message = format!("Could not trigger automation: {e}");
+                if matches!(e, CodaError::NotFound) {
+                    let _ = write!(
+                        message,
+                        "\n\nRule '{}' was not found. Use the rule ID from its webhook trigger settings.",
+                        params.rule_id
+                    );
+                }
+                return Ok(ToolOutcome::from_error("trigger_automation", &e).into_result(message));
+            }
+        };
+
+        let summary = format!("Triggered automation rule '{}'", params.rule_id);
+        let text = format!(
+            "{summary}.\nRequest ID: {}\n\nNote: {MUTATION_DELAY_NOTE}",
+            result.request_id
+        );
+        let data = serde_json::to_value(&result)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(ToolOutcome::success("trigger_automation", summary, data)
+            .with_warning(MUTATION_DELAY_NOTE)
+            .into_result(text))
+    }
+
+    // === Formula Tools ===
+
+    #[tool(description = "List all named formulas in a document.")]
+    async fn list_formulas(
+        &self,
+        Parameters(params): Parameters<ListFormulasParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!("/docs/{}/formulas", params.doc_id);
+
+        tracing::info!("list_formulas: doc_id={}", params.doc_id);
+
+        let mut formulas: FormulaList = self
+            .client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        if params.dedupe.unwrap_or(false) {
+            dedupe_by_id(&mut formulas.items);
+        }
+
+        let summary = format!("Found {} formulas", formulas.items.len());
+        let block = self.render(&formulas.items)?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{summary}\n\n{block}"
+        ))]))
+    }
+
+    #[tool(description = "Get a specific formula's current value.")]
+    async fn get_formula(
+        &self,
+        Parameters(params): Parameters<GetFormulaParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "get_formula: doc_id={}, formula_id={}",
+            params.doc_id,
+            params.formula_id
+        );
+
+        let formula = self
+            .fetch_formula(&params.doc_id, &params.formula_id)
+            .await?;
+
+        let block = self.render(&formula)?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Formula: {}\n\n{}",
+            formula.name, block
+        ))]))
+    }
+
+    #[tool(
+        description = "Poll a named formula until its value changes (or equals until_value), then return the value history. Polls are bounded to avoid hammering the API."
+    )]
+    async fn poll_formula(
+        &self,
+        Parameters(params): Parameters<PollFormulaParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_polls = params
+            .max_polls
+            .unwrap_or(FORMULA_DEFAULT_POLLS)
+            .min(FORMULA_MAX_POLLS);
+        let interval_secs = params
+            .interval_secs
+            .unwrap_or(FORMULA_DEFAULT_INTERVAL_SECS)
+            .clamp(FORMULA_MIN_INTERVAL_SECS, FORMULA_MAX_INTERVAL_SECS);
+
+        tracing::info!(
+            "poll_formula: doc_id={}, formula_id={}, max_polls={}, interval_secs={}, until_value={:?}",
+            params.doc_id,
+            params.formula_id,
+            max_polls,
+            interval_secs,
+            params.until_value
+        );
+
+        let initial = self
+            .fetch_formula(&params.doc_id, &params.formula_id)
+            .await?;
+        let baseline = initial.value.clone();
+        let mut history = vec![baseline.clone()];
+
+        let is_done = |value: &Option<serde_json::Value>| match &params.until_value {
+            Some(target) => value.as_ref() == Some(target),
+            None => *value != baseline,
+        };
+
+        let mut done = params.until_value.is_some() && is_done(&baseline);
+        let mut polls = 0;
+
+        while !done && polls < max_polls {
+            tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+            polls += 1;
+
+            let formula = self
+                .fetch_formula(&params.doc_id, &params.formula_id)
+                .await?;
+            done = is_done(&formula.value);
+            history.push(formula.value);
+        }
+
+        let summary = match (done, &params.until_value) {
+            (true, Some(_)) => format!(
+                "Formula '{}' reached the target value after {polls} polls",
+                initial.name
+            ),
+            (true, None) => format!("Formula '{}' changed after {polls} polls", initial.name),
+            (false, Some(_)) => format!(
+                "Formula '{}' did not reach the target value after {polls} polls",
+                initial.name
+            ),
+            (false, None) => format!(
+                "Formula '{}' did not change after {polls} polls",
+                initial.name
+            ),
+        };
+
+        let block = self.render(&serde_json::json!({
+            "matched": done,
+            "polls": polls,
+            "history": history,
+        }))?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{summary}\n\n{block}"
+        ))]))
+    }
+
+    // === Control Tools ===
+
+    #[tool(description = "List all controls (buttons, sliders, etc.) in a document.")]
+    async fn list_controls(
+        &self,
+        Parameters(params): Parameters<ListControlsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!("/docs/{}/controls", params.doc_id);
+
+        tracing::info!("list_controls: doc_id={}", params.doc_id);
+
+        let mut controls: ControlList = self
+            .client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+
+        if params.dedupe.unwrap_or(false) {
+            dedupe_by_id(&mut controls.items);
+        }
+
+        let summary = format!("Found {} controls", controls.items.len());
+        let block = self.render(&controls.items)?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "{summary}\n\n{block}"
+        ))]))
+    }
+
+    #[tool(
+        description = "Controls (sliders, checkboxes, selects, etc.) cannot be set through the Coda API; this tool explains that without making a request. Change the control in the Coda UI, or use push_button for buttons."
+    )]
+    async fn set_control_value(
+        &self,
+        Parameters(params): Parameters<SetControlValueParams>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::info!(
+            "set_control_value: doc_id={}, control_id={}, value={}",
+            params.doc_id,
+            params.control_id,
+            params.value
+        );
+
+        // The API exposes controls read-only; there is no endpoint to write a value
+        let message = format!(
+            "Controls can't be set through the Coda API. Change '{}' in the Coda UI instead.",
+            params.control_id
+        );
+        Ok(ToolOutcome::failure("set_control_value", message.clone()).into_result(message))
+    }
+
+    // === Diagnostics Tools ===
+
+    #[tool(
+        description = "Show the Coda account the API token belongs to (name, login email, token name, workspace). Use it to validate a token."
+    )]
+    async fn whoami(&self) -> Result<CallToolResult, McpError> {
+        tracing::info!("whoami");
+
+        let user: User = match self.client.get("/whoami").await {
+            Ok(user) => user,
+            Err(e) => {
+                let hint = e.category().hint("GET", "/whoami");
+                return Ok(ToolOutcome::from_error("whoami", &e)
+                    .into_result(format!("{e}\n\nHint: {hint}")));
+            }
+        };
+
+        if let (Some(workspace), Ok(mut cached)) = (&user.workspace, self.workspace.lock()) {
+            *cached = Some(workspace.clone());
+        }
+
+        let summary = format!("Authenticated as {} ({})", user.name, user.login_id);
+        let mut output = summary.clone();
+        if let Some(token_name) = &user.token_name {
+            let _ = write!(output, "\nToken: {token_name}");
+        }
+        if let Some(workspace) = &user.workspace {
+            let _ = write!(output, "\nWorkspace: {}", workspace.id);
+            if let Some(name) = &workspace.name {
+                let _ = write!(output, " ({name})");
+            }
+        }
+        let data = serde_json::to_value(&user)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let block = self.render(&data)?;
+        let _ = write!(output, "\n\n{block}");
+
+        Ok(ToolOutcome::success("whoami", summary, data).into_result(output))
+    }
+
+    #[tool(
+        description = "Get the ID and name of the token's workspace, from whoami. The result is cached; set refresh to look it up again."
+    )]
+    async fn get_workspace(
+        &self,
+        Parameters(params): Parameters<GetWorkspaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let refresh = params.refresh.unwrap_or(false);
+        tracing::info!("get_workspace: refresh={}", refresh);
+
+        let cached = !refresh && self.cached_workspace().is_some();
+        let workspace = match self.workspace(refresh).await {
+            Ok(workspace) => workspace,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("get_workspace", &e).into_result(e.to_string()));
+            }
+        };
+
+        let mut summary = format!("Workspace: {}", workspace.id);
+        if let Some(name) = &workspace.name {
+            let _ = write!(summary, " ({name})");
+        }
+        let mut output = summary.clone();
+        if cached {
+            output.push_str("\nCached from an earlier lookup; set refresh to look it up again.");
+        }
+        let mut data = serde_json::to_value(&workspace)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        data["cached"] = serde_json::Value::Bool(cached);
+
+        Ok(ToolOutcome::success("get_workspace", summary, data).into_result(output))
+    }
+
+    #[tool(
+        description = "Resolve a Coda browser URL (https://coda.io/d/...) to the doc, page, table, row or other object it points to, with the IDs other tools take. Start here when given a link instead of IDs."
+    )]
+    async fn resolve_link(
+        &self,
+        Parameters(params): Parameters<ResolveLinkParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = format!(
+            "/resolveBrowserLink?url={}&degradeGracefully=true",
+            urlencoding::encode(params.url.trim())
+        );
+
+        tracing::info!("resolve_link: url={}", params.url);
+
+        let link: ApiLink = match self.client.get(&path).await {
+            Ok(link) => link,
+            Err(e) => {
+                return Ok(ToolOutcome::from_error("resolve_link", &e).into_result(e.to_string()))
+            }
+        };
+
+        let resource = &link.resource;
+        let ids = resource.ids();
+        let summary = match &resource.name {
+            Some(name) => format!(
+                "Link resolves to {} '{name}' ({})",
+                resource.resource_type, resource.id
+            ),
+            None => format!(
+                "Link resolves to {} {}",
+                resource.resource_type, resource.id
+            ),
+        };
+        let mut output = summary.clone();
+        for (param, id) in &ids {
+            let _ = write!(output, "\n{param}: {id}");
+        }
+
+        let mut data = serde_json::to_value(&link)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        data["ids"] = serde_json::json!(ids);
+        let block = self.render(&data)?;
+        let _ = write!(output, "\n\n{block}");
+
+        Ok(ToolOutcome::success("resolve_link", summary, data).into_result(output))
+    }
+
+    #[tool(description = "Explain the most recent failed Coda API call and suggest how to fix it.")]
+    async fn explain_last_error(&self) -> Result<CallToolResult, McpError> {
+        tracing::info!("explain_last_error");
+
+        let Some(record) = self.client.last_error() else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No failed requests recorded in this session.",
+            )]));
+        };
+
+        let mut output = format!("Last failed call: {} {}", record.method, record.path);
+        if let Some(status) = record.status {
+            let _ = write!(output, " (HTTP {status})");
+        }
+        if let Some(error) = &record.error {
+            let _ = write!(output, "\nError: {error}");
+        }
+        if let Some(category) = record.category {
+            let _ = write!(
+                output,
+                "\n\nHint: {}",
+                category.hint(&record.method, &record.path)
+            );
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Check network reachability and latency for the Coda API and the hosts used for page export downloads. Does not require a valid token."
+    )]
+    async fn check_connectivity(&self) -> Result<CallToolResult, McpError> {
+        tracing::info!("check_connectivity");
+
+        let probes = self.client.check_connectivity().await;
+        let unreachable = probes.iter().filter(|p| !p.reachable).count();
+
+        let mut output = if unreachable == 0 {
+            format!("All {} hosts reachable", probes.len())
+        } else {
+            format!("{unreachable} of {} hosts unreachable", probes.len())
+        };
+        output.push('\n');
+        for probe in &probes {
+            if probe.reachable {
+                let status = probe
+                    .status
+                    .map(|s| format!("HTTP {s}, "))
+                    .unwrap_or_default();
+                let _ = write!(
+                    output,
+                    "\n{}: reachable ({status}{} ms)",
+                    probe.url, probe.latency_ms
+                );
+            } else {
+                let _ = write!(
+                    output,
+                    "\n{}: UNREACHABLE ({})",
+                    probe.url,
+                    probe.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Show the headers of the most recent Coda API response (requires CODA_DEBUG_HEADERS). Credentials and cookies are never included."
+    )]
+    async fn get_last_response_headers(&self) -> Result<CallToolResult, McpError> {
+        tracing::info!("get_last_response_headers");
+
+        if !self.client.debug_headers_enabled() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "Header capture is disabled. Set CODA_DEBUG_HEADERS=1 and restart the server.",
+            )]));
+        }
+
+        let Some(headers) = self.client.last_response_headers() else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No API responses recorded yet.",
+            )]));
+        };
+
+        let mut output = format!("Last response headers ({}):\n", headers.len());
+        for (name, value) in &headers {
+            let _ = write!(output, "\n{name}: {value}");
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Export this session's recent Coda API calls as JSON (tool, method, path, status, timing) for saving as a trace. Never includes cell values, query strings or tokens."
+    )]
+    async fn export_session_log(
+        &self,
+        Parameters(params): Parameters<ExportSessionLogParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let redact_ids = params.redact_ids.unwrap_or(false);
+        let clear = params.clear.unwrap_or(false);
+        tracing::info!(
+            "export_session_log: redact_ids={}, clear={}",
+            redact_ids,
+            clear
+        );
+
+        let records = if clear {
+            self.client.drain_recent_requests()
+        } else {
+            self.client.recent_requests()
+        };
+        let entries: Vec<SessionLogEntry> = records
+            .iter()
+            .map(|record| SessionLogEntry::from_record(record, redact_ids))
+            .collect();
+        let failed = entries.iter().filter(|e| !e.ok).count();
+
+        let mut summary = format!("Exported {} API calls ({failed} failed)", entries.len());
+        if clear {
+            summary.push_str("; log cleared");
+        }
+        let data = serde_json::json!({ "entries": entries, "cleared": clear });
+        let block = self.render(&entries)?;
+
+        Ok(
+            ToolOutcome::success("export_session_log", summary.clone(), data)
+                .into_result(format!("{summary}\n\n{block}")),
+        )
+    }
+}
+
+impl CodaMcpServer {
+    /// Gate for destructive tools under `CODA_SAFE_MODE`.
+    ///
+    /// Returns `None` when the operation may proceed: safe mode is off, or
+    /// `confirm` equals the target's ID or the `name` fetched from
+    /// `target_path`. Otherwise returns the refusal to send back.
+    async fn check_confirmation(
+        &self,
+        operation: &'static str,
+        target_id: &str,
+        target_path: &str,
+        confirm: Option<&str>,
+    ) -> Option<CallToolResult> {
+        if !self.config.safe_mode {
+            return None;
+        }
+
+        let message = match confirm {
+            None => format!(
+                "Safe mode is on: {operation} requires `confirm` set to the target's ID ('{target_id}') or name."
+            ),
+            Some(confirm) if confirm == target_id => return None,
+            Some(confirm) => {
+                let target: Result<serde_json::Value, CodaError> =
+                    self.client.get(target_path).await;
+                match target {
+                    Ok(target) if target["name"].as_str() == Some(confirm) => return None,
+                    Ok(_) => format!(
+                        "Safe mode is on: `confirm` value '{confirm}' does not match the ID or name of '{target_id}'. Nothing was deleted."
+                    ),
+                    Err(e) => format!(
+                        "Safe mode is on: could not look up '{target_id}' to check `confirm`: {e}"
+                    ),
+                }
+            }
+        };
+
+        tracing::warn!("{operation} refused by safe mode: target={target_id}");
+        Some(ToolOutcome::failure(operation, message.clone()).into_result(message))
+    }
+
+    /// Validated `to_file` path, or why it can't be used
+    fn export_path(&self, requested: &str) -> Result<PathBuf, String> {
+        let dir = self
+            .config
+            .export_dir
+            .as_deref()
+            .ok_or_else(|| "`to_file` requires CODA_EXPORT_DIR to be set.".to_string())?;
+        resolve_export_path(dir, requested)
+    }
+
+    /// Export format and `to_file` path of a page export, or the failure to
+    /// return when either is invalid
+    fn page_export_target<'a>(
+        &self,
+        operation: &'static str,
+        params: &'a GetPageParams,
+    ) -> Result<(&'a str, Option<PathBuf>), CallToolResult> {
+        let refuse =
+            |message: String| ToolOutcome::failure(operation, message.clone()).into_result(message);
+        let format = params.export_format().map_err(refuse)?;
+        let to_file = params
+            .to_file
+            .as_deref()
+            .map(|p| self.export_path(p))
+            .transpose()
+            .map_err(refuse)?;
+        Ok((format, to_file))
+    }
+
+    /// Render tool data as a fenced block in the configured `CODA_OUTPUT_FORMAT`,
+    /// recording it for `CODA_STRUCTURED_OUTPUT`
+    fn render<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<String, McpError> {
+        structured::record(value);
+        self.fence(value)
+    }
+
+    /// Length of `value`'s rendered block, for output budgets; unlike `render`
+    /// it is not recorded as tool data
+    fn rendered_len<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<usize, McpError> {
+        self.fence(value).map(|block| block.len())
+    }
+
+    fn fence<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<String, McpError> {
+        self.config
+            .output_format
+            .render(value)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))
+    }
+
+    /// Columns to redact: the configured `CODA_REDACT_COLUMNS` plus any per-call additions
+    fn redacted_columns(&self, extra: Option<&[String]>) -> Vec<String> {
+        let mut columns = self.config.redact_columns.clone();
+        columns.extend(extra.unwrap_or_default().iter().cloned());
+        columns
+    }
+
+    /// `useColumnNames` and `valueFormat` for a row read, from the call or
+    /// else the configured defaults. Column names stay on while redacting,
+    /// which matches cells by name, and `reference_ids` needs rich values.
+    fn row_read_query(
+        &self,
+        use_column_names: Option<bool>,
+        value_format: Option<ValueFormat>,
+        redacting: bool,
+        reference_ids: bool,
+    ) -> String {
+        let use_column_names = self.uses_column_names(use_column_names, redacting);
+        let mut query = format!("useColumnNames={use_column_names}");
+        let value_format = if reference_ids {
+            Some(ValueFormat::Rich)
+        } else {
+            value_format.or(self.config.default_value_format)
+        };
+        if let Some(format) = value_format {
+            let _ = write!(query, "&valueFormat={}", format.as_str());
+        }
+        query
+    }
+
+    /// Whether a row read keys values by column name, as in [`Self::row_read_query`]
+    fn uses_column_names(&self, use_column_names: Option<bool>, redacting: bool) -> bool {
+        redacting || use_column_names.unwrap_or(self.config.default_use_column_names)
+    }
+
+    /// Like [`Self::row_read_query`], but reads by column ID when `column_keys`
+    /// will re-key the values afterwards
+    fn keyed_row_read_query(
+        &self,
+        column_keys: Option<&ColumnKeys>,
+        use_column_names: Option<bool>,
+        value_format: Option<ValueFormat>,
+        redacting: bool,
+        reference_ids: bool,
+    ) -> String {
+        match column_keys {
+            Some(_) => self.row_read_query(Some(false), value_format, false, reference_ids),
+            None => self.row_read_query(use_column_names, value_format, redacting, reference_ids),
+        }
+    }
+
+    /// Keys for a name-keyed row read of a table whose columns share display
+    /// names, which `useColumnNames=true` would collapse into one value.
+    /// Columns are only listed for an explicit `use_column_names: true`;
+    /// otherwise cached columns are checked, so a plain read costs no extra
+    /// request. `None` for ID-keyed reads, unique names, or when the columns
+    /// aren't known, in which case the read goes ahead by name as before.
+    async fn colliding_column_keys(
+        &self,
+        doc_id: &str,
+        table_id: &str,
+        use_column_names: Option<bool>,
+        redacting: bool,
+    ) -> Option<ColumnKeys> {
+        if !self.uses_column_names(use_column_names, redacting) {
+            return None;
+        }
+        if let Some((columns, _)) = self.cached_columns(doc_id, table_id) {
+            return ColumnKeys::disambiguate(&columns.items);
+        }
+        if use_column_names != Some(true) {
+            return None;
+        }
+        match self.table_columns(doc_id, table_id).await {
+            Ok(columns) => ColumnKeys::disambiguate(&columns),
+            Err(e) => {
+                tracing::warn!(
+                    "Could not list columns of {}/{} to check for shared names: {}",
+                    doc_id,
+                    table_id,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Up to `max_rows` rows from `rows_path` (which already has a query
+    /// string), in pages of at most `page_size`. The flag is true when more
+    /// rows remained.
+    async fn fetch_rows(
+        &self,
+        rows_path: &str,
+        max_rows: usize,
+        page_size: u32,
+    ) -> Result<(Vec<Row>, bool), CodaError> {
+        let mut rows: Vec<Row> = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let remaining = u32::try_from(max_rows - rows.len()).unwrap_or(u32::MAX);
+            let path = paged_path(
+                rows_path,
+                Some(remaining.min(page_size)),
+                page_token.as_deref(),
+            );
+            let page: RowList = self.client.get(&path).await?;
+            rows.extend(page.items);
+            rows.truncate(max_rows);
+            match page.next_page_token {
+                Some(token) if rows.len() < max_rows => page_token = Some(token),
+                next => return Ok((rows, next.is_some())),
+            }
+        }
+    }
+
+    fn cached_workspace(&self) -> Option<WorkspaceReference> {
+        self.workspace.lock().ok().and_then(|w| w.clone())
+    }
+
+    /// The token's workspace, looked up through `/whoami` on first use (or
+    /// when `refresh` is set) and cached
+    async fn workspace(&self, refresh: bool) -> Result<WorkspaceReference, CodaError> {
+        if let Some(workspace) = self.cached_workspace().filter(|_| !refresh) {
+            return Ok(workspace);
+        }
+        let user: User = self.client.get("/whoami").await?;
+        let workspace = user.workspace.ok_or(CodaError::NoWorkspace {
+            login_id: user.login_id,
+        })?;
+        if let Ok(mut cached) = self.workspace.lock() {
+            *cached = Some(workspace.clone());
+        }
+        Ok(workspace)
+    }
+
+    /// A table's columns, from the doc context cache or the column cache
+    /// when either holds them
+    async fn table_columns(&self, doc_id: &str, table_id: &str) -> Result<Vec<Column>, CodaError> {
+        if let Some((columns, _)) = self.cached_columns(doc_id, table_id) {
+            return Ok(columns.items);
+        }
+        let path = format!("/docs/{doc_id}/tables/{table_id}/columns");
+        let columns: ColumnList = self.client.get(&path).await?;
+        self.cache_columns(doc_id, table_id, &columns);
+        Ok(columns.items)
+    }
+
+    /// A table's complete column list from the doc context cache, else the
+    /// column cache, with the name of the cache it came from
+    fn cached_columns(&self, doc_id: &str, table_id: &str) -> Option<(ColumnList, &'static str)> {
+        let (items, source) = self
+            .context_cache
+            .get(doc_id)
+            .and_then(|context| context.columns_of(table_id).map(<[Column]>::to_vec))
+            .map(|items| (items, "doc context cache"))
+            .or_else(|| {
+                self.column_cache
+                    .get(doc_id, table_id)
+                    .map(|columns| (columns.to_vec(), "column cache"))
+            })?;
+        let columns = ColumnList {
+            items,
+            next_page_token: None,
+        };
+        Some((columns, source))
+    }
+
+    /// Keep a fetched column list for `CODA_CACHE_TTL_SECS`, if it is complete
+    fn cache_columns(&self, doc_id: &str, table_id: &str, columns: &ColumnList) {
+        if columns.next_page_token.is_none() {
+            self.column_cache
+                .insert(doc_id, table_id, columns.items.clone());
+        }
+    }
+
+    /// Coerce the cells of a row write when `coerce` is set
+    async fn coerce_row_cells(
+        &self,
+        doc_id: &str,
+        table_id: &str,
+        coerce: Option<bool>,
+        cells: &mut HashMap<String, serde_json::Value>,
+    ) -> Result<Vec<Coercion>, McpError> {
+        if !coerce.unwrap_or(false) {
+            return Ok(Vec::new());
+        }
+        let columns = self
+            .table_columns(doc_id, table_id)
+            .await
+            .map_err(CodaError::into_mcp_error)?;
+        Ok(coerce_cells(&columns, cells))
+    }
+
+    /// Fetch per-column detail for calculated columns whose formula the list
+    /// endpoint omitted. At most `COLUMN_FORMULA_MAX_FETCHES` columns are fetched.
+    async fn fill_column_formulas(
+        &self,
+        doc_id: &str,
+        table_id: &str,
+        columns: &mut [Column],
+    ) -> Result<(), CodaError> {
+        let missing: Vec<usize> = columns
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.calculated == Some(true) && c.formula.is_none())
+            .map(|(i, _)| i)
+            .take(COLUMN_FORMULA_MAX_FETCHES)
+            .collect();
+
+        let details: Vec<(usize, Column)> = stream::iter(missing)
+            .map(|i| {
+                let path = format!("/docs/{doc_id}/tables/{table_id}/columns/{}", columns[i].id);
+                async move { Ok::<_, CodaError>((i, self.client.get(&path).await?)) }
+            })
+            .buffered(COLUMN_FORMULA_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        for (i, detail) in details {
+            columns[i].formula = detail.formula;
+        }
+        Ok(())
+    }
+
+    /// A named formula with its current value
+    async fn fetch_formula(&self, doc_id: &str, formula_id: &str) -> Result<Formula, McpError> {
+        let path = format!("/docs/{doc_id}/formulas/{formula_id}");
+        self.client
+            .get(&path)
+            .await
+            .map_err(CodaError::into_mcp_error)
+    }
+
+    /// Wait until a newly created doc is readable.
+    ///
+    /// Doc creation is asynchronous, so the doc can 404 for a short while.
+    async fn wait_ready(&self, doc_id: &str) -> Result<Doc, CodaError> {
+        self.get_when_ready(&format!("/docs/{doc_id}")).await
+    }
+
+    /// Every table and view in a doc, following page tokens until none is
+    /// returned or the API repeats one
+    async fn list_all_tables(&self, doc_id: &str) -> Result<Vec<Table>, CodaError> {
+        let mut tables = Vec::new();
+        let mut seen_tokens = HashSet::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let path = format!(
+                "/docs/{doc_id}/tables{}",
+                page_query(Some(TABLE_COUNT_PAGE_SIZE), page_token.as_deref())
+            );
+            let page: TableList = self.client.get(&path).await?;
+            tables.extend(page.items);
+            match page.next_page_token {
+                Some(token) if seen_tokens.insert(token.clone()) => page_token = Some(token),
+                _ => return Ok(tables),
+            }
+        }
+    }
+
+    /// Number of tables and views in a doc, following page tokens
+    async fn count_tables(&self, doc_id: &str) -> Result<usize, CodaError> {
+        Ok(self.list_all_tables(doc_id).await?.len())
+    }
+
+    /// Check a mutation's status, then poll up to `max_polls` more times while
+    /// it is pending. Returns the last status and the number of polls made.
+    async fn poll_mutation_status(
+        &self,
+        request_id: &str,
+        max_polls: u32,
+        interval_secs: u64,
+    ) -> Result<(MutationStatus, u32), CodaError> {
+        let path = format!("/mutationStatus/{}", urlencoding::encode(request_id));
+        let mut status: MutationStatus = self.client.get(&path).await?;
+        let mut polls = 0;
+        while !status.completed && polls < max_polls {
+            tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+            polls += 1;
+            status = self.client.get(&path).await?;
+        }
+        Ok((status, polls))
+    }
+
+    /// GET a newly created resource, retrying while it still 404s
+    async fn get_when_ready<T: serde::de::DeserializeOwned + serde::Serialize>(
+        &self,
+        path: &str,
+    ) -> Result<T, CodaError> {
+        let mut attempt = 1;
+        loop {
+            match self.client.get(path).await {
+                Err(CodaError::NotFound) if attempt < MAX_POLL_ATTEMPTS => {
+                    tracing::info!(
+                        "{} not ready, attempt {}/{}",
+                        path,
+                        attempt,
+                        MAX_POLL_ATTEMPTS
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn post_page(
+        &self,
+        doc_id: &str,
+        request: &CreatePageRequest,
+    ) -> Result<PageMutationResponse, CodaError> {
+        let path = format!("/docs/{doc_id}/pages");
+        tracing::info!(
+            "create_page: doc_id={}, name={}, parent={:?}",
+            doc_id,
+            request.name,
+            request.parent_page_id
+        );
+        self.client.post(&path, request).await
+    }
+
+    /// Export a page as `output_format` (`html` or `markdown`) and download
+    /// the result.
+    ///
+    /// Coda exports are asynchronous: the export is initiated, polled until
+    /// complete, then downloaded from a temporary link.
+    ///
+    /// With `retry_on_failure`, an export that fails with a message matching
+    /// `CODA_EXPORT_TRANSIENT_ERRORS` is started afresh, up to
+    /// `CODA_EXPORT_FAILURE_RETRIES` times.
+    async fn export_page_content(
+        &self,
+        doc_id: &str,
+        page_id: &str,
+        output_format: &str,
+        retry_on_failure: bool,
+    ) -> Result<ExportOutcome, McpError> {
+        let export_path = format!("/docs/{doc_id}/pages/{page_id}/export");
+        let export_request = ExportRequest {
+            output_format: output_format.to_string(),
+        };
+
+        let mut retries = 0;
+        loop {
+            // Step 1: Initiate export
+            tracing::info!("Initiating page export: POST {}", export_path);
+            let export: ExportResponse = self
+                .client
+                .post(&export_path, &export_request)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to initiate export: {}", e);
+                    e.into_mcp_error()
+                })?;
+            tracing::info!(
+                "Export initiated: id={}, status={}",
+                export.id,
+                export.status
+            );
+
+            let message = match self.poll_export_status(doc_id, page_id, &export.id).await? {
+                Ok(outcome) => return Ok(outcome),
+                Err(message) => message,
+            };
+            if !retry_on_failure
+                || retries >= self.config.export_failure_retries
+                || !self.config.is_transient_export_error(&message)
+            {
+                return Err(export_failed_error(&message, retries));
+            }
+            retries += 1;
+            tracing::warn!(
+                "Export {} failed transiently ({}), retrying {}/{}",
+                export.id,
+                message,
+                retries,
+                self.config.export_failure_retries
+            );
+        }
+    }
+
+    /// Poll an initiated export until complete, then download it.
+    ///
+    /// Running out of attempts is not an error: the export keeps going on
+    /// Coda's side, so its ID is handed back for `resume_page_export`.
+    async fn poll_export(
+        &self,
+        doc_id: &str,
+        page_id: &str,
+        export_id: &str,
+    ) -> Result<ExportOutcome, McpError> {
+        self.poll_export_status(doc_id, page_id, export_id)
+            .await?
+            .map_err(|message| export_failed_error(&message, 0))
+    }
+
+    /// `poll_export`, with an export that reports `failed` returned as the
+    /// inner `Err` holding its error message, so callers can decide whether
+    /// to start it again
+    async fn poll_export_status(
+        &self,
+        doc_id: &str,
+        page_id: &str,
+        export_id: &str,
+    ) -> Result<Result<ExportOutcome, String>, McpError> {
+        // Step 2: Poll for completion (CODA_EXPORT_MAX_ATTEMPTS, CODA_EXPORT_POLL_SECS apart)
+        let status_path = format!("/docs/{doc_id}/pages/{page_id}/export/{export_id}");
+        let max_attempts = self.config.export_max_attempts;
+
+        for attempt in 1..=max_attempts {
+            tracing::info!(
+                "Polling export status, attempt {}/{}: GET {}",
+                attempt,
+                max_attempts,
+                status_path
+            );
+
+            let status: ExportResponse = self.client.get(&status_path).await.map_err(|e| {
+                tracing::error!("Failed to poll export status: {}", e);
+                e.into_mcp_error()
+            })?;
+            tracing::info!("Export status: {}", status.status);
+
+            match status.status.as_str() {
+                "complete" => {
+                    // Step 3: Download content from temporary link
+                    let download_link = status.download_link.ok_or_else(|| {
+                        McpError::internal_error(
+                            "Export complete but no download link provided".to_string(),
+                            None,
+                        )
+                    })?;
+
+                    let download = self.download_export(&status_path, &download_link).await?;
+                    tracing::info!("Downloaded {} bytes", download.byte_len);
+
+                    return Ok(Ok(ExportOutcome::Complete(download)));
+                }
+                "failed" => {
+                    return Ok(Err(status
+                        .error
+                        .unwrap_or_else(|| "Unknown error".to_string())));
+                }
+                _ => {
+                    // Still processing, wait and retry
+                    tokio::time::sleep(Duration::from_secs(self.config.export_poll_secs)).await;
+                }
+            }
+        }
+
+        tracing::warn!("Export {} still in progress after polling", export_id);
+        Ok(Ok(ExportOutcome::TimedOut {
+            export_id: export_id.to_string(),
+            status_path,
+        }))
+    }
+
+    /// Download a completed export.
+    ///
+    /// Signed download links are short-lived, so a 403/404 from the storage
+    /// host is treated as expiry: the export status is re-polled once for a
+    /// fresh link and the download retried.
+    async fn download_export(
+        &self,
+        status_path: &str,
+        download_link: &str,
+    ) -> Result<Download, McpError> {
+        tracing::info!("Export complete, downloading from: {}", download_link);
+        let download = |link: &str| {
+            let link = link.to_string();
+            let reporter = progress::current().map(|p| p.byte_reporter("Downloading export"));
+            async move {
+                match reporter {
+                    Some(reporter) => {
+                        self.client
+                            .download_raw_with_progress(&link, reporter)
+                            .await
+                    }
+                    None => self.client.download_raw(&link).await,
+                }
+            }
+        };
+        let err = match download(download_link).await {
+            Ok(download) => return Ok(download),
+            Err(e) => e,
+        };
+        if !matches!(
+            err,
+            CodaError::Api {
+                status: 403 | 404,
+                ..
+            }
+        ) {
+            tracing::error!("Failed to download export: {}", err);
+            return Err(err.into_mcp_error());
+        }
+
+        tracing::warn!(
+            "Download link looks expired ({}), re-polling for a fresh one",
+            err
+        );
+        let status: ExportResponse = self.client.get(status_path).await.map_err(|e| {
+            tracing::error!("Failed to re-poll export status: {}", e);
+            e.into_mcp_error()
+        })?;
+        let Some(fresh_link) = status.download_link.filter(|_| status.status == "complete") else {
+            return Err(err.into_mcp_error());
+        };
+
+        tracing::info!("Retrying download from fresh link: {}", fresh_link);
+        download(&fresh_link).await.map_err(|e| {
+            tracing::error!("Failed to download export: {}", e);
+            e.into_mcp_error()
+        })
+    }
+}
+
+/// List up to `max_docs` docs visible to `client`'s token, following page
+/// tokens. The flag is true when more docs remained unscanned.
+async fn scan_docs(
+    client: &CodaClient,
+    query: Option<&str>,
+    max_docs: usize,
+) -> Result<(Vec<Doc>, bool), CodaError> {
+    let docs_path = match query {
+        Some(query) => format!("/docs?query={}", urlencoding::encode(query)),
+        None => "/docs".to_string(),
+    };
+
+    let mut scanned: Vec<Doc> = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let path = paged_path(
+            &docs_path,
+            Some(ACTIVE_DOCS_PAGE_SIZE),
+            page_token.as_deref(),
+        );
+        let page: DocList = client.get(&path).await?;
+        scanned.extend(page.items);
+        scanned.truncate(max_docs);
+        match page.next_page_token {
+            Some(token) if scanned.len() < max_docs => page_token = Some(token),
+            next => return Ok((scanned, next.is_some())),
+        }
+    }
+}
+
+/// Query string for a paginated list request, empty if neither is set
+fn page_query(limit: Option<u32>, page_token: Option<&str>) -> String {
+    let mut query = Vec::new();
+    if let Some(limit) = limit {
+        query.push(format!("limit={}", limit.min(1000)));
+    }
+    if let Some(token) = page_token {
+        query.push(format!("pageToken={}", urlencoding::encode(token)));
+    }
+    if query.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", query.join("&"))
+    }
+}
+
+/// `path` with the `page_query` parameters appended to any query it already has
+fn paged_path(path: &str, limit: Option<u32>, page_token: Option<&str>) -> String {
+    let query = page_query(limit, page_token);
+    match query.strip_prefix('?') {
+        Some(params) if path.contains('?') => format!("{path}&{params}"),
+        _ => format!("{path}{query}"),
+    }
+}
+
+/// List the value conversions of a coerced row write
+fn push_coercions(output: &mut String, coercions: &[Coercion]) {
+    if coercions.is_empty() {
+        return;
+    }
+    output.push_str("\nCoerced values:");
+    for c in coercions {
+        let _ = write!(output, "\n- {}: {} -> {}", c.column, c.from, c.to);
+    }
+}
+
+/// Append the token for the next page of a list, if there is one
+fn push_next_page(output: &mut String, next_page_token: Option<&str>) {
+    if let Some(token) = next_page_token {
+        let _ = write!(
+            output,
+            "\n\nMore results available. Next page token: {token}"
+        );
+    }
+}
+
+/// Restrict the router to `enabled` (when non-empty), then remove `disabled`.
+///
+/// Returns the configured names that don't match any registered tool.
+fn filter_tools<S>(
+    router: &mut ToolRouter<S>,
+    enabled: &[String],
+    disabled: &[String],
+) -> Vec<String>
+where
+    S: Send + Sync + 'static,
+{
+    let known: Vec<String> = router
+        .list_all()
+        .into_iter()
+        .map(|tool| tool.name.into_owned())
+        .collect();
+
+    let unknown = enabled
+        .iter()
+        .chain(disabled)
+        .filter(|name| !known.contains(name))
+        .cloned()
+        .collect();
+
+    if !enabled.is_empty() {
+        for name in known.iter().filter(|name| !enabled.contains(name)) {
+            router.remove_route(name);
+        }
+    }
+    for name in disabled {
+        router.remove_route(name);
+    }
+
+    unknown
+}
+
+/// Text output for an exported page, optionally led by the download checksum.
+/// When the content was written to `written`, the path replaces the content.
+fn format_page(
+    name: &str,
+    download: &Download,
+    include_checksum: bool,
+    written: Option<&Path>,
+) -> String {
+    let mut output = format!("Page: {name}");
+    if include_checksum {
+        let _ = write!(
+            output,
+            "\nBytes: {}\nSHA-256: {}",
+            download.byte_len, download.sha256
+        );
+    }
+    match written {
+        Some(path) => {
+            let _ = write!(
+                output,
+                "\n\nWrote {} bytes to {}",
+                download.content.len(),
+                path.display()
+            );
+        }
+        None => {
+            let _ = write!(output, "\n\nContent:\n{}", download.content);
+        }
+    }
+    output
+}
+
+/// `[{column, value}]` cells for a row mutation body
+fn cells_json(cells: &HashMap<String, serde_json::Value>) -> Vec<serde_json::Value> {
+    cells
+        .iter()
+        .map(|(col, val)| {
+            serde_json::json!({
+                "column": col,
+                "value": val
+            })
+        })
+        .collect()
+}
+
+/// Error for an export that reported `failed`, noting any fresh exports tried
+fn export_failed_error(message: &str, retries: u32) -> McpError {
+    let message = match retries {
+        0 => format!("Export failed: {message}"),
+        1 => format!("Export failed: {message} (after 1 retry)"),
+        n => format!("Export failed: {message} (after {n} retries)"),
+    };
+    McpError::internal_error(message, None)
+}
+
+/// Apply a page transform to downloaded content in place. Returns the
+/// untransformed export when `include_raw` is set and a transform was applied.
+/// The checksum fields keep describing the raw download.
+fn apply_page_transform(
+    download: &mut Download,
+    transform: Option<PageTransform>,
+    include_raw: Option<bool>,
+) -> Option<String> {
+    let transform = transform?;
+    let transformed = transform.apply(&download.content);
+    let raw = std::mem::replace(&mut download.content, transformed);
+    include_raw.unwrap_or(false).then_some(raw)
+}
+
+/// Write exported content to a path from `resolve_export_path`.
+///
+/// The content goes to a fresh sibling file first and is then renamed over
+/// `path`, so a symlink created at either path after validation is replaced
+/// rather than followed.
+fn write_export(path: &Path, content: &str) -> Result<(), McpError> {
+    let failed = |e: std::io::Error| {
+        McpError::internal_error(format!("Failed to write {}: {e}", path.display()), None)
+    };
+    let mut staged = path.as_os_str().to_os_string();
+    staged.push(format!(".{}.tmp", std::process::id()));
+    let staged = PathBuf::from(staged);
+
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&staged)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .and_then(|()| std::fs::rename(&staged, path));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&staged);
+        return Err(failed(e));
+    }
+    tracing::info!("Wrote {} bytes to {}", content.len(), path.display());
+    Ok(())
+}
+
+/// Resolve a `to_file` target inside `export_dir`, rejecting anything that
+/// could land outside it: `..` components, absolute paths elsewhere, and
+/// symlinks in the parent chain or at the target itself.
+fn resolve_export_path(export_dir: &Path, requested: &str) -> Result<PathBuf, String> {
+    let root = export_dir.canonicalize().map_err(|e| {
+        format!(
+            "CODA_EXPORT_DIR '{}' is not accessible: {e}",
+            export_dir.display()
+        )
+    })?;
+
+    let requested = Path::new(requested);
+    if requested
+        .components()
+        .any(|c| matches!(c, Component::ParentDir))
+    {
+        return Err(format!(
+            "`to_file` may not contain `..`: {}",
+            requested.display()
+        ));
+    }
+
+    let target = if requested.is_absolute() {
+        requested.to_path_buf()
+    } else {
+        root.join(requested)
+    };
+    let file_name = target
+        .file_name()
+        .ok_or_else(|| "`to_file` must name a file".to_string())?;
+    let parent = target
+        .parent()
+        .and_then(|p| p.canonicalize().ok())
+        .ok_or_else(|| {
+            format!(
+                "Directory for `to_file` does not exist: {}",
+                target.display()
+            )
+        })?;
+    if !parent.starts_with(&root) {
+        return Err(format!(
+            "`to_file` must be inside CODA_EXPORT_DIR ({})",
+            root.display()
+        ));
+    }
+
+    let resolved = parent.join(file_name);
+    if resolved
+        .symlink_metadata()
+        .is_ok_and(|m| m.file_type().is_symlink())
+    {
+        return Err(format!(
+            "`to_file` may not be a symlink: {}",
+            resolved.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Result of polling a page export
+enum ExportOutcome {
+    Complete(Download),
+    /// Polling gave up while the export was still running
+    TimedOut {
+        export_id: String,
+        status_path: String,
+    },
+}
+
+impl ExportOutcome {
+    /// Structured error result for a timed-out export, carrying what
+    /// `resume_page_export` needs to pick it back up
+    fn timeout_result(export_id: &str, status_path: &str, config: &Config) -> CallToolResult {
+        CallToolResult::structured_error(serde_json::json!({
+            "error": format!(
+                "Export timed out after {} seconds ({} polls, {}s apart)",
+                config.export_timeout_secs(),
+                config.export_max_attempts,
+                config.export_poll_secs
+            ),
+            "export_id": export_id,
+            "status_path": status_path,
+            "hint": "The export is still running. Call resume_page_export with this export_id to keep polling, or raise CODA_EXPORT_MAX_ATTEMPTS or CODA_EXPORT_POLL_SECS for large pages.",
+        }))
+    }
+}
+
+impl ServerHandler for CodaMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::LATEST,
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation::from_build_env(),
+            instructions: Some(
+                "Coda.io MCP Server - Interact with Coda documents, tables, and rows. \
+                 Requires CODA_API_TOKEN environment variable."
+                    .into(),
+            ),
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool = request.name.to_string();
+        let progress = Progress::from_context(&context);
+        let tcc = ToolCallContext::new(self, request, context);
+        // Attribute the API calls made while handling this tool in the recent-requests buffer
+        let call = PROGRESS.scope(
+            progress,
+            CURRENT_TOOL.scope(tool, self.tool_router.call(tcc)),
+        );
+        let call = async {
+            if !self.config.structured_output {
+                return call.await;
+            }
+            let blocks = DataBlocks::default();
+            let mut result = STRUCTURED.scope(blocks.clone(), call).await?;
+            blocks.append_to(&mut result);
+            Ok(result)
+        };
+        if !self.config.echo_requests {
+            return call.await;
+        }
+        let echo = EchoLog::default();
+        let mut result = ECHO.scope(echo.clone(), call).await?;
+        echo.append_to(&mut result);
+        Ok(result)
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            tools: self.tool_router.list_all(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Initialize logging to stderr (MCP uses stdout for JSON-RPC)
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+
+    tracing::info!("Starting coda-mcp server v{}", env!("CARGO_PKG_VERSION"));
+
+    // Load configuration
+    let config = Config::from_env()?;
+    tracing::info!("Configuration loaded, base URL: {}", config.base_url);
+
+    // Create HTTP client
+    let client = Arc::new(CodaClient::new(&config));
+
+    // Create and run MCP server
+    let server = CodaMcpServer::new(client, &config);
+    let service = server.serve(stdio()).await?;
+
+    tracing::info!("Server running, waiting for requests...");
+    service.waiting().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+    use models::{
+        ContentInsert, InsertionMode, PublishMode, RowFilter, RowSort, RowSource, TableAlias,
+        TableType,
+    };
+    use render::OutputFormat;
+    use wiremock::matchers::{body_json, body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup() -> (CodaMcpServer, MockServer) {
+        setup_with_config(Config::default()).await
+    }
+
+    async fn setup_with_config(config: Config) -> (CodaMcpServer, MockServer) {
+        let mock_server = MockServer::start().await;
+        let client = Arc::new(CodaClient::new_with_base_url(
+            "test_token",
+            &mock_server.uri(),
+        ));
+        let server = CodaMcpServer::new(client, &config);
+        (server, mock_server)
+    }
+
+    // === Server Info ===
+
+    #[test]
+    fn test_get_info() {
+        let mock_client = CodaClient::new_with_base_url("tok", "http://localhost:0");
+        let server = CodaMcpServer::new(Arc::new(mock_client), &Config::default());
+        let info = server.get_info();
+        // from_build_env() uses the rmcp crate name, not our package name
+        assert!(!info.server_info.name.is_empty());
+        assert!(!info.server_info.version.is_empty());
+        assert!(info.instructions.is_some());
+        assert!(info.instructions.unwrap().contains("Coda.io MCP Server"));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tool_removed_from_router() {
+        let (server, _mock_server) = setup_with_config(Config {
+            disabled_tools: vec!["delete_doc".to_string()],
+            ..Config::default()
+        })
+        .await;
+
+        assert!(!server.tool_router.has_route("delete_doc"));
+        assert!(server.tool_router.has_route("list_docs"));
+    }
+
+    #[tokio::test]
+    async fn test_enabled_tools_restrict_router() {
+        let (server, _mock_server) = setup_with_config(Config {
+            enabled_tools: vec!["list_docs".to_string(), "get_doc".to_string()],
+            disabled_tools: vec!["get_doc".to_string()],
+            ..Config::default()
+        })
+        .await;
+
+        let names: Vec<_> = server
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        assert_eq!(names, vec!["list_docs"]);
+    }
+
+    #[test]
+    fn test_filter_tools_reports_unknown_names() {
+        let mut router = CodaMcpServer::tool_router();
+        let unknown = filter_tools(
+            &mut router,
+            &[],
+            &["delete_doc".to_string(), "drop_everything".to_string()],
+        );
+
+        assert_eq!(unknown, vec!["drop_everything".to_string()]);
+        assert!(!router.has_route("delete_doc"));
+    }
+
+    // === Document Tools ===
+
+    #[tokio::test]
+    async fn test_list_docs_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .and(query_param("limit", "50"))
+            .and(header("Authorization", "Bearer test_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "doc1", "name": "Doc One"},
+                    {"id": "doc2", "name": "Doc Two"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs(Parameters(ListDocsParams {
+                limit: None,
+                query: None,
+                page_token: None,
+                dedupe: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 documents"));
+        assert!(text.contains("Doc One"));
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_with_query() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .and(query_param("query", "project"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "doc1", "name": "My Project"}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs(Parameters(ListDocsParams {
+                limit: Some(10),
+                query: Some("project".to_string()),
+                page_token: None,
+                dedupe: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 documents"));
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_pagination() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .and(query_param("limit", "2"))
+            .and(query_param("pageToken", "tok/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "doc3", "name": "Three"},
+                    {"id": "doc4", "name": "Four"}
+                ],
+                "nextPageToken": "tok2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs(Parameters(ListDocsParams {
+                limit: Some(2),
+                query: None,
+                page_token: Some("tok/1".to_string()),
+                dedupe: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 documents"));
+        assert!(text.ends_with("Next page token: tok2"));
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_active_since_absolute_timestamp() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .and(query_param("pageToken", "p2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "doc3", "name": "Recent", "updatedAt": "2024-03-01T09:00:00Z"},
+                    {"id": "doc4", "name": "Undated"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .and(query_param("limit", "100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "doc1", "name": "Older", "updatedAt": "2024-02-15T10:00:00.000Z"},
+                    {"id": "doc2", "name": "Stale", "updatedAt": "2024-01-10T10:00:00Z"}
+                ],
+                "nextPageToken": "p2"
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs_active_since(Parameters(ListDocsActiveSinceParams {
+                since: "2024-02-01T00:00:00Z".to_string(),
+                query: None,
+                max_docs: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(
+            text.contains("Found 2 documents updated since 2024-02-01T00:00:00Z (of 4 scanned)")
+        );
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        let ids: Vec<&str> = data["docs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["doc3", "doc1"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_active_since_relative_window() {
+        let (server, mock_server) = setup().await;
+        let days_ago = |days: i64| (Utc::now() - TimeDelta::days(days)).to_rfc3339();
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "doc1", "name": "Last month", "updatedAt": days_ago(30)},
+                    {"id": "doc2", "name": "This week", "updatedAt": days_ago(2)},
+                    {"id": "doc3", "name": "Yesterday", "updatedAt": days_ago(1)}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs_active_since(Parameters(ListDocsActiveSinceParams {
+                since: "7d".to_string(),
+                query: None,
+                max_docs: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 documents updated since"));
+        let yesterday = text.find("- Yesterday (doc3)").unwrap();
+        let this_week = text.find("- This week (doc2)").unwrap();
+        assert!(yesterday < this_week);
+        assert!(!text.contains("- Last month"));
+    }
+
+    #[tokio::test]
+    async fn test_list_all_docs_merges_accounts() {
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            api_token: "tok-a".to_string(),
+            api_tokens: vec!["tok-b".to_string(), "tok-c".to_string()],
+            base_url: mock_server.uri(),
+            ..Config::default()
+        };
+        let server = CodaMcpServer::new(Arc::new(CodaClient::new(&config)), &config);
+
+        for (token, login, ids) in [
+            ("tok-a", "ada@example.com", vec!["d1", "d2"]),
+            ("tok-b", "bob@example.com", vec!["d2", "d3"]),
+        ] {
+            let auth = format!("Bearer {token}");
+            Mock::given(method("GET"))
+                .and(path("/whoami"))
+                .and(header("Authorization", auth.as_str()))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({"name": login, "loginId": login})),
+                )
+                .mount(&mock_server)
+                .await;
+            let items: Vec<serde_json::Value> = ids
+                .iter()
+                .map(|id| serde_json::json!({"id": id, "name": format!("Doc {id}")}))
+                .collect();
+            Mock::given(method("GET"))
+                .and(path("/docs"))
+                .and(header("Authorization", auth.as_str()))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({"items": items})),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        // The third token is revoked
+        Mock::given(method("GET"))
+            .and(header("Authorization", "Bearer tok-c"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_all_docs(Parameters(ListAllDocsParams {
+                query: None,
+                max_docs_per_account: None,
+            }))
+            .await
+            .unwrap();
+
+        assert!(!result.is_error.unwrap_or(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 3 documents across 2 accounts"));
+        assert!(
+            text.contains("- Doc d2 (d2): token 1 (ada@example.com), token 2 (bob@example.com)")
+        );
+        assert!(text.contains("- Doc d3 (d3): token 2 (bob@example.com)"));
+
+        let envelope = result.structured_content.unwrap();
+        let rendered = envelope.to_string();
+        assert!(!text.contains("tok-") && !rendered.contains("tok-"));
+        let docs = envelope["data"]["docs"].as_array().unwrap();
+        let ids: Vec<&str> = docs.iter().map(|d| d["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["d1", "d2", "d3"]);
+        assert_eq!(docs[1]["accounts"].as_array().unwrap().len(), 2);
+        let failed = &envelope["data"]["accounts"]["failed"];
+        assert_eq!(failed[0]["input"]["account"], "token 3");
+        assert_eq!(failed[0]["error_category"], "unauthorized");
+        assert!(envelope["warnings"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("Could not list docs for token 3: Unauthorized"));
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_with_table_counts() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "doc1", "name": "Notes"},
+                    {"id": "doc2", "name": "Tracker"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "grid-1", "name": "Ideas"}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc2/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "grid-1", "name": "Tasks"},
+                    {"id": "grid-2", "name": "People"},
+                    {"id": "table-1", "name": "Open tasks", "type": "view"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs_with_table_counts(Parameters(ListDocsWithTableCountsParams {
+                query: None,
+                max_docs: None,
+                concurrency: None,
+                sort_by_count: Some(true),
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Counted tables in 2 docs"));
+        assert!(
+            text.find("- Tracker (doc2): 3 tables").unwrap()
+                < text.find("- Notes (doc1): 1 tables").unwrap()
+        );
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        let counts: Vec<(&str, u64)> = data["succeeded"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| {
+                (
+                    d["id"].as_str().unwrap(),
+                    d["table_count"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(counts, [("doc2", 3), ("doc1", 1)]);
+        assert!(data["failed"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_with_table_counts_stops_on_repeated_token() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "doc1", "name": "Notes"}]
+            })))
+            .mount(&mock_server)
+            .await;
+        // Tokens cycle between two values, which a last-token check would miss
+        for (token, next) in [(None, "a"), (Some("a"), "b"), (Some("b"), "a")] {
+            let mock = Mock::given(method("GET")).and(path("/docs/doc1/tables"));
+            let mock = match token {
+                Some(token) => mock.and(query_param("pageToken", token)),
+                None => mock.and(wiremock::matchers::query_param_is_missing("pageToken")),
+            };
+            mock.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "grid-1", "name": "Ideas"}],
+                "nextPageToken": next
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        }
+
+        let result = server
+            .list_docs_with_table_counts(Parameters(ListDocsWithTableCountsParams {
+                query: None,
+                max_docs: None,
+                concurrency: None,
+                sort_by_count: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("- Notes (doc1): 3 tables"), "{text}");
+    }
+
+    #[test]
+    fn test_paged_path() {
+        assert_eq!(paged_path("/docs", Some(10), None), "/docs?limit=10");
+        assert_eq!(
+            paged_path("/docs?query=a%20b", Some(10), Some("t 1")),
+            "/docs?query=a%20b&limit=10&pageToken=t%201"
+        );
+        assert_eq!(paged_path("/docs?query=x", None, None), "/docs?query=x");
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_active_since_invalid_window() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server
+            .list_docs_active_since(Parameters(ListDocsActiveSinceParams {
+                since: "last week".to_string(),
+                query: None,
+                max_docs: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Invalid time 'last week'"));
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_limit_capped_at_1000() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .and(query_param("limit", "1000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs(Parameters(ListDocsParams {
+                limit: Some(5000),
+                query: None,
+                page_token: None,
+                dedupe: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 0 documents"));
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_api_error() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_docs(Parameters(ListDocsParams {
+                limit: None,
+                query: None,
+                page_token: None,
+                dedupe: None,
+            }))
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Test Document"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Document: Test Document"));
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_error_includes_status() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+            .mount(&mock_server)
+            .await;
+
+        let err = server
+            .get_doc(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap_err();
+
+        let data = err.data.unwrap();
+        assert_eq!(data["status"], 500);
+        assert_eq!(data["category"], "server");
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_size_warns_over_limit() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Big Doc",
+                "docSize": {
+                    "totalRowCount": 250_000,
+                    "tableAndViewCount": 12,
+                    "pageCount": 4,
+                    "overApiSizeLimit": true
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc_size(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("WARNING: this doc is over the API size limit"));
+        assert!(text.contains("Total rows: 250000"));
+        assert!(text.contains("Tables and views: 12"));
+        assert!(text.contains("Pages: 4"));
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_size_within_limit() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Small Doc",
+                "docSize": {"totalRowCount": 10, "overApiSizeLimit": false}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc_size(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(!text.contains("WARNING"));
+        assert!(text.contains("Total rows: 10"));
+        assert!(text.contains("Pages: unknown"));
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_appearance_renders_icon_and_cover() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Gallery",
+                "icon": {"name": "rocket", "browserLink": "https://cdn.coda.io/rocket.png"},
+                "coverImage": {"browserLink": "https://codahosted.io/cover.jpg"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc_appearance(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(
+            text,
+            "Document: Gallery\nIcon: rocket (https://cdn.coda.io/rocket.png)\n\
+             Cover: https://codahosted.io/cover.jpg"
+        );
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["icon"]["name"], "rocket");
+        assert_eq!(
+            data["cover_image"]["browserLink"],
+            "https://codahosted.io/cover.jpg"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_link_published() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Handbook",
+                "browserLink": "https://coda.io/d/Handbook_ddoc1",
+                "published": {"browserLink": "https://coda.io/@acme/handbook"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc_link(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Link: https://coda.io/d/Handbook_ddoc1"));
+        assert!(text.contains("Published: https://coda.io/@acme/handbook"));
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_link_fallback() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Handbook"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc_link(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Link: https://coda.io/d/_ddoc1"));
+        assert!(text.contains("Published: no"));
+    }
+
+    #[tokio::test]
+    async fn test_get_workspace_cached_from_whoami() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/whoami"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "Ada",
+                "loginId": "ada@example.com",
+                "workspace": {"id": "ws-1", "name": "Research", "organizationId": "org-1"}
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let params = |refresh| Parameters(GetWorkspaceParams { refresh });
+
+        let first = server.get_workspace(params(None)).await.unwrap();
+        let data = &first.structured_content.unwrap()["data"];
+        assert_eq!(data["id"], "ws-1");
+        assert_eq!(data["name"], "Research");
+        assert_eq!(data["cached"], false);
+
+        // Served from the cache, without another whoami call
+        let second = server.get_workspace(params(None)).await.unwrap();
+        let data = &second.structured_content.unwrap()["data"];
+        assert_eq!(data["id"], "ws-1");
+        assert_eq!(data["cached"], true);
+
+        let refreshed = server.get_workspace(params(Some(true))).await.unwrap();
+        assert_eq!(
+            refreshed.structured_content.unwrap()["data"]["cached"],
+            false
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_workspace_missing_from_whoami() {
+        let (server, mock_server) = setup().await;
+        mount_whoami(&mock_server, "ada@example.com").await;
+
+        let result = server
+            .get_workspace(Parameters(GetWorkspaceParams { refresh: None }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("no workspace for ada@example.com"));
+    }
+
+    async fn mount_whoami(mock_server: &MockServer, login_id: &str) {
+        Mock::given(method("GET"))
+            .and(path("/whoami"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "Test User",
+                "loginId": login_id
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_get_my_doc_access_owner() {
+        let (server, mock_server) = setup().await;
+        mount_whoami(&mock_server, "ada@example.com").await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Plans",
+                "owner": "ada@example.com"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/acl/metadata"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "canShare": true,
+                "canCopy": true
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/acl/permissions"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"items": []})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_my_doc_access(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Access: owner"));
+        assert!(text.contains("Doc owner is ada@example.com"));
+        assert_eq!(
+            result.structured_content.unwrap()["data"]["access"],
+            "owner"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_my_doc_access_readonly() {
+        let (server, mock_server) = setup().await;
+        mount_whoami(&mock_server, "bob@example.com").await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Plans",
+                "owner": "ada@example.com"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/acl/metadata"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "canShare": false,
+                "canCopy": false
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/acl/permissions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "perm1", "access": "readonly",
+                     "principal": {"type": "email", "email": "bob@example.com"}},
+                    {"id": "perm2", "access": "write",
+                     "principal": {"type": "email", "email": "carol@example.com"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_my_doc_access(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Access: readonly"));
+        assert!(text.contains("Permission perm1 grants readonly to email"));
+        assert!(!text.contains("perm2"));
+    }
+
+    #[tokio::test]
+    async fn test_get_my_doc_access_none() {
+        let (server, mock_server) = setup().await;
+        mount_whoami(&mock_server, "bob@example.com").await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_my_doc_access(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Access: none"));
+    }
+
+    #[tokio::test]
+    async fn test_list_permissions() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/acl/permissions"))
+            .and(query_param("limit", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "perm1", "access": "write",
+                     "principal": {"type": "email", "email": "ada@example.com"}},
+                    {"id": "perm2", "access": "readonly", "principal": {"type": "anyone"}}
+                ],
+                "nextPageToken": "next1"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_permissions(Parameters(ListPermissionsParams {
+                doc_id: "doc1".to_string(),
+                limit: Some(2),
+                page_token: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 2 permissions"));
+        assert!(text.contains("ada@example.com"));
+        assert!(text.contains("next1"));
+    }
+
+    #[tokio::test]
+    async fn test_add_permission_email_readonly() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/acl/permissions"))
+            .and(body_json(serde_json::json!({
+                "access": "readonly",
+                "principal": {"type": "email", "email": "ada@example.com"},
+                "suppressEmail": true
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .add_permission(Parameters(AddPermissionParams {
+                doc_id: "doc1".to_string(),
+                access: DocAccess::Readonly,
+                email: Some("ada@example.com".to_string()),
+                domain: None,
+                suppress_email: Some(true),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Granted readonly access to email 'ada@example.com'"));
+    }
+
+    #[tokio::test]
+    async fn test_add_permission_requires_one_principal() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .add_permission(Parameters(AddPermissionParams {
+                doc_id: "doc1".to_string(),
+                access: DocAccess::Write,
+                email: None,
+                domain: None,
+                suppress_email: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_delete_permission() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/acl/permissions/perm1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_permission(Parameters(DeletePermissionParams {
+                doc_id: "doc1".to_string(),
+                permission_id: "perm1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["permission_id"], "perm1");
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_delete_permission_requires_confirm() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/acl/permissions/perm1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_permission(Parameters(DeletePermissionParams {
+                doc_id: "doc1".to_string(),
+                permission_id: "perm1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("delete_permission requires `confirm`"));
+        assert!(text.contains("'perm1'"));
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_delete_permission_confirmed_by_id() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/acl/permissions/perm1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_permission(Parameters(DeletePermissionParams {
+                doc_id: "doc1".to_string(),
+                permission_id: "perm1".to_string(),
+                confirm: Some("perm1".to_string()),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_analytics_doc_passes_dates_when_set() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/analytics"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"date": "2024-03-01", "views": 10, "totalSessions": 4},
+                    {"date": "2024-03-02", "views": 5, "totalSessions": 2}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .analytics_doc(Parameters(AnalyticsParams {
+                doc_id: "doc1".to_string(),
+                since: Some("2024-03-01".to_string()),
+                until: None,
+                scale: Some("daily".to_string()),
+                limit: None,
+                page_token: None,
+            }))
+            .await
+            .unwrap();
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("2 entries: 15 views, 6 sessions"));
+
+        server
+            .analytics_doc(Parameters(AnalyticsParams {
+                doc_id: "doc1".to_string(),
+                since: None,
+                until: None,
+                scale: None,
+                limit: None,
+                page_token: None,
+            }))
+            .await
+            .unwrap();
+
+        let queries: Vec<Option<String>> = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.url.query().map(ToString::to_string))
+            .collect();
+        assert_eq!(
+            queries,
+            [Some("sinceDate=2024-03-01&scale=daily".to_string()), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analytics_doc_pages() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/analytics"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"date": "2024-03-03", "views": 1, "totalSessions": 1}],
+                "nextPageToken": "tok3"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .analytics_doc(Parameters(AnalyticsParams {
+                doc_id: "doc1".to_string(),
+                since: Some("2024-03-01".to_string()),
+                until: None,
+                scale: None,
+                limit: Some(1),
+                page_token: Some("tok2".to_string()),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("tok3"), "{text}");
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(
+            requests[0].url.query(),
+            Some("sinceDate=2024-03-01&limit=1&pageToken=tok2")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analytics_doc_unauthorized_explains() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/analytics"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .analytics_doc(Parameters(AnalyticsParams {
+                doc_id: "doc1".to_string(),
+                since: None,
+                until: None,
+                scale: None,
+                limit: None,
+                page_token: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Analytics are probably not enabled"));
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_toc() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "p1", "name": "Overview"},
+                    {"id": "p2", "name": "Roadmap", "parent": {"id": "p1"}},
+                    {"id": "p3", "name": "Notes"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "grid-1", "name": "Tasks", "rowCount": 42},
+                    {"id": "grid-2", "name": "People"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc_toc(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Pages (3):\n- Overview\n  - Roadmap\n- Notes"));
+        assert!(text.contains("Tables (2):\n- Tasks (42 rows)\n- People"));
+
+        let structured = result.structured_content.unwrap();
+        assert_eq!(structured["pages"][0]["children"][0]["id"], "p2");
+        assert_eq!(structured["tables"][0]["rowCount"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_search_docs_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs"))
+            .and(query_param("query", "hello"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "d1", "name": "Hello World"}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .search_docs(Parameters(SearchDocsParams {
+                query: "hello".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 documents matching 'hello'"));
+    }
+
+    #[tokio::test]
+    async fn test_create_doc_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "My New Doc"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_doc(Parameters(CreateDocParams {
+                title: "My New Doc".to_string(),
+                folder_id: None,
+                source_doc: None,
+                timezone: None,
+                initial_pages: None,
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Document created successfully"));
+        assert!(text.contains("My New Doc"));
+    }
+
+    #[tokio::test]
+    async fn test_create_doc_seeds_initial_pages() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "Scaffold"
+            })))
+            .mount(&mock_server)
+            .await;
+        // Not readable on the first poll
+        Mock::given(method("GET"))
+            .and(path("/docs/new-doc"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/new-doc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "Scaffold"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/new-doc/pages"))
+            .and(body_partial_json(serde_json::json!({"name": "Overview"})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-1",
+                "requestId": "req-1"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/new-doc/pages"))
+            .and(body_partial_json(serde_json::json!({
+                "name": "Tasks",
+                "pageContent": {"canvasContent": {"format": "html"}}
+            })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-2",
+                "requestId": "req-2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_doc(Parameters(CreateDocParams {
+                title: "Scaffold".to_string(),
+                folder_id: None,
+                source_doc: None,
+                timezone: None,
+                initial_pages: Some(vec![
+                    PageSeed {
+                        name: "Overview".to_string(),
+                        content: Some("# Overview".to_string()),
+                        format: None,
+                    },
+                    PageSeed {
+                        name: "Tasks".to_string(),
+                        content: Some("<p>Todo</p>".to_string()),
+                        format: Some("html".to_string()),
+                    },
+                ]),
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("- Overview (canvas-1)"));
+        assert!(text.contains("- Tasks (canvas-2)"));
+    }
+
+    #[tokio::test]
+    async fn test_create_doc_reports_failed_pages() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "Scaffold"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/new-doc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "Scaffold"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/new-doc/pages"))
+            .and(body_partial_json(serde_json::json!({"name": "Overview"})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-1",
+                "requestId": "req-1"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/new-doc/pages"))
+            .and(body_partial_json(serde_json::json!({"name": "Tasks"})))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let seed = |name: &str| PageSeed {
+            name: name.to_string(),
+            content: None,
+            format: None,
+        };
+        let result = server
+            .create_doc(Parameters(CreateDocParams {
+                title: "Scaffold".to_string(),
+                folder_id: None,
+                source_doc: None,
+                timezone: None,
+                initial_pages: Some(vec![seed("Overview"), seed("Tasks")]),
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("- Overview (canvas-1)"));
+        assert!(text.contains("- Tasks FAILED: Permission denied"));
+        let pages = &result.structured_content.unwrap()["data"]["pages"];
+        assert_eq!(
+            pages["succeeded"],
+            serde_json::json!([{"name": "Overview", "id": "canvas-1"}])
+        );
+        assert_eq!(pages["failed"][0]["input"]["name"], "Tasks");
+        assert_eq!(pages["failed"][0]["error_category"], "forbidden");
+    }
+
+    #[tokio::test]
+    async fn test_create_doc_return_full() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "Full"
+            })))
+            .mount(&mock_server)
+            .await;
+        // Not readable on the first poll
+        Mock::given(method("GET"))
+            .and(path("/docs/new-doc"))
+            .respond_with(ResponseTemplate::new(404))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/new-doc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "Full",
+                "browserLink": "https://coda.io/d/Full_dnew-doc",
+                "owner": "me@example.com"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/new-doc/pages"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-1",
+                "requestId": "req-1"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/new-doc/pages/canvas-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "canvas-1",
+                "name": "Overview",
+                "browserLink": "https://coda.io/d/Full_dnew-doc/Overview_sucanvas-1"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_doc(Parameters(CreateDocParams {
+                title: "Full".to_string(),
+                folder_id: None,
+                source_doc: None,
+                timezone: None,
+                initial_pages: Some(vec![PageSeed {
+                    name: "Overview".to_string(),
+                    content: None,
+                    format: None,
+                }]),
+                return_full: Some(true),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("https://coda.io/d/Full_dnew-doc"));
+        assert!(text.contains("me@example.com"));
+        assert!(text.contains("Created pages:"));
+        assert!(text.contains("Overview_sucanvas-1"));
+    }
+
+    #[tokio::test]
+    async fn test_create_doc_rejects_unknown_page_format() {
+        let (server, mock_server) = setup().await;
+
+        // The doc must not be created when a seed is invalid
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_doc(Parameters(CreateDocParams {
+                title: "Seeded".to_string(),
+                folder_id: None,
+                source_doc: None,
+                timezone: None,
+                initial_pages: Some(vec![PageSeed {
+                    name: "Notes".to_string(),
+                    content: Some("Hi".to_string()),
+                    format: Some("md".to_string()),
+                }]),
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(
+            text,
+            "Page 'Notes': Unsupported content format 'md'. Use \"html\" or \"markdown\"."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_doc_with_all_options() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "new-doc",
+                "name": "From Template"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_doc(Parameters(CreateDocParams {
+                title: "From Template".to_string(),
+                folder_id: Some("folder1".to_string()),
+                source_doc: Some("template1".to_string()),
+                timezone: Some("Europe/London".to_string()),
+                initial_pages: None,
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Document created successfully"));
+    }
+
+    #[tokio::test]
+    async fn test_create_doc_api_error_returns_tool_error() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_doc(Parameters(CreateDocParams {
+                title: "Forbidden".to_string(),
+                folder_id: None,
+                source_doc: None,
+                timezone: None,
+                initial_pages: None,
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        // create_doc returns CallToolResult::error, not Err
+        assert!(result.is_error.unwrap_or(false));
+    }
+
+    #[tokio::test]
+    async fn test_delete_doc_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(202))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_doc(Parameters(DeleteDocParams {
+                doc_id: "doc1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("deleted successfully"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_doc_body() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/publish"))
+            .and(body_json(serde_json::json!({
+                "slug": "team-handbook",
+                "discoverable": true,
+                "categoryNames": ["Project management"],
+                "mode": "view"
+            })))
+            .respond_with(
+                ResponseTemplate::new(202).set_body_json(serde_json::json!({"requestId": "req-9"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .publish_doc(Parameters(PublishDocParams {
+                doc_id: "doc1".to_string(),
+                slug: Some("team-handbook".to_string()),
+                discoverable: Some(true),
+                earn_credit: None,
+                category_names: Some(vec!["Project management".to_string()]),
+                mode: Some(PublishMode::View),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["request_id"], "req-9");
+    }
+
+    #[tokio::test]
+    async fn test_publish_doc_ineligible_surfaces_api_message() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/publish"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "statusCode": 400,
+                "statusMessage": "Bad Request",
+                "message": "This doc cannot be published."
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .publish_doc(Parameters(PublishDocParams {
+                doc_id: "doc1".to_string(),
+                slug: None,
+                discoverable: None,
+                earn_credit: None,
+                category_names: None,
+                mode: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("could not be published: This doc cannot be published."));
+    }
+
+    #[tokio::test]
+    async fn test_unpublish_doc() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/publish"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .unpublish_doc(Parameters(UnpublishDocParams {
+                doc_id: "doc1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Doc 'doc1' unpublished"));
+    }
+
+    #[tokio::test]
+    async fn test_move_doc() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/docs/doc1"))
+            .and(body_json(serde_json::json!({"folderId": "fl-2"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Plans",
+                "folderId": "fl-2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .move_doc(Parameters(MoveDocParams {
+                doc_id: "doc1".to_string(),
+                folder_id: "fl-2".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Moved doc 'doc1' to folder 'fl-2'"));
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["folderId"], "fl-2");
+    }
+
+    #[tokio::test]
+    async fn test_move_doc_reports_unchanged_folder() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Plans",
+                "folderId": "fl-1"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .move_doc(Parameters(MoveDocParams {
+                doc_id: "doc1".to_string(),
+                folder_id: "fl-2".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Coda reports it in folder 'fl-1', not 'fl-2'"));
+    }
+
+    #[tokio::test]
+    async fn test_list_categories() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/categories"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"name": "Project management"}, {"name": "Education"}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server.list_categories().await.unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 2 categories\n- Project management\n- Education"));
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(
+            data["names"],
+            serde_json::json!(["Project management", "Education"])
+        );
+    }
+
+    async fn setup_safe_mode() -> (CodaMcpServer, MockServer) {
+        setup_with_config(Config {
+            safe_mode: true,
+            ..Config::default()
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_delete_doc_requires_confirm() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_doc(Parameters(DeleteDocParams {
+                doc_id: "doc1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Safe mode is on"));
+        assert!(text.contains("'doc1'"));
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_delete_doc_confirmed_by_id() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_doc(Parameters(DeleteDocParams {
+                doc_id: "doc1".to_string(),
+                confirm: Some("doc1".to_string()),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_delete_row_confirm_by_name() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/row1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "row1",
+                "name": "Launch plan"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows/row1"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let delete = |confirm: &str| {
+            server.delete_row(Parameters(DeleteRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "row1".to_string(),
+                confirm: Some(confirm.to_string()),
+            }))
+        };
+
+        let refused = delete("Launch").await.unwrap();
+        assert_eq!(refused.is_error, Some(true));
+        let text = &refused.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("does not match"));
+
+        let deleted = delete("Launch plan").await.unwrap();
+        assert_eq!(deleted.is_error, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_unpublish_doc_requires_confirm() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/publish"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .unpublish_doc(Parameters(UnpublishDocParams {
+                doc_id: "doc1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("unpublish_doc requires `confirm`"));
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_unpublish_doc_confirm_by_id_or_name() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1",
+                "name": "Roadmap"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/publish"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let unpublish = |confirm: &str| {
+            server.unpublish_doc(Parameters(UnpublishDocParams {
+                doc_id: "doc1".to_string(),
+                confirm: Some(confirm.to_string()),
+            }))
+        };
+
+        let refused = unpublish("Road").await.unwrap();
+        assert_eq!(refused.is_error, Some(true));
+        let text = &refused.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("does not match"));
+
+        assert_eq!(unpublish("doc1").await.unwrap().is_error, Some(false));
+        assert_eq!(unpublish("Roadmap").await.unwrap().is_error, Some(false));
+    }
+
+    fn rename_params(kind: RenameKind) -> RenameParams {
+        RenameParams {
+            kind,
+            doc_id: "doc1".to_string(),
+            page_id: Some("p1".to_string()),
+            new_name: "Renamed".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_doc() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/docs/doc1"))
+            .and(body_partial_json(serde_json::json!({"title": "Renamed"})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .rename(Parameters(rename_params(RenameKind::Doc)))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Renamed doc 'doc1' to 'Renamed'.");
+    }
+
+    #[tokio::test]
+    async fn test_rename_page() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/pages/p1"))
+            .and(body_partial_json(serde_json::json!({"name": "Renamed"})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req1",
+                "id": "p1"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .rename(Parameters(rename_params(RenameKind::Page)))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Renamed page 'p1' to 'Renamed'.");
+    }
+
+    #[tokio::test]
+    async fn test_rename_table_and_column_unsupported() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({})))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        for kind in [RenameKind::Table, RenameKind::Column] {
+            let result = server
+                .rename(Parameters(rename_params(kind)))
+                .await
+                .unwrap();
+
+            assert_eq!(result.is_error, Some(true));
+            let text = &result.content[0].raw.as_text().unwrap().text;
+            assert_eq!(
+                *text,
+                format!(
+                    "Renaming {0}s is not supported by the Coda API. Rename the {0} in the Coda UI instead.",
+                    kind.as_str()
+                )
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_page_requires_page_id() {
+        let (server, _mock_server) = setup().await;
+
+        let mut params = rename_params(RenameKind::Page);
+        params.page_id = None;
+        let result = server.rename(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("`page_id` is required"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_doc_error_returns_tool_error() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_doc(Parameters(DeleteDocParams {
+                doc_id: "doc1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        assert!(result.is_error.unwrap_or(false));
+
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["ok"], false);
+        assert_eq!(envelope["operation"], "delete_doc");
+        assert!(envelope["summary"]
+            .as_str()
+            .unwrap()
+            .starts_with("Not found"));
+        assert!(envelope["data"].is_null());
+        assert_eq!(envelope["status"], 404);
+        assert!(envelope["warnings"].as_array().unwrap().is_empty());
+    }
+
+    fn exists_params(kind: ExistsKind) -> ExistsParams {
+        ExistsParams {
+            kind,
+            doc_id: "doc1".to_string(),
+            page_id: Some("p1".to_string()),
+            table_id: Some("tbl1".to_string()),
+            row_id: Some("i-1".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exists_each_kind() {
+        let cases = [
+            (ExistsKind::Doc, "/docs/doc1", "Doc 'doc1'"),
+            (ExistsKind::Page, "/docs/doc1/pages/p1", "Page 'p1'"),
+            (ExistsKind::Table, "/docs/doc1/tables/tbl1", "Table 'tbl1'"),
+            (
+                ExistsKind::Row,
+                "/docs/doc1/tables/tbl1/rows/i-1",
+                "Row 'i-1'",
+            ),
+        ];
+
+        for (kind, resource, target) in cases {
+            for found in [true, false] {
+                let (server, mock_server) = setup().await;
+                let response = if found {
+                    ResponseTemplate::new(200).set_body_json(serde_json::json!({"id": "x"}))
+                } else {
+                    ResponseTemplate::new(404)
+                };
+                Mock::given(method("GET"))
+                    .and(path(resource))
+                    .respond_with(response)
+                    .expect(1)
+                    .mount(&mock_server)
+                    .await;
+
+                let result = server
+                    .exists(Parameters(exists_params(kind)))
+                    .await
+                    .unwrap();
+
+                assert!(!result.is_error.unwrap_or(false), "{resource}");
+                let text = &result.content[0].raw.as_text().unwrap().text;
+                let expected = if found { "exists" } else { "does not exist" };
+                assert_eq!(text, &format!("{target} {expected}."));
+                let envelope = result.structured_content.unwrap();
+                assert_eq!(envelope["data"]["exists"], found);
+                assert_eq!(envelope["data"]["kind"], kind.as_str());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exists_encodes_names_in_path() {
+        let (server, mock_server) = setup().await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/Sprint%20Tasks/rows/Fix%20login"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"id": "x"})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .exists(Parameters(ExistsParams {
+                table_id: Some("Sprint Tasks".to_string()),
+                row_id: Some("Fix login".to_string()),
+                ..exists_params(ExistsKind::Row)
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Row 'Fix login' exists.");
+    }
+
+    #[tokio::test]
+    async fn test_exists_reports_other_errors() {
+        let (server, mock_server) = setup().await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .exists(Parameters(exists_params(ExistsKind::Doc)))
+            .await
+            .unwrap();
+
+        assert!(result.is_error.unwrap_or(false));
+        let envelope = result.structured_content.unwrap();
+        assert!(envelope["data"].is_null());
+        assert_eq!(envelope["status"], 403);
+    }
+
+    #[tokio::test]
+    async fn test_exists_requires_ids_for_kind() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server
+            .exists(Parameters(ExistsParams {
+                row_id: None,
+                ..exists_params(ExistsKind::Row)
+            }))
+            .await
+            .unwrap();
+
+        assert!(result.is_error.unwrap_or(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "`row_id` is required to look up a row.");
+    }
+
+    // === Page Tools ===
+
+    #[tokio::test]
+    async fn test_create_page_sends_page_create_body() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages"))
+            .and(body_json(serde_json::json!({
+                "name": "Launch",
+                "subtitle": "Q3 plan",
+                "parentPageId": "canvas-root",
+                "pageContent": {
+                    "type": "canvas",
+                    "canvasContent": {"format": "html", "content": "<p>Hello</p>"}
+                }
+            })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-9",
+                "requestId": "req-9"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_page(Parameters(CreatePageParams {
+                doc_id: "doc1".to_string(),
+                name: "Launch".to_string(),
+                subtitle: Some("Q3 plan".to_string()),
+                parent_page_id: Some("canvas-root".to_string()),
+                page_content: Some("<p>Hello</p>".to_string()),
+                format: None,
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("ID: canvas-9"));
+        assert!(text.contains("a few seconds"));
+        assert_eq!(result.structured_content.unwrap()["data"]["id"], "canvas-9");
+    }
+
+    #[tokio::test]
+    async fn test_create_page_rejects_unknown_format() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .create_page(Parameters(CreatePageParams {
+                doc_id: "doc1".to_string(),
+                name: "Launch".to_string(),
+                subtitle: None,
+                parent_page_id: None,
+                page_content: Some("Hello".to_string()),
+                format: Some("text".to_string()),
+                return_full: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Unsupported content format 'text'"));
+    }
+
+    #[tokio::test]
+    async fn test_create_page_outline_sets_parent_links() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages"))
+            .and(body_partial_json(serde_json::json!({"name": "Guide"})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-1",
+                "requestId": "req-1"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // The parent must be readable before its subpages are created
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/canvas-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "canvas-1",
+                "name": "Guide"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages"))
+            .and(body_partial_json(serde_json::json!({
+                "name": "Setup",
+                "parentPageId": "canvas-1"
+            })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-2",
+                "requestId": "req-2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages"))
+            .and(body_partial_json(serde_json::json!({
+                "name": "Usage",
+                "parentPageId": "canvas-1"
+            })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-3",
+                "requestId": "req-3"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let leaf = |name: &str| OutlineNode {
+            name: name.to_string(),
+            content: None,
+            format: None,
+            children: Vec::new(),
+        };
+        let result = server
+            .create_page_outline(Parameters(CreatePageOutlineParams {
+                doc_id: "doc1".to_string(),
+                parent_page_id: None,
+                outline: vec![OutlineNode {
+                    children: vec![leaf("Setup"), leaf("Usage")],
+                    ..leaf("Guide")
+                }],
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("- Guide (canvas-1)\n  - Setup (canvas-2)\n  - Usage (canvas-3)"));
+        let pages = &result.structured_content.unwrap()["data"]["pages"];
+        assert_eq!(pages[0]["parent_page_id"], serde_json::Value::Null);
+        assert_eq!(pages[1]["parent_page_id"], "canvas-1");
+        assert_eq!(pages[2]["depth"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_page_outline_rejects_oversized_outline() {
+        let (server, _mock_server) = setup().await;
+        let outline = (0..=OUTLINE_MAX_PAGES)
+            .map(|i| OutlineNode {
+                name: format!("Page {i}"),
+                content: None,
+                format: None,
+                children: Vec::new(),
+            })
+            .collect();
+
+        let result = server
+            .create_page_outline(Parameters(CreatePageOutlineParams {
+                doc_id: "doc1".to_string(),
+                parent_page_id: None,
+                outline,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("101 pages"));
+    }
+
+    #[tokio::test]
+    async fn test_update_page_sends_only_given_fields() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/pages/canvas-1"))
+            .and(body_json(serde_json::json!({
+                "subtitle": "Updated weekly",
+                "contentUpdate": {
+                    "insertionMode": "replace",
+                    "canvasContent": {"format": "html", "content": "<p>New</p>"}
+                }
+            })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "canvas-1",
+                "requestId": "req-7"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .update_page(Parameters(UpdatePageParams {
+                doc_id: "doc1".to_string(),
+                page_id: "canvas-1".to_string(),
+                name: None,
+                subtitle: Some("Updated weekly".to_string()),
+                icon_name: None,
+                content_insert: Some(ContentInsert {
+                    content: "<p>New</p>".to_string(),
+                    format: None,
+                    mode: Some(InsertionMode::Replace),
+                }),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Request ID: req-7"));
+        assert!(text.contains(MUTATION_DELAY_NOTE));
+    }
+
+    #[tokio::test]
+    async fn test_list_pages_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "p1", "name": "Home"},
+                    {"id": "p2", "name": "About"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_pages(Parameters(ListPagesParams {
+                doc_id: "doc1".to_string(),
+                dedupe: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 pages"));
+    }
+
+    #[tokio::test]
+    async fn test_get_page_export_failed() {
+        let (server, mock_server) = setup().await;
+
+        // Step 1: Initiate export
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        // Step 2: Poll returns failed
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "failed",
+                "error": "Page too large"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_page(Parameters(GetPageParams {
+                doc_id: "doc1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
+            }))
+            .await;
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.message.contains("Export failed"));
+    }
+
+    /// Mount an export whose first attempt fails with `error` and whose
+    /// fresh export completes
+    async fn mount_failing_then_ok_export(mock_server: &MockServer, error: &str) {
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .up_to_n_times(1)
+            .mount(mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp2",
+                "status": "inProgress"
+            })))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "failed",
+                "error": error
+            })))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp2",
+                "status": "complete",
+                "downloadLink": format!("{}/export/content.html", mock_server.uri())
+            })))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/export/content.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>Recovered</p>"))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "p1", "name": "Plan"
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    fn retrying_page_params() -> GetPageParams {
+        GetPageParams {
+            doc_id: "doc1".to_string(),
+            page_id: "p1".to_string(),
+            include_checksum: None,
+            to_file: None,
+            transform: None,
+            include_raw: None,
+            retry_on_failure: Some(true),
+            format: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_page_retries_transient_export_failure() {
+        let (server, mock_server) = setup().await;
+        mount_failing_then_ok_export(&mock_server, "Temporary internal error").await;
+
+        let result = server
+            .get_page(Parameters(retrying_page_params()))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Recovered"));
+        let exports = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|r| r.method.as_str() == "POST")
+            .count();
+        assert_eq!(exports, 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_page_does_not_retry_permanent_export_failure() {
+        let (server, mock_server) = setup().await;
+        mount_failing_then_ok_export(&mock_server, "Page too large").await;
+
+        let err = server
+            .get_page(Parameters(retrying_page_params()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.message, "Export failed: Page too large");
+        let exports = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|r| r.method.as_str() == "POST")
+            .count();
+        assert_eq!(exports, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_page_complete_no_download_link() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "complete"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_page(Parameters(GetPageParams {
+                doc_id: "doc1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
+            }))
+            .await;
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.message.contains("no download link"));
+    }
+
+    #[tokio::test]
+    async fn test_copy_doc() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs"))
+            .and(body_json(serde_json::json!({
+                "title": "Q3 Plan",
+                "sourceDoc": "tmpl1",
+                "folderId": "fl-1",
+                "timezone": "Europe/Paris"
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": "doc9",
+                "name": "Q3 Plan"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .copy_doc(Parameters(CopyDocParams {
+                source_doc_id: "tmpl1".to_string(),
+                title: "Q3 Plan".to_string(),
+                folder_id: Some("fl-1".to_string()),
+                timezone: Some("Europe/Paris".to_string()),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Copied doc 'tmpl1' to 'Q3 Plan'\nID: doc9"));
+        assert!(text.contains("in the background"));
+        let outcome = result.structured_content.unwrap();
+        assert_eq!(outcome["data"]["doc_id"], "doc9");
+        assert_eq!(outcome["warnings"][0], COPY_DOC_NOTE);
+    }
+
+    // === Table Tools ===
+
+    #[tokio::test]
+    async fn test_list_tables_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "tbl1", "name": "Tasks", "rowCount": 42}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_tables(Parameters(ListTablesParams {
+                doc_id: "doc1".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                table_types: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 tables"));
+        assert!(text.contains("Tasks"));
+    }
+
+    #[tokio::test]
+    async fn test_list_tables_yaml_output() {
+        let (server, mock_server) = setup_with_config(Config {
+            output_format: OutputFormat::Yaml,
+            ..Config::default()
+        })
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "tbl1", "name": "Tasks", "rowCount": 42},
+                    {"id": "tbl2", "name": "2024", "rowCount": 0}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_tables(Parameters(ListTablesParams {
+                doc_id: "doc1".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                table_types: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(!text.contains("```json"));
+        let yaml = text
+            .split("```yaml\n")
+            .nth(1)
+            .and_then(|rest| rest.split("```").next())
+            .unwrap();
+        assert!(yaml.starts_with("- browserLink: null\n"));
+
+        // The block is valid YAML holding the same data as the JSON block
+        let parsed: serde_json::Value = serde_norway::from_str(yaml).unwrap();
+        let json_server = CodaMcpServer::new(
+            Arc::new(CodaClient::new_with_base_url(
+                "test_token",
+                &mock_server.uri(),
+            )),
+            &Config::default(),
+        );
+        let result = json_server
+            .list_tables(Parameters(ListTablesParams {
+                doc_id: "doc1".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                table_types: None,
+            }))
+            .await
+            .unwrap();
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        let json = text
+            .split("```json\n")
+            .nth(1)
+            .and_then(|rest| rest.split("```").next())
+            .unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+        // A numeric-looking name stays a string; counts stay numbers
+        assert_eq!(parsed[1]["name"], "2024");
+        assert_eq!(parsed[0]["rowCount"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_list_tables_filters_table_types() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .and(query_param("tableTypes", "table"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "tbl1", "name": "Tasks"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_tables(Parameters(ListTablesParams {
+                doc_id: "doc1".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                table_types: Some(vec![TableType::Table]),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 tables"));
+    }
+
+    #[tokio::test]
+    async fn test_list_tables_pagination() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .and(query_param("limit", "2"))
+            .and(query_param("pageToken", "tok/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "tbl1", "name": "Tasks"},
+                    {"id": "tbl2", "name": "People"}
+                ],
+                "nextPageToken": "tok2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_tables(Parameters(ListTablesParams {
+                doc_id: "doc1".to_string(),
+                limit: Some(2),
+                page_token: Some("tok/1".to_string()),
+                dedupe: None,
+                table_types: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 tables"));
+        assert!(text.ends_with("Next page token: tok2"));
+    }
+
+    #[tokio::test]
+    async fn test_get_table_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "tbl1",
+                "name": "Tasks",
+                "rowCount": 42
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_table(Parameters(GetTableParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Table: Tasks"));
+    }
+
+    #[tokio::test]
+    async fn test_get_table_shows_display_column_and_link() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "tbl1",
+                "name": "Tasks",
+                "browserLink": "https://coda.io/d/_ddoc1#Tasks_tutbl1",
+                "displayColumn": {"id": "c-name", "type": "column"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_table(Parameters(GetTableParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Display column: c-name"));
+        assert!(text.contains("Link: https://coda.io/d/_ddoc1#Tasks_tutbl1"));
+    }
+
+    #[tokio::test]
+    async fn test_list_columns_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "col1", "name": "Name"},
+                    {"id": "col2", "name": "Status"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_columns(Parameters(ListColumnsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                include_formulas: None,
+                writable_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 columns"));
+    }
+
+    fn list_columns_params() -> ListColumnsParams {
+        ListColumnsParams {
+            alias: None,
+            doc_id: "doc1".to_string(),
+            table_id: "tbl1".to_string(),
+            limit: None,
+            page_token: None,
+            dedupe: None,
+            include_formulas: None,
+            writable_only: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_columns_reuses_column_cache() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "col1", "name": "Name"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let first = server
+            .list_columns(Parameters(list_columns_params()))
+            .await
+            .unwrap();
+        let text = &first.content[0].raw.as_text().unwrap().text;
+        assert!(!text.contains("from column cache"));
+
+        let second = server
+            .list_columns(Parameters(list_columns_params()))
+            .await
+            .unwrap();
+        let text = &second.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 columns (from column cache)"));
+    }
+
+    #[tokio::test]
+    async fn test_list_columns_refetches_after_column_cache_expiry() {
+        let (server, mock_server) = setup_with_config(Config {
+            column_cache_ttl_secs: 1,
+            ..Config::default()
+        })
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "col1", "name": "Name"}]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        server
+            .list_columns(Parameters(list_columns_params()))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let result = server
+            .list_columns(Parameters(list_columns_params()))
+            .await
+            .unwrap();
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(!text.contains("from column cache"));
+    }
+
+    #[tokio::test]
+    async fn test_list_columns_column_cache_disabled() {
+        let (server, mock_server) = setup_with_config(Config {
+            column_cache_ttl_secs: 0,
+            ..Config::default()
+        })
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "col1", "name": "Name"}]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        for _ in 0..2 {
+            let result = server
+                .list_columns(Parameters(list_columns_params()))
+                .await
+                .unwrap();
+            let text = &result.content[0].raw.as_text().unwrap().text;
+            assert!(!text.contains("from column cache"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_columns_pagination() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .and(query_param("limit", "1000"))
+            .and(query_param("pageToken", "tok1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "col1", "name": "Name"}],
+                "nextPageToken": "tok2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_columns(Parameters(ListColumnsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: Some(5000),
+                page_token: Some("tok1".to_string()),
+                dedupe: None,
+                include_formulas: None,
+                writable_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 columns"));
+        assert!(text.ends_with("Next page token: tok2"));
+    }
+
+    #[tokio::test]
+    async fn test_list_columns_writable_only() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "c-name", "name": "Name", "format": {"type": "text"}},
+                    {"id": "c-qty", "name": "Qty", "format": {"type": "number"}},
+                    {"id": "c-stage", "name": "Stage", "format": {"type": "select"}},
+                    {"id": "c-total", "name": "Total", "format": {"type": "number"},
+                     "calculated": true},
+                    {"id": "c-go", "name": "Go", "format": {"type": "button"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_columns(Parameters(ListColumnsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                include_formulas: None,
+                writable_only: Some(true),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 3 columns"));
+        assert!(text.contains("c-name"));
+        assert!(text.contains("c-qty"));
+        assert!(text.contains("c-stage"));
+        assert!(!text.contains("c-total"));
+        assert!(!text.contains("c-go"));
+    }
+
+    #[tokio::test]
+    async fn test_list_columns_include_formulas() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "c-name", "name": "Name"},
+                    {"id": "c-total", "name": "Total", "calculated": true,
+                     "formula": "thisRow.Price * thisRow.Qty"},
+                    {"id": "c-tax", "name": "Tax", "calculated": true}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        // Only the calculated column missing its formula is fetched individually
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns/c-tax"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "c-tax",
+                "name": "Tax",
+                "calculated": true,
+                "formula": "thisRow.Total * 0.2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .list_columns(Parameters(ListColumnsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                include_formulas: Some(true),
+                writable_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("- Total: `thisRow.Price * thisRow.Qty`"));
+        assert!(text.contains("- Tax: `thisRow.Total * 0.2`"));
+        assert!(!text.contains("- Name:"));
+    }
+
+    #[tokio::test]
+    async fn test_get_column() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns/c-stage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "c-stage",
+                "name": "Stage",
+                "format": {
+                    "type": "select",
+                    "options": [{"name": "Backlog"}, "Done"]
+                },
+                "defaultValue": "Backlog"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_column(Parameters(GetColumnParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                column_id: "c-stage".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with(
+            "Column: Stage\nType: select\nDefault: Backlog\nOptions: Backlog, Done\n\n"
+        ));
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["id"], "c-stage");
+        assert_eq!(data["format"]["options"][1], "Done");
+        assert_eq!(data["defaultValue"], "Backlog");
+    }
+
+    #[tokio::test]
+    async fn test_get_column_options() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns/c-stage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "c-stage",
+                "name": "Stage",
+                "format": {
+                    "type": "select",
+                    "options": [{"name": "Backlog"}, {"name": "Doing"}, {"name": "Done"}]
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_column_options(Parameters(GetColumnParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                column_id: "c-stage".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Options (3):\n- Backlog\n- Doing\n- Done"));
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(
+            data["options"],
+            serde_json::json!(["Backlog", "Doing", "Done"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_column_options_not_select() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns/c-name"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "c-name",
+                "name": "Name",
+                "format": {"type": "text"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_column_options(Parameters(GetColumnParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                column_id: "c-name".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("not a select column (format type: text)"));
+    }
+
+    #[tokio::test]
+    async fn test_get_table_relationships() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "grid-tasks", "name": "Tasks"},
+                    {"id": "grid-people", "name": "People"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/grid-tasks/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "c-title", "name": "Title", "format": {"type": "text"}},
+                    {"id": "c-owner", "name": "Owner", "format": {
+                        "type": "lookup",
+                        "table": {"id": "grid-people", "name": "People"}
+                    }}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/grid-people/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "c-name", "name": "Name", "format": {"type": "text"}}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_table_relationships(Parameters(GetTableRelationshipsParams {
+                doc_id: "doc1".to_string(),
+                table_limit: None,
+                concurrency: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 relationships across 2 of 2 tables"));
+        assert!(text.contains("- Tasks.Owner -> People"));
+        assert!(text.contains(r#""grid-tasks": ["#));
+        assert!(!text.contains(r#""grid-people": ["#));
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_schema_respects_table_limit() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "tbl1", "name": "Tasks"},
+                    {"id": "tbl2", "name": "Projects"},
+                    {"id": "tbl3", "name": "Archive"}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        for table in ["tbl1", "tbl2"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/docs/doc1/tables/{table}/columns")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [{"id": "col1", "name": "Name"}]
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        // Tables beyond the limit must not be introspected
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl3/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": []
+            })))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_doc_schema(Parameters(GetDocSchemaParams {
+                doc_id: "doc1".to_string(),
+                table_limit: Some(2),
+                concurrency: Some(2),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Described 2 of 3 tables with columns (1 listed by name only)"));
+        assert!(text.contains("Archive"));
+        assert_eq!(text.matches("\"columns\"").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_schema_follows_table_pages() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .and(query_param("pageToken", "next"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "tbl2", "name": "Projects"}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "tbl1", "name": "Tasks"}],
+                "nextPageToken": "next"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        for table in ["tbl1", "tbl2"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/docs/doc1/tables/{table}/columns")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [{"id": "col1", "name": "Name"}]
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let result = server
+            .get_doc_schema(Parameters(GetDocSchemaParams {
+                doc_id: "doc1".to_string(),
+                table_limit: None,
+                concurrency: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Described 2 of 2 tables with columns"));
+        assert!(text.contains("Projects"));
+    }
+
+    #[tokio::test]
+    async fn test_load_doc_context_serves_list_columns_from_cache() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "doc1", "name": "Roadmap"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "p1", "name": "Overview"}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "tbl1", "name": "Tasks", "rowCount": 2}]
+            })))
+            .mount(&mock_server)
+            .await;
+        // Only the prefetch may hit the columns endpoint
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "col1", "name": "Name"},
+                    {"id": "col2", "name": "Status"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("limit", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "name": "Row 1", "values": {"Name": "Alice"}}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .load_doc_context(Parameters(LoadDocContextParams {
+                doc_id: "doc1".to_string(),
+                table_limit: None,
+                sample_rows: Some(1),
+                concurrency: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Loaded 'Roadmap': 1 pages, 1 tables"));
+        assert!(text.contains("- Tasks (tbl1): 2 columns"));
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        assert_eq!(data["samples"]["tbl1"][0]["id"], "r1");
+
+        let result = server
+            .list_columns(Parameters(ListColumnsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "Tasks".to_string(),
+                limit: None,
+                page_token: None,
+                dedupe: None,
+                include_formulas: None,
+                writable_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 columns (from doc context cache)"));
+        let columns_requests = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.url.path() == "/docs/doc1/tables/tbl1/columns")
+            .count();
+        assert_eq!(columns_requests, 1);
+    }
+
+    // === Row Tools ===
+
+    #[tokio::test]
+    async fn test_get_rows_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("useColumnNames", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "name": "Row 1", "values": {"Name": "Alice"}},
+                    {"id": "r2", "name": "Row 2", "values": {"Name": "Bob"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: None,
+                filter: None,
+                redact_columns: None,
+                dedupe: None,
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 2 rows"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_uses_configured_read_defaults() {
+        let (server, mock_server) = setup_with_config(Config {
+            default_use_column_names: false,
+            default_value_format: Some(ValueFormat::SimpleWithArrays),
+            ..Config::default()
+        })
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("useColumnNames", "false"))
+            .and(query_param("valueFormat", "simpleWithArrays"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "name": "Row 1", "values": {"c-name": ["Alice"]}}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .and(query_param("useColumnNames", "true"))
+            .and(query_param("valueFormat", "rich"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "name": "Row 1",
+                "values": {"Name": "Alice"}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: None,
+                filter: None,
+                redact_columns: None,
+                dedupe: None,
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+        assert!(result.content[0]
+            .raw
+            .as_text()
+            .unwrap()
+            .text
+            .contains("c-name"));
+
+        // Per-call values win over the configured defaults
+        let result = server
+            .get_row(Parameters(GetRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                redact_columns: None,
+                reference_ids: None,
+                use_column_names: Some(true),
+                value_format: Some(ValueFormat::Rich),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_get_row_keeps_column_names_while_redacting() {
+        let (server, mock_server) = setup_with_config(Config {
+            default_use_column_names: false,
+            ..Config::default()
+        })
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .and(query_param("useColumnNames", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "name": "Row 1",
+                "values": {"SSN": "123-45-6789"}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_row(Parameters(GetRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                redact_columns: Some(vec!["ssn".to_string()]),
+                reference_ids: None,
+                use_column_names: Some(false),
+                value_format: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("[REDACTED]"));
+        assert!(!text.contains("123-45-6789"));
+    }
+
+    /// Mount a columns list where two columns share the name "Name"
+    async fn mount_shared_name_columns(mock_server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "c-1", "name": "Name"},
+                    {"id": "c-2", "name": "Name"},
+                    {"id": "c-3", "name": "Email"}
+                ]
+            })))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_disambiguates_shared_column_names() {
+        let (server, mock_server) = setup().await;
+        mount_shared_name_columns(&mock_server).await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("useColumnNames", "false"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "name": "Row 1", "values": {"c-1": "Ada", "c-2": "Lovelace", "c-3": "ada@example.com"}}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                limit: None,
+                fetch_all: None,
+                use_column_names: Some(true),
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 rows"));
+        assert!(text.contains("Name (c-1)"));
+        assert!(text.contains("Lovelace"));
+        assert!(text.contains("Name (c-2)"));
+        assert!(text.contains("\"Email\""));
+        assert!(text.contains("Warning: several columns share the display name 'Name'"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_skips_column_check_by_default() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": []
+            })))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "name": "Row 1", "values": {"Name": "Ada"}}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                limit: None,
+                fetch_all: None,
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 rows"));
+        assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_row_redacts_shared_column_names() {
+        let (server, mock_server) = setup().await;
+        mount_shared_name_columns(&mock_server).await;
+        // Listing the columns caches them, so the plain read below checks them
+        server
+            .list_columns(Parameters(list_columns_params()))
+            .await
+            .unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .and(query_param("useColumnNames", "false"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "values": {"c-1": "Ada", "c-2": "Lovelace", "c-3": "ada@example.com"}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_row(Parameters(GetRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                redact_columns: Some(vec!["name".to_string()]),
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(!text.contains("Lovelace"));
+        assert!(text.contains("ada@example.com"));
+        assert!(text.contains("Warning: several columns share"));
+    }
+
+    fn fetch_all_params() -> GetRowsParams {
+        GetRowsParams {
+            alias: None,
+            doc_id: "doc1".to_string(),
+            table_id: "tbl1".to_string(),
+            limit: Some(5),
+            query: None,
+            filter: None,
+            redact_columns: None,
+            dedupe: None,
+            format: None,
+            reference_ids: None,
+            use_column_names: None,
+            value_format: None,
+            fetch_all: Some(true),
+            max_output_bytes: None,
+            page_token: None,
+            from_end: None,
+            sort_by: None,
+            visible_only: None,
+        }
+    }
+
+    /// Mount a rows page served for `token` (the first page when `None`)
+    async fn mount_rows_page(
+        mock_server: &MockServer,
+        token: Option<&str>,
+        ids: &[&str],
+        next: Option<&str>,
+    ) {
+        let items: Vec<serde_json::Value> = ids
+            .iter()
+            .map(|id| serde_json::json!({"id": id, "name": id, "values": {}}))
+            .collect();
+        let mut mock = Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("limit", "1000"));
+        mock = match token {
+            Some(token) => mock.and(query_param("pageToken", token)),
+            None => mock.and(wiremock::matchers::query_param_is_missing("pageToken")),
+        };
+        mock.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "items": items,
+            "nextPageToken": next
+        })))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_resolves_alias() {
+        let config = Config {
+            aliases: [(
+                "tasks".to_string(),
+                TableAlias {
+                    doc_id: "AbCd".to_string(),
+                    table_id: "grid-1".to_string(),
+                },
+            )]
+            .into(),
+            ..Config::default()
+        };
+        let (server, mock_server) = setup_with_config(config).await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/AbCd/tables/grid-1/rows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "name": "Ship", "values": {}}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                doc_id: String::new(),
+                table_id: String::new(),
+                alias: Some("tasks".to_string()),
+                fetch_all: None,
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 1 rows"), "{text}");
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: Some("bugs".to_string()),
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Unknown alias 'bugs'. Known aliases: tasks.");
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_fetch_all_follows_page_tokens() {
+        let (server, mock_server) = setup().await;
+        mount_rows_page(&mock_server, None, &["r1", "r2"], Some("tok2")).await;
+        mount_rows_page(&mock_server, Some("tok2"), &["r3"], None).await;
+
+        let result = server
+            .get_rows(Parameters(fetch_all_params()))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 3 rows across 2 pages (all matching rows)"));
+        assert!(text.contains("r3"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_dedupe_across_pages() {
+        let (server, mock_server) = setup().await;
+        // The row on the page boundary is returned by both pages
+        mount_rows_page(&mock_server, None, &["r1", "r2"], Some("tok2")).await;
+        mount_rows_page(&mock_server, Some("tok2"), &["r2", "r3"], None).await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                dedupe: Some(true),
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 3 rows across 2 pages"), "{text}");
+        assert_eq!(text.matches("\"id\": \"r2\"").count(), 1);
+        assert!(text.contains("\"id\": \"r3\""));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_fetch_all_stops_at_cap() {
+        let (server, mock_server) = setup().await;
+        mount_rows_page(&mock_server, None, &["r1", "r2", "r3"], Some("tok2")).await;
+        mount_rows_page(&mock_server, Some("tok2"), &["r4", "r5"], Some("tok3")).await;
+
+        let result = server
+            .get_rows(Parameters(fetch_all_params()))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with(&format!(
+            "Found {GET_ROWS_MAX_ROWS} rows across 2 pages (truncated at the {GET_ROWS_MAX_ROWS}-row cap"
+        )));
+        assert!(!text.contains("r5"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_from_end_returns_last_rows() {
+        let (server, mock_server) = setup().await;
+        mount_rows_page(&mock_server, None, &["r1", "r2"], Some("tok2")).await;
+        mount_rows_page(&mock_server, Some("tok2"), &["r3"], None).await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                fetch_all: None,
+                from_end: Some(2),
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found the last 2 of 3 rows across 2 pages"));
+        let r2 = text.find("\"r2\"").unwrap();
+        let r3 = text.find("\"r3\"").unwrap();
+        assert!(r2 < r3);
+        assert!(!text.contains("\"r1\""));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_from_end_rejects_page_token() {
+        let (server, mock_server) = setup().await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                from_end: Some(2),
+                page_token: Some("tok2".to_string()),
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let structured = result.structured_content.as_ref().unwrap();
+        assert_eq!(structured["operation"], "get_rows");
+        assert_eq!(structured["ok"], false);
+        assert!(structured["summary"]
+            .as_str()
+            .unwrap()
+            .starts_with("`from_end` scans the whole table"));
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_fetch_all_stops_on_repeated_token() {
+        let (server, mock_server) = setup().await;
+        mount_rows_page(&mock_server, None, &["r1"], Some("tok2")).await;
+        // The API hands back the token it was just given
+        mount_rows_page(&mock_server, Some("tok2"), &["r2"], Some("tok2")).await;
+
+        let result = server
+            .get_rows(Parameters(fetch_all_params()))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 2 rows across 2 pages (stopped early"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_stops_at_output_budget() {
+        let (server, mock_server) = setup().await;
+        for (token, ids, next) in [
+            (None, ["r1"], Some("tok2")),
+            (Some("tok2"), ["r2"], Some("tok3")),
+            (Some("tok3"), ["r3"], Some("tok4")),
+        ] {
+            let items: Vec<serde_json::Value> = ids
+                .iter()
+                .map(|id| serde_json::json!({"id": id, "name": id, "values": {}}))
+                .collect();
+            let mut mock = Mock::given(method("GET"))
+                .and(path("/docs/doc1/tables/tbl1/rows"))
+                .and(query_param("limit", "1"));
+            mock = match token {
+                Some(token) => mock.and(query_param("pageToken", token)),
+                None => mock.and(wiremock::matchers::query_param_is_missing("pageToken")),
+            };
+            mock.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": items,
+                "nextPageToken": next
+            })))
+            .mount(&mock_server)
+            .await;
+        }
+        let page: Vec<Row> = serde_json::from_value(serde_json::json!([
+            {"id": "r1", "name": "r1", "values": {}}
+        ]))
+        .unwrap();
+        let page_bytes = server.rendered_len(&page).unwrap();
+
+        // Room for two pages but not a third
+        let budget = u32::try_from(page_bytes * 5 / 2).unwrap();
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                limit: Some(1),
+                fetch_all: None,
+                max_output_bytes: Some(budget),
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with(&format!(
+            "Found 2 rows across 2 pages (stopped at the {budget}-byte output budget"
+        )));
+        assert!(text.contains("r2"));
+        assert!(!text.contains("r3"));
+        assert!(text.ends_with("Next page token: tok3"));
+
+        // The token picks up at the page that did not fit
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                limit: Some(1),
+                fetch_all: None,
+                page_token: Some("tok3".to_string()),
+                from_end: None,
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 1 row"));
+        assert!(text.contains("r3"));
+        assert!(text.ends_with("Next page token: tok4"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_rows_reports_changes() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server
+            .diff_rows(Parameters(DiffRowsParams {
+                before: vec![
+                    serde_json::json!({"id": "r1", "name": "Ship", "values": {"Status": "Open"}}),
+                    serde_json::json!({"id": "r2", "name": "Docs", "values": {"Status": "Open"}}),
+                ],
+                after: vec![
+                    serde_json::json!({"id": "r1", "name": "Ship", "values": {"Status": "Done"}}),
+                    serde_json::json!({"id": "r3", "name": "QA", "values": {"Status": "Open"}}),
+                ],
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with(
+            "1 added, 1 removed, 1 changed, 0 unchanged\n+ r3\n- r2\n~ r1\n    Status: \"Open\" -> \"Done\""
+        ));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["changed"][0]["cells"][0]["new"], "Done");
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_delta_reports_changed_cell() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("useColumnNames", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "name": "Ship", "values": {"Name": "Ship", "Status": "Done"}},
+                    {"id": "r2", "name": "Docs", "values": {"Name": "Docs", "Status": "Open"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let baseline = serde_json::from_value(serde_json::json!({
+            "r1": {"Name": "Ship", "Status": "Open"},
+            "r2": {"Name": "Docs", "Status": "Open"}
+        }))
+        .unwrap();
+        let result = server
+            .get_rows_delta(Parameters(GetRowsDeltaParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                baseline,
+                use_column_names: Some(true),
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("0 added, 0 removed, 1 changed, 1 unchanged"));
+        assert!(text.contains("~ r1\n    Status: \"Open\" -> \"Done\""));
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        assert_eq!(
+            data["changed"][0]["cells"],
+            serde_json::json!([{"column": "Status", "old": "Open", "new": "Done"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_rows_rejects_rows_without_id() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server
+            .diff_rows(Parameters(DiffRowsParams {
+                before: vec![],
+                after: vec![serde_json::json!({"values": {"Status": "Done"}})],
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Invalid row 0 in `after`"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_with_query() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("query", "Status:\"Active\""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "name": "Row 1", "values": {"Status": "Active"}}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: Some(10),
+                query: Some("Status:\"Active\"".to_string()),
+                filter: None,
+                redact_columns: None,
+                dedupe: None,
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 rows"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_with_filter_escapes_quotes() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("query", r#""Status":"Won\"t Fix""#))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "values": {"Status": "Won\"t Fix"}}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: None,
+                filter: Some(RowFilter {
+                    column: "Status".to_string(),
+                    value: serde_json::json!("Won\"t Fix"),
+                }),
+                redact_columns: None,
+                dedupe: None,
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 rows"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_rejects_query_and_filter() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: Some("Status:\"Active\"".to_string()),
+                filter: Some(RowFilter {
+                    column: "Status".to_string(),
+                    value: serde_json::json!("Active"),
+                }),
+                redact_columns: None,
+                dedupe: None,
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_columnar() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "values": {"Name": "Alice", "Score": 95}},
+                    {"id": "r2", "values": {"Name": "Bob"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: None,
+                filter: None,
+                redact_columns: None,
+                dedupe: None,
+                format: Some(RowsFormat::Columnar),
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        let json = text
+            .split("```json\n")
+            .nth(1)
+            .and_then(|s| s.strip_suffix("\n```"))
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(value["row_ids"], serde_json::json!(["r1", "r2"]));
+        assert_eq!(
+            value["columns"]["Name"],
+            serde_json::json!(["Alice", "Bob"])
+        );
+        assert_eq!(value["columns"]["Score"], serde_json::json!([95, null]));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_sends_sort_visibility_and_value_format() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("sortBy", "updatedAt"))
+            .and(query_param("visibleOnly", "true"))
+            .and(query_param("valueFormat", "rich"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "values": {}}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                sort_by: Some(RowSort::UpdatedAt),
+                visible_only: Some(true),
+                value_format: Some(ValueFormat::Rich),
+                limit: None,
+                fetch_all: None,
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 1 rows"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_omits_unset_sort_and_visibility() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(wiremock::matchers::query_param_is_missing("sortBy"))
+            .and(wiremock::matchers::query_param_is_missing("visibleOnly"))
+            .and(wiremock::matchers::query_param_is_missing("valueFormat"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": []
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        server
+            .get_rows(Parameters(GetRowsParams {
+                limit: None,
+                fetch_all: None,
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_csv() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "values": {"Name": "Alice", "Score": 95}},
+                    {"id": "r2", "values": {"Name": "Bob", "Team": "Red, Blue"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                format: Some(RowsFormat::Csv),
+                limit: None,
+                fetch_all: None,
+                ..fetch_all_params()
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 2 rows"));
+        assert!(text.contains("```csv\nName,Score,Team\nAlice,95,\nBob,,\"Red, Blue\"\n```"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_reference_ids() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("valueFormat", "rich"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "i-1", "values": {
+                        "Task": "Ship it",
+                        "Owner": {
+                            "@type": "StructuredValue",
+                            "additionalType": "row",
+                            "name": "Alice",
+                            "url": "https://coda.io/d/Team_ddoc1#People_tu1/r5",
+                            "tableId": "grid-people",
+                            "rowId": "i-5"
+                        }
+                    }},
+                    {"id": "i-2", "values": {"Task": "Plan"}}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: None,
+                filter: None,
+                redact_columns: None,
+                dedupe: None,
+                format: None,
+                reference_ids: Some(true),
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        let refs_json = text
+            .split("References:\n```json\n")
+            .nth(1)
+            .and_then(|s| s.strip_suffix("\n```"))
+            .unwrap();
+        let refs: serde_json::Value = serde_json::from_str(refs_json).unwrap();
+        assert_eq!(
+            refs,
+            serde_json::json!({"i-1": [{
+                "column": "Owner",
+                "rowId": "i-5",
+                "tableId": "grid-people",
+                "docId": "doc1",
+                "name": "Alice"
+            }]})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_limit_capped() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("limit", "1000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: Some(9999),
+                query: None,
+                filter: None,
+                redact_columns: None,
+                dedupe: None,
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 0 rows"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_dedupe() {
+        let (server, mock_server) = setup().await;
+
+        // Overlapping items, as seen when results straddle a page boundary
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r3", "values": {"Name": "Carol"}},
+                    {"id": "r1", "values": {"Name": "Alice"}},
+                    {"id": "r2", "values": {"Name": "Bob"}},
+                    {"id": "r3", "values": {"Name": "Carol"}},
+                    {"id": "r2", "values": {"Name": "Bob"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: None,
+                filter: None,
+                redact_columns: None,
+                dedupe: Some(true),
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 3 rows"));
+        let r1 = text.find("\"r1\"").unwrap();
+        let r2 = text.find("\"r2\"").unwrap();
+        let r3 = text.find("\"r3\"").unwrap();
+        assert!(r1 < r2 && r2 < r3);
+    }
+
+    #[tokio::test]
+    async fn test_check_query_valid() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("limit", "1"))
+            .and(query_param("query", "\"Status\":\"Done\""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "r1", "name": "Row 1", "values": {"c1": "Done"}}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .check_query(Parameters(CheckQueryParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                query: "\"Status\":\"Done\"".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Query ok; it matches rows");
+        assert!(!text.contains("Row 1"));
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        assert_eq!(data["valid"], true);
+        assert_eq!(data["matches_rows"], true);
+    }
+
+    #[tokio::test]
+    async fn test_check_query_reports_rejection_message() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "statusCode": 400,
+                "statusMessage": "Bad Request",
+                "message": "Invalid query: unknown column 'Stat'"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .check_query(Parameters(CheckQueryParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                query: "Stat:Done".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Query rejected: Invalid query: unknown column 'Stat'");
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        assert_eq!(data["valid"], false);
+        assert_eq!(data["message"], "Invalid query: unknown column 'Stat'");
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_multi_annotates_provenance() {
+        let (server, mock_server) = setup().await;
+
+        for (table_id, name, row_id) in [
+            ("grid-tasks", "Tasks", "i-task"),
+            ("grid-bugs", "Bugs", "i-bug"),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/docs/doc1/tables/{table_id}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "id": table_id,
+                    "name": name
+                })))
+                .mount(&mock_server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(format!("/docs/doc1/tables/{table_id}/rows")))
+                .and(query_param("query", "\"Status\":\"Open\""))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [{"id": row_id, "values": {"Status": "Open"}}]
+                })))
+                .mount(&mock_server)
+                .await;
+        }
+
+        let result = server
+            .get_rows_multi(Parameters(GetRowsMultiParams {
+                sources: vec![
+                    RowSource {
+                        doc_id: "doc1".to_string(),
+                        table_id: "grid-tasks".to_string(),
+                    },
+                    RowSource {
+                        doc_id: "doc1".to_string(),
+                        table_id: "grid-bugs".to_string(),
+                    },
+                ],
+                limit: None,
+                query: Some("\"Status\":\"Open\"".to_string()),
+                redact_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("\"table_name\": \"Tasks\""));
+        let tables = result.structured_content.unwrap()["data"]["succeeded"].clone();
+        assert_eq!(tables.as_array().unwrap().len(), 2);
+        assert_eq!(tables[0]["doc_id"], "doc1");
+        assert_eq!(tables[0]["table_id"], "grid-tasks");
+        assert_eq!(tables[0]["table_name"], "Tasks");
+        assert_eq!(tables[0]["rows"][0]["id"], "i-task");
+        assert_eq!(tables[1]["table_id"], "grid-bugs");
+        assert_eq!(tables[1]["table_name"], "Bugs");
+        assert_eq!(tables[1]["rows"][0]["id"], "i-bug");
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_multi_reports_partial_failure() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/grid-tasks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "grid-tasks",
+                "name": "Tasks"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/grid-tasks/rows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"id": "i-task", "values": {}}]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/grid-gone"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/grid-gone/rows"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows_multi(Parameters(GetRowsMultiParams {
+                sources: vec![
+                    RowSource {
+                        doc_id: "doc1".to_string(),
+                        table_id: "grid-tasks".to_string(),
+                    },
+                    RowSource {
+                        doc_id: "doc1".to_string(),
+                        table_id: "grid-gone".to_string(),
+                    },
+                ],
+                limit: None,
+                query: None,
+                redact_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Found 1 rows across 1 tables (1 failed)"));
+        assert!(text.contains("- doc1/grid-gone FAILED: Not found"));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["warnings"].as_array().unwrap().len(), 1);
+        let data = &envelope["data"];
+        assert_eq!(data["succeeded"][0]["table_id"], "grid-tasks");
+        assert_eq!(
+            data["failed"],
+            serde_json::json!([{
+                "input": {"doc_id": "doc1", "table_id": "grid-gone"},
+                "error_category": "not_found",
+                "message": CodaError::NotFound.to_string()
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_multi_requires_sources() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server
+            .get_rows_multi(Parameters(GetRowsMultiParams {
+                sources: vec![],
+                limit: None,
+                query: None,
+                redact_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_table_data_quality_fill_rates() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("pageToken", "page2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r3", "values": {"Name": "Cy", "Email": "  ", "Tags": []}},
+                    {"id": "r4", "values": {"Name": "Di", "Email": null, "Tags": ["x"]}}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("limit", "500"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "values": {"Name": "Al", "Email": "al@x.io", "Tags": []}},
+                    {"id": "r2", "values": {"Name": "Bo", "Email": "", "Tags": []}}
+                ],
+                "nextPageToken": "page2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .table_data_quality(Parameters(TableDataQualityParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                max_rows: None,
+            }))
+            .await
+            .unwrap();
+
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["rows_sampled"], 4);
+        assert_eq!(envelope["data"]["truncated"], false);
+        let columns = envelope["data"]["columns"].as_array().unwrap();
+        let ranked: Vec<_> = columns
+            .iter()
+            .map(|c| (c["column"].as_str().unwrap(), c["filled"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(ranked, vec![("Name", 4), ("Email", 1), ("Tags", 1)]);
+        assert_eq!(columns[1]["percent"], 25.0);
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Profiled 3 columns over 4 rows"));
+        assert!(text.contains("- Name: 4/4 (100%)"));
+    }
+
+    #[tokio::test]
+    async fn test_column_profile_numeric_column() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns/Score"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "c-score", "name": "Score"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("valueFormat", "simple"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "values": {"c-score": 10}},
+                    {"id": "r2", "values": {"c-score": 9.5}},
+                    {"id": "r3", "values": {"c-score": 10}},
+                    {"id": "r4", "values": {"c-score": 100}},
+                    {"id": "r5", "values": {"c-score": ""}}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .column_profile(Parameters(ColumnProfileParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                column_id: "Score".to_string(),
+                max_rows: None,
+                top_k: Some(2),
+            }))
+            .await
+            .unwrap();
+
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["rows_scanned"], 5);
+        let profile = &data["profile"];
+        assert_eq!(profile["count"], 4);
+        assert_eq!(profile["empty"], 1);
+        assert_eq!(profile["distinct"], 3);
+        // Numeric, so 100 is the max rather than sorting before 9.5
+        assert_eq!(profile["min"], 9.5);
+        assert_eq!(profile["max"], 100);
+        assert_eq!(
+            profile["top_values"],
+            serde_json::json!([{"value": 10, "count": 2}, {"value": 100, "count": 1}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_table_data_quality_truncates_at_max_rows() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(query_param("limit", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "r1", "values": {"Name": "Al"}},
+                    {"id": "r2", "values": {"Name": ""}}
+                ],
+                "nextPageToken": "page2"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .table_data_quality(Parameters(TableDataQualityParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                max_rows: Some(2),
+            }))
+            .await
+            .unwrap();
+
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["truncated"], true);
+        assert_eq!(envelope["data"]["columns"][0]["percent"], 50.0);
+        assert_eq!(envelope["warnings"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_row_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "name": "Row 1",
+                "values": {"Name": "Alice", "Score": 95}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_row(Parameters(GetRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                redact_columns: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Row: r1"));
+    }
+
+    #[tokio::test]
+    async fn test_get_rows_redacts_columns() {
+        let (server, mock_server) = setup_with_config(Config {
+            redact_columns: vec!["email".to_string()],
+            ..Config::default()
+        })
+        .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{
+                    "id": "r1",
+                    "values": {
+                        "Name": "Alice",
+                        "Email": "alice@example.com",
+                        "SSN": "123-45-6789"
+                    }
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_rows(Parameters(GetRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                limit: None,
+                query: None,
+                filter: None,
+                redact_columns: Some(vec!["SSN".to_string()]),
+                dedupe: None,
+                format: None,
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+                fetch_all: None,
+                max_output_bytes: None,
+                page_token: None,
+                from_end: None,
+                sort_by: None,
+                visible_only: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Alice"));
+        assert!(!text.contains("alice@example.com"));
+        assert!(!text.contains("123-45-6789"));
+        assert_eq!(text.matches("[REDACTED]").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_row_redacts_columns() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "values": {"Name": "Alice", "Salary": 120_000}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_row(Parameters(GetRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                redact_columns: Some(vec!["salary".to_string()]),
+                reference_ids: None,
+                use_column_names: None,
+                value_format: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Alice"));
+        assert!(text.contains("[REDACTED]"));
+        assert!(!text.contains("120000"));
+    }
+
+    fn wait_params(max_polls: Option<u32>) -> WaitForRowValueParams {
+        WaitForRowValueParams {
+            doc_id: "doc1".to_string(),
+            table_id: "tbl1".to_string(),
+            row_id: "r1".to_string(),
+            column_name: "Status".to_string(),
+            expected_value: serde_json::json!("Sent"),
+            max_polls,
+            interval_secs: Some(0),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_row_value_matches_on_second_poll() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "values": {"Status": "Pending"}
+            })))
+            .up_to_n_times(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "values": {"Status": "Sent"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .wait_for_row_value(Parameters(wait_params(None)))
+            .await
+            .unwrap();
+
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["matched"], true);
+        assert_eq!(envelope["data"]["polls"], 2);
+        assert_eq!(envelope["data"]["value"], "Sent");
+        assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_row_value_gives_up_after_max_polls() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "values": {"Status": "Pending"}
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .wait_for_row_value(Parameters(wait_params(Some(1))))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["matched"], false);
+        assert_eq!(envelope["data"]["value"], "Pending");
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_row_value_unknown_column() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "r1",
+                "values": {"State": "Pending"}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .wait_for_row_value(Parameters(wait_params(None)))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Column 'Status' not found"));
+    }
+
+    #[tokio::test]
+    async fn test_get_mutation_status_waits_for_completion() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/mutationStatus/req-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"completed": false})),
+            )
+            .up_to_n_times(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/mutationStatus/req-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"completed": true})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_mutation_status(Parameters(GetMutationStatusParams {
+                request_id: "req-1".to_string(),
+                wait: Some(true),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Mutation 'req-1' completed");
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        assert_eq!(data["completed"], true);
+        assert_eq!(data["polls"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_mutation_status_without_wait_checks_once() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/mutationStatus/req-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"completed": false})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_mutation_status(Parameters(GetMutationStatusParams {
+                request_id: "req-1".to_string(),
+                wait: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(text, "Mutation 'req-1' still pending");
+        assert_eq!(
+            result.structured_content.as_ref().unwrap()["data"]["completed"],
+            false
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_mutation_result_polls_then_fetches_rows() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/mutationStatus/req-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"completed": false})),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/mutationStatus/req-1"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"completed": true})),
+            )
+            .mount(&mock_server)
+            .await;
+        for (row_id, name) in [("r1", "Alice"), ("r2", "Bob")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/docs/doc1/tables/tbl1/rows/{row_id}")))
+                .and(query_param("useColumnNames", "true"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "id": row_id,
+                    "values": {"Name": name}
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r3"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_mutation_result(Parameters(GetMutationResultParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                request_id: Some("req-1".to_string()),
+                row_ids: Some(vec!["r1".to_string(), "r2".to_string(), "r3".to_string()]),
+                max_polls: Some(3),
+                interval_secs: Some(0),
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Mutation completed after 1 polls; fetched 2 of 3 rows"));
+        let structured = result.structured_content.as_ref().unwrap();
+        assert_eq!(structured["data"]["rows"][0]["values"]["Name"], "Alice");
+        assert_eq!(structured["data"]["rows"][1]["values"]["Name"], "Bob");
+        assert_eq!(structured["warnings"][0], "Rows not found: r3");
+
+        // Status was polled to completion before any row was read
+        let requests = mock_server.received_requests().await.unwrap();
+        let paths: Vec<&str> = requests.iter().map(|r| r.url.path()).collect();
+        assert_eq!(
+            paths[..2],
+            ["/mutationStatus/req-1", "/mutationStatus/req-1"]
+        );
+        assert_eq!(paths.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_get_mutation_result_requires_target() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server
+            .get_mutation_result(Parameters(GetMutationResultParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                request_id: None,
+                row_ids: None,
+                max_polls: None,
+                interval_secs: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_add_row_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-abc",
+                "addedRowIds": ["new-row-1"]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let mut cells = std::collections::HashMap::new();
+        cells.insert(
+            "Name".to_string(),
+            serde_json::Value::String("Charlie".to_string()),
+        );
+        cells.insert(
+            "Score".to_string(),
+            serde_json::Value::Number(serde_json::Number::from(100)),
+        );
+
+        let result = server
+            .add_row(Parameters(AddRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                cells,
+                coerce: None,
+                key_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Row added successfully"));
+        assert!(text.contains("req-abc"));
+        assert!(text.contains("new-row-1"));
+
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["ok"], true);
+        assert_eq!(envelope["operation"], "add_row");
+        assert_eq!(envelope["summary"], "Row added");
+        assert_eq!(envelope["data"]["addedRowIds"][0], "new-row-1");
+        assert_eq!(envelope["warnings"][0], MUTATION_DELAY_NOTE);
+        assert!(!text.contains("Row IDs by input"));
+    }
+
+    #[tokio::test]
+    async fn test_add_row_sends_key_columns_only_when_given() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-abc",
+                "addedRowIds": []
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let params = |key_columns: Option<Vec<String>>| AddRowParams {
+            alias: None,
+            doc_id: "doc1".to_string(),
+            table_id: "tbl1".to_string(),
+            cells: std::collections::HashMap::from([(
+                "Email".to_string(),
+                serde_json::json!("dana@example.com"),
+            )]),
+            coerce: None,
+            key_columns,
+        };
+        let result = server
+            .add_row(Parameters(params(Some(vec!["Email".to_string()]))))
+            .await
+            .unwrap();
+        server.add_row(Parameters(params(None))).await.unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Row upserted on Email."));
+        assert_eq!(
+            result.structured_content.unwrap()["summary"],
+            "Row upserted"
+        );
+        let bodies: Vec<serde_json::Value> = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| request.body_json().unwrap())
+            .collect();
+        assert_eq!(bodies[0]["keyColumns"], serde_json::json!(["Email"]));
+        assert!(bodies[1].get("keyColumns").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_add_rows_sends_all_rows_in_one_request() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-many",
+                "addedRowIds": ["i-1", "i-2", "i-3"]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let row = |name: &str, score: i64| {
+            std::collections::HashMap::from([
+                ("Name".to_string(), serde_json::json!(name)),
+                ("Score".to_string(), serde_json::json!(score)),
+            ])
+        };
+        let result = server
+            .add_rows(Parameters(AddRowsParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                rows: vec![row("Ann", 1), row("Bo", 2), row("Cy", 3)],
+                key_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Added 3 rows."));
+        assert!(text.contains("Added row IDs: i-1, i-2, i-3"));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["addedRowIds"][2], "i-3");
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let body: serde_json::Value = requests[0].body_json().unwrap();
+        assert!(body.get("keyColumns").is_none());
+        let rows = body["rows"].as_array().unwrap();
+        assert_eq!(rows.len(), 3);
+        for (row, (name, score)) in rows.iter().zip([("Ann", 1), ("Bo", 2), ("Cy", 3)]) {
+            let mut cells: Vec<(String, serde_json::Value)> = row["cells"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|cell| {
+                    (
+                        cell["column"].as_str().unwrap().to_string(),
+                        cell["value"].clone(),
+                    )
+                })
+                .collect();
+            cells.sort_by(|a, b| a.0.cmp(&b.0));
+            assert_eq!(
+                cells,
+                vec![
+                    ("Name".to_string(), serde_json::json!(name)),
+                    ("Score".to_string(), serde_json::json!(score)),
+                ]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_row_renders_row_id_map() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-abc",
+                "addedRowIds": ["new-row-1"],
+                "rowIdMap": {"0": "new-row-1"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .add_row(Parameters(AddRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                cells: std::collections::HashMap::from([(
+                    "Name".to_string(),
+                    serde_json::json!("Dana"),
+                )]),
+                coerce: None,
+                key_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Row IDs by input:\n- 0 -> new-row-1\n\nNote:"));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["rowIdMap"]["0"], "new-row-1");
+    }
+
+    #[tokio::test]
+    async fn test_add_row_coerces_to_column_types() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "c-score", "name": "Score", "format": {"type": "number"}},
+                    {"id": "c-done", "name": "Done", "format": {"type": "checkbox"}}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-abc",
+                "addedRowIds": ["new-row-1"]
+            })))
+            .mount(&mock_server)
+            .await;
+        let cells = || {
+            std::collections::HashMap::from([
+                ("Done".to_string(), serde_json::json!("yes")),
+                ("Score".to_string(), serde_json::json!("12.5")),
+            ])
+        };
+        let sent_cells = |body: &[u8]| {
+            let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+            body["rows"][0]["cells"].as_array().unwrap().clone()
+        };
+
+        let result = server
+            .add_row(Parameters(AddRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                cells: cells(),
+                coerce: Some(true),
+                key_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(
+            text.contains("Coerced values:\n- Done: \"yes\" -> true\n- Score: \"12.5\" -> 12.5")
+        );
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["coercions"][1]["column"], "Score");
+        let requests = mock_server.received_requests().await.unwrap();
+        let sent = sent_cells(&requests[1].body);
+        assert!(sent.contains(&serde_json::json!({"column": "Done", "value": true})));
+        assert!(sent.contains(&serde_json::json!({"column": "Score", "value": 12.5})));
+
+        // Without `coerce`, values go out as given and columns aren't fetched
+        let result = server
+            .add_row(Parameters(AddRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                cells: cells(),
+                coerce: None,
+                key_columns: None,
+            }))
+            .await
+            .unwrap();
+
+        let envelope = result.structured_content.unwrap();
+        assert!(envelope["data"].get("coercions").is_none());
+        let requests = mock_server.received_requests().await.unwrap();
+        let sent = sent_cells(&requests[2].body);
+        assert!(sent.contains(&serde_json::json!({"column": "Score", "value": "12.5"})));
+    }
+
+    #[tokio::test]
+    async fn test_update_row_coerces_numbers() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "c-score", "name": "Score", "format": {"type": "number"}},
+                    {"id": "c-due", "name": "Due", "format": {"type": "date"}}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-xyz"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .update_row(Parameters(UpdateRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                cells: std::collections::HashMap::from([
+                    ("c-score".to_string(), serde_json::json!("42")),
+                    ("Due".to_string(), serde_json::json!("2024-01-15")),
+                ]),
+                coerce: Some(true),
+            }))
+            .await
+            .unwrap();
+
+        let requests = mock_server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        let cells = body["row"]["cells"].as_array().unwrap();
+        assert!(cells.contains(&serde_json::json!({"column": "c-score", "value": 42})));
+        // Dates pass through unchanged
+        assert!(cells.contains(&serde_json::json!({"column": "Due", "value": "2024-01-15"})));
+
+        let envelope = result.structured_content.unwrap();
+        let coercions = envelope["data"]["coercions"].as_array().unwrap();
+        assert_eq!(coercions.len(), 1);
+        assert_eq!(coercions[0]["to"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_update_row_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-xyz"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let mut cells = std::collections::HashMap::new();
+        cells.insert(
+            "Status".to_string(),
+            serde_json::Value::String("Done".to_string()),
+        );
+
+        let result = server
+            .update_row(Parameters(UpdateRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                cells,
+                coerce: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Row updated successfully"));
+        assert!(text.contains("req-xyz"));
+    }
+
+    #[tokio::test]
+    async fn test_append_to_multiselect_keeps_existing_values() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns/Tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "c-tags",
+                "name": "Tags",
+                "format": {"type": "select", "isArray": true}
+            })))
+            .mount(&mock_server)
+            .await;
+        for (row_id, tags) in [
+            ("r1", serde_json::json!(["bug", "ui"])),
+            ("r2", serde_json::json!(["urgent"])),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(format!("/docs/doc1/tables/tbl1/rows/{row_id}")))
+                .and(query_param("valueFormat", "simpleWithArrays"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "id": row_id,
+                    "values": {"Tags": tags}
+                })))
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .and(body_json(serde_json::json!({
+                "row": {"cells": [{"column": "c-tags", "value": ["bug", "ui", "urgent"]}]}
+            })))
+            .respond_with(
+                ResponseTemplate::new(202).set_body_json(serde_json::json!({"requestId": "req-1"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // r2 already has the tag, so it is not written
+        Mock::given(method("PUT"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r2"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .append_to_multiselect(Parameters(AppendToMultiselectParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                row_ids: Some(vec!["r1".to_string(), "r2".to_string()]),
+                query: None,
+                column_name: "Tags".to_string(),
+                values_to_add: vec!["urgent".to_string()],
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Added options to 1 rows in 'Tags' (1 already had them)"));
+        assert!(text.contains("- r1: +urgent"));
+        assert!(text.contains("- r2: unchanged"));
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        assert_eq!(data["succeeded"][0]["request_id"], "req-1");
+    }
+
+    #[tokio::test]
+    async fn test_append_to_multiselect_rejects_other_columns() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1/columns/Status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "c-status",
+                "name": "Status",
+                "format": {"type": "select"}
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .append_to_multiselect(Parameters(AppendToMultiselectParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                row_ids: None,
+                query: Some("Status:Open".to_string()),
+                column_name: "Status".to_string(),
+                values_to_add: vec!["Done".to_string()],
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(
+            text,
+            "Column 'Status' is not a multi-select column (format type: select)."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_append_to_multiselect_requires_row_selection() {
+        let (server, mock_server) = setup().await;
+
+        let params =
+            |row_ids: Option<Vec<String>>, query: Option<&str>| AppendToMultiselectParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                row_ids,
+                query: query.map(str::to_string),
+                column_name: "Tags".to_string(),
+                values_to_add: vec!["urgent".to_string()],
+            };
+        let text = |result: &CallToolResult| result.content[0].raw.as_text().unwrap().text.clone();
+
+        let result = server
+            .append_to_multiselect(Parameters(params(None, None)))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(
+            text(&result),
+            "Pass `row_ids` or `query` to select the rows."
+        );
+
+        let result = server
+            .append_to_multiselect(Parameters(params(
+                Some(vec!["r1".to_string()]),
+                Some("Status:Open"),
+            )))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(text(&result), "Pass either `row_ids` or `query`, not both.");
+
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_row_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(202))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_row(Parameters(DeleteRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Row deleted successfully"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_row_error() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_row(Parameters(DeleteRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                row_id: "r1".to_string(),
+                confirm: None,
+            }))
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_rows_sends_ids_in_one_request() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .and(body_json(serde_json::json!({"rowIds": ["r1", "r2", "r3"]})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-del",
+                "rowIds": ["r1", "r2", "r3"]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_rows(Parameters(DeleteRowsParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                row_ids: vec!["r1".to_string(), "r2".to_string(), "r3".to_string()],
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Deleted 3 rows."));
+        assert!(text.contains("Request ID: req-del"));
+        assert!(text.contains("Deleted row IDs: r1, r2, r3"));
+        let data = &result.structured_content.as_ref().unwrap()["data"];
+        assert_eq!(data["requestId"], "req-del");
+        assert_eq!(data["rowIds"], serde_json::json!(["r1", "r2", "r3"]));
+    }
+
+    #[tokio::test]
+    async fn test_delete_rows_rejects_empty_list() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_rows(Parameters(DeleteRowsParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                row_ids: vec![],
+                confirm: None,
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    fn safe_mode_delete_rows_params(confirm: Option<&str>) -> DeleteRowsParams {
+        DeleteRowsParams {
+            doc_id: "doc1".to_string(),
+            table_id: "tbl1".to_string(),
+            alias: None,
+            row_ids: vec!["r1".to_string(), "r2".to_string()],
+            confirm: confirm.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_delete_rows_requires_confirm() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .delete_rows(Parameters(safe_mode_delete_rows_params(None)))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("delete_rows requires `confirm`"));
+        assert!(text.contains("'tbl1'"));
+    }
+
+    #[tokio::test]
+    async fn test_safe_mode_delete_rows_confirm_by_id_or_name() {
+        let (server, mock_server) = setup_safe_mode().await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/tables/tbl1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "tbl1",
+                "name": "Tasks"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-del",
+                "rowIds": ["r1", "r2"]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let refused = server
+            .delete_rows(Parameters(safe_mode_delete_rows_params(Some("r1"))))
+            .await
+            .unwrap();
+        assert_eq!(refused.is_error, Some(true));
+        let text = &refused.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("does not match"));
+
+        for confirm in ["tbl1", "Tasks"] {
+            let result = server
+                .delete_rows(Parameters(safe_mode_delete_rows_params(Some(confirm))))
+                .await
+                .unwrap();
+            assert_eq!(result.is_error, Some(false));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_push_button_success() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1/buttons/c-go"))
+            .and(body_json(serde_json::json!({})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-btn",
+                "rowId": "r1",
+                "columnId": "c-go"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .push_button(Parameters(PushButtonParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                row_id: "r1".to_string(),
+                column_id: "c-go".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Pushed button 'c-go' on row 'r1'"));
+        assert!(text.contains("Request ID: req-btn"));
+    }
+
+    #[tokio::test]
+    async fn test_push_button_resolves_alias() {
+        let config = Config {
+            aliases: [(
+                "tasks".to_string(),
+                TableAlias {
+                    doc_id: "AbCd".to_string(),
+                    table_id: "grid-1".to_string(),
+                },
+            )]
+            .into(),
+            ..Config::default()
+        };
+        let (server, mock_server) = setup_with_config(config).await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/AbCd/tables/grid-1/rows/r1/buttons/c-go"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "requestId": "req-btn",
+                "rowId": "r1",
+                "columnId": "c-go"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .push_button(Parameters(PushButtonParams {
+                doc_id: String::new(),
+                table_id: String::new(),
+                alias: Some("tasks".to_string()),
+                row_id: "r1".to_string(),
+                column_id: "c-go".to_string(),
+            }))
+            .await
+            .unwrap();
+
+        assert_ne!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_push_button_on_non_button_column() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows/r1/buttons/c-name"))
+            .respond_with(
+                ResponseTemplate::new(400).set_body_string("Column c-name is not a button"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .push_button(Parameters(PushButtonParams {
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                alias: None,
+                row_id: "r1".to_string(),
+                column_id: "c-name".to_string(),
+            }))
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .unwrap();
 
-        Ok(CallToolResult::success(vec![Content::text(
-            "Row deleted successfully.\n\nNote: Changes may take a few seconds to appear."
-                .to_string(),
-        )]))
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("API error 400: Column c-name is not a button"));
+        assert!(text.contains("Column 'c-name' may not be a button column."));
     }
 
-    // === Formula Tools ===
-
-    #[tool(description = "List all named formulas in a document.")]
-    async fn list_formulas(
-        &self,
-        Parameters(params): Parameters<ListFormulasParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/formulas", params.doc_id);
+    #[tokio::test]
+    async fn test_trigger_automation_returns_request_id() {
+        let (server, mock_server) = setup().await;
 
-        tracing::info!("list_formulas: doc_id={}", params.doc_id);
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/hooks/automation/grid-auto-1"))
+            .and(body_json(serde_json::json!({"ticket": 42})))
+            .respond_with(
+                ResponseTemplate::new(202).set_body_json(serde_json::json!({"requestId": "req-7"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
 
-        let formulas: FormulaList = self
-            .client
-            .get(&path)
+        let result = server
+            .trigger_automation(Parameters(TriggerAutomationParams {
+                doc_id: "doc1".to_string(),
+                rule_id: "grid-auto-1".to_string(),
+                payload: Some(serde_json::json!({"ticket": 42})),
+            }))
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let summary = format!("Found {} formulas", formulas.items.len());
-        let json = serde_json::to_string_pretty(&formulas.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .unwrap();
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Request ID: req-7"));
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["requestId"], "req-7");
     }
 
-    #[tool(description = "Get a specific formula's current value.")]
-    async fn get_formula(
-        &self,
-        Parameters(params): Parameters<GetFormulaParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/formulas/{}", params.doc_id, params.formula_id);
+    #[tokio::test]
+    async fn test_trigger_automation_unknown_rule_is_tool_error() {
+        let (server, mock_server) = setup().await;
 
-        tracing::info!(
-            "get_formula: doc_id={}, formula_id={}",
-            params.doc_id,
-            params.formula_id
-        );
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/hooks/automation/missing"))
+            .and(body_json(serde_json::json!({})))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
 
-        let formula: Formula = self
-            .client
-            .get(&path)
+        let result = server
+            .trigger_automation(Parameters(TriggerAutomationParams {
+                doc_id: "doc1".to_string(),
+                rule_id: "missing".to_string(),
+                payload: None,
+            }))
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let json = serde_json::to_string_pretty(&formula)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            .unwrap();
 
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "Formula: {}\n\n```json\n{}\n```",
-            formula.name, json
-        ))]))
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Rule 'missing' was not found"));
     }
 
-    // === Control Tools ===
+    // === Formula Tools ===
 
-    #[tool(description = "List all controls (buttons, sliders, etc.) in a document.")]
-    async fn list_controls(
-        &self,
-        Parameters(params): Parameters<ListControlsParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let path = format!("/docs/{}/controls", params.doc_id);
+    #[tokio::test]
+    async fn test_list_formulas_success() {
+        let (server, mock_server) = setup().await;
 
-        tracing::info!("list_controls: doc_id={}", params.doc_id);
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/formulas"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {"id": "f1", "name": "Total", "value": 42}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
 
-        let controls: ControlList = self
-            .client
-            .get(&path)
+        let result = server
+            .list_formulas(Parameters(ListFormulasParams {
+                doc_id: "doc1".to_string(),
+                dedupe: None,
+            }))
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let summary = format!("Found {} controls", controls.items.len());
-        let json = serde_json::to_string_pretty(&controls.items)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        Ok(CallToolResult::success(vec![Content::text(format!(
-            "{summary}\n\n```json\n{json}\n```"
-        ))]))
-    }
-}
+            .unwrap();
 
-#[tool_handler]
-impl ServerHandler for CodaMcpServer {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            protocol_version: ProtocolVersion::LATEST,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            server_info: Implementation::from_build_env(),
-            instructions: Some(
-                "Coda.io MCP Server - Interact with Coda documents, tables, and rows. \
-                 Requires CODA_API_TOKEN environment variable."
-                    .into(),
-            ),
-        }
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Found 1 formulas"));
     }
-}
-
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    // Initialize logging to stderr (MCP uses stdout for JSON-RPC)
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
-        .init();
-
-    tracing::info!("Starting coda-mcp server v{}", env!("CARGO_PKG_VERSION"));
 
-    // Load configuration
-    let config = Config::from_env()?;
-    tracing::info!("Configuration loaded, base URL: {}", config.base_url);
+    #[tokio::test]
+    async fn test_get_formula_success() {
+        let (server, mock_server) = setup().await;
 
-    // Create HTTP client
-    let client = Arc::new(CodaClient::new(&config));
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/formulas/f1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "f1",
+                "name": "Total",
+                "value": 42
+            })))
+            .mount(&mock_server)
+            .await;
 
-    // Create and run MCP server
-    let server = CodaMcpServer::new(client);
-    let service = server.serve(stdio()).await?;
+        let result = server
+            .get_formula(Parameters(GetFormulaParams {
+                doc_id: "doc1".to_string(),
+                formula_id: "f1".to_string(),
+            }))
+            .await
+            .unwrap();
 
-    tracing::info!("Server running, waiting for requests...");
-    service.waiting().await?;
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Formula: Total"));
+    }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_poll_formula_detects_change() {
+        let (server, mock_server) = setup().await;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use wiremock::matchers::{header, method, path, query_param};
-    use wiremock::{Mock, MockServer, ResponseTemplate};
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/formulas/f1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "f1",
+                "name": "Revenue",
+                "value": 100
+            })))
+            .up_to_n_times(2)
+            .mount(&mock_server)
+            .await;
 
-    async fn setup() -> (CodaMcpServer, MockServer) {
-        let mock_server = MockServer::start().await;
-        let client = Arc::new(CodaClient::new_with_base_url(
-            "test_token",
-            &mock_server.uri(),
-        ));
-        let server = CodaMcpServer::new(client);
-        (server, mock_server)
-    }
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/formulas/f1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "f1",
+                "name": "Revenue",
+                "value": 150
+            })))
+            .mount(&mock_server)
+            .await;
 
-    // === Server Info ===
+        let result = server
+            .poll_formula(Parameters(PollFormulaParams {
+                doc_id: "doc1".to_string(),
+                formula_id: "f1".to_string(),
+                interval_secs: Some(0),
+                max_polls: Some(5),
+                until_value: None,
+            }))
+            .await
+            .unwrap();
 
-    #[test]
-    fn test_get_info() {
-        let mock_client = CodaClient::new_with_base_url("tok", "http://localhost:0");
-        let server = CodaMcpServer::new(Arc::new(mock_client));
-        let info = server.get_info();
-        // from_build_env() uses the rmcp crate name, not our package name
-        assert!(!info.server_info.name.is_empty());
-        assert!(!info.server_info.version.is_empty());
-        assert!(info.instructions.is_some());
-        assert!(info.instructions.unwrap().contains("Coda.io MCP Server"));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Formula 'Revenue' changed after 2 polls"));
+        assert!(text.contains("\"matched\": true"));
+        assert!(text.contains("150"));
     }
 
-    // === Document Tools ===
-
     #[tokio::test]
-    async fn test_list_docs_success() {
+    async fn test_poll_formula_exhausts_polls() {
         let (server, mock_server) = setup().await;
 
         Mock::given(method("GET"))
-            .and(path("/docs"))
-            .and(query_param("limit", "50"))
-            .and(header("Authorization", "Bearer test_token"))
+            .and(path("/docs/doc1/formulas/f1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [
-                    {"id": "doc1", "name": "Doc One"},
-                    {"id": "doc2", "name": "Doc Two"}
-                ]
+                "id": "f1",
+                "name": "Revenue",
+                "value": 100
             })))
+            .expect(3)
             .mount(&mock_server)
             .await;
 
         let result = server
-            .list_docs(Parameters(ListDocsParams {
-                limit: None,
-                query: None,
+            .poll_formula(Parameters(PollFormulaParams {
+                doc_id: "doc1".to_string(),
+                formula_id: "f1".to_string(),
+                interval_secs: Some(0),
+                max_polls: Some(2),
+                until_value: Some(serde_json::json!(200)),
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 2 documents"));
-        assert!(text.contains("Doc One"));
+        assert!(text.contains("did not reach the target value after 2 polls"));
+        assert!(text.contains("\"matched\": false"));
     }
 
     #[tokio::test]
-    async fn test_list_docs_with_query() {
+    async fn test_poll_formula_target_already_reached_skips_polling() {
         let (server, mock_server) = setup().await;
 
         Mock::given(method("GET"))
-            .and(path("/docs"))
-            .and(query_param("query", "project"))
+            .and(path("/docs/doc1/formulas/f1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [{"id": "doc1", "name": "My Project"}]
+                "id": "f1",
+                "name": "Revenue",
+                "value": 200
             })))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
         let result = server
-            .list_docs(Parameters(ListDocsParams {
-                limit: Some(10),
-                query: Some("project".to_string()),
+            .poll_formula(Parameters(PollFormulaParams {
+                doc_id: "doc1".to_string(),
+                formula_id: "f1".to_string(),
+                interval_secs: Some(0),
+                max_polls: Some(5),
+                until_value: Some(serde_json::json!(200)),
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 1 documents"));
+        assert!(text.contains("Formula 'Revenue' reached the target value after 0 polls"));
+        assert!(text.contains("\"polls\": 0"));
     }
 
+    // === Control Tools ===
+
     #[tokio::test]
-    async fn test_list_docs_limit_capped_at_1000() {
+    async fn test_list_controls_success() {
         let (server, mock_server) = setup().await;
 
         Mock::given(method("GET"))
-            .and(path("/docs"))
-            .and(query_param("limit", "1000"))
+            .and(path("/docs/doc1/controls"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": []
+                "items": [
+                    {"id": "c1", "name": "Submit", "controlType": "button"},
+                    {"id": "c2", "name": "Progress", "controlType": "slider", "value": 75}
+                ]
             })))
             .mount(&mock_server)
             .await;
 
         let result = server
-            .list_docs(Parameters(ListDocsParams {
-                limit: Some(5000),
-                query: None,
+            .list_controls(Parameters(ListControlsParams {
+                doc_id: "doc1".to_string(),
+                dedupe: None,
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 0 documents"));
+        assert!(text.contains("Found 2 controls"));
     }
 
     #[tokio::test]
-    async fn test_list_docs_api_error() {
+    async fn test_set_control_value_unsupported() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("GET"))
-            .and(path("/docs"))
-            .respond_with(ResponseTemplate::new(401))
-            .mount(&mock_server)
-            .await;
-
         let result = server
-            .list_docs(Parameters(ListDocsParams {
-                limit: None,
-                query: None,
+            .set_control_value(Parameters(SetControlValueParams {
+                doc_id: "doc1".to_string(),
+                control_id: "ctrl-slider".to_string(),
+                value: serde_json::json!(75),
             }))
-            .await;
+            .await
+            .unwrap();
 
-        assert!(result.is_err());
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert_eq!(
+            text,
+            "Controls can't be set through the Coda API. Change 'ctrl-slider' in the Coda UI instead."
+        );
+        assert_eq!(
+            result.structured_content.unwrap()["operation"],
+            "set_control_value"
+        );
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
     }
 
+    // === Diagnostics Tools ===
+
     #[tokio::test]
-    async fn test_get_doc_success() {
+    async fn test_explain_last_error_none() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server.explain_last_error().await.unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("No failed requests"));
+    }
+
+    #[tokio::test]
+    async fn test_explain_last_error_write_forbidden() {
         let (server, mock_server) = setup().await;
 
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
         Mock::given(method("GET"))
             .and(path("/docs/doc1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
                 "id": "doc1",
-                "name": "Test Document"
+                "name": "Doc"
             })))
             .mount(&mock_server)
             .await;
 
-        let result = server
+        let mut cells = std::collections::HashMap::new();
+        cells.insert("Name".to_string(), serde_json::json!("Alice"));
+        let add = server
+            .add_row(Parameters(AddRowParams {
+                alias: None,
+                doc_id: "doc1".to_string(),
+                table_id: "tbl1".to_string(),
+                cells,
+                coerce: None,
+                key_columns: None,
+            }))
+            .await;
+        assert!(add.is_err());
+
+        // A later successful call does not hide the failure
+        server
             .get_doc(Parameters(GetDocParams {
                 doc_id: "doc1".to_string(),
             }))
             .await
             .unwrap();
 
+        let result = server.explain_last_error().await.unwrap();
+
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Document: Test Document"));
+        assert!(text.contains("POST /docs/doc1/tables/tbl1/rows (HTTP 403)"));
+        assert!(text.contains("lacks write scope"));
     }
 
     #[tokio::test]
-    async fn test_search_docs_success() {
+    async fn test_check_connectivity_reports_unreachable_host() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("GET"))
-            .and(path("/docs"))
-            .and(query_param("query", "hello"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [{"id": "d1", "name": "Hello World"}]
-            })))
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(200))
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .search_docs(Parameters(SearchDocsParams {
-                query: "hello".to_string(),
-            }))
-            .await
-            .unwrap();
+        let result = server.check_connectivity().await.unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 1 documents matching 'hello'"));
+        assert!(text.starts_with("1 of 2 hosts unreachable"));
+        assert!(text.contains(&format!("{}: reachable (HTTP 200,", mock_server.uri())));
+        assert!(text.contains("http://127.0.0.1:1: UNREACHABLE"));
     }
 
     #[tokio::test]
-    async fn test_create_doc_success() {
-        let (server, mock_server) = setup().await;
+    async fn test_get_last_response_headers() {
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            api_token: "secret_token".to_string(),
+            base_url: mock_server.uri(),
+            debug_headers: true,
+            ..Config::default()
+        };
+        let server = CodaMcpServer::new(Arc::new(CodaClient::new(&config)), &config);
 
-        Mock::given(method("POST"))
-            .and(path("/docs"))
-            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
-                "id": "new-doc",
-                "name": "My New Doc"
-            })))
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"id": "doc1", "name": "Doc"}))
+                    .insert_header("x-ratelimit-remaining", "42")
+                    .insert_header("set-cookie", "session=abc"),
+            )
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .create_doc(Parameters(CreateDocParams {
-                title: "My New Doc".to_string(),
-                folder_id: None,
-                source_doc: None,
-                timezone: None,
+        server
+            .get_doc(Parameters(GetDocParams {
+                doc_id: "doc1".to_string(),
             }))
             .await
             .unwrap();
 
+        let result = server.get_last_response_headers().await.unwrap();
+
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Document created successfully"));
-        assert!(text.contains("My New Doc"));
+        assert!(text.contains("x-ratelimit-remaining: 42"));
+        assert!(!text.contains("set-cookie"));
+        assert!(!text.contains("secret_token"));
     }
 
     #[tokio::test]
-    async fn test_create_doc_with_all_options() {
+    async fn test_get_last_response_headers_disabled() {
+        let (server, _mock_server) = setup().await;
+
+        let result = server.get_last_response_headers().await.unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Header capture is disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_whoami_success() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("POST"))
-            .and(path("/docs"))
-            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
-                "id": "new-doc",
-                "name": "From Template"
+        Mock::given(method("GET"))
+            .and(path("/whoami"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "Ada Lovelace",
+                "loginId": "ada@example.com",
+                "type": "user",
+                "tokenName": "mcp",
+                "workspace": {"id": "ws-1", "name": "Analytics"}
             })))
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .create_doc(Parameters(CreateDocParams {
-                title: "From Template".to_string(),
-                folder_id: Some("folder1".to_string()),
-                source_doc: Some("template1".to_string()),
-                timezone: Some("Europe/London".to_string()),
-            }))
-            .await
-            .unwrap();
+        let result = server.whoami().await.unwrap();
 
+        assert_eq!(result.is_error, Some(false));
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Document created successfully"));
+        assert!(text.starts_with(
+            "Authenticated as Ada Lovelace (ada@example.com)\nToken: mcp\nWorkspace: ws-1 (Analytics)"
+        ));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["loginId"], "ada@example.com");
     }
 
     #[tokio::test]
-    async fn test_create_doc_api_error_returns_tool_error() {
+    async fn test_whoami_bad_token() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("POST"))
-            .and(path("/docs"))
-            .respond_with(ResponseTemplate::new(403))
+        Mock::given(method("GET"))
+            .and(path("/whoami"))
+            .respond_with(ResponseTemplate::new(401))
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .create_doc(Parameters(CreateDocParams {
-                title: "Forbidden".to_string(),
-                folder_id: None,
-                source_doc: None,
-                timezone: None,
-            }))
-            .await
-            .unwrap();
+        let result = server.whoami().await.unwrap();
 
-        // create_doc returns CallToolResult::error, not Err
-        assert!(result.is_error.unwrap_or(false));
+        assert_eq!(result.is_error, Some(true));
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Unauthorized."));
+        assert!(text.contains("update CODA_API_TOKEN"));
+        assert_eq!(result.structured_content.unwrap()["status"], 401);
     }
 
     #[tokio::test]
-    async fn test_delete_doc_success() {
+    async fn test_resolve_link() {
         let (server, mock_server) = setup().await;
+        let url = "https://coda.io/d/Roadmap_dAbCd/Tasks_suGrid#Tasks_tuGrid/r5&x=1";
 
-        Mock::given(method("DELETE"))
-            .and(path("/docs/doc1"))
-            .respond_with(ResponseTemplate::new(202))
+        Mock::given(method("GET"))
+            .and(path("/resolveBrowserLink"))
+            // query_param compares decoded values, so this checks the URL survived encoding
+            .and(query_param("url", url))
+            .and(query_param("degradeGracefully", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "type": "apiLink",
+                "href": "https://coda.io/apis/v1/resolveBrowserLink",
+                "browserLink": url,
+                "resource": {
+                    "type": "row",
+                    "id": "i-r5",
+                    "name": "Ship it",
+                    "href": "https://coda.io/apis/v1/docs/AbCd/tables/grid-1/rows/i-r5"
+                }
+            })))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
         let result = server
-            .delete_doc(Parameters(DeleteDocParams {
-                doc_id: "doc1".to_string(),
+            .resolve_link(Parameters(ResolveLinkParams {
+                url: url.to_string(),
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("deleted successfully"));
+        assert!(text.starts_with(
+            "Link resolves to row 'Ship it' (i-r5)\ndoc_id: AbCd\nrow_id: i-r5\ntable_id: grid-1\n\n"
+        ));
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["type"], "apiLink");
+        assert_eq!(data["resource"]["type"], "row");
+        assert_eq!(data["ids"]["table_id"], "grid-1");
     }
 
     #[tokio::test]
-    async fn test_delete_doc_error_returns_tool_error() {
+    async fn test_resolve_link_not_found() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("DELETE"))
-            .and(path("/docs/doc1"))
+        Mock::given(method("GET"))
+            .and(path("/resolveBrowserLink"))
             .respond_with(ResponseTemplate::new(404))
             .mount(&mock_server)
             .await;
 
         let result = server
-            .delete_doc(Parameters(DeleteDocParams {
-                doc_id: "doc1".to_string(),
+            .resolve_link(Parameters(ResolveLinkParams {
+                url: "https://coda.io/d/Gone_dXyz".to_string(),
             }))
             .await
             .unwrap();
 
-        assert!(result.is_error.unwrap_or(false));
+        assert_eq!(result.is_error, Some(true));
+        assert_eq!(result.structured_content.unwrap()["status"], 404);
     }
 
-    // === Page Tools ===
-
     #[tokio::test]
-    async fn test_list_pages_success() {
+    async fn test_structured_output_appends_json_content() {
         let (server, mock_server) = setup().await;
 
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/pages"))
+            .and(path("/docs"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
                 "items": [
-                    {"id": "p1", "name": "Home"},
-                    {"id": "p2", "name": "About"}
+                    {"id": "doc1", "name": "Doc One"},
+                    {"id": "doc2", "name": "Doc Two"}
                 ]
             })))
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .list_pages(Parameters(ListPagesParams {
-                doc_id: "doc1".to_string(),
-            }))
+        let params = || {
+            Parameters(ListDocsParams {
+                limit: None,
+                query: None,
+                page_token: None,
+                dedupe: None,
+            })
+        };
+        let blocks = DataBlocks::default();
+        let mut result = STRUCTURED
+            .scope(blocks.clone(), server.list_docs(params()))
             .await
             .unwrap();
+        blocks.append_to(&mut result);
 
+        assert_eq!(result.content.len(), 2);
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 2 pages"));
+        assert!(text.contains("Found 2 documents"));
+        assert!(text.contains("```json"));
+        let json: serde_json::Value =
+            serde_json::from_str(&result.content[1].raw.as_text().unwrap().text).unwrap();
+        assert_eq!(json[0]["id"], "doc1");
+        assert_eq!(json[1]["name"], "Doc Two");
+
+        // Without the scope, output stays text-only
+        let result = server.list_docs(params()).await.unwrap();
+        assert_eq!(result.content.len(), 1);
     }
 
     #[tokio::test]
-    async fn test_get_page_export_failed() {
+    async fn test_echo_requests_appends_masked_request() {
         let (server, mock_server) = setup().await;
 
-        // Step 1: Initiate export
         Mock::given(method("POST"))
-            .and(path("/docs/doc1/pages/p1/export"))
+            .and(path("/docs/doc1/tables/tbl1/rows"))
             .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
-                "id": "exp1",
-                "status": "inProgress"
+                "requestId": "req-abc",
+                "addedRowIds": ["new-row-1"]
             })))
             .mount(&mock_server)
             .await;
 
-        // Step 2: Poll returns failed
+        let cells = HashMap::from([(
+            "Note".to_string(),
+            serde_json::json!("pasted test_token by mistake"),
+        )]);
+        let echo = EchoLog::default();
+        let mut result = ECHO
+            .scope(
+                echo.clone(),
+                server.add_row(Parameters(AddRowParams {
+                    alias: None,
+                    doc_id: "doc1".to_string(),
+                    table_id: "tbl1".to_string(),
+                    cells,
+                    coerce: None,
+                    key_columns: None,
+                })),
+            )
+            .await
+            .unwrap();
+        echo.append_to(&mut result);
+
+        let echoed = &result.content.last().unwrap().raw.as_text().unwrap().text;
+        assert!(echoed.starts_with("Requests made (1):"));
+        assert!(echoed.contains("POST /docs/doc1/tables/tbl1/rows"));
+        assert!(echoed.contains("Authorization: Bearer [REDACTED]"));
+        assert!(echoed.contains("pasted [REDACTED] by mistake"));
+        assert!(!echoed.contains("test_token"));
+
+        let requests = &result.structured_content.unwrap()["requests"];
+        assert_eq!(requests[0]["method"], "POST");
+        assert_eq!(
+            requests[0]["body"]["rows"][0]["cells"][0]["value"],
+            "pasted [REDACTED] by mistake"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_session_log_reflects_calls_and_clears() {
+        let (server, mock_server) = setup().await;
+
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .and(path("/docs/doc1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "id": "exp1",
-                "status": "failed",
-                "error": "Page too large"
+                "id": "doc1",
+                "name": "Roadmap"
             })))
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .get_page(Parameters(GetPageParams {
-                doc_id: "doc1".to_string(),
-                page_id: "p1".to_string(),
+        CURRENT_TOOL
+            .scope(
+                "get_doc".to_string(),
+                server.get_doc(Parameters(GetDocParams {
+                    doc_id: "doc1".to_string(),
+                })),
+            )
+            .await
+            .unwrap();
+        let _ = server
+            .get_doc(Parameters(GetDocParams {
+                doc_id: "missing".to_string(),
             }))
             .await;
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.message.contains("Export failed"));
+        let result = server
+            .export_session_log(Parameters(ExportSessionLogParams {
+                redact_ids: Some(true),
+                clear: None,
+            }))
+            .await
+            .unwrap();
+
+        let envelope = result.structured_content.unwrap();
+        let entries = envelope["data"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["tool"], "get_doc");
+        assert_eq!(entries[0]["method"], "GET");
+        assert_eq!(entries[0]["path"], "/docs/{id}");
+        assert_eq!(entries[0]["ok"], true);
+        assert_eq!(entries[1]["tool"], serde_json::Value::Null);
+        assert_eq!(entries[1]["ok"], false);
+        assert_eq!(entries[1]["status"], 404);
+        // Error messages can echo values, so they are left out
+        assert!(entries[1].get("error").is_none());
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.starts_with("Exported 2 API calls (1 failed)"));
+        assert!(!text.contains("missing"));
+
+        let result = server
+            .export_session_log(Parameters(ExportSessionLogParams {
+                redact_ids: None,
+                clear: Some(true),
+            }))
+            .await
+            .unwrap();
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["entries"][1]["path"], "/docs/missing");
+        assert_eq!(envelope["data"]["cleared"], true);
+
+        let result = server
+            .export_session_log(Parameters(ExportSessionLogParams {
+                redact_ids: None,
+                clear: None,
+            }))
+            .await
+            .unwrap();
+        let envelope = result.structured_content.unwrap();
+        assert!(envelope["data"]["entries"].as_array().unwrap().is_empty());
     }
 
+    // === get_page full success workflow ===
+
     #[tokio::test]
-    async fn test_get_page_complete_no_download_link() {
+    async fn test_get_page_success() {
         let (server, mock_server) = setup().await;
 
+        // Step 1: Initiate export
         Mock::given(method("POST"))
             .and(path("/docs/doc1/pages/p1/export"))
             .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
@@ -1106,424 +12447,640 @@ mod tests {
             .mount(&mock_server)
             .await;
 
+        // Step 2: Poll returns complete with downloadLink pointing at mock server
+        let download_url = format!("{}/export/content.html", mock_server.uri());
         Mock::given(method("GET"))
             .and(path("/docs/doc1/pages/p1/export/exp1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
                 "id": "exp1",
-                "status": "complete"
+                "status": "complete",
+                "downloadLink": download_url
             })))
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .get_page(Parameters(GetPageParams {
-                doc_id: "doc1".to_string(),
-                page_id: "p1".to_string(),
-            }))
+        // Step 3: Download content from the link
+        Mock::given(method("GET"))
+            .and(path("/export/content.html"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("<html><body>Page content here</body></html>"),
+            )
+            .mount(&mock_server)
             .await;
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.message.contains("no download link"));
-    }
-
-    // === Table Tools ===
-
-    #[tokio::test]
-    async fn test_list_tables_success() {
-        let (server, mock_server) = setup().await;
-
+        // Step 4: Get page metadata
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/tables"))
+            .and(path("/docs/doc1/pages/p1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [
-                    {"id": "tbl1", "name": "Tasks", "rowCount": 42}
-                ]
+                "id": "p1",
+                "name": "Welcome Page"
             })))
             .mount(&mock_server)
             .await;
 
         let result = server
-            .list_tables(Parameters(ListTablesParams {
+            .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 1 tables"));
-        assert!(text.contains("Tasks"));
+        assert!(text.contains("Page: Welcome Page"));
+        assert!(text.contains("Page content here"));
+    }
+
+    /// Mount a page export for `doc1`/`p1` that completes at once with `html`
+    async fn mount_page_export(mock_server: &MockServer, html: &str) {
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "complete",
+                "downloadLink": format!("{}/export/content.html", mock_server.uri())
+            })))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/export/content.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(html))
+            .mount(mock_server)
+            .await;
     }
 
     #[tokio::test]
-    async fn test_get_table_success() {
+    async fn test_get_page_exports_markdown() {
         let (server, mock_server) = setup().await;
-
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .and(body_json(serde_json::json!({"outputFormat": "markdown"})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .expect(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        mount_page_export(&mock_server, "# Welcome\n\nHello").await;
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/tables/tbl1"))
+            .and(path("/docs/doc1/pages/p1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "id": "tbl1",
-                "name": "Tasks",
-                "rowCount": 42
+                "id": "p1",
+                "name": "Welcome Page"
             })))
             .mount(&mock_server)
             .await;
 
         let result = server
-            .get_table(Parameters(GetTableParams {
+            .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: Some("markdown".to_string()),
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Table: Tasks"));
+        assert!(text.contains("# Welcome"));
     }
 
     #[tokio::test]
-    async fn test_list_columns_success() {
+    async fn test_get_page_rejects_unknown_format() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("GET"))
-            .and(path("/docs/doc1/tables/tbl1/columns"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [
-                    {"id": "col1", "name": "Name"},
-                    {"id": "col2", "name": "Status"}
-                ]
-            })))
-            .mount(&mock_server)
-            .await;
-
         let result = server
-            .list_columns(Parameters(ListColumnsParams {
+            .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: Some("pdf".to_string()),
             }))
             .await
             .unwrap();
 
+        assert_eq!(result.is_error, Some(true));
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 2 columns"));
+        assert!(text.contains("Unsupported page format 'pdf'"));
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
     }
 
-    // === Row Tools ===
-
     #[tokio::test]
-    async fn test_get_rows_success() {
+    async fn test_get_page_full_rejects_include_raw_with_to_file() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("GET"))
-            .and(path("/docs/doc1/tables/tbl1/rows"))
-            .and(query_param("useColumnNames", "true"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [
-                    {"id": "r1", "name": "Row 1", "values": {"Name": "Alice"}},
-                    {"id": "r2", "name": "Row 2", "values": {"Name": "Bob"}}
-                ]
-            })))
-            .mount(&mock_server)
-            .await;
-
         let result = server
-            .get_rows(Parameters(GetRowsParams {
+            .get_page_full(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                limit: None,
-                query: None,
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: Some("page.txt".to_string()),
+                transform: Some(PageTransform::Text),
+                include_raw: Some(true),
+                retry_on_failure: None,
+                format: None,
             }))
             .await
             .unwrap();
 
+        assert_eq!(result.is_error, Some(true));
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 2 rows"));
+        assert!(text.contains("can't be combined with `to_file`"));
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
     }
 
-    #[tokio::test]
-    async fn test_get_rows_with_query() {
-        let (server, mock_server) = setup().await;
-
+    async fn mount_named_page_export(mock_server: &MockServer, page_id: &str, name: &str) {
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/tables/tbl1/rows"))
-            .and(query_param("query", "Status:\"Active\""))
+            .and(path(format!("/docs/doc1/pages/{page_id}")))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [{"id": "r1", "name": "Row 1", "values": {"Status": "Active"}}]
+                "id": page_id, "name": name
             })))
-            .mount(&mock_server)
+            .mount(mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("/docs/doc1/pages/{page_id}/export")))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": format!("exp-{page_id}"),
+                "status": "inProgress"
+            })))
+            .mount(mock_server)
             .await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/docs/doc1/pages/{page_id}/export/exp-{page_id}"
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "complete",
+                "id": format!("exp-{page_id}"),
+                "downloadLink": format!("{}/export/{page_id}.html", mock_server.uri())
+            })))
+            .mount(mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/export/{page_id}.html")))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!("<p>{name}</p>")))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_export_pages_returns_block_per_page() {
+        let (server, mock_server) = setup().await;
+        mount_named_page_export(&mock_server, "p1", "Plan").await;
+        mount_named_page_export(&mock_server, "p2", "Notes").await;
 
         let result = server
-            .get_rows(Parameters(GetRowsParams {
+            .export_pages(Parameters(ExportPagesParams {
                 doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                limit: Some(10),
-                query: Some("Status:\"Active\"".to_string()),
+                page_ids: vec!["p1".to_string(), "p2".to_string()],
+                format: None,
+                transform: None,
+                retry_on_failure: None,
             }))
             .await
             .unwrap();
 
-        let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 1 rows"));
+        let texts: Vec<&str> = result
+            .content
+            .iter()
+            .map(|c| c.raw.as_text().unwrap().text.as_str())
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                "Exported 2 of 2 pages",
+                "Page: Plan\n\nContent:\n<p>Plan</p>",
+                "Page: Notes\n\nContent:\n<p>Notes</p>",
+            ]
+        );
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["succeeded"][1]["page_id"], "p2");
     }
 
     #[tokio::test]
-    async fn test_get_rows_limit_capped() {
+    async fn test_export_pages_passes_format() {
         let (server, mock_server) = setup().await;
-
-        Mock::given(method("GET"))
-            .and(path("/docs/doc1/tables/tbl1/rows"))
-            .and(query_param("limit", "1000"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": []
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .and(body_json(serde_json::json!({"outputFormat": "markdown"})))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp-p1",
+                "status": "inProgress"
             })))
+            .expect(1)
             .mount(&mock_server)
             .await;
+        mount_named_page_export(&mock_server, "p1", "Plan").await;
+
+        let params = |format: &str, transform| ExportPagesParams {
+            doc_id: "doc1".to_string(),
+            page_ids: vec!["p1".to_string()],
+            format: Some(format.to_string()),
+            transform,
+            retry_on_failure: None,
+        };
 
         let result = server
-            .get_rows(Parameters(GetRowsParams {
-                doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                limit: Some(9999),
-                query: None,
-            }))
+            .export_pages(Parameters(params("markdown", None)))
             .await
             .unwrap();
-
-        let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 0 rows"));
+        assert_eq!(result.is_error, Some(false));
+
+        for (format, transform, expected) in [
+            ("pdf", None, "Unsupported page format 'pdf'"),
+            (
+                "markdown",
+                Some(PageTransform::Text),
+                "`transform` works on HTML exports",
+            ),
+        ] {
+            let result = server
+                .export_pages(Parameters(params(format, transform)))
+                .await
+                .unwrap();
+            assert_eq!(result.is_error, Some(true));
+            let text = &result.content[0].raw.as_text().unwrap().text;
+            assert!(text.starts_with(expected), "{text}");
+        }
     }
 
     #[tokio::test]
-    async fn test_get_row_success() {
+    async fn test_export_pages_placeholder_for_failed_page() {
         let (server, mock_server) = setup().await;
-
+        mount_named_page_export(&mock_server, "p1", "Plan").await;
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "id": "r1",
-                "name": "Row 1",
-                "values": {"Name": "Alice", "Score": 95}
-            })))
+            .and(path("/docs/doc1/pages/gone"))
+            .respond_with(ResponseTemplate::new(404))
             .mount(&mock_server)
             .await;
 
         let result = server
-            .get_row(Parameters(GetRowParams {
+            .export_pages(Parameters(ExportPagesParams {
                 doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                row_id: "r1".to_string(),
+                page_ids: vec!["gone".to_string(), "p1".to_string()],
+                format: None,
+                transform: Some(PageTransform::Text),
+                retry_on_failure: None,
             }))
             .await
             .unwrap();
 
-        let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Row: r1"));
+        assert_eq!(result.is_error, Some(false));
+        let texts: Vec<&str> = result
+            .content
+            .iter()
+            .map(|c| c.raw.as_text().unwrap().text.as_str())
+            .collect();
+        assert_eq!(texts[0], "Exported 1 of 2 pages");
+        assert!(texts[1].starts_with("Page: gone\n\nExport failed: Not found."));
+        assert_eq!(texts[2], "Page: Plan\n\nContent:\nPlan");
+        let data = &result.structured_content.unwrap()["data"];
+        assert_eq!(data["failed"][0]["input"], "gone");
+        assert_eq!(data["failed"][0]["error_category"], "not_found");
     }
 
     #[tokio::test]
-    async fn test_add_row_success() {
+    async fn test_get_page_text_transform_with_raw() {
         let (server, mock_server) = setup().await;
-
-        Mock::given(method("POST"))
-            .and(path("/docs/doc1/tables/tbl1/rows"))
-            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
-                "requestId": "req-abc",
-                "addedRowIds": ["new-row-1"]
+        let html = "<h1>Plan</h1><p>Ship <b>v2</b> &amp; celebrate</p>";
+        mount_page_export(&mock_server, html).await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "p1", "name": "Plan"
             })))
             .mount(&mock_server)
             .await;
 
-        let mut cells = std::collections::HashMap::new();
-        cells.insert(
-            "Name".to_string(),
-            serde_json::Value::String("Charlie".to_string()),
-        );
-        cells.insert(
-            "Score".to_string(),
-            serde_json::Value::Number(serde_json::Number::from(100)),
-        );
+        let params = |include_raw| GetPageParams {
+            doc_id: "doc1".to_string(),
+            page_id: "p1".to_string(),
+            include_checksum: None,
+            to_file: None,
+            transform: Some(PageTransform::Text),
+            include_raw,
+            retry_on_failure: None,
+            format: None,
+        };
 
         let result = server
-            .add_row(Parameters(AddRowParams {
-                doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                cells,
-            }))
+            .get_page(Parameters(params(Some(true))))
             .await
             .unwrap();
 
-        let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Row added successfully"));
-        assert!(text.contains("req-abc"));
-        assert!(text.contains("new-row-1"));
+        assert_eq!(result.content.len(), 2);
+        let transformed = &result.content[0].raw.as_text().unwrap().text;
+        assert!(transformed.ends_with("Content:\nPlan\nShip v2 & celebrate"));
+        let raw = &result.content[1].raw.as_text().unwrap().text;
+        assert_eq!(raw, &format!("Raw export:\n{html}"));
+
+        let result = server.get_page(Parameters(params(None))).await.unwrap();
+        assert_eq!(result.content.len(), 1);
     }
 
     #[tokio::test]
-    async fn test_update_row_success() {
+    async fn test_extract_page_tables_returns_csv() {
         let (server, mock_server) = setup().await;
-
-        Mock::given(method("PUT"))
-            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
-            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
-                "requestId": "req-xyz"
-            })))
-            .mount(&mock_server)
-            .await;
-
-        let mut cells = std::collections::HashMap::new();
-        cells.insert(
-            "Status".to_string(),
-            serde_json::Value::String("Done".to_string()),
-        );
+        mount_page_export(
+            &mock_server,
+            "<h1>Plan</h1><table><caption>Budget</caption>\
+             <tr><th>Item</th><th>Cost</th></tr>\
+             <tr><td>Laptops, 3</td><td>4500</td></tr></table>\
+             <p>Notes</p><table><tr><td>a</td><td>b</td></tr></table>",
+        )
+        .await;
 
         let result = server
-            .update_row(Parameters(UpdateRowParams {
+            .extract_page_tables(Parameters(ExtractPageTablesParams {
                 doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                row_id: "r1".to_string(),
-                cells,
+                page_id: "p1".to_string(),
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Row updated successfully"));
-        assert!(text.contains("req-xyz"));
+        assert!(text.starts_with("Found 2 tables on page 'p1'"));
+        assert!(text.contains("## Table 1: Budget\n\n```csv\nItem,Cost\n\"Laptops, 3\",4500\n```"));
+        assert!(text.contains("## Table 2\n\n```csv\na,b\n```"));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["data"]["tables"][0]["caption"], "Budget");
+        assert_eq!(envelope["data"]["tables"][1]["rows"], 1);
     }
 
     #[tokio::test]
-    async fn test_delete_row_success() {
+    async fn test_extract_page_tables_none_found() {
         let (server, mock_server) = setup().await;
-
-        Mock::given(method("DELETE"))
-            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
-            .respond_with(ResponseTemplate::new(202))
-            .mount(&mock_server)
-            .await;
+        mount_page_export(&mock_server, "<html><body><p>Just prose</p></body></html>").await;
 
         let result = server
-            .delete_row(Parameters(DeleteRowParams {
+            .extract_page_tables(Parameters(ExtractPageTablesParams {
                 doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                row_id: "r1".to_string(),
+                page_id: "p1".to_string(),
             }))
             .await
             .unwrap();
 
+        assert_eq!(result.is_error, Some(false));
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Row deleted successfully"));
+        assert_eq!(text, "No tables found on page 'p1'.");
     }
 
     #[tokio::test]
-    async fn test_delete_row_error() {
+    async fn test_get_page_include_checksum() {
         let (server, mock_server) = setup().await;
 
-        Mock::given(method("DELETE"))
-            .and(path("/docs/doc1/tables/tbl1/rows/r1"))
-            .respond_with(ResponseTemplate::new(404))
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .delete_row(Parameters(DeleteRowParams {
-                doc_id: "doc1".to_string(),
-                table_id: "tbl1".to_string(),
-                row_id: "r1".to_string(),
-            }))
-            .await;
-
-        assert!(result.is_err());
-    }
-
-    // === Formula Tools ===
+        let download_url = format!("{}/export/content.html", mock_server.uri());
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "complete",
+                "downloadLink": download_url
+            })))
+            .mount(&mock_server)
+            .await;
 
-    #[tokio::test]
-    async fn test_list_formulas_success() {
-        let (server, mock_server) = setup().await;
+        Mock::given(method("GET"))
+            .and(path("/export/content.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("abc"))
+            .mount(&mock_server)
+            .await;
 
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/formulas"))
+            .and(path("/docs/doc1/pages/p1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [
-                    {"id": "f1", "name": "Total", "value": 42}
-                ]
+                "id": "p1",
+                "name": "Tiny Page"
             })))
             .mount(&mock_server)
             .await;
 
         let result = server
-            .list_formulas(Parameters(ListFormulasParams {
+            .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: Some(true),
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await
             .unwrap();
 
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 1 formulas"));
+        assert!(text.contains("Bytes: 3"));
+        assert!(text
+            .contains("SHA-256: ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+        assert!(text.ends_with("Content:\nabc"));
+    }
+
+    /// Fresh, empty directory under the system temp dir
+    fn temp_export_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("coda-mcp-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
     }
 
     #[tokio::test]
-    async fn test_get_formula_success() {
-        let (server, mock_server) = setup().await;
+    async fn test_get_page_to_file() {
+        let dir = temp_export_dir("to-file");
+        let (server, mock_server) = setup_with_config(Config {
+            export_dir: Some(dir.clone()),
+            ..Config::default()
+        })
+        .await;
 
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .mount(&mock_server)
+            .await;
+        let download_url = format!("{}/export/content.html", mock_server.uri());
         Mock::given(method("GET"))
-            .and(path("/docs/doc1/formulas/f1"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
             .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "id": "f1",
-                "name": "Total",
-                "value": 42
+                "id": "exp1",
+                "status": "complete",
+                "downloadLink": download_url
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/export/content.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>Big page</p>"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "p1",
+                "name": "Big Page"
             })))
             .mount(&mock_server)
             .await;
 
         let result = server
-            .get_formula(Parameters(GetFormulaParams {
+            .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
-                formula_id: "f1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: Some("page.html".to_string()),
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await
             .unwrap();
 
+        let written = dir.canonicalize().unwrap().join("page.html");
+        assert_eq!(
+            std::fs::read_to_string(&written).unwrap(),
+            "<p>Big page</p>"
+        );
         let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Formula: Total"));
-    }
+        assert!(text.contains(&format!("Wrote 15 bytes to {}", written.display())));
+        assert!(!text.contains("<p>Big page</p>"));
 
-    // === Control Tools ===
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
     #[tokio::test]
-    async fn test_list_controls_success() {
-        let (server, mock_server) = setup().await;
-
-        Mock::given(method("GET"))
-            .and(path("/docs/doc1/controls"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "items": [
-                    {"id": "c1", "name": "Submit", "controlType": "button"},
-                    {"id": "c2", "name": "Progress", "controlType": "slider", "value": 75}
-                ]
-            })))
+    async fn test_get_page_to_file_rejects_escape() {
+        let dir = temp_export_dir("escape");
+        let (server, mock_server) = setup_with_config(Config {
+            export_dir: Some(dir.join("exports")),
+            ..Config::default()
+        })
+        .await;
+        std::fs::create_dir_all(dir.join("exports")).unwrap();
+
+        // Nothing is exported when the target is rejected
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
             .mount(&mock_server)
             .await;
 
-        let result = server
-            .list_controls(Parameters(ListControlsParams {
-                doc_id: "doc1".to_string(),
-            }))
-            .await
-            .unwrap();
+        for target in [
+            "../outside.html".to_string(),
+            dir.join("outside.html").display().to_string(),
+        ] {
+            let result = server
+                .get_page(Parameters(GetPageParams {
+                    doc_id: "doc1".to_string(),
+                    page_id: "p1".to_string(),
+                    include_checksum: None,
+                    to_file: Some(target),
+                    transform: None,
+                    include_raw: None,
+                    retry_on_failure: None,
+                    format: None,
+                }))
+                .await
+                .unwrap();
+            assert_eq!(result.is_error, Some(true));
+        }
+        assert!(!dir.join("outside.html").exists());
 
-        let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Found 2 controls"));
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    // === get_page full success workflow ===
+    #[cfg(unix)]
+    #[test]
+    fn test_write_export_replaces_symlink() {
+        let dir = temp_export_dir("write-symlink");
+        let outside = dir.join("outside.txt");
+        std::fs::write(&outside, "keep").unwrap();
+        let target = dir.join("page.html");
+        std::os::unix::fs::symlink(&outside, &target).unwrap();
+
+        write_export(&target, "<p>Hi</p>").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&outside).unwrap(), "keep");
+        assert!(!target.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "<p>Hi</p>");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_export_path() {
+        let dir = temp_export_dir("resolve");
+        let root = dir.canonicalize().unwrap();
+
+        assert_eq!(
+            resolve_export_path(&dir, "a.html").unwrap(),
+            root.join("a.html")
+        );
+        assert_eq!(
+            resolve_export_path(&dir, &root.join("b.html").display().to_string()).unwrap(),
+            root.join("b.html")
+        );
+        assert!(resolve_export_path(&dir, "../a.html").is_err());
+        assert!(resolve_export_path(&dir, "missing/a.html").is_err());
+        assert!(resolve_export_path(&dir, "/etc/passwd").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 
     #[tokio::test]
-    async fn test_get_page_success() {
+    async fn test_get_page_full_success() {
         let (server, mock_server) = setup().await;
 
-        // Step 1: Initiate export
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "p1",
+                "name": "Welcome Page",
+                "contentType": "canvas",
+                "parent": {"id": "p0", "name": "Home"}
+            })))
+            .mount(&mock_server)
+            .await;
+
         Mock::given(method("POST"))
             .and(path("/docs/doc1/pages/p1/export"))
             .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
@@ -1533,7 +13090,6 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        // Step 2: Poll returns complete with downloadLink pointing at mock server
         let download_url = format!("{}/export/content.html", mock_server.uri());
         Mock::given(method("GET"))
             .and(path("/docs/doc1/pages/p1/export/exp1"))
@@ -1545,37 +13101,37 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        // Step 3: Download content from the link
         Mock::given(method("GET"))
             .and(path("/export/content.html"))
             .respond_with(
                 ResponseTemplate::new(200)
-                    .set_body_string("<html><body>Page content here</body></html>"),
+                    .set_body_string("<html><body>Full content</body></html>"),
             )
             .mount(&mock_server)
             .await;
 
-        // Step 4: Get page metadata
-        Mock::given(method("GET"))
-            .and(path("/docs/doc1/pages/p1"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "id": "p1",
-                "name": "Welcome Page"
-            })))
-            .mount(&mock_server)
-            .await;
-
         let result = server
-            .get_page(Parameters(GetPageParams {
+            .get_page_full(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
                 page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await
             .unwrap();
 
-        let text = &result.content[0].raw.as_text().unwrap().text;
-        assert!(text.contains("Page: Welcome Page"));
-        assert!(text.contains("Page content here"));
+        let structured = result.structured_content.unwrap();
+        assert_eq!(structured["metadata"]["name"], "Welcome Page");
+        assert_eq!(structured["metadata"]["contentType"], "canvas");
+        assert_eq!(structured["metadata"]["parent"]["id"], "p0");
+        assert_eq!(
+            structured["content"],
+            "<html><body>Full content</body></html>"
+        );
     }
 
     #[tokio::test]
@@ -1593,6 +13149,12 @@ mod tests {
             .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
                 page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await;
 
@@ -1624,6 +13186,12 @@ mod tests {
             .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
                 page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await;
 
@@ -1658,16 +13226,204 @@ mod tests {
             .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
                 page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await;
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(
-            err.message.contains("timed out"),
-            "Expected timeout error, got: {}",
-            err.message
+        // Timeout is a tool error carrying the in-progress export ID, not a protocol error
+        let result = result.unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let data = result.structured_content.unwrap();
+        assert!(data["error"].as_str().unwrap().contains("timed out"));
+        assert_eq!(data["export_id"], "exp1");
+        assert_eq!(data["status_path"], "/docs/doc1/pages/p1/export/exp1");
+    }
+
+    #[tokio::test]
+    async fn test_get_page_export_timeout_uses_configured_polling() {
+        let (server, mock_server) = setup_with_config(Config {
+            export_max_attempts: 5,
+            export_poll_secs: 0,
+            ..Config::default()
+        })
+        .await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .expect(5)
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_page(Parameters(GetPageParams {
+                doc_id: "doc1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
+            }))
+            .await
+            .unwrap();
+
+        let data = result.structured_content.unwrap();
+        assert_eq!(
+            data["error"],
+            "Export timed out after 0 seconds (5 polls, 0s apart)"
         );
+        assert!(data["hint"]
+            .as_str()
+            .unwrap()
+            .contains("CODA_EXPORT_MAX_ATTEMPTS"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_page_export_to_completion() {
+        let (server, mock_server) = setup().await;
+
+        // No new export is started when resuming
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let download_url = format!("{}/export/content.html", mock_server.uri());
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "complete",
+                "downloadLink": download_url
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/export/content.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>Resumed content</p>"))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "p1",
+                "name": "Big Page"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .resume_page_export(Parameters(ResumePageExportParams {
+                doc_id: "doc1".to_string(),
+                page_id: "p1".to_string(),
+                export_id: "exp1".to_string(),
+                include_checksum: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Page: Big Page"));
+        assert!(text.contains("Resumed content"));
+    }
+
+    #[tokio::test]
+    async fn test_get_page_refreshes_expired_download_link() {
+        let (server, mock_server) = setup().await;
+
+        Mock::given(method("POST"))
+            .and(path("/docs/doc1/pages/p1/export"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "inProgress"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        // First poll hands out a link that has already expired, the re-poll a fresh one
+        let expired_url = format!("{}/export/expired.html", mock_server.uri());
+        let fresh_url = format!("{}/export/fresh.html", mock_server.uri());
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "complete",
+                "downloadLink": expired_url
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1/export/exp1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "exp1",
+                "status": "complete",
+                "downloadLink": fresh_url
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/export/expired.html"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("Request has expired"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/export/fresh.html"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<p>Fresh content</p>"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/docs/doc1/pages/p1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "p1",
+                "name": "Welcome Page"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = server
+            .get_page(Parameters(GetPageParams {
+                doc_id: "doc1".to_string(),
+                page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("Fresh content"));
     }
 
     #[tokio::test]
@@ -1705,6 +13461,12 @@ mod tests {
             .get_page(Parameters(GetPageParams {
                 doc_id: "doc1".to_string(),
                 page_id: "p1".to_string(),
+                include_checksum: None,
+                to_file: None,
+                transform: None,
+                include_raw: None,
+                retry_on_failure: None,
+                format: None,
             }))
             .await;
 
@@ -0,0 +1,362 @@
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Sharing capabilities of the current user, from `GET /docs/{id}/acl/metadata`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // mirrors the API response
+pub struct AclMetadata {
+    #[serde(rename = "canShare", default)]
+    pub can_share: bool,
+    #[serde(rename = "canShareWithWorkspace", default)]
+    pub can_share_with_workspace: bool,
+    #[serde(rename = "canShareWithOrg", default)]
+    pub can_share_with_org: bool,
+    #[serde(rename = "canCopy", default)]
+    pub can_copy: bool,
+}
+
+/// Level of access to a doc, ordered from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DocAccess {
+    None,
+    Readonly,
+    Comment,
+    Write,
+    /// Only derived from the doc's `owner`; never granted through a permission
+    Owner,
+    /// A level that couldn't be determined or that this server doesn't
+    /// recognize; orders below `None` so it never outranks a known level
+    #[serde(other)]
+    #[schemars(skip)]
+    Unknown,
+}
+
+impl DocAccess {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Readonly => "readonly",
+            Self::Comment => "comment",
+            Self::Write => "write",
+            Self::Owner => "owner",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    fn rank(self) -> u8 {
+        match self {
+            Self::Unknown => 0,
+            Self::None => 1,
+            Self::Readonly => 2,
+            Self::Comment => 3,
+            Self::Write => 4,
+            Self::Owner => 5,
+        }
+    }
+}
+
+impl Ord for DocAccess {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for DocAccess {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Principal {
+    /// `email`, `domain` or `anyone`
+    #[serde(rename = "type")]
+    pub principal_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
+impl Principal {
+    /// Whether this principal covers the user with the given login email
+    pub fn matches(&self, login_id: &str) -> bool {
+        match self.principal_type.as_str() {
+            "anyone" => true,
+            "email" => self
+                .email
+                .as_deref()
+                .is_some_and(|email| email.eq_ignore_ascii_case(login_id)),
+            "domain" => match (self.domain.as_deref(), login_id.rsplit_once('@')) {
+                (Some(domain), Some((_, user_domain))) => domain.eq_ignore_ascii_case(user_domain),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Permission {
+    pub id: String,
+    pub principal: Principal,
+    pub access: DocAccess,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionList {
+    pub items: Vec<Permission>,
+    #[serde(rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListPermissionsParams {
+    /// The document ID
+    pub doc_id: String,
+    /// Maximum number of permissions to return (default: API default, max: 1000)
+    pub limit: Option<u32>,
+    /// Token from a previous call's output to fetch the next page
+    pub page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AddPermissionParams {
+    /// The document ID
+    pub doc_id: String,
+    /// Access to grant: "readonly", "comment" or "write"
+    pub access: DocAccess,
+    /// Email address to share with (set exactly one of `email` and `domain`)
+    pub email: Option<String>,
+    /// Email domain to share with, e.g. "example.com"
+    pub domain: Option<String>,
+    /// Don't send the notification email to an email principal (default: false)
+    pub suppress_email: Option<bool>,
+}
+
+impl AddPermissionParams {
+    /// Build the request body, checking the access level and that exactly
+    /// one principal is given
+    pub fn to_request(&self) -> Result<AddPermissionRequest, String> {
+        if matches!(
+            self.access,
+            DocAccess::Unknown | DocAccess::None | DocAccess::Owner
+        ) {
+            return Err(format!(
+                "Access '{}' cannot be granted; use readonly, comment or write.",
+                self.access.as_str()
+            ));
+        }
+        let principal = match (self.email.as_deref(), self.domain.as_deref()) {
+            (Some(email), None) => Principal {
+                principal_type: "email".to_string(),
+                email: Some(email.to_string()),
+                domain: None,
+            },
+            (None, Some(domain)) => Principal {
+                principal_type: "domain".to_string(),
+                email: None,
+                domain: Some(domain.to_string()),
+            },
+            _ => return Err("Set exactly one of `email` and `domain`.".to_string()),
+        };
+        Ok(AddPermissionRequest {
+            access: self.access,
+            principal,
+            suppress_email: self.suppress_email,
+        })
+    }
+}
+
+/// Body of `POST /docs/{id}/acl/permissions`
+#[derive(Debug, Serialize)]
+pub struct AddPermissionRequest {
+    pub access: DocAccess,
+    pub principal: Principal,
+    #[serde(rename = "suppressEmail", skip_serializing_if = "Option::is_none")]
+    pub suppress_email: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeletePermissionParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The permission ID, from `list_permissions`
+    pub permission_id: String,
+    /// The permission's ID; required when the server runs in safe mode
+    pub confirm: Option<String>,
+}
+
+/// Work out `login_id`'s access to a readable doc from its owner, the
+/// caller's ACL metadata and the doc's permissions (each optional, since
+/// non-sharers may not be allowed to read the ACL).
+///
+/// Returns the access level plus the evidence it was derived from.
+pub fn effective_access(
+    login_id: &str,
+    owner: Option<&str>,
+    metadata: Option<&AclMetadata>,
+    permissions: Option<&[Permission]>,
+) -> (DocAccess, Vec<String>) {
+    let mut evidence = Vec::new();
+
+    if let Some(owner) = owner {
+        if owner.eq_ignore_ascii_case(login_id) {
+            evidence.push(format!("Doc owner is {owner}"));
+            return (DocAccess::Owner, evidence);
+        }
+        evidence.push(format!("Doc owner is {owner}, not {login_id}"));
+    }
+
+    let mut access = DocAccess::Unknown;
+    match permissions {
+        Some(permissions) => {
+            access = DocAccess::Readonly;
+            evidence.push("Doc metadata is readable".to_string());
+            let mut unrecognized = false;
+            for permission in permissions.iter().filter(|p| p.principal.matches(login_id)) {
+                evidence.push(format!(
+                    "Permission {} grants {} to {}",
+                    permission.id,
+                    permission.access.as_str(),
+                    permission.principal.principal_type
+                ));
+                unrecognized |= permission.access == DocAccess::Unknown;
+                access = access.max(permission.access);
+            }
+            // The unrecognized grant may be higher than anything known
+            if unrecognized {
+                access = DocAccess::Unknown;
+            }
+        }
+        // Without the permission list, being allowed to share implies edit access
+        None => {
+            if metadata.is_some_and(|m| m.can_share) {
+                evidence
+                    .push("Permission list unavailable; canShare implies edit access".to_string());
+                access = DocAccess::Write;
+            } else {
+                evidence.push(
+                    "Permission list unavailable and canShare not set; access level unknown"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    if let Some(metadata) = metadata {
+        evidence.push(format!(
+            "ACL metadata: canShare={}, canCopy={}",
+            metadata.can_share, metadata.can_copy
+        ));
+    }
+
+    (access, evidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_list_deserialize() {
+        let json = r#"{
+            "items": [
+                {"id": "perm1", "access": "write",
+                 "principal": {"type": "email", "email": "ada@example.com"}},
+                {"id": "perm2", "access": "readonly",
+                 "principal": {"type": "domain", "domain": "example.com"}},
+                {"id": "perm3", "access": "comment", "principal": {"type": "anyone"}}
+            ]
+        }"#;
+
+        let list: PermissionList = serde_json::from_str(json).unwrap();
+        assert_eq!(list.items.len(), 3);
+        assert_eq!(list.items[0].access, DocAccess::Write);
+        assert_eq!(list.items[2].principal.principal_type, "anyone");
+    }
+
+    #[test]
+    fn test_principal_matches() {
+        let email: Principal =
+            serde_json::from_str(r#"{"type": "email", "email": "Ada@Example.com"}"#).unwrap();
+        assert!(email.matches("ada@example.com"));
+        assert!(!email.matches("bob@example.com"));
+
+        let domain: Principal =
+            serde_json::from_str(r#"{"type": "domain", "domain": "example.com"}"#).unwrap();
+        assert!(domain.matches("bob@example.com"));
+        assert!(!domain.matches("bob@other.com"));
+
+        let anyone: Principal = serde_json::from_str(r#"{"type": "anyone"}"#).unwrap();
+        assert!(anyone.matches("anybody@anywhere.org"));
+    }
+
+    #[test]
+    fn test_add_permission_request() {
+        let params = AddPermissionParams {
+            doc_id: "doc1".to_string(),
+            access: DocAccess::Comment,
+            email: None,
+            domain: Some("example.com".to_string()),
+            suppress_email: None,
+        };
+        let body = serde_json::to_value(params.to_request().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "access": "comment",
+                "principal": {"type": "domain", "domain": "example.com"},
+            })
+        );
+
+        let both = AddPermissionParams {
+            email: Some("ada@example.com".to_string()),
+            ..params
+        };
+        assert!(both.to_request().unwrap_err().contains("exactly one"));
+
+        let owner = AddPermissionParams {
+            access: DocAccess::Owner,
+            domain: None,
+            ..both
+        };
+        assert!(owner
+            .to_request()
+            .unwrap_err()
+            .contains("cannot be granted"));
+    }
+
+    #[test]
+    fn test_effective_access_unknown() {
+        let permissions: PermissionList = serde_json::from_str(
+            r#"{"items": [{"id": "perm1", "access": "editor",
+                "principal": {"type": "email", "email": "ada@example.com"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(permissions.items[0].access, DocAccess::Unknown);
+
+        let (access, evidence) =
+            effective_access("ada@example.com", None, None, Some(&permissions.items));
+        assert_eq!(access, DocAccess::Unknown);
+        assert!(evidence.contains(&"Permission perm1 grants unknown to email".to_string()));
+
+        let (access, _) = effective_access("ada@example.com", None, None, Some(&[]));
+        assert_eq!(access, DocAccess::Readonly);
+
+        let metadata = AclMetadata::default();
+        let (access, evidence) = effective_access("ada@example.com", None, Some(&metadata), None);
+        assert_eq!(access, DocAccess::Unknown);
+        assert!(evidence[0].contains("access level unknown"));
+    }
+
+    #[test]
+    fn test_doc_access_ordering() {
+        assert!(DocAccess::Owner > DocAccess::Write);
+        assert!(DocAccess::Write > DocAccess::Comment);
+        assert!(DocAccess::Comment > DocAccess::Readonly);
+        assert!(DocAccess::Readonly > DocAccess::None);
+        assert!(DocAccess::None > DocAccess::Unknown);
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AddRowParams, AddRowsParams, AppendToMultiselectParams, CheckQueryParams, DeleteRowParams,
+    DeleteRowsParams, GetColumnParams, GetRowParams, GetRowsDeltaParams, GetRowsParams,
+    GetTableParams, ListColumnsParams, PushButtonParams, UpdateRowParams,
+};
+
+/// A friendly name for a table, configured with `CODA_ALIASES`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableAlias {
+    pub doc_id: String,
+    pub table_id: String,
+}
+
+/// Tool params that address a table by `doc_id` and `table_id`, or by `alias`
+pub trait TableTarget {
+    /// The alias, and the doc and table IDs it fills in
+    fn target_mut(&mut self) -> (Option<&str>, &mut String, &mut String);
+}
+
+macro_rules! impl_table_target {
+    ($($ty:ty),*) => {
+        $(impl TableTarget for $ty {
+            fn target_mut(&mut self) -> (Option<&str>, &mut String, &mut String) {
+                (self.alias.as_deref(), &mut self.doc_id, &mut self.table_id)
+            }
+        })*
+    };
+}
+
+impl_table_target!(
+    GetTableParams,
+    ListColumnsParams,
+    GetRowsParams,
+    GetRowParams,
+    AddRowParams,
+    AddRowsParams,
+    UpdateRowParams,
+    DeleteRowParams,
+    DeleteRowsParams,
+    GetRowsDeltaParams,
+    CheckQueryParams,
+    PushButtonParams,
+    AppendToMultiselectParams,
+    GetColumnParams
+);
+
+/// Check configured aliases, returning a message naming each invalid one
+pub fn validate_aliases(aliases: &BTreeMap<String, TableAlias>) -> Result<(), String> {
+    let invalid: Vec<&str> = aliases
+        .iter()
+        .filter(|(name, alias)| {
+            name.trim().is_empty()
+                || alias.doc_id.trim().is_empty()
+                || alias.table_id.trim().is_empty()
+        })
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "aliases need a name and a non-empty doc_id and table_id: '{}'",
+            invalid.join("', '")
+        ))
+    }
+}
+
+/// Fill a target's `doc_id` and `table_id` from its alias. IDs given
+/// alongside an alias must match it. Without an alias both IDs are required.
+pub fn resolve_target(
+    aliases: &BTreeMap<String, TableAlias>,
+    target: &mut impl TableTarget,
+) -> Result<(), String> {
+    let (alias, doc_id, table_id) = target.target_mut();
+    let Some(name) = alias else {
+        if doc_id.is_empty() || table_id.is_empty() {
+            return Err("`doc_id` and `table_id` are required unless `alias` is set.".to_string());
+        }
+        return Ok(());
+    };
+
+    let Some(resolved) = aliases.get(name.trim()) else {
+        return Err(if aliases.is_empty() {
+            format!("Unknown alias '{name}': no aliases are configured. Set CODA_ALIASES.")
+        } else {
+            let known: Vec<&str> = aliases.keys().map(String::as_str).collect();
+            format!(
+                "Unknown alias '{name}'. Known aliases: {}.",
+                known.join(", ")
+            )
+        });
+    };
+    for (field, given, expected) in [
+        ("doc_id", &*doc_id, &resolved.doc_id),
+        ("table_id", &*table_id, &resolved.table_id),
+    ] {
+        if !given.is_empty() && given != expected {
+            return Err(format!(
+                "Alias '{name}' has {field} '{expected}', which conflicts with the given '{given}'."
+            ));
+        }
+    }
+    doc_id.clone_from(&resolved.doc_id);
+    table_id.clone_from(&resolved.table_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> BTreeMap<String, TableAlias> {
+        serde_json::from_str(r#"{"tasks": {"doc_id": "AbCd", "table_id": "grid-1"}}"#).unwrap()
+    }
+
+    fn params(json: &str) -> GetTableParams {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_target_alias() {
+        let mut target = params(r#"{"alias": "tasks"}"#);
+        resolve_target(&aliases(), &mut target).unwrap();
+        assert_eq!(target.doc_id, "AbCd");
+        assert_eq!(target.table_id, "grid-1");
+
+        // Matching IDs alongside the alias are fine; conflicting ones are not
+        let mut target = params(r#"{"alias": "tasks", "doc_id": "AbCd"}"#);
+        assert!(resolve_target(&aliases(), &mut target).is_ok());
+        let mut target = params(r#"{"alias": "tasks", "table_id": "grid-2"}"#);
+        let err = resolve_target(&aliases(), &mut target).unwrap_err();
+        assert!(err.contains("conflicts with the given 'grid-2'"), "{err}");
+    }
+
+    #[test]
+    fn test_resolve_target_errors() {
+        let mut target = params(r#"{"alias": "bugs"}"#);
+        let err = resolve_target(&aliases(), &mut target).unwrap_err();
+        assert_eq!(err, "Unknown alias 'bugs'. Known aliases: tasks.");
+
+        let err = resolve_target(&BTreeMap::new(), &mut target).unwrap_err();
+        assert!(err.contains("no aliases are configured"), "{err}");
+
+        let mut target = params(r#"{"doc_id": "AbCd"}"#);
+        let err = resolve_target(&aliases(), &mut target).unwrap_err();
+        assert!(err.contains("required unless `alias` is set"), "{err}");
+
+        let mut target = params(r#"{"doc_id": "AbCd", "table_id": "grid-9"}"#);
+        assert!(resolve_target(&BTreeMap::new(), &mut target).is_ok());
+        assert_eq!(target.table_id, "grid-9");
+    }
+
+    #[test]
+    fn test_validate_aliases() {
+        assert!(validate_aliases(&aliases()).is_ok());
+        let bad: BTreeMap<String, TableAlias> =
+            serde_json::from_str(r#"{"tasks": {"doc_id": "AbCd", "table_id": " "}}"#).unwrap();
+        assert!(validate_aliases(&bad).unwrap_err().contains("'tasks'"));
+    }
+}
@@ -0,0 +1,72 @@
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AnalyticsParams {
+    /// The document ID
+    pub doc_id: String,
+    /// First day to include: YYYY-MM-DD, an RFC3339 timestamp or a duration like 30d (optional)
+    pub since: Option<String>,
+    /// Last day to include, in the same forms as `since` (optional)
+    pub until: Option<String>,
+    /// "daily" for one entry per day or "cumulative" for running totals (optional, default: API default)
+    pub scale: Option<String>,
+    /// Maximum number of entries to return (default: API default, max: 1000)
+    pub limit: Option<u32>,
+    /// Token from a previous call's output to fetch the next page
+    pub page_token: Option<String>,
+}
+
+/// One period of a doc's analytics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocAnalyticsEntry {
+    /// Day the entry covers, as YYYY-MM-DD
+    pub date: String,
+    pub views: Option<u64>,
+    pub copies: Option<u64>,
+    pub likes: Option<u64>,
+    #[serde(rename = "sessionsMobile")]
+    pub sessions_mobile: Option<u64>,
+    #[serde(rename = "sessionsDesktop")]
+    pub sessions_desktop: Option<u64>,
+    #[serde(rename = "sessionsOther")]
+    pub sessions_other: Option<u64>,
+    #[serde(rename = "totalSessions")]
+    pub total_sessions: Option<u64>,
+}
+
+/// Response of `GET /docs/{id}/analytics`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocAnalytics {
+    pub items: Vec<DocAnalyticsEntry>,
+    #[serde(rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+}
+
+impl DocAnalytics {
+    /// Sum of `field` over the entries that report it
+    pub fn total(&self, field: impl Fn(&DocAnalyticsEntry) -> Option<u64>) -> u64 {
+        self.items.iter().filter_map(field).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doc_analytics_totals() {
+        let json = r#"{
+            "items": [
+                {"date": "2024-03-01", "views": 10, "totalSessions": 4, "likes": 1},
+                {"date": "2024-03-02", "views": 5, "totalSessions": 2},
+                {"date": "2024-03-03"}
+            ]
+        }"#;
+
+        let analytics: DocAnalytics = serde_json::from_str(json).unwrap();
+        assert_eq!(analytics.total(|e| e.views), 15);
+        assert_eq!(analytics.total(|e| e.total_sessions), 6);
+        assert_eq!(analytics.total(|e| e.copies), 0);
+    }
+}
@@ -0,0 +1,19 @@
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TriggerAutomationParams {
+    /// The document ID
+    pub doc_id: String,
+    /// ID of the automation rule, from its "Webhook invoked" trigger settings
+    pub rule_id: String,
+    /// JSON payload the rule can read (default: {})
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Response to triggering an automation rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationResponse {
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+}
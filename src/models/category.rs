@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// A publishing category, from `GET /categories`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Category {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryList {
+    pub items: Vec<Category>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_list_deserialize() {
+        let json = r#"{"items": [{"name": "Project management"}, {"name": "Education"}]}"#;
+
+        let list: CategoryList = serde_json::from_str(json).unwrap();
+        let names: Vec<_> = list.items.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Project management", "Education"]);
+    }
+}
@@ -1,10 +1,71 @@
+use std::collections::HashMap;
+
 use rmcp::schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Row, TableReference};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnFormat {
     #[serde(rename = "type")]
     pub format_type: Option<String>,
+    /// Table whose rows a lookup column references
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<TableReference>,
+    /// Whether a lookup column holds multiple references
+    #[serde(rename = "isArray", skip_serializing_if = "Option::is_none")]
+    pub is_array: Option<bool>,
+    /// Allowed values of a select column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<SelectOption>>,
+    /// Decimal places shown by number, percent and currency columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub precision: Option<u32>,
+    #[serde(
+        rename = "useThousandsSeparator",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub use_thousands_separator: Option<bool>,
+    /// ISO currency code of a currency column
+    #[serde(rename = "currencyCode", skip_serializing_if = "Option::is_none")]
+    pub currency_code: Option<String>,
+    /// Display pattern of a date, time or duration column, e.g. `YYYY-MM-DD`
+    #[serde(rename = "format", skip_serializing_if = "Option::is_none")]
+    pub display_format: Option<String>,
+    /// How a number, scale or select column is drawn, e.g. `slider` or `chips`
+    #[serde(rename = "displayType", skip_serializing_if = "Option::is_none")]
+    pub display_type: Option<String>,
+    /// Bounds and step of a slider or scale column
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<Value>,
+}
+
+/// A select column option: either a bare label or an object with a `name`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SelectOption {
+    Named {
+        name: String,
+        #[serde(rename = "backgroundColor", skip_serializing_if = "Option::is_none")]
+        background_color: Option<String>,
+        #[serde(rename = "foregroundColor", skip_serializing_if = "Option::is_none")]
+        foreground_color: Option<String>,
+    },
+    Label(String),
+}
+
+impl SelectOption {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Named { name, .. } => name,
+            Self::Label(label) => label,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +76,232 @@ pub struct Column {
     pub href: Option<String>,
     pub name: String,
     pub format: Option<ColumnFormat>,
+    /// Whether this is the table's display column
+    pub display: Option<bool>,
+    /// Whether the column's values are computed by a formula
+    pub calculated: Option<bool>,
+    /// Formula expression, for calculated columns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+    #[serde(rename = "defaultValue", skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+}
+
+/// Column format types that restrict values to a fixed option list
+const SELECT_FORMAT_TYPES: &[&str] = &["select", "selectList", "multiSelect"];
+
+/// Column format types that always hold a list of options
+const MULTI_SELECT_FORMAT_TYPES: &[&str] = &["selectList", "multiSelect"];
+
+/// Column format types that hold numbers
+const NUMERIC_FORMAT_TYPES: &[&str] = &["number", "percent", "currency", "slider", "scale"];
+
+/// Column format types whose values are computed or triggered rather than set
+const NON_WRITABLE_FORMAT_TYPES: &[&str] = &["formula", "lookup", "button"];
+
+impl Column {
+    /// The table this column looks up into, if it is a lookup column
+    pub fn referenced_table(&self) -> Option<&TableReference> {
+        self.format.as_ref().and_then(|f| f.table.as_ref())
+    }
+
+    /// Format type, e.g. `text`, `select` or `lookup`
+    pub fn format_type(&self) -> Option<&str> {
+        self.format.as_ref().and_then(|f| f.format_type.as_deref())
+    }
+
+    /// Whether this is a select or multi-select column
+    pub fn is_select(&self) -> bool {
+        self.format_type()
+            .is_some_and(|t| SELECT_FORMAT_TYPES.contains(&t))
+    }
+
+    /// Whether this select column holds several options per cell
+    pub fn is_multi_select(&self) -> bool {
+        let Some(format_type) = self.format_type() else {
+            return false;
+        };
+        MULTI_SELECT_FORMAT_TYPES.contains(&format_type)
+            || (format_type == "select"
+                && self.format.as_ref().and_then(|f| f.is_array) == Some(true))
+    }
+
+    /// `value` converted to this column's type, or `None` when it already
+    /// fits or converting would be a guess. Date columns take ISO strings as
+    /// they are, so they are never converted.
+    pub fn coerce(&self, value: &Value) -> Option<Value> {
+        match (self.format_type()?, value) {
+            (format, Value::String(s)) if NUMERIC_FORMAT_TYPES.contains(&format) => {
+                let s = s.trim();
+                if let Ok(n) = s.parse::<i64>() {
+                    return Some(n.into());
+                }
+                let n = s.parse::<f64>().ok()?;
+                serde_json::Number::from_f64(n).map(Value::Number)
+            }
+            ("checkbox", Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" => Some(Value::Bool(true)),
+                "false" | "no" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ("checkbox", Value::Number(n)) => match n.as_i64()? {
+                1 => Some(Value::Bool(true)),
+                0 => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ("text", Value::Number(_) | Value::Bool(_)) => Some(Value::String(value.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Whether row writes can set this column's value
+    pub fn is_writable(&self) -> bool {
+        if self.calculated == Some(true) {
+            return false;
+        }
+        !self
+            .format_type()
+            .is_some_and(|t| NON_WRITABLE_FORMAT_TYPES.contains(&t))
+    }
+}
+
+/// A cell value converted to its column's type before a write
+#[derive(Debug, Clone, Serialize)]
+pub struct Coercion {
+    /// Column as given in the cells, by ID or name
+    pub column: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+/// Coerce `cells` (keyed by column ID or name) in place to the types of
+/// `columns`, returning the conversions made, ordered by column. Cells whose
+/// column is unknown are left alone.
+pub fn coerce_cells(columns: &[Column], cells: &mut HashMap<String, Value>) -> Vec<Coercion> {
+    let mut coercions: Vec<Coercion> = cells
+        .iter_mut()
+        .filter_map(|(key, value)| {
+            let column = columns.iter().find(|c| c.id == *key || c.name == *key)?;
+            let coerced = column.coerce(value)?;
+            let from = std::mem::replace(value, coerced.clone());
+            Some(Coercion {
+                column: key.clone(),
+                from,
+                to: coerced,
+            })
+        })
+        .collect();
+    coercions.sort_by(|a, b| a.column.cmp(&b.column));
+    coercions
+}
+
+/// A multi-select cell (as read with `valueFormat=simpleWithArrays`) with
+/// `additions` appended, keeping existing options as-is and in order. Returns
+/// the merged list and the options that were actually new.
+pub fn merge_select_values(current: &Value, additions: &[String]) -> (Vec<Value>, Vec<String>) {
+    let mut merged: Vec<Value> = match current {
+        Value::Array(items) => items.clone(),
+        Value::String(s) if s.is_empty() => Vec::new(),
+        Value::Null => Vec::new(),
+        other => vec![other.clone()],
+    };
+    let mut added = Vec::new();
+    for value in additions {
+        if !merged
+            .iter()
+            .any(|item| item.as_str() == Some(value.as_str()))
+        {
+            merged.push(Value::String(value.clone()));
+            added.push(value.clone());
+        }
+    }
+    (merged, added)
+}
+
+/// Keys for row values read by column ID, for a table where several columns
+/// share a display name. Coda keys `useColumnNames=true` values by name, so
+/// all but one of those columns would be silently dropped.
+#[derive(Debug, Clone)]
+pub struct ColumnKeys {
+    /// Column ID -> key: the display name, or `Name (c-id)` for a shared name
+    keys: HashMap<String, String>,
+    /// Display names used by more than one column, sorted
+    pub duplicates: Vec<String>,
+}
+
+impl ColumnKeys {
+    /// Keys for `columns`, or `None` when every display name is unique
+    pub fn disambiguate(columns: &[Column]) -> Option<Self> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for column in columns {
+            *counts.entry(column.name.as_str()).or_default() += 1;
+        }
+        let mut duplicates: Vec<String> = counts
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .map(|(name, _)| (*name).to_string())
+            .collect();
+        if duplicates.is_empty() {
+            return None;
+        }
+        duplicates.sort();
+        let keys = columns
+            .iter()
+            .map(|column| {
+                let key = if counts[column.name.as_str()] > 1 {
+                    format!("{} ({})", column.name, column.id)
+                } else {
+                    column.name.clone()
+                };
+                (column.id.clone(), key)
+            })
+            .collect();
+        Some(Self { keys, duplicates })
+    }
+
+    /// Re-key a row read with `useColumnNames=false` from column IDs to
+    /// these keys. Unknown column IDs are kept as they are.
+    pub fn rekey(&self, row: &mut Row) {
+        if let Some(values) = row.values.take() {
+            row.values = Some(
+                values
+                    .into_iter()
+                    .map(|(id, value)| (self.keys.get(&id).cloned().unwrap_or(id), value))
+                    .collect(),
+            );
+        }
+    }
+
+    /// `columns` (display names, for [`Row::redact`]) plus the disambiguated
+    /// keys of the shared names among them
+    pub fn redacted(&self, columns: &[String]) -> Vec<String> {
+        let mut redacted = columns.to_vec();
+        for (id, key) in &self.keys {
+            let name = key.strip_suffix(&format!(" ({id})")).unwrap_or(key);
+            if key != name && columns.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+                redacted.push(key.clone());
+            }
+        }
+        redacted
+    }
+
+    /// Warning for the tool output naming the shared display names
+    pub fn warning(&self) -> String {
+        let names: Vec<String> = self.duplicates.iter().map(|n| format!("'{n}'")).collect();
+        format!(
+            "Warning: several columns share the display name {}; their values are keyed `Name (column ID)` so none is dropped.",
+            names.join(", ")
+        )
+    }
+}
+
+/// Lightweight reference to a column, e.g. a table's display column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnReference {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub reference_type: Option<String>,
+    pub href: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,16 +313,87 @@ pub struct ColumnList {
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListColumnsParams {
-    /// The document ID
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// Maximum number of columns to return (default: API default, max: 1000)
+    pub limit: Option<u32>,
+    /// Token from a previous call's output to fetch the next page
+    pub page_token: Option<String>,
+    /// Remove duplicate IDs and sort results by ID (default: false)
+    pub dedupe: Option<bool>,
+    /// List the formula expression of each calculated column (default: false)
+    pub include_formulas: Option<bool>,
+    /// Only return columns whose values can be set, excluding formula, lookup and button columns (default: false)
+    pub writable_only: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetColumnParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
     pub doc_id: String,
-    /// The table ID or name
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
     pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// The column ID or name
+    pub column_id: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_merge_select_values() {
+        let additions = ["urgent".to_string(), "bug".to_string()];
+
+        let (merged, added) = merge_select_values(&serde_json::json!(["bug", "ui"]), &additions);
+        assert_eq!(merged, ["bug", "ui", "urgent"]);
+        assert_eq!(added, ["urgent"]);
+
+        let (merged, added) = merge_select_values(&serde_json::json!(""), &additions);
+        assert_eq!(merged, ["urgent", "bug"]);
+        assert_eq!(added, ["urgent", "bug"]);
+
+        let (merged, _) = merge_select_values(&serde_json::json!("ui"), &additions);
+        assert_eq!(merged, ["ui", "urgent", "bug"]);
+
+        // Non-string options are written back unchanged, not stringified
+        let (merged, added) = merge_select_values(&serde_json::json!([3, "bug"]), &additions);
+        assert_eq!(
+            merged,
+            [
+                serde_json::json!(3),
+                serde_json::json!("bug"),
+                serde_json::json!("urgent")
+            ]
+        );
+        assert_eq!(added, ["urgent"]);
+    }
+
+    #[test]
+    fn test_is_multi_select() {
+        let column = |format: serde_json::Value| -> Column {
+            serde_json::from_value(
+                serde_json::json!({"id": "c1", "name": "Tags", "format": format}),
+            )
+            .unwrap()
+        };
+
+        assert!(column(serde_json::json!({"type": "selectList"})).is_multi_select());
+        assert!(column(serde_json::json!({"type": "select", "isArray": true})).is_multi_select());
+        assert!(!column(serde_json::json!({"type": "select"})).is_multi_select());
+        assert!(!column(serde_json::json!({"type": "text"})).is_multi_select());
+    }
+
     #[test]
     fn test_column_deserialize() {
         let json = r#"{
@@ -52,6 +410,37 @@ mod tests {
         assert_eq!(col.format.unwrap().format_type, Some("select".to_string()));
     }
 
+    #[test]
+    fn test_column_detail_format() {
+        let json = r#"{
+            "id": "c-price",
+            "name": "Price",
+            "format": {
+                "type": "currency",
+                "precision": 2,
+                "useThousandsSeparator": true,
+                "currencyCode": "EUR"
+            },
+            "defaultValue": "0"
+        }"#;
+
+        let col: Column = serde_json::from_str(json).unwrap();
+        let format = col.format.as_ref().unwrap();
+        assert_eq!(format.precision, Some(2));
+        assert_eq!(format.use_thousands_separator, Some(true));
+        assert_eq!(format.currency_code.as_deref(), Some("EUR"));
+        assert!(format.display_format.is_none());
+        assert_eq!(col.default_value.as_deref(), Some("0"));
+
+        let slider: ColumnFormat = serde_json::from_str(
+            r#"{"type": "slider", "minimum": 0, "maximum": 10, "step": 0.5, "displayType": "slider"}"#,
+        )
+        .unwrap();
+        assert_eq!(slider.maximum, Some(serde_json::json!(10)));
+        assert_eq!(slider.step, Some(serde_json::json!(0.5)));
+        assert_eq!(slider.display_type.as_deref(), Some("slider"));
+    }
+
     #[test]
     fn test_column_without_format() {
         let json = r#"{"id": "col1", "name": "Name"}"#;
@@ -59,6 +448,196 @@ mod tests {
         assert!(col.format.is_none());
     }
 
+    #[test]
+    fn test_column_deserialize_formula() {
+        let json = r#"{
+            "id": "c-total",
+            "name": "Total",
+            "calculated": true,
+            "display": false,
+            "formula": "thisRow.Price * thisRow.Qty"
+        }"#;
+        let col: Column = serde_json::from_str(json).unwrap();
+        assert_eq!(col.calculated, Some(true));
+        assert_eq!(col.display, Some(false));
+        assert_eq!(col.formula.as_deref(), Some("thisRow.Price * thisRow.Qty"));
+    }
+
+    #[test]
+    fn test_column_lookup_format() {
+        let json = r#"{
+            "id": "c-owner",
+            "name": "Owner",
+            "format": {
+                "type": "lookup",
+                "isArray": false,
+                "table": {"id": "grid-people", "type": "table", "name": "People"}
+            }
+        }"#;
+        let col: Column = serde_json::from_str(json).unwrap();
+        let table = col.referenced_table().unwrap();
+        assert_eq!(table.id, "grid-people");
+        assert_eq!(table.name.as_deref(), Some("People"));
+        assert_eq!(col.format.unwrap().is_array, Some(false));
+    }
+
+    #[test]
+    fn test_column_select_options() {
+        let json = r#"{
+            "id": "c-stage",
+            "name": "Stage",
+            "format": {
+                "type": "select",
+                "options": [{"name": "Open", "backgroundColor": "white"}, "Closed"]
+            }
+        }"#;
+        let col: Column = serde_json::from_str(json).unwrap();
+        assert!(col.is_select());
+        let labels: Vec<_> = col
+            .format
+            .unwrap()
+            .options
+            .unwrap()
+            .iter()
+            .map(|o| o.label().to_string())
+            .collect();
+        assert_eq!(labels, vec!["Open", "Closed"]);
+    }
+
+    #[test]
+    fn test_column_is_writable() {
+        let json = r#"{
+            "items": [
+                {"id": "c1", "name": "Name", "format": {"type": "text"}},
+                {"id": "c2", "name": "Total", "format": {"type": "number"}, "calculated": true},
+                {"id": "c3", "name": "Owner", "format": {"type": "lookup"}},
+                {"id": "c4", "name": "Approve", "format": {"type": "button"}},
+                {"id": "c5", "name": "Notes"}
+            ]
+        }"#;
+        let list: ColumnList = serde_json::from_str(json).unwrap();
+        let writable: Vec<_> = list
+            .items
+            .iter()
+            .filter(|c| c.is_writable())
+            .map(|c| c.id.as_str())
+            .collect();
+        assert_eq!(writable, vec!["c1", "c5"]);
+    }
+
+    fn typed(format_type: &str) -> Column {
+        serde_json::from_value(serde_json::json!({
+            "id": format!("c-{format_type}"),
+            "name": format_type,
+            "format": {"type": format_type}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_coerce_numbers() {
+        let number = typed("number");
+        assert_eq!(
+            number.coerce(&serde_json::json!("42")),
+            Some(serde_json::json!(42))
+        );
+        assert_eq!(
+            number.coerce(&serde_json::json!(" 2.5 ")),
+            Some(serde_json::json!(2.5))
+        );
+        assert_eq!(
+            typed("currency").coerce(&serde_json::json!("-3")),
+            Some(serde_json::json!(-3))
+        );
+        // Ambiguous or already numeric values are left alone
+        assert_eq!(number.coerce(&serde_json::json!("1,234")), None);
+        assert_eq!(number.coerce(&serde_json::json!("NaN")), None);
+        assert_eq!(number.coerce(&serde_json::json!(7)), None);
+    }
+
+    #[test]
+    fn test_coerce_booleans() {
+        let checkbox = typed("checkbox");
+        assert_eq!(
+            checkbox.coerce(&serde_json::json!("TRUE")),
+            Some(serde_json::json!(true))
+        );
+        assert_eq!(
+            checkbox.coerce(&serde_json::json!("no")),
+            Some(serde_json::json!(false))
+        );
+        assert_eq!(
+            checkbox.coerce(&serde_json::json!(1)),
+            Some(serde_json::json!(true))
+        );
+        assert_eq!(checkbox.coerce(&serde_json::json!("maybe")), None);
+        assert_eq!(checkbox.coerce(&serde_json::json!(2)), None);
+    }
+
+    #[test]
+    fn test_coerce_cells_no_op() {
+        let columns = vec![typed("text"), typed("date"), typed("number")];
+        let mut cells = HashMap::from([
+            ("text".to_string(), serde_json::json!("hello")),
+            ("c-date".to_string(), serde_json::json!("2024-01-15")),
+            ("number".to_string(), serde_json::json!(3)),
+            ("Unknown".to_string(), serde_json::json!("5")),
+        ]);
+        let before = cells.clone();
+
+        assert!(coerce_cells(&columns, &mut cells).is_empty());
+        assert_eq!(cells, before);
+    }
+
+    #[test]
+    fn test_coerce_cells_reports_changes() {
+        let columns = vec![typed("text"), typed("number")];
+        let mut cells = HashMap::from([
+            ("c-number".to_string(), serde_json::json!("10")),
+            ("text".to_string(), serde_json::json!(5)),
+        ]);
+
+        let coercions = coerce_cells(&columns, &mut cells);
+
+        assert_eq!(cells["c-number"], serde_json::json!(10));
+        assert_eq!(cells["text"], serde_json::json!("5"));
+        let changed: Vec<&str> = coercions.iter().map(|c| c.column.as_str()).collect();
+        assert_eq!(changed, ["c-number", "text"]);
+        assert_eq!(coercions[0].from, serde_json::json!("10"));
+    }
+
+    #[test]
+    fn test_column_keys_disambiguate() {
+        let columns: Vec<Column> = serde_json::from_value(serde_json::json!([
+            {"id": "c-1", "name": "Name"},
+            {"id": "c-2", "name": "Name"},
+            {"id": "c-3", "name": "Email"}
+        ]))
+        .unwrap();
+        let keys = ColumnKeys::disambiguate(&columns).unwrap();
+        assert_eq!(keys.duplicates, vec!["Name"]);
+
+        let mut row: Row = serde_json::from_value(serde_json::json!({
+            "id": "i-1",
+            "values": {"c-1": "Ada", "c-2": "Lovelace", "c-3": "ada@example.com", "c-9": 1}
+        }))
+        .unwrap();
+        keys.rekey(&mut row);
+        let values = row.values.as_ref().unwrap();
+        assert_eq!(values["Name (c-1)"], "Ada");
+        assert_eq!(values["Name (c-2)"], "Lovelace");
+        assert_eq!(values["Email"], "ada@example.com");
+        assert_eq!(values["c-9"], 1);
+
+        row.redact(&keys.redacted(&["name".to_string()]));
+        let values = row.values.as_ref().unwrap();
+        assert_eq!(values["Name (c-1)"], "[REDACTED]");
+        assert_eq!(values["Name (c-2)"], "[REDACTED]");
+        assert_eq!(values["Email"], "ada@example.com");
+
+        assert!(ColumnKeys::disambiguate(&columns[1..]).is_none());
+    }
+
     #[test]
     fn test_column_list_deserialize() {
         let json = r#"{
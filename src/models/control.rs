@@ -24,6 +24,18 @@ pub struct ControlList {
 pub struct ListControlsParams {
     /// The document ID
     pub doc_id: String,
+    /// Remove duplicate IDs and sort results by ID (default: false)
+    pub dedupe: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SetControlValueParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The control ID or name
+    pub control_id: String,
+    /// The value that was meant to be set; controls can't be written through the API
+    pub value: serde_json::Value,
 }
 
 #[cfg(test)]
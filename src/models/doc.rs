@@ -1,6 +1,10 @@
+use chrono::{DateTime, Utc};
 use rmcp::schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::PageSeed;
+use crate::time;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Doc {
     pub id: String,
@@ -15,6 +19,94 @@ pub struct Doc {
     pub updated_at: Option<String>,
     #[serde(rename = "folderId")]
     pub folder_id: Option<String>,
+    #[serde(rename = "docSize", skip_serializing_if = "Option::is_none")]
+    pub doc_size: Option<DocSize>,
+    #[serde(rename = "browserLink", skip_serializing_if = "Option::is_none")]
+    pub browser_link: Option<String>,
+    /// Present only for published docs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<DocPublished>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Icon>,
+    #[serde(rename = "coverImage", skip_serializing_if = "Option::is_none")]
+    pub cover_image: Option<Image>,
+}
+
+impl Doc {
+    /// Browser URL for the doc, falling back to the canonical `/d/_d{id}` form
+    pub fn link(&self) -> String {
+        self.browser_link
+            .clone()
+            .unwrap_or_else(|| format!("https://coda.io/d/_d{}", self.id))
+    }
+
+    /// `Icon:` and `Cover:` lines, saying "none" for whichever is unset
+    pub fn appearance(&self) -> String {
+        let icon = self.icon.as_ref().map_or_else(
+            || "none".to_string(),
+            |icon| match (&icon.name, &icon.browser_link) {
+                (Some(name), Some(link)) => format!("{name} ({link})"),
+                (Some(name), None) => name.clone(),
+                (None, Some(link)) => link.clone(),
+                (None, None) => "none".to_string(),
+            },
+        );
+        let cover = self
+            .cover_image
+            .as_ref()
+            .and_then(|image| image.browser_link.as_deref())
+            .unwrap_or("none");
+        format!("Icon: {icon}\nCover: {cover}")
+    }
+
+    /// `updatedAt` as a timestamp, if present and valid RFC3339
+    pub fn updated_time(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+            .as_deref()
+            .and_then(|t| time::parse_timestamp(t).ok())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocPublished {
+    /// Public URL of the published doc
+    #[serde(rename = "browserLink")]
+    pub browser_link: String,
+    pub description: Option<String>,
+    pub discoverable: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Icon {
+    /// Coda icon name
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub icon_type: Option<String>,
+    #[serde(rename = "browserLink")]
+    pub browser_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Image {
+    #[serde(rename = "browserLink")]
+    pub browser_link: Option<String>,
+    #[serde(rename = "type")]
+    pub image_type: Option<String>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocSize {
+    #[serde(rename = "totalRowCount")]
+    pub total_row_count: Option<u64>,
+    #[serde(rename = "tableAndViewCount")]
+    pub table_and_view_count: Option<u64>,
+    #[serde(rename = "pageCount")]
+    pub page_count: Option<u64>,
+    /// When true, the doc is too large for the API to read all rows
+    #[serde(rename = "overApiSizeLimit", default)]
+    pub over_api_size_limit: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +122,67 @@ pub struct ListDocsParams {
     pub limit: Option<u32>,
     /// Search query to filter docs by name
     pub query: Option<String>,
+    /// Token from a previous call's output to fetch the next page
+    pub page_token: Option<String>,
+    /// Remove duplicate IDs and sort results by ID (default: false)
+    pub dedupe: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListDocsActiveSinceParams {
+    /// Start of the window: an RFC3339 timestamp (e.g. 2024-01-15T00:00:00Z) or a relative duration such as 7d, 24h or 30m
+    pub since: String,
+    /// Search query to filter docs by name
+    pub query: Option<String>,
+    /// Maximum number of docs to scan before filtering (default: 500, max: 5000)
+    pub max_docs: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListDocsWithTableCountsParams {
+    /// Search query to filter docs by name
+    pub query: Option<String>,
+    /// Maximum number of docs to count tables for (default: 50, max: 200)
+    pub max_docs: Option<u32>,
+    /// Number of docs to count concurrently (default: 4, max: 8)
+    pub concurrency: Option<u32>,
+    /// Order docs by table count, most first, instead of listing order (default: false)
+    pub sort_by_count: Option<bool>,
+}
+
+/// A doc annotated with its number of tables and views
+#[derive(Debug, Clone, Serialize)]
+pub struct DocTableCount {
+    pub id: String,
+    pub name: String,
+    pub table_count: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListAllDocsParams {
+    /// Search query to filter docs by name
+    pub query: Option<String>,
+    /// Maximum number of docs to list per account (default: 200, max: 1000)
+    pub max_docs_per_account: Option<u32>,
+}
+
+/// A doc from `list_all_docs`, with the accounts that can see it
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountDoc {
+    #[serde(flatten)]
+    pub doc: Doc,
+    /// Labels of the accounts the doc was listed for, e.g. `token 2 (ada@example.com)`
+    pub accounts: Vec<String>,
+}
+
+/// One account's part of a `list_all_docs` call
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountDocCount {
+    /// `token N`, numbered in configuration order, plus the login when known
+    pub account: String,
+    pub doc_count: usize,
+    /// Whether the account had more docs than were listed
+    pub truncated: bool,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -38,6 +191,18 @@ pub struct GetDocParams {
     pub doc_id: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LoadDocContextParams {
+    /// The document ID
+    pub doc_id: String,
+    /// Maximum number of tables to load columns and sample rows for (default: 50, max: 200)
+    pub table_limit: Option<u32>,
+    /// Sample rows fetched per table (default: 5, max: 50; 0 skips samples)
+    pub sample_rows: Option<u32>,
+    /// Number of tables to load concurrently (default: 4, max: 8)
+    pub concurrency: Option<u32>,
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct SearchDocsParams {
     /// Search query
@@ -57,18 +222,131 @@ pub struct CreateDocParams {
     /// Timezone for the document (optional, e.g., `America/Los_Angeles`)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
+    /// Pages to create once the new doc is ready (optional)
+    #[serde(default, skip_serializing)]
+    pub initial_pages: Option<Vec<PageSeed>>,
+    /// Wait until the doc is ready, then return its full metadata and that of any seeded pages (default: false)
+    #[serde(default, skip_serializing)]
+    pub return_full: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CopyDocParams {
+    /// The document to copy; every page, table and row is copied
+    pub source_doc_id: String,
+    /// Title for the copy
+    pub title: String,
+    /// Folder to create the copy in (default: the API default)
+    pub folder_id: Option<String>,
+    /// Timezone for the copy, e.g. `America/Los_Angeles` (optional)
+    pub timezone: Option<String>,
+}
+
+impl From<&CopyDocParams> for CreateDocParams {
+    /// `copy_doc` sends the same body as `create_doc` with `sourceDoc`
+    fn from(params: &CopyDocParams) -> Self {
+        Self {
+            title: params.title.clone(),
+            folder_id: params.folder_id.clone(),
+            source_doc: Some(params.source_doc_id.clone()),
+            timezone: params.timezone.clone(),
+            initial_pages: None,
+            return_full: None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct DeleteDocParams {
     /// The document ID to delete
     pub doc_id: String,
+    /// Target's ID or name; required when the server runs in safe mode
+    pub confirm: Option<String>,
+}
+
+/// How readers can interact with a published doc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PublishMode {
+    View,
+    Play,
+    Edit,
+}
+
+/// Params for `publish_doc`; everything but `doc_id` is the request body
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PublishDocParams {
+    /// The document ID to publish
+    #[serde(skip_serializing)]
+    pub doc_id: String,
+    /// Slug for the published URL (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    /// List the doc in the gallery and search results (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discoverable: Option<bool>,
+    /// Require readers to sign in, earning the publisher credit (optional)
+    #[serde(
+        rename(serialize = "earnCredit"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub earn_credit: Option<bool>,
+    /// Gallery category names, from `list_categories` (optional)
+    #[serde(
+        rename(serialize = "categoryNames"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub category_names: Option<Vec<String>>,
+    /// "view", "play" or "edit" (optional, default: API default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<PublishMode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UnpublishDocParams {
+    /// The document ID to unpublish
+    pub doc_id: String,
+    /// Target's ID or name; required when the server runs in safe mode
+    pub confirm: Option<String>,
+}
+
+/// Params for `move_doc`; the body is just `folderId`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct MoveDocParams {
+    /// The document ID to move
+    #[serde(skip_serializing)]
+    pub doc_id: String,
+    /// ID of the folder to move the doc into
+    #[serde(rename(serialize = "folderId"))]
+    pub folder_id: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_publish_doc_params_body() {
+        let params: PublishDocParams = serde_json::from_str(
+            r#"{"doc_id": "doc1", "earn_credit": true, "category_names": ["Education"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&params).unwrap(),
+            serde_json::json!({"earnCredit": true, "categoryNames": ["Education"]})
+        );
+    }
+
+    #[test]
+    fn test_move_doc_params_serialize() {
+        let params: MoveDocParams =
+            serde_json::from_str(r#"{"doc_id": "doc1", "folder_id": "fl-2"}"#).unwrap();
+        assert_eq!(
+            serde_json::to_value(&params).unwrap(),
+            serde_json::json!({"folderId": "fl-2"})
+        );
+    }
+
     #[test]
     fn test_doc_deserialize() {
         let json = r#"{
@@ -98,6 +376,80 @@ mod tests {
         assert!(doc.owner.is_none());
     }
 
+    #[test]
+    fn test_doc_deserialize_with_size() {
+        let json = r#"{
+            "id": "doc123",
+            "name": "Big Doc",
+            "docSize": {
+                "totalRowCount": 125000,
+                "tableAndViewCount": 42,
+                "pageCount": 17,
+                "overApiSizeLimit": true
+            }
+        }"#;
+
+        let doc: Doc = serde_json::from_str(json).unwrap();
+        let size = doc.doc_size.unwrap();
+        assert_eq!(size.total_row_count, Some(125_000));
+        assert_eq!(size.table_and_view_count, Some(42));
+        assert_eq!(size.page_count, Some(17));
+        assert!(size.over_api_size_limit);
+    }
+
+    #[test]
+    fn test_doc_link() {
+        let json = r#"{
+            "id": "doc123",
+            "name": "Shared Doc",
+            "browserLink": "https://coda.io/d/Shared-Doc_ddoc123",
+            "published": {"browserLink": "https://coda.io/@team/shared-doc"}
+        }"#;
+        let doc: Doc = serde_json::from_str(json).unwrap();
+        assert_eq!(doc.link(), "https://coda.io/d/Shared-Doc_ddoc123");
+        assert_eq!(
+            doc.published.unwrap().browser_link,
+            "https://coda.io/@team/shared-doc"
+        );
+
+        let bare: Doc = serde_json::from_str(r#"{"id": "doc123", "name": "Bare"}"#).unwrap();
+        assert_eq!(bare.link(), "https://coda.io/d/_ddoc123");
+    }
+
+    #[test]
+    fn test_doc_deserialize_appearance() {
+        let json = r#"{
+            "id": "doc123",
+            "name": "Gallery",
+            "icon": {
+                "name": "rocket",
+                "type": "image/png",
+                "browserLink": "https://cdn.coda.io/icons/png/color/rocket-100.png"
+            },
+            "coverImage": {
+                "browserLink": "https://codahosted.io/docs/doc123/blobs/cover.jpg",
+                "type": "image/jpeg",
+                "width": 1600,
+                "height": 400
+            }
+        }"#;
+
+        let doc: Doc = serde_json::from_str(json).unwrap();
+        let icon = doc.icon.as_ref().unwrap();
+        assert_eq!(icon.name.as_deref(), Some("rocket"));
+        assert_eq!(icon.icon_type.as_deref(), Some("image/png"));
+        let cover = doc.cover_image.as_ref().unwrap();
+        assert_eq!(cover.width, Some(1600.0));
+        assert_eq!(
+            doc.appearance(),
+            "Icon: rocket (https://cdn.coda.io/icons/png/color/rocket-100.png)\n\
+             Cover: https://codahosted.io/docs/doc123/blobs/cover.jpg"
+        );
+
+        let bare: Doc = serde_json::from_str(r#"{"id": "doc123", "name": "Bare"}"#).unwrap();
+        assert_eq!(bare.appearance(), "Icon: none\nCover: none");
+    }
+
     #[test]
     fn test_doc_list_deserialize() {
         let json = r#"{
@@ -125,6 +477,11 @@ mod tests {
             created_at: None,
             updated_at: None,
             folder_id: None,
+            doc_size: None,
+            browser_link: None,
+            published: None,
+            icon: None,
+            cover_image: None,
         };
 
         let json = serde_json::to_string(&doc).unwrap();
@@ -138,6 +495,7 @@ mod tests {
         let params: ListDocsParams = serde_json::from_str(json).unwrap();
         assert!(params.limit.is_none());
         assert!(params.query.is_none());
+        assert!(params.dedupe.is_none());
     }
 
     #[test]
@@ -180,12 +538,16 @@ mod tests {
             folder_id: None,
             source_doc: None,
             timezone: None,
+            initial_pages: None,
+            return_full: Some(true),
         };
         let json = serde_json::to_string(&params).unwrap();
         assert!(json.contains("\"title\":\"Test\""));
         assert!(!json.contains("folderId"));
         assert!(!json.contains("sourceDoc"));
         assert!(!json.contains("timezone"));
+        assert!(!json.contains("initial_pages"));
+        assert!(!json.contains("return_full"));
     }
 
     #[test]
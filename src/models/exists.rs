@@ -0,0 +1,52 @@
+use rmcp::schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Kind of object to look up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistsKind {
+    Doc,
+    Page,
+    Table,
+    Row,
+}
+
+impl ExistsKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Doc => "doc",
+            Self::Page => "page",
+            Self::Table => "table",
+            Self::Row => "row",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExistsParams {
+    /// What to look up: "doc", "page", "table", or "row"
+    pub kind: ExistsKind,
+    /// The document ID
+    pub doc_id: String,
+    /// The page ID or name (required for kind "page")
+    pub page_id: Option<String>,
+    /// The table ID or name (required for kinds "table" and "row")
+    pub table_id: Option<String>,
+    /// The row ID (required for kind "row")
+    pub row_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exists_params_deserialize() {
+        let json = r#"{"kind": "row", "doc_id": "doc1", "table_id": "t1", "row_id": "i-1"}"#;
+        let params: ExistsParams = serde_json::from_str(json).unwrap();
+        assert_eq!(params.kind, ExistsKind::Row);
+        assert_eq!(params.row_id, Some("i-1".to_string()));
+        assert!(params.page_id.is_none());
+        assert!(serde_json::from_str::<ExistsKind>(r#""column""#).is_err());
+    }
+}
@@ -22,6 +22,8 @@ pub struct FormulaList {
 pub struct ListFormulasParams {
     /// The document ID
     pub doc_id: String,
+    /// Remove duplicate IDs and sort results by ID (default: false)
+    pub dedupe: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -32,6 +34,20 @@ pub struct GetFormulaParams {
     pub formula_id: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PollFormulaParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The formula ID or name
+    pub formula_id: String,
+    /// Seconds between polls (default: 5, max: 60)
+    pub interval_secs: Option<u64>,
+    /// Maximum number of polls after the initial fetch (default: 10, max: 60)
+    pub max_polls: Option<u32>,
+    /// Stop when the formula equals this value instead of on any change
+    pub until_value: Option<serde_json::Value>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Path collections whose IDs a resolved link reports, keyed as tool params
+const LINK_ID_PARAMS: &[(&str, &str)] = &[
+    ("docs", "doc_id"),
+    ("pages", "page_id"),
+    ("tables", "table_id"),
+    ("columns", "column_id"),
+    ("rows", "row_id"),
+    ("formulas", "formula_id"),
+    ("controls", "control_id"),
+];
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResolveLinkParams {
+    /// A Coda browser URL, e.g. `https://coda.io/d/_dAbCdEfGh/Tasks_suXyz`
+    pub url: String,
+}
+
+/// Result of `GET /resolveBrowserLink`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiLink {
+    #[serde(rename = "type")]
+    pub link_type: Option<String>,
+    pub href: Option<String>,
+    #[serde(rename = "browserLink")]
+    pub browser_link: Option<String>,
+    pub resource: LinkResource,
+}
+
+/// The doc, page, table, row or other object a link points to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkResource {
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    pub id: String,
+    pub name: Option<String>,
+    /// API URL of the resource
+    pub href: Option<String>,
+}
+
+impl LinkResource {
+    /// IDs found in the resource's API URL, keyed by the tool param that takes
+    /// them, e.g. `{"doc_id": "AbCd", "table_id": "grid-1", "row_id": "i-x"}`
+    pub fn ids(&self) -> BTreeMap<&'static str, String> {
+        let Some(href) = &self.href else {
+            return BTreeMap::new();
+        };
+        let path = url::Url::parse(href).map_or_else(|_| href.clone(), |u| u.path().to_string());
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        segments
+            .windows(2)
+            .filter_map(|pair| {
+                let (_, param) = LINK_ID_PARAMS.iter().find(|(c, _)| *c == pair[0])?;
+                let id = urlencoding::decode(pair[1]).ok()?.into_owned();
+                Some((*param, id))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_link_deserialize_and_ids() {
+        let json = r#"{
+            "type": "apiLink",
+            "href": "https://coda.io/apis/v1/resolveBrowserLink?url=x",
+            "browserLink": "https://coda.io/d/_dAbCd#Tasks_tuGrid/r5",
+            "resource": {
+                "type": "row",
+                "id": "i-xyz",
+                "name": "Ship it",
+                "href": "https://coda.io/apis/v1/docs/AbCd/tables/grid-1/rows/i-xyz"
+            }
+        }"#;
+
+        let link: ApiLink = serde_json::from_str(json).unwrap();
+        assert_eq!(link.resource.resource_type, "row");
+        let ids = link.resource.ids();
+        assert_eq!(ids["doc_id"], "AbCd");
+        assert_eq!(ids["table_id"], "grid-1");
+        assert_eq!(ids["row_id"], "i-xyz");
+        assert_eq!(ids.len(), 3);
+    }
+
+    #[test]
+    fn test_link_resource_ids_without_href() {
+        let resource: LinkResource =
+            serde_json::from_str(r#"{"type": "doc", "id": "AbCd"}"#).unwrap();
+        assert!(resource.ids().is_empty());
+    }
+}
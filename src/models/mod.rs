@@ -1,15 +1,86 @@
+pub mod acl;
+pub mod alias;
+pub mod analytics;
+pub mod automation;
+pub mod category;
 pub mod column;
 pub mod control;
 pub mod doc;
+pub mod exists;
 pub mod formula;
+pub mod link;
+pub mod outcome;
 pub mod page;
+pub mod rename;
 pub mod row;
+pub mod session;
 pub mod table;
+pub mod user;
 
+pub use acl::*;
+pub use alias::*;
+pub use analytics::*;
+pub use automation::*;
+pub use category::*;
 pub use column::*;
 pub use control::*;
 pub use doc::*;
+pub use exists::*;
 pub use formula::*;
+pub use link::*;
+pub use outcome::*;
 pub use page::*;
+pub use rename::*;
 pub use row::*;
+pub use session::*;
 pub use table::*;
+pub use user::*;
+
+/// A resource with a Coda ID
+pub trait Identified {
+    fn id(&self) -> &str;
+}
+
+macro_rules! impl_identified {
+    ($($ty:ty),*) => {
+        $(impl Identified for $ty {
+            fn id(&self) -> &str {
+                &self.id
+            }
+        })*
+    };
+}
+
+impl_identified!(Column, Control, Doc, Formula, Page, Row, Table);
+
+/// Sort items by ID and drop repeats, keeping the first occurrence of each ID.
+///
+/// Paginated fetches can return the same item twice near page boundaries.
+pub fn dedupe_by_id<T: Identified>(items: &mut Vec<T>) {
+    items.sort_by(|a, b| a.id().cmp(b.id()));
+    items.dedup_by(|a, b| a.id() == b.id());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedupe_by_id() {
+        let mut pages: Vec<Page> = serde_json::from_str(
+            r#"[
+                {"id": "p3", "name": "Three"},
+                {"id": "p1", "name": "One"},
+                {"id": "p3", "name": "Three (again)"},
+                {"id": "p2", "name": "Two"}
+            ]"#,
+        )
+        .unwrap();
+
+        dedupe_by_id(&mut pages);
+
+        let ids: Vec<&str> = pages.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["p1", "p2", "p3"]);
+        assert_eq!(pages[2].name, "Three");
+    }
+}
@@ -0,0 +1,183 @@
+use rmcp::model::{CallToolResult, Content};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{CodaError, ErrorCategory};
+
+/// Shared envelope carried in a tool result's structured content,
+/// alongside the human-readable text
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolOutcome {
+    pub ok: bool,
+    /// Name of the tool that produced the outcome
+    pub operation: &'static str,
+    /// One-line description of what happened
+    pub summary: String,
+    /// Tool-specific payload, `null` if there is none
+    pub data: Value,
+    pub warnings: Vec<String>,
+    /// HTTP status of the API error behind a failure, if there was one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+impl ToolOutcome {
+    pub fn success(operation: &'static str, summary: impl Into<String>, data: Value) -> Self {
+        Self {
+            ok: true,
+            operation,
+            summary: summary.into(),
+            data,
+            warnings: Vec::new(),
+            status: None,
+        }
+    }
+
+    pub fn failure(operation: &'static str, summary: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            operation,
+            summary: summary.into(),
+            data: Value::Null,
+            warnings: Vec::new(),
+            status: None,
+        }
+    }
+
+    /// Failure caused by an API error, keeping its HTTP status
+    pub fn from_error(operation: &'static str, error: &CodaError) -> Self {
+        Self {
+            status: error.status(),
+            ..Self::failure(operation, error.to_string())
+        }
+    }
+
+    #[must_use]
+    pub fn with_warning(mut self, warning: impl Into<String>) -> Self {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Build the tool result: `text` as content, the envelope as structured
+    /// content, and `is_error` set for failures
+    pub fn into_result(self, text: impl Into<String>) -> CallToolResult {
+        let content = vec![Content::text(text.into())];
+        let mut result = if self.ok {
+            CallToolResult::success(content)
+        } else {
+            CallToolResult::error(content)
+        };
+        result.structured_content = Some(serde_json::to_value(&self).unwrap_or_default());
+        result
+    }
+}
+
+/// Per-item report from a tool that acts on several inputs, so clients can
+/// handle partial success without parsing the text
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult<T> {
+    pub succeeded: Vec<T>,
+    pub failed: Vec<BatchFailure>,
+}
+
+/// An input a batch tool could not process
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchFailure {
+    /// The input as the caller gave it
+    pub input: Value,
+    pub error_category: ErrorCategory,
+    pub message: String,
+}
+
+impl<T> Default for BatchResult<T> {
+    fn default() -> Self {
+        Self {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+impl BatchFailure {
+    pub fn new(input: &impl Serialize, error: &CodaError) -> Self {
+        Self {
+            input: serde_json::to_value(input).unwrap_or_default(),
+            error_category: error.category(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl<T> BatchResult<T> {
+    pub fn push_failure(&mut self, input: &impl Serialize, error: &CodaError) {
+        self.failed.push(BatchFailure::new(input, error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_success_envelope() {
+        let result = ToolOutcome::success("add_row", "Row added", serde_json::json!({"id": 1}))
+            .with_warning("Changes may take a few seconds to appear")
+            .into_result("Row added successfully.");
+
+        assert_eq!(result.is_error, Some(false));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["ok"], true);
+        assert_eq!(envelope["operation"], "add_row");
+        assert_eq!(envelope["summary"], "Row added");
+        assert_eq!(envelope["data"]["id"], 1);
+        assert_eq!(envelope["warnings"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            result.content[0].raw.as_text().unwrap().text,
+            "Row added successfully."
+        );
+    }
+
+    #[test]
+    fn test_failure_envelope() {
+        let result = ToolOutcome::failure("delete_doc", "Not found").into_result("Not found");
+
+        assert_eq!(result.is_error, Some(true));
+        let envelope = result.structured_content.unwrap();
+        assert_eq!(envelope["ok"], false);
+        assert_eq!(envelope["operation"], "delete_doc");
+        assert!(envelope["data"].is_null());
+        assert!(envelope["warnings"].as_array().unwrap().is_empty());
+        assert!(envelope.get("status").is_none());
+    }
+
+    #[test]
+    fn test_batch_result_records_failures() {
+        let mut batch: BatchResult<&str> = BatchResult::default();
+        batch.succeeded.push("t1");
+        batch.push_failure(&serde_json::json!({"table_id": "t2"}), &CodaError::NotFound);
+
+        let json = serde_json::to_value(&batch).unwrap();
+        assert_eq!(json["succeeded"], serde_json::json!(["t1"]));
+        assert_eq!(json["failed"][0]["input"]["table_id"], "t2");
+        assert_eq!(json["failed"][0]["error_category"], "not_found");
+        assert!(json["failed"][0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Not found"));
+    }
+
+    #[test]
+    fn test_from_error_keeps_status() {
+        let envelope = ToolOutcome::from_error("delete_row", &CodaError::RateLimited)
+            .into_result("Rate limited")
+            .structured_content
+            .unwrap();
+
+        assert_eq!(envelope["ok"], false);
+        assert_eq!(envelope["status"], 429);
+        assert!(envelope["summary"]
+            .as_str()
+            .unwrap()
+            .starts_with("Rate limited"));
+    }
+}
@@ -1,5 +1,6 @@
 use rmcp::schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageParent {
@@ -20,6 +21,50 @@ pub struct Page {
     pub parent: Option<PageParent>,
     #[serde(rename = "contentType")]
     pub content_type: Option<String>,
+    #[serde(rename = "browserLink", skip_serializing_if = "Option::is_none")]
+    pub browser_link: Option<String>,
+}
+
+/// A page with its nested subpages
+#[derive(Debug, Clone, Serialize)]
+pub struct PageNode {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PageNode>,
+}
+
+/// Arrange a flat page list into a tree using each page's `parent`.
+///
+/// Pages whose parent is not in the list become roots; listing order is kept.
+pub fn build_page_tree(pages: &[Page]) -> Vec<PageNode> {
+    fn children_of(pages: &[Page], parent: Option<&str>) -> Vec<PageNode> {
+        pages
+            .iter()
+            .filter(|p| {
+                let page_parent = p
+                    .parent
+                    .as_ref()
+                    .map(|pp| pp.id.as_str())
+                    .filter(|id| pages.iter().any(|other| other.id == *id));
+                page_parent == parent
+            })
+            .map(|p| PageNode {
+                id: p.id.clone(),
+                name: p.name.clone(),
+                children: children_of(pages, Some(&p.id)),
+            })
+            .collect()
+    }
+    children_of(pages, None)
+}
+
+/// Append `nodes` as an indented bullet list, two spaces per level
+pub fn write_page_outline(output: &mut String, nodes: &[PageNode], depth: usize) {
+    for node in nodes {
+        let _ = write!(output, "\n{}- {}", "  ".repeat(depth), node.name);
+        write_page_outline(output, &node.children, depth + 1);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +88,8 @@ pub struct PageContent {
 pub struct ListPagesParams {
     /// The document ID
     pub doc_id: String,
+    /// Remove duplicate IDs and sort results by ID (default: false)
+    pub dedupe: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -51,6 +98,375 @@ pub struct GetPageParams {
     pub doc_id: String,
     /// The page ID or name
     pub page_id: String,
+    /// Include the byte length and SHA-256 of the downloaded export (default: false)
+    pub include_checksum: Option<bool>,
+    /// Write the content to this path inside `CODA_EXPORT_DIR` instead of returning it
+    pub to_file: Option<String>,
+    /// Transform applied to the exported HTML (default: none)
+    pub transform: Option<PageTransform>,
+    /// With a transform, also return the untransformed export, labeled "raw"; not allowed with `to_file` (default: false)
+    pub include_raw: Option<bool>,
+    /// Start a fresh export when one fails with a transient error such as a temporary internal error (default: false)
+    pub retry_on_failure: Option<bool>,
+    /// Export format: "html" (default) or "markdown", which is smaller and easier to read
+    pub format: Option<String>,
+}
+
+/// Export formats Coda accepts as `outputFormat`
+pub const PAGE_EXPORT_FORMATS: &[&str] = &["html", "markdown"];
+
+impl GetPageParams {
+    /// The requested export format, validated with the options that depend
+    /// on it before an export is started
+    pub fn export_format(&self) -> Result<&str, String> {
+        let format = checked_export_format(self.format.as_deref(), self.transform)?;
+        if self.include_raw == Some(true) && self.transform.is_some() && self.to_file.is_some() {
+            return Err(
+                "`include_raw` returns the raw export inline; it can't be combined with `to_file`."
+                    .to_string(),
+            );
+        }
+        Ok(format)
+    }
+}
+
+/// An export format and the transform applied to it, checked together
+fn checked_export_format(
+    format: Option<&str>,
+    transform: Option<PageTransform>,
+) -> Result<&str, String> {
+    let format = format.unwrap_or("html");
+    if !PAGE_EXPORT_FORMATS.contains(&format) {
+        return Err(format!(
+            "Unsupported page format '{format}'. Use \"html\" or \"markdown\"."
+        ));
+    }
+    if format != "html" && transform.is_some() {
+        return Err(format!(
+            "`transform` works on HTML exports; it can't be combined with format \"{format}\"."
+        ));
+    }
+    Ok(format)
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExportPagesParams {
+    /// The document ID
+    pub doc_id: String,
+    /// IDs or names of the pages to export, each returned as its own content block (max 20)
+    pub page_ids: Vec<String>,
+    /// Export format for every page: "html" (default) or "markdown"
+    pub format: Option<String>,
+    /// Transform applied to each exported page (default: none, HTML)
+    pub transform: Option<PageTransform>,
+    /// Start a fresh export when one fails with a transient error (default: false)
+    pub retry_on_failure: Option<bool>,
+}
+
+impl ExportPagesParams {
+    /// The requested export format, validated against `transform`
+    pub fn export_format(&self) -> Result<&str, String> {
+        checked_export_format(self.format.as_deref(), self.transform)
+    }
+}
+
+/// Transform applied to exported page HTML before it is returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PageTransform {
+    /// Plain text: tags dropped, entities decoded, one block element per line
+    Text,
+}
+
+impl PageTransform {
+    pub fn apply(self, html: &str) -> String {
+        match self {
+            Self::Text => crate::html::to_text(html),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResumePageExportParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The page ID or name
+    pub page_id: String,
+    /// Export ID returned by a timed-out `get_page` or `get_page_full`
+    pub export_id: String,
+    /// Include the byte length and SHA-256 of the downloaded export (default: false)
+    pub include_checksum: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExtractPageTablesParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The page ID or name
+    pub page_id: String,
+}
+
+/// A page to create, with optional canvas content
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PageSeed {
+    /// Page name
+    pub name: String,
+    /// Initial page content (optional)
+    pub content: Option<String>,
+    /// Content format: "markdown" (default) or "html"
+    pub format: Option<String>,
+}
+
+impl PageSeed {
+    /// Reject an unsupported content format before the doc or page is created
+    pub fn check_format(&self) -> Result<(), String> {
+        check_content_format(self.format.as_deref())
+            .map_err(|message| format!("Page '{}': {message}", self.name))
+    }
+}
+
+/// Reject a canvas content format other than "html" or "markdown"
+pub fn check_content_format(format: Option<&str>) -> Result<(), String> {
+    match format {
+        Some(format) if !PAGE_EXPORT_FORMATS.contains(&format) => Err(format!(
+            "Unsupported content format '{format}'. Use \"html\" or \"markdown\"."
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A page in an outline for `create_page_outline`, with its subpages
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct OutlineNode {
+    /// Page name
+    pub name: String,
+    /// Initial page content (optional)
+    pub content: Option<String>,
+    /// Content format: "markdown" (default) or "html"
+    pub format: Option<String>,
+    /// Subpages, created under this page in order
+    #[serde(default)]
+    pub children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    pub fn seed(&self) -> PageSeed {
+        PageSeed {
+            name: self.name.clone(),
+            content: self.content.clone(),
+            format: self.format.clone(),
+        }
+    }
+
+    /// Check the content format of every page in this node's subtree
+    pub fn check_formats(&self) -> Result<(), String> {
+        self.seed().check_format()?;
+        self.children.iter().try_for_each(Self::check_formats)
+    }
+
+    /// Number of pages in this node's subtree, including itself
+    pub fn page_count(&self) -> usize {
+        1 + self.children.iter().map(Self::page_count).sum::<usize>()
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreatePageOutlineParams {
+    /// The document ID
+    pub doc_id: String,
+    /// Existing page to create the outline under (default: top level)
+    pub parent_page_id: Option<String>,
+    /// Pages to create, each with optional nested `children` (max 100 pages in total)
+    pub outline: Vec<OutlineNode>,
+}
+
+/// A page `create_doc` created from `initial_pages`
+#[derive(Debug, Clone, Serialize)]
+pub struct SeededPage {
+    pub name: String,
+    pub id: String,
+}
+
+/// A page created by `create_page_outline`
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlinePage {
+    pub name: String,
+    pub id: String,
+    pub parent_page_id: Option<String>,
+    /// Nesting level within the outline, 0 for top-level entries
+    pub depth: usize,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CreatePageParams {
+    /// The document ID
+    pub doc_id: String,
+    /// Page name
+    pub name: String,
+    /// Page subtitle (optional)
+    pub subtitle: Option<String>,
+    /// Existing page to nest the new page under (default: top level)
+    pub parent_page_id: Option<String>,
+    /// Initial canvas content (optional)
+    pub page_content: Option<String>,
+    /// Content format: "html" (default) or "markdown"
+    pub format: Option<String>,
+    /// Wait for the page and return its complete metadata instead of just the ID (default: false)
+    pub return_full: Option<bool>,
+}
+
+impl From<&CreatePageParams> for CreatePageRequest {
+    fn from(params: &CreatePageParams) -> Self {
+        let seed = PageSeed {
+            name: params.name.clone(),
+            content: params.page_content.clone(),
+            format: Some(params.format.clone().unwrap_or_else(|| "html".to_string())),
+        };
+        Self {
+            subtitle: params.subtitle.clone(),
+            parent_page_id: params.parent_page_id.clone(),
+            ..Self::from(&seed)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatePageRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    /// Page to nest the new page under
+    #[serde(rename = "parentPageId", skip_serializing_if = "Option::is_none")]
+    pub parent_page_id: Option<String>,
+    #[serde(rename = "pageContent", skip_serializing_if = "Option::is_none")]
+    pub page_content: Option<PageContentInput>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageContentInput {
+    #[serde(rename = "type")]
+    pub content_type: String,
+    #[serde(rename = "canvasContent")]
+    pub canvas_content: CanvasContent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CanvasContent {
+    pub format: String,
+    pub content: String,
+}
+
+impl From<&PageSeed> for CreatePageRequest {
+    fn from(seed: &PageSeed) -> Self {
+        Self {
+            name: seed.name.clone(),
+            subtitle: None,
+            parent_page_id: None,
+            page_content: seed.content.as_ref().map(|content| PageContentInput {
+                content_type: "canvas".to_string(),
+                canvas_content: CanvasContent {
+                    format: seed
+                        .format
+                        .clone()
+                        .unwrap_or_else(|| "markdown".to_string()),
+                    content: content.clone(),
+                },
+            }),
+        }
+    }
+}
+
+/// How `update_page` content is combined with the existing page content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum InsertionMode {
+    /// Add after the existing content
+    #[default]
+    Append,
+    /// Replace the existing content
+    Replace,
+}
+
+/// Canvas content to add to a page
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ContentInsert {
+    /// Content to insert
+    pub content: String,
+    /// Content format: "html" (default) or "markdown"
+    pub format: Option<String>,
+    /// "append" (default) or "replace"
+    pub mode: Option<InsertionMode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpdatePageParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The page ID or name
+    pub page_id: String,
+    /// New page name
+    pub name: Option<String>,
+    /// New page subtitle
+    pub subtitle: Option<String>,
+    /// New page icon, by Coda icon name
+    pub icon_name: Option<String>,
+    /// Content to append to or replace the page's canvas with
+    pub content_insert: Option<ContentInsert>,
+}
+
+/// `pageUpdate` body; only the fields the caller set are sent
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpdatePageRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    #[serde(rename = "iconName", skip_serializing_if = "Option::is_none")]
+    pub icon_name: Option<String>,
+    #[serde(rename = "contentUpdate", skip_serializing_if = "Option::is_none")]
+    pub content_update: Option<ContentUpdate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentUpdate {
+    #[serde(rename = "insertionMode")]
+    pub insertion_mode: InsertionMode,
+    #[serde(rename = "canvasContent")]
+    pub canvas_content: CanvasContent,
+}
+
+impl UpdatePageRequest {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.subtitle.is_none()
+            && self.icon_name.is_none()
+            && self.content_update.is_none()
+    }
+}
+
+impl From<&UpdatePageParams> for UpdatePageRequest {
+    fn from(params: &UpdatePageParams) -> Self {
+        Self {
+            name: params.name.clone(),
+            subtitle: params.subtitle.clone(),
+            icon_name: params.icon_name.clone(),
+            content_update: params.content_insert.as_ref().map(|insert| ContentUpdate {
+                insertion_mode: insert.mode.unwrap_or_default(),
+                canvas_content: CanvasContent {
+                    format: insert.format.clone().unwrap_or_else(|| "html".to_string()),
+                    content: insert.content.clone(),
+                },
+            }),
+        }
+    }
+}
+
+/// Response to creating or updating a page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageMutationResponse {
+    pub id: String,
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
 }
 
 // Export workflow types for canvas pages
@@ -75,6 +491,77 @@ pub struct ExportResponse {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_page_seed_check_format() {
+        let seed: PageSeed =
+            serde_json::from_str(r#"{"name": "Notes", "content": "Hi", "format": "rtf"}"#).unwrap();
+        assert_eq!(
+            seed.check_format().unwrap_err(),
+            "Page 'Notes': Unsupported content format 'rtf'. Use \"html\" or \"markdown\"."
+        );
+
+        let seed: PageSeed = serde_json::from_str(r#"{"name": "Notes"}"#).unwrap();
+        assert!(seed.check_format().is_ok());
+    }
+
+    #[test]
+    fn test_create_page_request_from_seed() {
+        let seed: PageSeed =
+            serde_json::from_str(r##"{"name": "Notes", "content": "# Hello"}"##).unwrap();
+        let json = serde_json::to_value(CreatePageRequest::from(&seed)).unwrap();
+        assert_eq!(json["name"], "Notes");
+        assert_eq!(json["pageContent"]["type"], "canvas");
+        assert_eq!(json["pageContent"]["canvasContent"]["format"], "markdown");
+        assert_eq!(json["pageContent"]["canvasContent"]["content"], "# Hello");
+
+        let seed: PageSeed = serde_json::from_str(r#"{"name": "Empty"}"#).unwrap();
+        let json = serde_json::to_value(CreatePageRequest::from(&seed)).unwrap();
+        assert!(json.get("pageContent").is_none());
+    }
+
+    #[test]
+    fn test_update_page_request_skips_unset_fields() {
+        let params: UpdatePageParams = serde_json::from_str(
+            r#"{"doc_id": "d1", "page_id": "p1", "name": "Renamed",
+                "content_insert": {"content": "More", "format": "markdown"}}"#,
+        )
+        .unwrap();
+
+        let json = serde_json::to_value(UpdatePageRequest::from(&params)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "Renamed",
+                "contentUpdate": {
+                    "insertionMode": "append",
+                    "canvasContent": {"format": "markdown", "content": "More"}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_page_tree() {
+        let pages: Vec<Page> = serde_json::from_str(
+            r#"[
+                {"id": "p1", "name": "Overview"},
+                {"id": "p2", "name": "Details", "parent": {"id": "p1"}},
+                {"id": "p3", "name": "Deep", "parent": {"id": "p2"}},
+                {"id": "p4", "name": "Orphan", "parent": {"id": "missing"}},
+                {"id": "p5", "name": "Appendix"}
+            ]"#,
+        )
+        .unwrap();
+
+        let tree = build_page_tree(&pages);
+
+        let roots: Vec<&str> = tree.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(roots, ["Overview", "Orphan", "Appendix"]);
+        assert_eq!(tree[0].children[0].name, "Details");
+        assert_eq!(tree[0].children[0].children[0].name, "Deep");
+    }
+
     #[test]
     fn test_export_request_serialize() {
         let req = ExportRequest {
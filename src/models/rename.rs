@@ -0,0 +1,55 @@
+use rmcp::schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Kind of object to rename
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RenameKind {
+    Doc,
+    Page,
+    /// Not supported by the Coda API; accepted so the tool can explain why
+    Column,
+    /// Not supported by the Coda API; accepted so the tool can explain why
+    Table,
+}
+
+impl RenameKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Doc => "doc",
+            Self::Page => "page",
+            Self::Column => "column",
+            Self::Table => "table",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RenameParams {
+    /// What to rename: "doc" or "page"
+    pub kind: RenameKind,
+    /// The document ID
+    pub doc_id: String,
+    /// The page ID or name (required for kind "page")
+    pub page_id: Option<String>,
+    /// The new name
+    pub new_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_params_deserialize() {
+        let json = r#"{"kind": "page", "doc_id": "doc1", "page_id": "p1", "new_name": "Notes"}"#;
+        let params: RenameParams = serde_json::from_str(json).unwrap();
+        assert_eq!(params.kind, RenameKind::Page);
+        assert_eq!(params.page_id, Some("p1".to_string()));
+    }
+
+    #[test]
+    fn test_rename_kind_rejects_unknown() {
+        assert!(serde_json::from_str::<RenameKind>(r#""formula""#).is_err());
+    }
+}
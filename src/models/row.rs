@@ -1,6 +1,9 @@
 use rmcp::schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+
+use crate::html::csv_field;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Row {
@@ -13,6 +16,332 @@ pub struct Row {
     pub values: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// A reference cell's target, extracted from `valueFormat=rich` output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CellReference {
+    pub column: String,
+    #[serde(rename = "rowId")]
+    pub row_id: String,
+    #[serde(rename = "tableId")]
+    pub table_id: String,
+    #[serde(rename = "docId", skip_serializing_if = "Option::is_none")]
+    pub doc_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl CellReference {
+    fn from_value(column: &str, value: &serde_json::Value) -> Option<Self> {
+        let object = value.as_object()?;
+        let row_id = object.get("rowId")?.as_str()?;
+        let table_id = object.get("tableId")?.as_str()?;
+        let doc_id = object
+            .get("url")
+            .and_then(serde_json::Value::as_str)
+            .and_then(doc_id_from_url);
+        Some(Self {
+            column: column.to_string(),
+            row_id: row_id.to_string(),
+            table_id: table_id.to_string(),
+            doc_id,
+            name: object
+                .get("name")
+                .and_then(serde_json::Value::as_str)
+                .map(String::from),
+        })
+    }
+}
+
+/// Doc ID from a Coda browser URL, e.g. `https://coda.io/d/Roadmap_dAbC123/...` -> `AbC123`
+fn doc_id_from_url(url: &str) -> Option<String> {
+    let segment = url.split("/d/").nth(1)?.split(['/', '#', '?']).next()?;
+    let (_, id) = segment.rsplit_once("_d")?;
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+impl Row {
+    /// Reference cells in this row, sorted by column name.
+    ///
+    /// Requires rows fetched with `valueFormat=rich`; multi-reference cells
+    /// yield one entry per referenced row.
+    pub fn references(&self) -> Vec<CellReference> {
+        let mut refs: Vec<CellReference> = self
+            .values
+            .iter()
+            .flatten()
+            .flat_map(|(column, value)| match value {
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .filter_map(|v| CellReference::from_value(column, v))
+                    .collect::<Vec<_>>(),
+                _ => CellReference::from_value(column, value)
+                    .into_iter()
+                    .collect(),
+            })
+            .collect();
+        refs.sort_by(|a, b| a.column.cmp(&b.column));
+        refs
+    }
+
+    /// Replace the values of the given columns with `[REDACTED]`.
+    /// Column names are matched case-insensitively.
+    pub fn redact(&mut self, columns: &[String]) {
+        if columns.is_empty() {
+            return;
+        }
+        if let Some(values) = &mut self.values {
+            for (key, value) in values.iter_mut() {
+                if columns.iter().any(|c| c.eq_ignore_ascii_case(key)) {
+                    *value = serde_json::Value::String("[REDACTED]".to_string());
+                }
+            }
+        }
+    }
+}
+
+/// How the API renders cell values, sent as `valueFormat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ValueFormat {
+    /// Plain values (API default)
+    Simple,
+    /// Plain values, with multi-value cells as arrays
+    SimpleWithArrays,
+    /// Structured values, e.g. references with their row, table and doc IDs
+    Rich,
+}
+
+impl ValueFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Simple => "simple",
+            Self::SimpleWithArrays => "simpleWithArrays",
+            Self::Rich => "rich",
+        }
+    }
+
+    /// Parse `simple`, `simpleWithArrays` or `rich` (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        [Self::Simple, Self::SimpleWithArrays, Self::Rich]
+            .into_iter()
+            .find(|format| format.as_str().eq_ignore_ascii_case(value))
+    }
+}
+
+/// Output shape for `get_rows`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RowsFormat {
+    /// One object per row (default)
+    #[default]
+    Rows,
+    /// `{columns: {name: [values...]}, row_ids: [...]}`, loadable into dataframes
+    Columnar,
+    /// CSV with a header of every column name, the most compact shape
+    Csv,
+}
+
+/// Column-oriented view of rows; every column array has one entry per row
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnarRows {
+    pub columns: BTreeMap<String, Vec<serde_json::Value>>,
+    pub row_ids: Vec<String>,
+}
+
+impl ColumnarRows {
+    /// Pivot rows into columns, filling `null` where a row lacks a column
+    pub fn from_rows(rows: &[Row]) -> Self {
+        let mut columns: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+        for row in rows {
+            for key in row.values.iter().flat_map(HashMap::keys) {
+                columns.entry(key.clone()).or_default();
+            }
+        }
+        for row in rows {
+            for (name, values) in &mut columns {
+                let value = row
+                    .values
+                    .as_ref()
+                    .and_then(|v| v.get(name))
+                    .cloned()
+                    .unwrap_or(serde_json::Value::Null);
+                values.push(value);
+            }
+        }
+        Self {
+            columns,
+            row_ids: rows.iter().map(|r| r.id.clone()).collect(),
+        }
+    }
+
+    /// Render as CSV: a header of the column names, then one line per row.
+    /// Strings are written as is, `null` as an empty field, and lists or
+    /// objects as compact JSON.
+    pub fn to_csv(&self) -> String {
+        let mut lines = vec![self
+            .columns
+            .keys()
+            .map(|name| csv_field(name))
+            .collect::<Vec<_>>()
+            .join(",")];
+        for i in 0..self.row_ids.len() {
+            let fields: Vec<String> = self
+                .columns
+                .values()
+                .map(|values| match &values[i] {
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::String(s) => csv_field(s),
+                    other => csv_field(&other.to_string()),
+                })
+                .collect();
+            lines.push(fields.join(","));
+        }
+        let mut csv = lines.join("\n");
+        csv.push('\n');
+        csv
+    }
+}
+
+/// How many sampled rows have a value in one column
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnFillRate {
+    pub column: String,
+    pub filled: usize,
+    pub total: usize,
+    /// `filled` as a percentage of `total`, rounded to one decimal place
+    pub percent: f64,
+}
+
+impl ColumnFillRate {
+    /// Fill rate of every column seen in `rows`, most complete first.
+    ///
+    /// `null`, blank strings and empty arrays count as empty, as does a
+    /// column missing from a row.
+    pub fn from_rows(rows: &[Row]) -> Vec<Self> {
+        let mut filled: BTreeMap<&str, usize> = BTreeMap::new();
+        for row in rows {
+            for (column, value) in row.values.iter().flatten() {
+                let count = filled.entry(column).or_default();
+                if !is_blank(value) {
+                    *count += 1;
+                }
+            }
+        }
+        let total = rows.len();
+        let mut rates: Vec<Self> = filled
+            .into_iter()
+            .map(|(column, filled)| Self {
+                column: column.to_string(),
+                filled,
+                total,
+                #[allow(clippy::cast_precision_loss)] // row counts are far below 2^52
+                percent: (filled as f64 * 1000.0 / total as f64).round() / 10.0,
+            })
+            .collect();
+        // Stable sort keeps ties in column-name order
+        rates.sort_by_key(|r| std::cmp::Reverse(r.filled));
+        rates
+    }
+}
+
+fn is_blank(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => true,
+        serde_json::Value::String(s) => s.trim().is_empty(),
+        serde_json::Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// One value and how many sampled rows hold it
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueCount {
+    pub value: serde_json::Value,
+    pub count: usize,
+}
+
+/// Summary statistics of one column over sampled rows
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnProfile {
+    /// Rows with a non-empty value
+    pub count: usize,
+    /// Rows where the column is empty or missing
+    pub empty: usize,
+    pub distinct: usize,
+    /// Numeric when every non-empty value is a number, else lexical
+    pub min: Option<serde_json::Value>,
+    pub max: Option<serde_json::Value>,
+    /// Most frequent values, ties broken by value
+    pub top_values: Vec<ValueCount>,
+}
+
+impl ColumnProfile {
+    /// Profile the values of `column` across `rows` in one pass, keeping the
+    /// `top_k` most frequent values. Empty cells count as in
+    /// [`ColumnFillRate::from_rows`].
+    pub fn from_rows(rows: &[Row], column: &str, top_k: usize) -> Self {
+        let mut counts: BTreeMap<String, (&serde_json::Value, usize)> = BTreeMap::new();
+        let mut numbers: Vec<(f64, &serde_json::Value)> = Vec::new();
+        let mut all_numeric = true;
+        let mut count = 0;
+        for value in rows
+            .iter()
+            .filter_map(|row| row.values.as_ref()?.get(column))
+            .filter(|value| !is_blank(value))
+        {
+            count += 1;
+            match value.as_f64() {
+                Some(number) => numbers.push((number, value)),
+                None => all_numeric = false,
+            }
+            let key = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            counts.entry(key).or_insert((value, 0)).1 += 1;
+        }
+
+        let (min, max) = if count == 0 {
+            (None, None)
+        } else if all_numeric {
+            let min = numbers.iter().min_by(|a, b| a.0.total_cmp(&b.0));
+            let max = numbers.iter().max_by(|a, b| a.0.total_cmp(&b.0));
+            (
+                min.map(|(_, v)| (*v).clone()),
+                max.map(|(_, v)| (*v).clone()),
+            )
+        } else {
+            // Keys are sorted, so the lexical extremes are the map's ends
+            (
+                counts.values().next().map(|(v, _)| (*v).clone()),
+                counts.values().next_back().map(|(v, _)| (*v).clone()),
+            )
+        };
+
+        let distinct = counts.len();
+        let mut top: Vec<(&serde_json::Value, usize)> = counts.into_values().collect();
+        // Stable sort keeps ties in key order
+        top.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+        top.truncate(top_k);
+
+        Self {
+            count,
+            empty: rows.len() - count,
+            distinct,
+            min,
+            max,
+            top_values: top
+                .into_iter()
+                .map(|(value, count)| ValueCount {
+                    value: value.clone(),
+                    count,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RowList {
     pub items: Vec<Row>,
@@ -26,66 +355,740 @@ pub struct RowMutationResponse {
     pub request_id: String,
     #[serde(rename = "addedRowIds")]
     pub added_row_ids: Option<Vec<String>>,
+    /// Stored row ID for each submitted row, keyed by its index in the request
+    #[serde(rename = "rowIdMap", skip_serializing_if = "Option::is_none")]
+    pub row_id_map: Option<BTreeMap<String, String>>,
+}
+
+impl RowMutationResponse {
+    /// `input -> row ID` lines from `rowIdMap`, in input order
+    pub fn row_id_lines(&self) -> Vec<String> {
+        let mut entries: Vec<_> = self.row_id_map.iter().flatten().collect();
+        // Numeric keys sort by value, so "10" comes after "9"; any other keys
+        // follow them, kept in map order by the stable sort
+        entries.sort_by_key(|(input, _)| {
+            let index = input.parse::<usize>().ok();
+            (index.is_none(), index)
+        });
+        entries
+            .into_iter()
+            .map(|(input, id)| format!("{input} -> {id}"))
+            .collect()
+    }
+}
+
+/// Response to deleting several rows in one request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowsDeleteResponse {
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+    #[serde(rename = "rowIds")]
+    pub row_ids: Vec<String>,
+}
+
+/// Response of `GET /mutationStatus/{requestId}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MutationStatus {
+    /// Whether Coda has applied the mutation
+    pub completed: bool,
+    /// Set when the mutation completed with a problem, e.g. a row it skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Row order for `get_rows`, sent as `sortBy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum RowSort {
+    /// Oldest rows first
+    CreatedAt,
+    /// The order rows appear in the table, e.g. after sorting it in the UI
+    Natural,
+    /// Least recently updated rows first
+    UpdatedAt,
+}
+
+impl RowSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CreatedAt => "createdAt",
+            Self::Natural => "natural",
+            Self::UpdatedAt => "updatedAt",
+        }
+    }
+}
+
+/// Equality filter on a single column
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct RowFilter {
+    /// Column ID (e.g. "c-abc123") or name
+    pub column: String,
+    /// Value to match: string, number, or boolean
+    pub value: serde_json::Value,
+}
+
+impl RowFilter {
+    /// Compile to Coda's `column:value` query syntax.
+    ///
+    /// Both sides are JSON-encoded, so quotes and backslashes are escaped and
+    /// colons inside names or values stay inside the quoted string. Column IDs
+    /// are left bare.
+    pub fn to_query(&self) -> String {
+        let column = if is_column_id(&self.column) {
+            self.column.clone()
+        } else {
+            serde_json::Value::String(self.column.clone()).to_string()
+        };
+        format!("{column}:{}", self.value)
+    }
+}
+
+/// Whether `s` looks like a Coda column ID (`c-` followed by alphanumerics)
+fn is_column_id(s: &str) -> bool {
+    s.strip_prefix("c-").is_some_and(|rest| {
+        !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetRowsParams {
-    /// The document ID
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
     pub doc_id: String,
-    /// The table ID or name
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
     pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
     /// Maximum rows to return (default: 100)
     pub limit: Option<u32>,
     /// Query to filter rows (Coda formula syntax)
     pub query: Option<String>,
+    /// Structured filter: rows where `column` equals `value`. Escaped automatically; use instead of `query`
+    pub filter: Option<RowFilter>,
+    /// Column names whose values should be replaced with "[REDACTED]" (case-insensitive)
+    pub redact_columns: Option<Vec<String>>,
+    /// Remove duplicate IDs and sort results by ID (default: false)
+    pub dedupe: Option<bool>,
+    /// Output format: "rows" (default), "columnar", or "csv" for the fewest tokens
+    pub format: Option<RowsFormat>,
+    /// Fetch rich values and list the row/table/doc IDs that reference cells point to (default: false)
+    pub reference_ids: Option<bool>,
+    /// Key cells by column name rather than ID (default: server setting, normally true)
+    pub use_column_names: Option<bool>,
+    /// Cell value format: "simple", "simpleWithArrays" or "rich" (default: server setting, normally the API default)
+    pub value_format: Option<ValueFormat>,
+    /// Follow page tokens to fetch every matching row, up to 10000; `limit` is ignored (default: false)
+    pub fetch_all: Option<bool>,
+    /// Follow page tokens of `limit` rows until the rendered rows would exceed this many bytes, then return the token to continue from
+    pub max_output_bytes: Option<u32>,
+    /// Token from a previous call's output to continue from
+    pub page_token: Option<String>,
+    /// Return only the last N matching rows, in table order. Pages through every matching row (up to 10000) to find them; `limit` is ignored
+    pub from_end: Option<u32>,
+    /// Row order: "createdAt", "natural" (table order) or "updatedAt" (default: API default)
+    pub sort_by: Option<RowSort>,
+    /// Only return rows and columns visible in the table, e.g. respecting filters in a view (default: false)
+    pub visible_only: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetRowParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// The row ID
+    pub row_id: String,
+    /// Column names whose values should be replaced with "[REDACTED]" (case-insensitive)
+    pub redact_columns: Option<Vec<String>>,
+    /// Fetch rich values and list the row/table/doc IDs that reference cells point to (default: false)
+    pub reference_ids: Option<bool>,
+    /// Key cells by column name rather than ID (default: server setting, normally true)
+    pub use_column_names: Option<bool>,
+    /// Cell value format: "simple", "simpleWithArrays" or "rich" (default: server setting, normally the API default)
+    pub value_format: Option<ValueFormat>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TableDataQualityParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The table ID or name
+    pub table_id: String,
+    /// Maximum rows to sample, fetched page by page (default: 1000, max: 10000)
+    pub max_rows: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ColumnProfileParams {
+    /// The document ID
+    pub doc_id: String,
+    /// The table ID or name
+    pub table_id: String,
+    /// The column ID or name
+    pub column_id: String,
+    /// Maximum rows to scan, fetched page by page (default: 1000, max: 10000)
+    pub max_rows: Option<u32>,
+    /// How many of the most frequent values to return (default: 5, max: 50)
+    pub top_k: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WaitForRowValueParams {
     /// The document ID
     pub doc_id: String,
     /// The table ID or name
     pub table_id: String,
     /// The row ID
     pub row_id: String,
+    /// Name of the column to watch
+    pub column_name: String,
+    /// Value to wait for; a string also matches a cell whose value renders the same, e.g. "5" matches 5
+    pub expected_value: serde_json::Value,
+    /// Maximum number of polls after the initial fetch (default: 10, max: 60)
+    pub max_polls: Option<u32>,
+    /// Seconds between polls (default: 5, max: 60)
+    pub interval_secs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
-pub struct AddRowParams {
+pub struct GetMutationStatusParams {
+    /// `requestId` returned by a write
+    pub request_id: String,
+    /// Poll until the mutation completes, up to a fixed limit (default: false)
+    pub wait: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetMutationResultParams {
     /// The document ID
     pub doc_id: String,
     /// The table ID or name
     pub table_id: String,
+    /// `requestId` returned by a row write; polled until the mutation completes
+    pub request_id: Option<String>,
+    /// IDs of the rows to read back once the mutation is done (max: 100)
+    pub row_ids: Option<Vec<String>>,
+    /// Maximum number of status polls after the initial check (default: 10, max: 60)
+    pub max_polls: Option<u32>,
+    /// Seconds between status polls (default: 5, max: 60)
+    pub interval_secs: Option<u64>,
+}
+
+/// Whether a cell value matches the value a caller is waiting for
+pub fn cell_matches(cell: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    if cell == expected {
+        return true;
+    }
+    match (cell, expected) {
+        (
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_),
+            serde_json::Value::String(expected),
+        ) => serde_json::from_str::<serde_json::Value>(expected).is_ok_and(|v| v == *cell),
+        _ => false,
+    }
+}
+
+/// A table to read rows from in `get_rows_multi`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RowSource {
+    /// The document ID
+    pub doc_id: String,
+    /// The table ID or name
+    pub table_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetRowsMultiParams {
+    /// Tables to read, possibly from different docs (max 20)
+    pub sources: Vec<RowSource>,
+    /// Maximum rows per table (default: 100, max: 1000)
+    pub limit: Option<u32>,
+    /// Query applied to every table, e.g. `"Status":"Done"`
+    pub query: Option<String>,
+    /// Column names whose values should be replaced with "[REDACTED]" (case-insensitive)
+    pub redact_columns: Option<Vec<String>>,
+}
+
+/// Rows read from one `get_rows_multi` source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourcedTable {
+    pub doc_id: String,
+    pub table_id: String,
+    pub table_name: String,
+    pub rows: Vec<Row>,
+}
+
+/// A row annotated with the doc and table it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct SourcedRow {
+    pub doc_id: String,
+    pub table_id: String,
+    pub table_name: String,
+    #[serde(flatten)]
+    pub row: Row,
+}
+
+/// A cell whose value differs between two snapshots of a row. A column
+/// missing from one side shows as `null` there.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CellChange {
+    pub column: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedRow {
+    pub id: String,
+    pub name: Option<String>,
+    /// Changed cells, sorted by column
+    pub cells: Vec<CellChange>,
+}
+
+/// Differences between two row snapshots, matched by row ID
+#[derive(Debug, Clone, Serialize)]
+pub struct RowDiff {
+    /// Rows only in the second snapshot, in its order
+    pub added: Vec<Row>,
+    /// Rows only in the first snapshot, in its order
+    pub removed: Vec<Row>,
+    /// Rows in both with different values, in the second snapshot's order
+    pub changed: Vec<ChangedRow>,
+    pub unchanged: usize,
+}
+
+impl RowDiff {
+    /// Compare two snapshots. Only cell values are compared; when an ID
+    /// appears more than once in a snapshot, its first row is used.
+    pub fn between(before: &[Row], after: &[Row]) -> Self {
+        let mut old_by_id: HashMap<&str, &Row> = HashMap::new();
+        for row in before {
+            old_by_id.entry(row.id.as_str()).or_insert(row);
+        }
+        let mut new_ids = HashSet::new();
+
+        let mut diff = Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            unchanged: 0,
+        };
+        for row in after {
+            if !new_ids.insert(row.id.as_str()) {
+                continue;
+            }
+            let Some(old) = old_by_id.get(row.id.as_str()) else {
+                diff.added.push(row.clone());
+                continue;
+            };
+            let cells = cell_changes(old, row);
+            if cells.is_empty() {
+                diff.unchanged += 1;
+            } else {
+                diff.changed.push(ChangedRow {
+                    id: row.id.clone(),
+                    name: row.name.clone().or_else(|| old.name.clone()),
+                    cells,
+                });
+            }
+        }
+        let mut removed_ids = HashSet::new();
+        diff.removed = before
+            .iter()
+            .filter(|row| !new_ids.contains(row.id.as_str()) && removed_ids.insert(row.id.as_str()))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Compare current rows against a baseline of `row ID -> {column: value}`.
+    /// Rows in both are compared only on the columns the baseline lists, so a
+    /// partial baseline doesn't report every other column as changed.
+    /// Removed rows carry their baseline values and are sorted by ID.
+    pub fn against_baseline(
+        baseline: HashMap<String, HashMap<String, serde_json::Value>>,
+        current: &[Row],
+    ) -> Self {
+        let mut before: Vec<Row> = baseline
+            .into_iter()
+            .map(|(id, values)| Row {
+                id,
+                row_type: None,
+                href: None,
+                name: None,
+                index: None,
+                values: Some(values),
+            })
+            .collect();
+        before.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let columns_by_id: HashMap<&str, HashSet<&String>> = before
+            .iter()
+            .map(|row| {
+                (
+                    row.id.as_str(),
+                    row.values.iter().flatten().map(|(k, _)| k).collect(),
+                )
+            })
+            .collect();
+        let after: Vec<Row> = current
+            .iter()
+            .map(|row| {
+                let mut row = row.clone();
+                if let (Some(columns), Some(values)) =
+                    (columns_by_id.get(row.id.as_str()), &mut row.values)
+                {
+                    values.retain(|column, _| columns.contains(column));
+                }
+                row
+            })
+            .collect();
+        Self::between(&before, &after)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// One-line count of added, removed, changed and unchanged rows
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return format!("No differences across {} rows", self.unchanged);
+        }
+        format!(
+            "{} added, {} removed, {} changed, {} unchanged",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len(),
+            self.unchanged
+        )
+    }
+
+    /// The summary followed by one `+`/`-`/`~` line per row, with each
+    /// changed cell indented under its row
+    pub fn describe(&self) -> String {
+        let mut output = self.summary();
+        for row in &self.added {
+            let _ = write!(output, "\n+ {}", row.id);
+        }
+        for row in &self.removed {
+            let _ = write!(output, "\n- {}", row.id);
+        }
+        for row in &self.changed {
+            let _ = write!(output, "\n~ {}", row.id);
+            for cell in &row.cells {
+                let _ = write!(
+                    output,
+                    "\n    {}: {} -> {}",
+                    cell.column, cell.old, cell.new
+                );
+            }
+        }
+        output
+    }
+}
+
+fn cell_changes(old: &Row, new: &Row) -> Vec<CellChange> {
+    let empty = HashMap::new();
+    let old_values = old.values.as_ref().unwrap_or(&empty);
+    let new_values = new.values.as_ref().unwrap_or(&empty);
+    let columns: BTreeSet<&String> = old_values.keys().chain(new_values.keys()).collect();
+    columns
+        .into_iter()
+        .filter_map(|column| {
+            let old = old_values.get(column).cloned().unwrap_or_default();
+            let new = new_values.get(column).cloned().unwrap_or_default();
+            (old != new).then(|| CellChange {
+                column: column.clone(),
+                old,
+                new,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DiffRowsParams {
+    /// Rows before the change, e.g. from an earlier `get_rows` call: objects with `id` and `values`
+    pub before: Vec<serde_json::Value>,
+    /// Rows after the change, in the same shape
+    pub after: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetRowsDeltaParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// Known state of the rows: row ID -> {column: value}. Only the listed columns are compared
+    pub baseline: HashMap<String, HashMap<String, serde_json::Value>>,
+    /// Key current values by column name rather than ID, to match the baseline (default: configured default)
+    pub use_column_names: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AppendToMultiselectParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// Rows to update (max 100); use this or `query`
+    pub row_ids: Option<Vec<String>>,
+    /// Query selecting the rows to update (Coda formula syntax); must match at most 100 rows
+    pub query: Option<String>,
+    /// The multi-select column's name or ID
+    pub column_name: String,
+    /// Options to add to each row, keeping the ones it already has
+    pub values_to_add: Vec<String>,
+}
+
+/// A row updated by `append_to_multiselect`
+#[derive(Debug, Clone, Serialize)]
+pub struct TaggedRow {
+    pub row_id: String,
+    /// Options that were new to the row; empty when it already had them all
+    pub added: Vec<String>,
+    /// Request ID of the write, if one was needed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AddRowParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
     /// Cell values as key-value pairs (column name -> value)
     pub cells: HashMap<String, serde_json::Value>,
+    /// Convert values to their column's type first, e.g. "5" to 5 for number columns (default: false)
+    pub coerce: Option<bool>,
+    /// Upsert: update the existing row whose values in these columns match instead of adding a new one
+    pub key_columns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AddRowsParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// Rows to add, each as cell values keyed by column name (max 500)
+    pub rows: Vec<HashMap<String, serde_json::Value>>,
+    /// Upsert: update existing rows whose values in these columns match instead of adding new ones
+    pub key_columns: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct UpdateRowParams {
-    /// The document ID
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
     pub doc_id: String,
-    /// The table ID or name
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
     pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
     /// The row ID to update
     pub row_id: String,
     /// Cell values to update (column name -> value)
     pub cells: HashMap<String, serde_json::Value>,
+    /// Convert values to their column's type first, e.g. "5" to 5 for number columns (default: false)
+    pub coerce: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct DeleteRowParams {
-    /// The document ID
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
     pub doc_id: String,
-    /// The table ID or name
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
     pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
     /// The row ID to delete
     pub row_id: String,
+    /// Target's ID or name; required when the server runs in safe mode
+    pub confirm: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeleteRowsParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// IDs of the rows to delete
+    pub row_ids: Vec<String>,
+    /// Table's ID or name; required when the server runs in safe mode
+    pub confirm: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PushButtonParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// The row ID or name
+    pub row_id: String,
+    /// The button column's ID or name
+    pub column_id: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CheckQueryParams {
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
+    pub doc_id: String,
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
+    pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+    /// Query to validate (Coda formula syntax, e.g. `"Status":"Done"`)
+    pub query: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn snapshot(rows: serde_json::Value) -> Vec<Row> {
+        serde_json::from_value(rows).unwrap()
+    }
+
+    #[test]
+    fn test_row_diff_added_removed_and_changed() {
+        let before = snapshot(serde_json::json!([
+            {"id": "r1", "name": "Ship", "values": {"Status": "Open", "Owner": "Ann"}},
+            {"id": "r2", "name": "Docs", "values": {"Status": "Open"}},
+            {"id": "r3", "name": "Blog", "values": {"Status": "Done"}}
+        ]));
+        let after = snapshot(serde_json::json!([
+            {"id": "r4", "name": "QA", "values": {"Status": "Open"}},
+            {"id": "r1", "name": "Ship", "values": {"Status": "Done", "Owner": "Ann", "Due": "2024-05-01"}},
+            {"id": "r3", "name": "Blog", "values": {"Status": "Done"}}
+        ]));
+
+        let diff = RowDiff::between(&before, &after);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].id, "r4");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].id, "r2");
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].id, "r1");
+        assert_eq!(
+            diff.changed[0].cells,
+            vec![
+                CellChange {
+                    column: "Due".to_string(),
+                    old: serde_json::Value::Null,
+                    new: serde_json::json!("2024-05-01"),
+                },
+                CellChange {
+                    column: "Status".to_string(),
+                    old: serde_json::json!("Open"),
+                    new: serde_json::json!("Done"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_row_diff_identical_and_duplicate_ids() {
+        let rows = snapshot(serde_json::json!([
+            {"id": "r1", "values": {"Status": "Open"}},
+            {"id": "r1", "values": {"Status": "Ignored"}}
+        ]));
+
+        let diff = RowDiff::between(&rows, &rows[..1]);
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn test_row_diff_against_partial_baseline() {
+        let baseline = serde_json::from_value(serde_json::json!({
+            "r2": {"Status": "Open"},
+            "r1": {"Status": "Open"},
+            "r9": {"Status": "Done"}
+        }))
+        .unwrap();
+        let current = snapshot(serde_json::json!([
+            {"id": "r1", "values": {"Status": "Done", "Owner": "Ann"}},
+            {"id": "r2", "values": {"Status": "Open", "Owner": "Bob"}},
+            {"id": "r3", "values": {"Status": "Open"}}
+        ]));
+
+        let diff = RowDiff::against_baseline(baseline, &current);
+
+        // Owner isn't in the baseline, so it isn't compared
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].id, "r1");
+        assert_eq!(diff.changed[0].cells.len(), 1);
+        assert_eq!(diff.changed[0].cells[0].column, "Status");
+        assert_eq!(diff.added[0].id, "r3");
+        assert_eq!(diff.removed[0].id, "r9");
+    }
+
+    #[test]
+    fn test_row_diff_describe() {
+        let before = snapshot(serde_json::json!([
+            {"id": "r1", "values": {"Status": "Open"}},
+            {"id": "r2", "values": {"Status": "Open"}}
+        ]));
+        let after = snapshot(serde_json::json!([
+            {"id": "r1", "values": {"Status": "Done"}},
+            {"id": "r3", "values": {"Status": "Open"}}
+        ]));
+
+        assert_eq!(
+            RowDiff::between(&before, &after).describe(),
+            "1 added, 1 removed, 1 changed, 0 unchanged\n+ r3\n- r2\n~ r1\n    Status: \"Open\" -> \"Done\""
+        );
+        assert_eq!(
+            RowDiff::between(&before, &before).describe(),
+            "No differences across 2 rows"
+        );
+    }
+
     #[test]
     fn test_row_deserialize() {
         let json = r#"{
@@ -107,6 +1110,245 @@ mod tests {
         assert_eq!(values.get("Age").unwrap(), 30);
     }
 
+    #[test]
+    fn test_row_redact_case_insensitive() {
+        let json = r#"{"id": "row1", "values": {"Name": "John", "SSN": "123-45-6789"}}"#;
+        let mut row: Row = serde_json::from_str(json).unwrap();
+
+        row.redact(&["ssn".to_string()]);
+
+        let values = row.values.unwrap();
+        assert_eq!(values.get("SSN").unwrap(), "[REDACTED]");
+        assert_eq!(values.get("Name").unwrap(), "John");
+    }
+
+    /// Split a compiled query back into its JSON-decoded column and value
+    fn parse_query(query: &str) -> (String, serde_json::Value) {
+        let mut stream = serde_json::Deserializer::from_str(query).into_iter::<serde_json::Value>();
+        if query.starts_with('"') {
+            let column = stream.next().unwrap().unwrap();
+            let rest = &query[stream.byte_offset()..];
+            let value = serde_json::from_str(rest.strip_prefix(':').unwrap()).unwrap();
+            (column.as_str().unwrap().to_string(), value)
+        } else {
+            let (column, value) = query.split_once(':').unwrap();
+            (column.to_string(), serde_json::from_str(value).unwrap())
+        }
+    }
+
+    fn filter(column: &str, value: serde_json::Value) -> RowFilter {
+        RowFilter {
+            column: column.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_row_filter_simple() {
+        let query = filter("Status", serde_json::json!("Active")).to_query();
+        assert_eq!(query, r#""Status":"Active""#);
+    }
+
+    #[test]
+    fn test_row_filter_escapes_quotes_and_backslashes() {
+        let query = filter("Status", serde_json::json!(r#"Won"t Fix"#)).to_query();
+        assert_eq!(query, r#""Status":"Won\"t Fix""#);
+
+        let query = filter("Path", serde_json::json!(r"C:\temp")).to_query();
+        assert_eq!(query, r#""Path":"C:\\temp""#);
+    }
+
+    #[test]
+    fn test_row_filter_round_trips() {
+        let cases = [
+            filter("Status", serde_json::json!(r#"Won"t Fix"#)),
+            filter("Path", serde_json::json!(r#"a\b\"c"#)),
+            filter("Time: Start", serde_json::json!("10:30")),
+            filter(r#"Say "hi""#, serde_json::json!("x")),
+            filter("Score", serde_json::json!(42)),
+            filter("Done", serde_json::json!(true)),
+            filter("c-abc123", serde_json::json!("a:b")),
+        ];
+        for case in cases {
+            let (column, value) = parse_query(&case.to_query());
+            assert_eq!(column, case.column);
+            assert_eq!(value, case.value);
+        }
+    }
+
+    #[test]
+    fn test_row_filter_column_id_unquoted() {
+        let query = filter("c-abc123", serde_json::json!(5)).to_query();
+        assert_eq!(query, "c-abc123:5");
+        // Names that merely start with "c-" but contain spaces are quoted
+        let query = filter("c-level staff", serde_json::json!(5)).to_query();
+        assert_eq!(query, r#""c-level staff":5"#);
+    }
+
+    #[test]
+    fn test_fill_rates_count_falsy_values_as_filled() {
+        let rows: Vec<Row> = serde_json::from_str(
+            r#"[
+                {"id": "r1", "values": {"Done": false, "Qty": 0, "Note": ""}},
+                {"id": "r2", "values": {"Done": true, "Note": "ok"}}
+            ]"#,
+        )
+        .unwrap();
+
+        let rates = ColumnFillRate::from_rows(&rows);
+        let ranked: Vec<_> = rates
+            .iter()
+            .map(|r| (r.column.as_str(), r.filled))
+            .collect();
+
+        // Qty is missing from r2, so it counts as empty there
+        assert_eq!(ranked, [("Done", 2), ("Note", 1), ("Qty", 1)]);
+        assert!(rates.iter().all(|r| r.total == 2));
+    }
+
+    #[test]
+    fn test_column_profile_lexical_values() {
+        let rows: Vec<Row> = serde_json::from_str(
+            r#"[
+                {"id": "r1", "values": {"Team": "Red"}},
+                {"id": "r2", "values": {"Team": "Blue"}},
+                {"id": "r3", "values": {"Team": "Red"}},
+                {"id": "r4", "values": {"Team": " "}},
+                {"id": "r5", "values": {}}
+            ]"#,
+        )
+        .unwrap();
+
+        let profile = ColumnProfile::from_rows(&rows, "Team", 1);
+
+        assert_eq!(profile.count, 3);
+        assert_eq!(profile.empty, 2);
+        assert_eq!(profile.distinct, 2);
+        assert_eq!(profile.min, Some(serde_json::json!("Blue")));
+        assert_eq!(profile.max, Some(serde_json::json!("Red")));
+        assert_eq!(profile.top_values.len(), 1);
+        assert_eq!(profile.top_values[0].value, "Red");
+        assert_eq!(profile.top_values[0].count, 2);
+    }
+
+    #[test]
+    fn test_columnar_rows_fills_missing_with_null() {
+        let rows: Vec<Row> = serde_json::from_str(
+            r#"[
+                {"id": "r1", "values": {"Name": "Alice", "Score": 95}},
+                {"id": "r2", "values": {"Name": "Bob"}},
+                {"id": "r3", "values": {"Score": 70, "Team": "Red"}}
+            ]"#,
+        )
+        .unwrap();
+
+        let columnar = ColumnarRows::from_rows(&rows);
+
+        assert_eq!(columnar.row_ids, ["r1", "r2", "r3"]);
+        assert_eq!(columnar.columns.len(), 3);
+        for values in columnar.columns.values() {
+            assert_eq!(values.len(), 3);
+        }
+        assert_eq!(
+            columnar.columns["Name"],
+            [
+                serde_json::json!("Alice"),
+                serde_json::json!("Bob"),
+                serde_json::Value::Null
+            ]
+        );
+        assert_eq!(
+            columnar.columns["Team"],
+            [
+                serde_json::Value::Null,
+                serde_json::Value::Null,
+                serde_json::json!("Red")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_columnar_rows_to_csv() {
+        let rows: Vec<Row> = serde_json::from_str(
+            r#"[
+                {"id": "r1", "values": {"Name": "Smith, Alice", "Tags": ["a", "b"]}},
+                {"id": "r2", "values": {"Name": "Bob \"B\"", "Score": 7}}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ColumnarRows::from_rows(&rows).to_csv(),
+            "Name,Score,Tags\n\"Smith, Alice\",,\"[\"\"a\"\",\"\"b\"\"]\"\n\"Bob \"\"B\"\"\",7,\n"
+        );
+    }
+
+    #[test]
+    fn test_row_sort_deserialize() {
+        let sort: RowSort = serde_json::from_str(r#""updatedAt""#).unwrap();
+        assert_eq!(sort, RowSort::UpdatedAt);
+        assert_eq!(RowSort::CreatedAt.as_str(), "createdAt");
+        assert!(serde_json::from_str::<RowSort>(r#""name""#).is_err());
+    }
+
+    #[test]
+    fn test_rows_format_deserialize() {
+        let format: RowsFormat = serde_json::from_str(r#""columnar""#).unwrap();
+        assert_eq!(format, RowsFormat::Columnar);
+        assert_eq!(RowsFormat::default(), RowsFormat::Rows);
+        assert!(serde_json::from_str::<RowsFormat>(r#""parquet""#).is_err());
+    }
+
+    #[test]
+    fn test_row_references_from_rich_values() {
+        let json = r#"{
+            "id": "i-1",
+            "values": {
+                "Name": "Task A",
+                "Owner": {
+                    "@context": "http://schema.org/",
+                    "@type": "StructuredValue",
+                    "additionalType": "row",
+                    "name": "Alice",
+                    "url": "https://coda.io/d/Team_dAbC123#People_tu1/r5",
+                    "tableId": "grid-people",
+                    "rowId": "i-5",
+                    "tableUrl": "https://coda.io/d/Team_dAbC123#People_tu1"
+                },
+                "Blockers": [
+                    {"additionalType": "row", "name": "B", "tableId": "grid-tasks", "rowId": "i-2"},
+                    {"additionalType": "row", "name": "C", "tableId": "grid-tasks", "rowId": "i-3"}
+                ]
+            }
+        }"#;
+        let row: Row = serde_json::from_str(json).unwrap();
+
+        let refs = row.references();
+
+        assert_eq!(refs.len(), 3);
+        assert_eq!(refs[0].column, "Blockers");
+        assert_eq!(refs[0].row_id, "i-2");
+        assert_eq!(refs[1].row_id, "i-3");
+        assert_eq!(refs[2].column, "Owner");
+        assert_eq!(refs[2].row_id, "i-5");
+        assert_eq!(refs[2].table_id, "grid-people");
+        assert_eq!(refs[2].doc_id, Some("AbC123".to_string()));
+        assert_eq!(refs[2].name, Some("Alice".to_string()));
+    }
+
+    #[test]
+    fn test_doc_id_from_url() {
+        assert_eq!(
+            doc_id_from_url("https://coda.io/d/_dXyZ#Tbl_tu/r1"),
+            Some("XyZ".to_string())
+        );
+        assert_eq!(
+            doc_id_from_url("https://coda.io/d/My-Doc_dAbC/Page_su1"),
+            Some("AbC".to_string())
+        );
+        assert_eq!(doc_id_from_url("https://example.com/x"), None);
+    }
+
     #[test]
     fn test_row_list_deserialize() {
         let json = r#"{
@@ -121,6 +1363,18 @@ mod tests {
         assert_eq!(list.items[0].id, "row1");
     }
 
+    #[test]
+    fn test_cell_matches() {
+        use serde_json::json;
+
+        assert!(cell_matches(&json!("Done"), &json!("Done")));
+        assert!(cell_matches(&json!(5), &json!("5")));
+        assert!(cell_matches(&json!(true), &json!("true")));
+        assert!(!cell_matches(&json!("Done"), &json!("done")));
+        assert!(!cell_matches(&json!(5), &json!(6)));
+        assert!(!cell_matches(&serde_json::Value::Null, &json!("null")));
+    }
+
     #[test]
     fn test_row_mutation_response() {
         let json = r#"{
@@ -134,6 +1388,31 @@ mod tests {
             resp.added_row_ids,
             Some(vec!["row1".to_string(), "row2".to_string()])
         );
+        assert!(resp.row_id_map.is_none());
+        assert!(resp.row_id_lines().is_empty());
+    }
+
+    #[test]
+    fn test_row_mutation_response_row_id_map() {
+        let json = r#"{
+            "requestId": "req123",
+            "addedRowIds": ["i-new"],
+            "rowIdMap": {"0": "i-old", "1": "i-new", "10": "i-late", "2": "i-mid", "a": "i-a"}
+        }"#;
+
+        let resp: RowMutationResponse = serde_json::from_str(json).unwrap();
+        let map = resp.row_id_map.as_ref().unwrap();
+        assert_eq!(map["1"], "i-new");
+        assert_eq!(
+            resp.row_id_lines(),
+            [
+                "0 -> i-old",
+                "1 -> i-new",
+                "2 -> i-mid",
+                "10 -> i-late",
+                "a -> i-a"
+            ]
+        );
     }
 
     #[test]
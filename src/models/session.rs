@@ -0,0 +1,106 @@
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::client::RequestRecord;
+use crate::error::ErrorCategory;
+
+/// Path segments followed by the ID of an item in that collection
+const ID_COLLECTIONS: &[&str] = &[
+    "docs",
+    "pages",
+    "tables",
+    "columns",
+    "rows",
+    "formulas",
+    "controls",
+    "export",
+    "folders",
+    "permissions",
+    "buttons",
+];
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExportSessionLogParams {
+    /// Replace doc, page, table, row and other IDs in paths with `{id}` (default: false)
+    pub redact_ids: Option<bool>,
+    /// Empty the log after exporting it (default: false)
+    pub clear: Option<bool>,
+}
+
+/// One API call in an exported session log. Carries no cell values, query
+/// strings, error messages or credentials.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionLogEntry {
+    /// Tool that made the call, if known
+    pub tool: Option<String>,
+    pub method: String,
+    /// API path without its query string
+    pub path: String,
+    pub started_at: String,
+    pub elapsed_ms: u64,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<ErrorCategory>,
+}
+
+impl SessionLogEntry {
+    pub fn from_record(record: &RequestRecord, redact_ids: bool) -> Self {
+        let path = record.path.split('?').next().unwrap_or_default();
+        Self {
+            tool: record.tool.clone(),
+            method: record.method.clone(),
+            path: if redact_ids {
+                redact_path_ids(path)
+            } else {
+                path.to_string()
+            },
+            started_at: record.started_at.clone(),
+            elapsed_ms: record.elapsed_ms,
+            ok: !record.is_error(),
+            status: record.status,
+            category: record.category,
+        }
+    }
+}
+
+/// Replace each segment that follows a known collection name with `{id}`
+pub fn redact_path_ids(path: &str) -> String {
+    let mut previous = "";
+    path.split('/')
+        .map(|segment| {
+            let out = if ID_COLLECTIONS.contains(&previous) && !segment.is_empty() {
+                "{id}"
+            } else {
+                segment
+            };
+            previous = segment;
+            out
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_path_ids() {
+        assert_eq!(
+            redact_path_ids("/docs/AbCd/tables/grid-1/rows/i-xyz"),
+            "/docs/{id}/tables/{id}/rows/{id}"
+        );
+        assert_eq!(
+            redact_path_ids("/docs/AbCd/pages/canvas-1/export/exp-9"),
+            "/docs/{id}/pages/{id}/export/{id}"
+        );
+        assert_eq!(
+            redact_path_ids("/docs/AbCd/acl/metadata"),
+            "/docs/{id}/acl/metadata"
+        );
+        assert_eq!(redact_path_ids("/docs"), "/docs");
+        assert_eq!(redact_path_ids("/whoami"), "/whoami");
+    }
+}
@@ -1,6 +1,8 @@
 use rmcp::schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::{Column, ColumnReference, PageParent};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Table {
     pub id: String,
@@ -10,6 +12,26 @@ pub struct Table {
     pub name: String,
     #[serde(rename = "rowCount")]
     pub row_count: Option<u32>,
+    #[serde(rename = "displayColumn")]
+    pub display_column: Option<ColumnReference>,
+    /// The page this table lives on
+    pub parent: Option<PageParent>,
+    #[serde(rename = "browserLink")]
+    pub browser_link: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<String>,
+}
+
+/// Lightweight reference to a table, e.g. the target of a lookup column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableReference {
+    pub id: String,
+    pub name: Option<String>,
+    pub href: Option<String>,
+    #[serde(rename = "browserLink", skip_serializing_if = "Option::is_none")]
+    pub browser_link: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,18 +41,94 @@ pub struct TableList {
     pub next_page_token: Option<String>,
 }
 
+/// Kind of table to list, sent in `tableTypes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TableType {
+    /// A base table holding its own rows
+    Table,
+    /// A view of another table
+    View,
+}
+
+impl TableType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::View => "view",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListTablesParams {
     /// The document ID
     pub doc_id: String,
+    /// Maximum number of tables to return (default: API default, max: 1000)
+    pub limit: Option<u32>,
+    /// Token from a previous call's output to fetch the next page
+    pub page_token: Option<String>,
+    /// Remove duplicate IDs and sort results by ID (default: false)
+    pub dedupe: Option<bool>,
+    /// Only list these kinds: "table" and/or "view", e.g. just "table" to skip views (default: both)
+    pub table_types: Option<Vec<TableType>>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetTableParams {
-    /// The document ID
+    /// The document ID (not needed with `alias`)
+    #[serde(default)]
     pub doc_id: String,
-    /// The table ID or name
+    /// The table ID or name (not needed with `alias`)
+    #[serde(default)]
     pub table_id: String,
+    /// Name of a configured table alias, used in place of `doc_id` and `table_id`
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetDocSchemaParams {
+    /// The document ID
+    pub doc_id: String,
+    /// Maximum number of tables to describe with columns (default: 50, max: 200).
+    /// Remaining tables are returned by name only.
+    pub table_limit: Option<u32>,
+    /// Number of tables to introspect concurrently (default: 4, max: 8)
+    pub concurrency: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetTableRelationshipsParams {
+    /// The document ID
+    pub doc_id: String,
+    /// Maximum number of tables whose columns are inspected (default: 50, max: 200)
+    pub table_limit: Option<u32>,
+    /// Number of tables to inspect concurrently (default: 4, max: 8)
+    pub concurrency: Option<u32>,
+}
+
+/// A lookup column in one table referencing rows of another
+#[derive(Debug, Clone, Serialize)]
+pub struct TableEdge {
+    pub from_table_id: String,
+    pub from_table: String,
+    pub column_id: String,
+    pub column: String,
+    pub to_table_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_table: Option<String>,
+}
+
+/// A table with its columns, as returned by `get_doc_schema`.
+/// `columns` is `None` for tables beyond the `table_limit`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "rowCount", skip_serializing_if = "Option::is_none")]
+    pub row_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
 }
 
 #[cfg(test)]
@@ -53,6 +151,48 @@ mod tests {
         assert_eq!(table.row_count, Some(42));
     }
 
+    #[test]
+    fn test_table_deserialize_full() {
+        let json = r#"{
+            "id": "grid-abc123",
+            "type": "table",
+            "tableType": "table",
+            "href": "https://coda.io/apis/v1/docs/doc1/tables/grid-abc123",
+            "browserLink": "https://coda.io/d/_ddoc1#Tasks_tuabc",
+            "name": "Tasks",
+            "parent": {
+                "id": "canvas-xyz",
+                "type": "page",
+                "href": "https://coda.io/apis/v1/docs/doc1/pages/canvas-xyz",
+                "name": "Planning"
+            },
+            "displayColumn": {
+                "id": "c-name",
+                "type": "column",
+                "href": "https://coda.io/apis/v1/docs/doc1/tables/grid-abc123/columns/c-name"
+            },
+            "rowCount": 42,
+            "createdAt": "2024-01-01T00:00:00.000Z",
+            "updatedAt": "2024-02-01T12:30:00.000Z"
+        }"#;
+
+        let table: Table = serde_json::from_str(json).unwrap();
+        assert_eq!(table.display_column.unwrap().id, "c-name");
+        assert_eq!(table.parent.unwrap().name, Some("Planning".to_string()));
+        assert_eq!(
+            table.browser_link,
+            Some("https://coda.io/d/_ddoc1#Tasks_tuabc".to_string())
+        );
+        assert_eq!(
+            table.created_at,
+            Some("2024-01-01T00:00:00.000Z".to_string())
+        );
+        assert_eq!(
+            table.updated_at,
+            Some("2024-02-01T12:30:00.000Z".to_string())
+        );
+    }
+
     #[test]
     fn test_table_list_deserialize() {
         let json = r#"{
@@ -75,9 +215,28 @@ mod tests {
             href: None,
             name: "My Table".to_string(),
             row_count: Some(100),
+            display_column: None,
+            parent: None,
+            browser_link: None,
+            created_at: None,
+            updated_at: None,
         };
 
         let json = serde_json::to_string(&table).unwrap();
         assert!(json.contains("\"rowCount\":100"));
     }
+
+    #[test]
+    fn test_table_schema_names_only_omits_columns() {
+        let schema = TableSchema {
+            id: "tbl1".to_string(),
+            name: "Tasks".to_string(),
+            row_count: None,
+            columns: None,
+        };
+
+        let json = serde_json::to_string(&schema).unwrap();
+        assert!(!json.contains("columns"));
+        assert!(!json.contains("rowCount"));
+    }
 }
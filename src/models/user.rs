@@ -0,0 +1,57 @@
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The user the API token belongs to, from `GET /whoami`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub name: String,
+    /// Email address the user logs in with
+    #[serde(rename = "loginId")]
+    pub login_id: String,
+    #[serde(rename = "type")]
+    pub user_type: Option<String>,
+    pub href: Option<String>,
+    #[serde(rename = "tokenName")]
+    pub token_name: Option<String>,
+    pub workspace: Option<WorkspaceReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceReference {
+    pub id: String,
+    pub name: Option<String>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "browserLink")]
+    pub browser_link: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetWorkspaceParams {
+    /// Look the workspace up again instead of using the cached one (default: false)
+    pub refresh: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_deserialize() {
+        let json = r#"{
+            "name": "Ada Lovelace",
+            "loginId": "ada@example.com",
+            "type": "user",
+            "href": "https://coda.io/apis/v1/whoami",
+            "tokenName": "mcp",
+            "workspace": {"id": "ws-1", "type": "workspace", "organizationId": "org-1"}
+        }"#;
+
+        let user: User = serde_json::from_str(json).unwrap();
+        assert_eq!(user.login_id, "ada@example.com");
+        assert_eq!(user.token_name.as_deref(), Some("mcp"));
+        let workspace = user.workspace.unwrap();
+        assert_eq!(workspace.id, "ws-1");
+        assert_eq!(workspace.organization_id.as_deref(), Some("org-1"));
+    }
+}
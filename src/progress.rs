@@ -0,0 +1,132 @@
+//! MCP progress notifications for long-running tool calls.
+//!
+//! A client that wants progress sends a `progressToken` in the request's
+//! `_meta`. `call_tool` then scopes a [`Progress`] over the tool call, and code
+//! deep inside the call reports through [`current`] without the token being
+//! threaded through every signature. Calls without a token report nothing.
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use rmcp::model::{ProgressNotificationParam, ProgressToken};
+use rmcp::service::RequestContext;
+use rmcp::{Peer, RoleServer};
+
+/// Bytes between download progress notifications
+const BYTE_REPORT_INTERVAL: u64 = 256 * 1024;
+
+tokio::task_local! {
+    /// Progress sink of the tool call being handled, if the client asked for one
+    pub static PROGRESS: Option<Progress>;
+}
+
+/// Where a tool call's progress notifications go
+#[derive(Clone)]
+pub struct Progress {
+    peer: Peer<RoleServer>,
+    token: ProgressToken,
+}
+
+impl Progress {
+    /// Progress sink for a request, if it carries a progress token
+    pub fn from_context(context: &RequestContext<RoleServer>) -> Option<Self> {
+        Some(Self {
+            peer: context.peer.clone(),
+            token: context.meta.get_progress_token()?,
+        })
+    }
+
+    /// Callback taking `(bytes so far, Content-Length)` that sends throttled
+    /// byte-count notifications. They are sent in order from a background
+    /// task, so reporting never blocks the download.
+    pub fn byte_reporter(&self, label: &str) -> impl FnMut(u64, Option<u64>) + Send {
+        let (tx, mut rx) = mpsc::unbounded::<ProgressNotificationParam>();
+        let peer = self.peer.clone();
+        tokio::spawn(async move {
+            while let Some(param) = rx.next().await {
+                if let Err(e) = peer.notify_progress(param).await {
+                    tracing::debug!("Failed to send progress notification: {}", e);
+                    break;
+                }
+            }
+        });
+
+        let token = self.token.clone();
+        let label = label.to_string();
+        let mut throttle = ByteThrottle::default();
+        move |bytes, total| {
+            if throttle.should_report(bytes, total) {
+                #[allow(clippy::cast_precision_loss)] // downloads are capped far below 2^52 bytes
+                let _ = tx.unbounded_send(ProgressNotificationParam {
+                    progress_token: token.clone(),
+                    progress: bytes as f64,
+                    total: total.map(|t| t as f64),
+                    message: Some(format!("{label}: {}", byte_message(bytes, total))),
+                });
+            }
+        }
+    }
+}
+
+/// The current tool call's progress sink, if any
+pub fn current() -> Option<Progress> {
+    PROGRESS.try_with(Clone::clone).ok().flatten()
+}
+
+/// Limits byte progress to one report per `BYTE_REPORT_INTERVAL`, plus the
+/// final byte of a body with a known length
+#[derive(Debug, Default)]
+struct ByteThrottle {
+    last_reported: Option<u64>,
+}
+
+impl ByteThrottle {
+    fn should_report(&mut self, bytes: u64, total: Option<u64>) -> bool {
+        let due = match self.last_reported {
+            None => true,
+            Some(last) => bytes >= last + BYTE_REPORT_INTERVAL || Some(bytes) == total,
+        };
+        if due && self.last_reported != Some(bytes) {
+            self.last_reported = Some(bytes);
+            return true;
+        }
+        false
+    }
+}
+
+fn byte_message(bytes: u64, total: Option<u64>) -> String {
+    match total {
+        Some(total) if total > 0 => {
+            format!(
+                "{bytes} of {total} bytes ({}%)",
+                bytes.saturating_mul(100) / total
+            )
+        }
+        _ => format!("{bytes} bytes"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_throttle() {
+        let mut throttle = ByteThrottle::default();
+        let total = Some(3 * BYTE_REPORT_INTERVAL);
+
+        assert!(throttle.should_report(1024, total));
+        assert!(!throttle.should_report(2048, total));
+        assert!(throttle.should_report(BYTE_REPORT_INTERVAL + 1024, total));
+        assert!(!throttle.should_report(2 * BYTE_REPORT_INTERVAL, total));
+        // The last chunk is always reported, once
+        assert!(throttle.should_report(3 * BYTE_REPORT_INTERVAL, total));
+        assert!(!throttle.should_report(3 * BYTE_REPORT_INTERVAL, total));
+    }
+
+    #[test]
+    fn test_byte_message() {
+        assert_eq!(byte_message(512, Some(2048)), "512 of 2048 bytes (25%)");
+        assert_eq!(byte_message(512, None), "512 bytes");
+        assert_eq!(byte_message(0, Some(0)), "0 bytes");
+    }
+}
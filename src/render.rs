@@ -0,0 +1,165 @@
+//! Rendering of the data blocks embedded in tool output.
+//!
+//! JSON is the default. YAML goes through `serde_norway`, which quotes strings
+//! that would otherwise read back as another type, so scalars round-trip.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Format of the fenced data block in tool output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_norway::Error),
+}
+
+impl OutputFormat {
+    /// Parse `json` or `yaml`/`yml` (case-insensitive)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Render `value` as a fenced code block tagged with the format's name
+    pub fn render<T: Serialize + ?Sized>(self, value: &T) -> Result<String, RenderError> {
+        match self {
+            Self::Json => Ok(format!(
+                "```json\n{}\n```",
+                serde_json::to_string_pretty(value)?
+            )),
+            Self::Yaml => {
+                let yaml = to_yaml(&serde_json::to_value(value)?)?;
+                Ok(format!("```yaml\n{yaml}```"))
+            }
+        }
+    }
+}
+
+/// Serialize a JSON value as a block-style YAML document, ending in a newline.
+/// Going through `Value` keeps map keys sorted, as in the JSON block.
+pub fn to_yaml(value: &Value) -> Result<String, serde_norway::Error> {
+    serde_norway::to_string(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_output_format_parse() {
+        assert_eq!(OutputFormat::parse("YAML"), Some(OutputFormat::Yaml));
+        assert_eq!(OutputFormat::parse(" yml "), Some(OutputFormat::Yaml));
+        assert_eq!(OutputFormat::parse("json"), Some(OutputFormat::Json));
+        assert_eq!(OutputFormat::parse("toml"), None);
+    }
+
+    /// Parse rendered YAML back, as a client reading the block would
+    fn read_back(yaml: &str) -> Value {
+        serde_norway::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_to_yaml_nested_block_style() {
+        let value = json!([
+            {"id": "doc1", "name": "Roadmap", "owner": {"name": "Ann"}, "tags": ["a", "b"]},
+            {"id": "doc2", "name": "Notes", "owner": {}, "tags": []},
+            [1, [2]]
+        ]);
+
+        let yaml = to_yaml(&value).unwrap();
+        assert!(yaml.starts_with("- id: doc1\n  name: Roadmap\n  owner:\n    name: Ann\n"));
+        assert_eq!(read_back(&yaml), value);
+    }
+
+    #[test]
+    fn test_to_yaml_scalars_round_trip() {
+        let scalars = json!([
+            42,
+            0.5,
+            -1,
+            false,
+            null,
+            "42",
+            "yes",
+            "no",
+            "on",
+            "Y",
+            "true",
+            "null",
+            "~",
+            "0x1F",
+            "0b1",
+            "0o7",
+            "1e3",
+            "+1",
+            ".inf",
+            "1_000",
+            "12:30",
+            "2024-01-15",
+            "...",
+            "---",
+            "a: b",
+            "a #b",
+            "- item",
+            "? key",
+            "#tag",
+            "&anchor",
+            "*ref",
+            "!tag",
+            "",
+            " x",
+            "x ",
+            "line1\nline2",
+            "trailing\n",
+            "tab\there",
+            "say \"hi\"",
+            "it's",
+            "Hello world",
+            "https://coda.io/d/abc"
+        ]);
+
+        for scalar in scalars.as_array().unwrap() {
+            let yaml = to_yaml(scalar).unwrap();
+            assert_eq!(
+                &read_back(&yaml),
+                scalar,
+                "for {scalar}, rendered as {yaml:?}"
+            );
+
+            let in_map = json!({"value": scalar, "list": [scalar]});
+            assert_eq!(
+                read_back(&to_yaml(&in_map).unwrap()),
+                in_map,
+                "for {scalar}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_fences_by_format() {
+        let value = json!({"id": "doc1"});
+
+        assert_eq!(
+            OutputFormat::Json.render(&value).unwrap(),
+            "```json\n{\n  \"id\": \"doc1\"\n}\n```"
+        );
+        assert_eq!(
+            OutputFormat::Yaml.render(&value).unwrap(),
+            "```yaml\nid: doc1\n```"
+        );
+        assert_eq!(to_yaml(&json!("top")).unwrap(), "top\n");
+    }
+}
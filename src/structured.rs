@@ -0,0 +1,66 @@
+//! Raw JSON content items for `CODA_STRUCTURED_OUTPUT`.
+//!
+//! Tool output embeds its data as a fenced block in the text. When structured
+//! output is on, `call_tool` scopes a [`DataBlocks`] over the call, every
+//! value rendered into a fence is also recorded through [`record`], and the
+//! values are appended afterwards as `Content::json` items, so clients can
+//! parse the data without unwrapping the fence.
+
+use rmcp::model::{CallToolResult, Content};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+tokio::task_local! {
+    /// Data rendered by the tool call being handled, when structured output is on
+    pub static STRUCTURED: DataBlocks;
+}
+
+/// Values rendered during one tool call, in render order
+#[derive(Clone, Default)]
+pub struct DataBlocks(Arc<Mutex<Vec<serde_json::Value>>>);
+
+impl DataBlocks {
+    pub fn values(&self) -> Vec<serde_json::Value> {
+        self.0.lock().map(|v| v.clone()).unwrap_or_default()
+    }
+
+    /// Add one JSON content item per recorded value after the text of `result`
+    pub fn append_to(&self, result: &mut CallToolResult) {
+        for value in self.values() {
+            if let Ok(content) = Content::json(value) {
+                result.content.push(content);
+            }
+        }
+    }
+}
+
+/// Record a rendered value in the current tool call's data, if there is a scope
+pub fn record<T: Serialize + ?Sized>(value: &T) {
+    let _ = STRUCTURED.try_with(|blocks| {
+        if let (Ok(value), Ok(mut values)) = (serde_json::to_value(value), blocks.0.lock()) {
+            values.push(value);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_outside_scope_is_ignored() {
+        record(&serde_json::json!({"ignored": true}));
+
+        let blocks = DataBlocks::default();
+        STRUCTURED
+            .scope(blocks.clone(), async {
+                record(&vec![1, 2]);
+            })
+            .await;
+
+        let mut result = CallToolResult::success(vec![Content::text("Found 2")]);
+        blocks.append_to(&mut result);
+        assert_eq!(result.content.len(), 2);
+        assert_eq!(result.content[1].raw.as_text().unwrap().text, "[1,2]");
+    }
+}
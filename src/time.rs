@@ -0,0 +1,143 @@
+//! Parsing of the timestamps and relative durations tools accept.
+//!
+//! Timestamps are RFC3339 (`2024-01-15T00:00:00Z`, any offset). Durations are
+//! a non-negative whole number followed by `d`, `h` or `m` (`7d`, `24h`,
+//! `30m`). Dates are `YYYY-MM-DD`, or either of the above reduced to its UTC
+//! date. Every tool that takes any of these goes through here, so they accept
+//! the same forms and report the same errors.
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+/// Parse an RFC3339 timestamp, normalized to UTC
+pub fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| {
+            format!("Invalid timestamp '{value}': expected RFC3339 like 2024-01-15T00:00:00Z")
+        })
+}
+
+/// Parse a relative duration such as `7d`, `24h` or `30m`
+pub fn parse_duration(value: &str) -> Result<TimeDelta, String> {
+    let value = value.trim();
+    value
+        .char_indices()
+        .last()
+        .and_then(|(i, unit)| Some((value[..i].parse::<i64>().ok()?, unit)))
+        .and_then(|(amount, unit)| match unit {
+            'd' => TimeDelta::try_days(amount),
+            'h' => TimeDelta::try_hours(amount),
+            'm' => TimeDelta::try_minutes(amount),
+            _ => None,
+        })
+        .filter(|delta| *delta >= TimeDelta::zero())
+        .ok_or_else(|| {
+            format!("Invalid duration '{value}': expected a whole number of days, hours or minutes like 7d, 24h or 30m")
+        })
+}
+
+/// Start of a time window: an RFC3339 timestamp, or a relative duration
+/// counted back from `now`
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(delta) = parse_duration(value) {
+        return now
+            .checked_sub_signed(delta)
+            .ok_or_else(|| format!("Duration '{value}' is too large"));
+    }
+    parse_timestamp(value).map_err(|_| {
+        format!(
+            "Invalid time '{value}': expected an RFC3339 timestamp like 2024-01-15T00:00:00Z or a duration like 7d, 24h or 30m"
+        )
+    })
+}
+
+/// Parse a calendar date: `YYYY-MM-DD`, or anything [`parse_since`]
+/// accepts, taken as its UTC date
+pub fn parse_date(value: &str, now: DateTime<Utc>) -> Result<NaiveDate, String> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    parse_since(value, now)
+        .map(|t| t.date_naive())
+        .map_err(|_| {
+            format!(
+                "Invalid date '{value}': expected YYYY-MM-DD, an RFC3339 timestamp or a duration like 7d"
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        parse_timestamp("2024-03-10T12:00:00Z").unwrap()
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("2024-01-15T08:00:00+02:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2024-01-15T06:00:00+00:00"
+        );
+        assert_eq!(
+            parse_timestamp(" 2024-01-15T00:00:00.500Z ")
+                .unwrap()
+                .to_rfc3339(),
+            "2024-01-15T00:00:00.500+00:00"
+        );
+        for invalid in ["", "2024-01-15", "2024-01-15 00:00:00", "7d", "yesterday"] {
+            let err = parse_timestamp(invalid).unwrap_err();
+            assert!(err.contains("expected RFC3339"), "{invalid}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("7d").unwrap(), TimeDelta::days(7));
+        assert_eq!(parse_duration(" 24h ").unwrap(), TimeDelta::hours(24));
+        assert_eq!(parse_duration("0m").unwrap(), TimeDelta::zero());
+        for invalid in ["", "7", "d", "7w", "-1d", "1.5h", "9999999999999999d"] {
+            let err = parse_duration(invalid).unwrap_err();
+            assert!(err.contains("like 7d, 24h or 30m"), "{invalid}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let at = |value: &str| parse_since(value, now()).map(|t| t.to_rfc3339());
+
+        assert_eq!(at("7d").unwrap(), "2024-03-03T12:00:00+00:00");
+        assert_eq!(at(" 24h ").unwrap(), "2024-03-09T12:00:00+00:00");
+        assert_eq!(at("30m").unwrap(), "2024-03-10T11:30:00+00:00");
+        assert_eq!(
+            at("2024-01-15T08:00:00+02:00").unwrap(),
+            "2024-01-15T06:00:00+00:00"
+        );
+        for invalid in ["", "7", "d", "7w", "-1d", "2024-01-15", "yesterday"] {
+            let err = at(invalid).unwrap_err();
+            assert!(
+                err.contains("expected an RFC3339 timestamp"),
+                "{invalid}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let on = |value: &str| parse_date(value, now()).map(|d| d.to_string());
+
+        assert_eq!(on("2024-01-15").unwrap(), "2024-01-15");
+        assert_eq!(on("7d").unwrap(), "2024-03-03");
+        assert_eq!(on("2024-01-15T23:30:00-02:00").unwrap(), "2024-01-16");
+        for invalid in ["", "2024-13-01", "15/01/2024", "yesterday"] {
+            let err = on(invalid).unwrap_err();
+            assert!(err.contains("expected YYYY-MM-DD"), "{invalid}: {err}");
+        }
+    }
+}
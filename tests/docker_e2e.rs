@@ -14,16 +14,14 @@ fn docker_available() -> bool {
     Command::new("docker")
         .arg("version")
         .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .is_ok_and(|o| o.status.success())
 }
 
 fn image_exists() -> bool {
     Command::new("docker")
         .args(["image", "inspect", IMAGE])
         .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .is_ok_and(|o| o.status.success())
 }
 
 fn get_token() -> Option<String> {